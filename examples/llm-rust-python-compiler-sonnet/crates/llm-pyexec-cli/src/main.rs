@@ -1,5 +1,8 @@
-use clap::Parser;
-use llm_pyexec::{execute, ExecutionSettings, DEFAULT_ALLOWED_MODULES};
+use clap::{Parser, Subcommand};
+use llm_pyexec::{
+    environment_report, execute, frozen_module_source, frozen_modules, BytecodeCache,
+    ExecutionSettings, InterpreterPool, DEFAULT_ALLOWED_MODULES,
+};
 use std::io::{self, Read};
 
 /// Execute Python code and emit JSON result.
@@ -17,11 +20,60 @@ struct Args {
     /// Comma-separated list of allowed modules (default: standard set)
     #[arg(long)]
     modules: Option<String>,
+
+    /// Comma-separated list of modules allowed on top of `--modules` or the
+    /// standard set
+    #[arg(long)]
+    extra_modules: Option<String>,
+
+    /// Append `cache_stats` (bytecode cache hits/misses) and `pool_stats`
+    /// (interpreter pool utilization) objects to the JSON output
+    #[arg(long)]
+    stats: bool,
+
+    /// Exit with the snippet's status when it calls `sys.exit()` with a
+    /// nonzero code, instead of always exiting 0
+    #[arg(long)]
+    propagate_exit_code: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    /// Print the frozen Python modules compiled into this binary as JSON
+    Frozen {
+        /// Include each module's full source in a `source` field
+        #[arg(long)]
+        with_source: bool,
+    },
+    /// Print which default modules import in this environment as JSON; exits 1
+    /// if any does not
+    Doctor,
 }
 
 fn main() {
     let args = Args::parse();
 
+    match args.command {
+        Some(Command::Frozen { with_source }) => {
+            print_frozen(with_source);
+            return;
+        }
+        Some(Command::Doctor) => {
+            let report = environment_report();
+            let value =
+                serde_json::to_value(&report).expect("EnvironmentReport is always serializable");
+            println!("{value}");
+            if !report.all_importable() {
+                std::process::exit(1);
+            }
+            return;
+        }
+        None => {}
+    }
+
     // Read Python source.
     let code = if let Some(path) = args.file {
         std::fs::read_to_string(&path).unwrap_or_else(|e| {
@@ -39,22 +91,67 @@ fn main() {
 
     // Build settings.
     let allowed_modules: Vec<String> = if let Some(m) = args.modules {
-        m.split(',').map(|s| s.trim().to_string()).collect()
+        split_modules(&m)
     } else {
         DEFAULT_ALLOWED_MODULES.iter().map(|s| s.to_string()).collect()
     };
+    let extra_allowed_modules = args
+        .extra_modules
+        .as_deref()
+        .map(split_modules)
+        .unwrap_or_default();
 
     let settings = ExecutionSettings {
         timeout_ns: args.timeout,
         max_output_bytes: 1_048_576,
         allowed_modules,
+        extra_allowed_modules,
+        ..ExecutionSettings::default()
     };
+    if let Err(e) = settings.validate() {
+        eprintln!("Invalid settings: {e}");
+        std::process::exit(1);
+    }
 
     // Execute.
     let result = execute(&code, settings);
 
-    // Serialize to JSON. Always exits 0.
-    let json = serde_json::to_string(&result).expect("ExecutionResult is always serializable");
-    println!("{json}");
-    // Exit 0 always — errors are encoded in the JSON, not the exit code.
+    // Serialize to JSON.
+    let mut value = serde_json::to_value(&result).expect("ExecutionResult is always serializable");
+    if args.stats {
+        let stats = serde_json::to_value(BytecodeCache::global().stats())
+            .expect("CacheStats is always serializable");
+        value["cache_stats"] = stats;
+        let pool = serde_json::to_value(InterpreterPool::global().metrics())
+            .expect("PoolMetrics is always serializable");
+        value["pool_stats"] = pool;
+    }
+    println!("{value}");
+    // Exit 0 unless asked to propagate `sys.exit()` — errors are encoded in
+    // the JSON, not the exit code.
+    if let Some(code) = result.exit_code.filter(|_| args.propagate_exit_code) {
+        std::process::exit(code);
+    }
+}
+
+/// Emit a JSON array of frozen module audit records.
+fn print_frozen(with_source: bool) {
+    let modules: Vec<serde_json::Value> = frozen_modules()
+        .into_iter()
+        .map(|info| {
+            let source = with_source.then(|| frozen_module_source(&info.name));
+            let mut value =
+                serde_json::to_value(info).expect("FrozenModuleInfo is always serializable");
+            if let Some(source) = source {
+                value["source"] = serde_json::Value::from(source);
+            }
+            value
+        })
+        .collect();
+    println!("{}", serde_json::Value::Array(modules));
+}
+
+/// Split a comma-separated module list, trimming whitespace.
+fn split_modules(list: &str) -> Vec<String> {
+    list.split(',').map(|s| s.trim().to_string()).collect()
 }
@@ -4,94 +4,412 @@
 //! digest of the corresponding source code. This avoids recompiling identical source
 //! strings across successive `execute()` calls.
 //!
-//! # Environment variable
+//! # Environment variables
 //!
-//! `PYEXEC_BYTECODE_CACHE_SIZE` — maximum number of entries; defaults to `256`.
-//! Setting it to `0` is treated as `1` (no panic, always keep at least one entry).
+//! `PYEXEC_CACHE_CAPACITY` — maximum number of entries for the global cache;
+//! defaults to `256`. `PYEXEC_BYTECODE_CACHE_SIZE` is the older name and is
+//! consulted only when `PYEXEC_CACHE_CAPACITY` is unset. Setting either to `0`
+//! is treated as `1` (no panic, always keep at least one entry).
+//!
+//! `PYEXEC_CACHE_TTL_SECS` — optional time-to-live for global cache entries, in
+//! seconds. Unset or `0` means entries never expire.
+//!
+//! All are read exactly once, on the first call to [`BytecodeCache::global`];
+//! later changes to the environment have no effect. Use
+//! [`BytecodeCache::set_capacity`] to resize at runtime, or
+//! [`BytecodeCache::init_global`] to install a cache configured in code.
+//!
+//! # Expiry
+//!
+//! A cache built with [`BytecodeCache::with_ttl`] stamps each entry on insert and
+//! on every hit. [`BytecodeCache::get`] treats an entry idle for longer than the
+//! TTL as a miss and removes it; [`BytecodeCache::purge_expired`] sweeps them all.
+//! LRU ordering among live entries is unaffected.
 //!
 //! # Thread safety
 //!
 //! [`BytecodeCache`] wraps its inner LRU cache in a `Mutex` so it can be shared
 //! across threads via the `global()` singleton.
+//!
+//! # Statistics
+//!
+//! Hits, misses, inserts, and evictions are tracked with atomic counters and
+//! reported by [`BytecodeCache::stats`]. Counters are independent of the LRU
+//! contents: [`BytecodeCache::clear`] leaves them untouched, and
+//! [`BytecodeCache::reset_stats`] zeroes them without touching the entries.
+//!
+//! # Persistence
+//!
+//! [`BytecodeCache::save_to`] writes the entries to a file and
+//! [`BytecodeCache::load_from`] preloads them in a fresh process. The file is a
+//! header line `llm-pyexec-bytecode-cache <version>` followed by a JSON array of
+//! `{"key": "<hex>", "value": "..."}` objects, least-recently-used first. Files
+//! with any version other than [`PERSIST_FORMAT_VERSION`] are rejected.
 
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, BufWriter, Write};
 use std::num::NonZeroUsize;
+use std::ops::Deref;
+use std::path::Path;
+use std::str::FromStr;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
 
 use lru::LruCache;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
 use sha2::{Digest, Sha256};
 
+use crate::types::AlreadyInitialized;
+
 /// A 32-byte SHA-256 digest used as a cache key.
-pub type CacheKey = [u8; 32];
+///
+/// The canonical text form is 64 lowercase hex characters: [`Display`](fmt::Display),
+/// [`to_hex`](Self::to_hex) and serde all produce it, and [`FromStr`] /
+/// [`from_hex`](Self::from_hex) accept it (either case). Dereferences to the raw
+/// `[u8; 32]` digest.
+#[derive(Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct CacheKey([u8; 32]);
+
+/// Error returned when parsing a [`CacheKey`] from a hex string fails.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CacheKeyParseError {
+    /// The input was not exactly 64 characters long.
+    InvalidLength {
+        /// Length of the rejected input, in bytes.
+        len: usize,
+    },
+    /// The input contained a character outside `[0-9a-fA-F]`.
+    InvalidHexChar {
+        /// Byte offset of the offending character.
+        index: usize,
+    },
+}
+
+impl fmt::Display for CacheKeyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CacheKeyParseError::InvalidLength { len } => {
+                write!(f, "cache key must be 64 hex characters, got {len}")
+            }
+            CacheKeyParseError::InvalidHexChar { index } => {
+                write!(f, "invalid hex character at index {index}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for CacheKeyParseError {}
+
+impl CacheKey {
+    /// Wrap a raw 32-byte digest.
+    pub const fn from_bytes(bytes: [u8; 32]) -> Self {
+        CacheKey(bytes)
+    }
+
+    /// Return the raw 32-byte digest.
+    pub const fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Encode the key as 64 lowercase hex characters.
+    pub fn to_hex(&self) -> String {
+        const DIGITS: &[u8; 16] = b"0123456789abcdef";
+        let mut out = String::with_capacity(64);
+        for byte in self.0 {
+            out.push(DIGITS[(byte >> 4) as usize] as char);
+            out.push(DIGITS[(byte & 0x0f) as usize] as char);
+        }
+        out
+    }
+
+    /// Decode a key from 64 hex characters (upper- or lowercase).
+    pub fn from_hex(hex: &str) -> Result<Self, CacheKeyParseError> {
+        let raw = hex.as_bytes();
+        if raw.len() != 64 {
+            return Err(CacheKeyParseError::InvalidLength { len: raw.len() });
+        }
+        let nibble = |index: usize| -> Result<u8, CacheKeyParseError> {
+            match raw[index] {
+                c @ b'0'..=b'9' => Ok(c - b'0'),
+                c @ b'a'..=b'f' => Ok(c - b'a' + 10),
+                c @ b'A'..=b'F' => Ok(c - b'A' + 10),
+                _ => Err(CacheKeyParseError::InvalidHexChar { index }),
+            }
+        };
+        let mut bytes = [0u8; 32];
+        for (i, byte) in bytes.iter_mut().enumerate() {
+            *byte = (nibble(2 * i)? << 4) | nibble(2 * i + 1)?;
+        }
+        Ok(CacheKey(bytes))
+    }
+}
+
+impl From<[u8; 32]> for CacheKey {
+    fn from(bytes: [u8; 32]) -> Self {
+        CacheKey(bytes)
+    }
+}
+
+impl Deref for CacheKey {
+    type Target = [u8; 32];
+
+    fn deref(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl AsRef<[u8]> for CacheKey {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+impl fmt::Debug for CacheKey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "CacheKey({})", self.to_hex())
+    }
+}
+
+impl FromStr for CacheKey {
+    type Err = CacheKeyParseError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        CacheKey::from_hex(s)
+    }
+}
+
+impl Serialize for CacheKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_hex())
+    }
+}
+
+impl<'de> Deserialize<'de> for CacheKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let hex = String::deserialize(deserializer)?;
+        CacheKey::from_hex(&hex).map_err(serde::de::Error::custom)
+    }
+}
+
+/// Magic prefix of the header line written by [`BytecodeCache::save_to`].
+const PERSIST_MAGIC: &str = "llm-pyexec-bytecode-cache";
+
+/// Current on-disk format version for [`BytecodeCache::save_to`] / [`BytecodeCache::load_from`].
+pub const PERSIST_FORMAT_VERSION: u32 = 1;
+
+/// One cache entry as stored on disk.
+#[derive(Serialize, Deserialize)]
+struct PersistedEntry {
+    key: CacheKey,
+    value: String,
+}
 
 /// Compute the SHA-256 hash of `source` bytes and return it as a [`CacheKey`].
 ///
+/// Accepts anything byte-like (`&str`, `String`, `&[u8]`, `Vec<u8>`), so byte
+/// sources can be hashed without a lossy UTF-8 conversion. A `&str` and its
+/// `as_bytes()` produce the same key.
+///
 /// The same input always produces the same 32-byte output; different inputs
 /// produce distinct outputs with overwhelming probability.
-pub fn cache_key(source: &str) -> CacheKey {
+pub fn cache_key(source: impl AsRef<[u8]>) -> CacheKey {
     let mut hasher = Sha256::new();
-    hasher.update(source.as_bytes());
-    hasher.finalize().into()
+    hasher.update(source.as_ref());
+    CacheKey(hasher.finalize().into())
+}
+
+/// A point-in-time snapshot of [`BytecodeCache`] counters.
+///
+/// Returned by [`BytecodeCache::stats`]. All counters are cumulative since the
+/// cache was created or since the last [`BytecodeCache::reset_stats`] call.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Number of [`BytecodeCache::get`] calls that found an entry.
+    pub hits: u64,
+    /// Number of [`BytecodeCache::get`] calls that found no entry.
+    pub misses: u64,
+    /// Number of [`BytecodeCache::insert`] calls.
+    pub inserts: u64,
+    /// Number of entries evicted to make room for a new key.
+    pub evictions: u64,
+    /// Number of entries removed because their TTL elapsed.
+    #[serde(default)]
+    pub expirations: u64,
+}
+
+impl CacheStats {
+    /// Fraction of lookups that were hits, in `0.0..=1.0`.
+    ///
+    /// Returns `0.0` when no lookups have been recorded.
+    pub fn hit_ratio(&self) -> f64 {
+        let lookups = self.hits + self.misses;
+        if lookups == 0 {
+            0.0
+        } else {
+            self.hits as f64 / lookups as f64
+        }
+    }
+}
+
+/// A cached value plus the instant it was last inserted or hit.
+struct CacheEntry {
+    value: String,
+    touched: Instant,
 }
 
 /// LRU cache mapping [`CacheKey`] → compiled bytecode `String`.
 ///
-/// Create a local instance with [`BytecodeCache::new`] or obtain the
+/// Create a local instance with [`BytecodeCache::new`] (or
+/// [`BytecodeCache::with_ttl`] for expiring entries) or obtain the
 /// process-wide singleton with [`BytecodeCache::global`].
 pub struct BytecodeCache {
-    inner: Mutex<LruCache<CacheKey, String>>,
-    capacity: usize,
+    inner: Mutex<LruCache<CacheKey, CacheEntry>>,
+    capacity: AtomicUsize,
+    ttl: Option<Duration>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    inserts: AtomicU64,
+    evictions: AtomicU64,
+    expirations: AtomicU64,
 }
 
+/// Backing storage for [`BytecodeCache::global`].
+static GLOBAL_CACHE: OnceLock<BytecodeCache> = OnceLock::new();
+
 impl BytecodeCache {
     /// Create a new [`BytecodeCache`] with the given maximum number of entries.
     ///
     /// `capacity` is clamped to a minimum of `1`; passing `0` is safe and will
-    /// behave as though `capacity == 1`.
+    /// behave as though `capacity == 1`. Entries never expire.
     pub fn new(capacity: usize) -> Self {
+        Self::build(capacity, None)
+    }
+
+    /// Create a new [`BytecodeCache`] whose entries expire after `ttl` without
+    /// being inserted or hit.
+    ///
+    /// `capacity` is clamped exactly as in [`new`](Self::new).
+    pub fn with_ttl(capacity: usize, ttl: Duration) -> Self {
+        Self::build(capacity, Some(ttl))
+    }
+
+    fn build(capacity: usize, ttl: Option<Duration>) -> Self {
         let cap = NonZeroUsize::new(capacity.max(1)).expect("capacity >= 1");
         Self {
             inner: Mutex::new(LruCache::new(cap)),
-            capacity: capacity.max(1),
+            capacity: AtomicUsize::new(capacity.max(1)),
+            ttl,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            inserts: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            expirations: AtomicU64::new(0),
         }
     }
 
     /// Return the process-wide singleton [`BytecodeCache`].
     ///
-    /// The capacity is read once from the `PYEXEC_BYTECODE_CACHE_SIZE`
-    /// environment variable. If the variable is absent or unparseable the
-    /// default capacity of `256` is used. A value of `0` is treated as `1`.
+    /// The capacity is read once from the `PYEXEC_CACHE_CAPACITY` environment
+    /// variable (falling back to the older `PYEXEC_BYTECODE_CACHE_SIZE`). If
+    /// neither is set to a parseable value the default capacity of `256` is
+    /// used. A value of `0` is treated as `1`.
+    ///
+    /// `PYEXEC_CACHE_TTL_SECS`, if set to a positive integer, is read at the same
+    /// time and enables expiry (see [`with_ttl`](Self::with_ttl)).
     pub fn global() -> &'static BytecodeCache {
-        static INSTANCE: OnceLock<BytecodeCache> = OnceLock::new();
-        INSTANCE.get_or_init(|| {
-            let capacity = std::env::var("PYEXEC_BYTECODE_CACHE_SIZE")
-                .ok()
+        GLOBAL_CACHE.get_or_init(|| {
+            let capacity = ["PYEXEC_CACHE_CAPACITY", "PYEXEC_BYTECODE_CACHE_SIZE"]
+                .iter()
+                .find_map(|name| std::env::var(name).ok())
                 .and_then(|v| v.parse::<usize>().ok())
                 .unwrap_or(256);
-            BytecodeCache::new(capacity)
+            let ttl = std::env::var("PYEXEC_CACHE_TTL_SECS")
+                .ok()
+                .and_then(|v| v.parse::<u64>().ok())
+                .filter(|&secs| secs > 0)
+                .map(Duration::from_secs);
+            BytecodeCache::build(capacity, ttl)
         })
     }
 
+    /// Install `cache` as the process-wide singleton returned by
+    /// [`global`](Self::global), in place of the env-configured default.
+    ///
+    /// Must be called before anything calls `global()` — including the first
+    /// cached [`execute`](crate::execute). Fails with [`AlreadyInitialized`]
+    /// once the global exists, in which case `cache` is dropped.
+    pub fn init_global(cache: BytecodeCache) -> Result<(), AlreadyInitialized> {
+        GLOBAL_CACHE.set(cache).map_err(|_| AlreadyInitialized)
+    }
+
+    /// Return the configured time-to-live, or `None` if entries never expire.
+    pub fn ttl(&self) -> Option<Duration> {
+        self.ttl
+    }
+
+    /// Returns `true` if `entry` has outlived the TTL as of `now`.
+    fn is_expired(&self, entry: &CacheEntry, now: Instant) -> bool {
+        self.ttl
+            .is_some_and(|ttl| now.saturating_duration_since(entry.touched) > ttl)
+    }
+
     /// Look up `key` in the cache.
     ///
     /// Returns `Some(bytecode)` on a hit and advances the entry to the most-recently-used
-    /// position; returns `None` on a miss.
+    /// position; returns `None` on a miss. Either outcome is counted in [`stats`](Self::stats).
+    /// An expired entry is removed and reported as a miss.
     pub fn get(&self, key: &CacheKey) -> Option<String> {
-        self.inner
-            .lock()
-            .expect("BytecodeCache mutex poisoned")
-            .get(key)
-            .cloned()
+        let now = Instant::now();
+        let mut inner = self.inner.lock().expect("BytecodeCache mutex poisoned");
+        let expired = inner
+            .peek(key)
+            .is_some_and(|entry| self.is_expired(entry, now));
+        if expired {
+            inner.pop(key);
+            self.expirations.fetch_add(1, Ordering::Relaxed);
+        }
+        let value = inner.get_mut(key).map(|entry| {
+            entry.touched = now;
+            entry.value.clone()
+        });
+        drop(inner);
+
+        if value.is_some() {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        value
     }
 
     /// Insert `key` → `value` into the cache.
     ///
     /// If the cache is already at capacity the least-recently-used entry is
-    /// evicted to make room.
+    /// evicted to make room. Overwriting an existing key is not an eviction.
     pub fn insert(&self, key: CacheKey, value: String) {
-        self.inner
+        let entry = CacheEntry {
+            value,
+            touched: Instant::now(),
+        };
+        let displaced = self
+            .inner
             .lock()
             .expect("BytecodeCache mutex poisoned")
-            .put(key, value);
+            .push(key, entry);
+        self.inserts.fetch_add(1, Ordering::Relaxed);
+        // `push` returns the old entry for an overwrite of the same key, or the
+        // LRU entry it had to drop to make room for a new key.
+        if matches!(displaced, Some((old_key, _)) if old_key != key) {
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
     }
 
     /// Return the number of entries currently in the cache.
@@ -109,7 +427,21 @@ impl BytecodeCache {
 
     /// Return the maximum number of entries the cache can hold before eviction.
     pub fn capacity(&self) -> usize {
-        self.capacity
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    /// Change the maximum number of entries.
+    ///
+    /// Shrinking evicts least-recently-used entries until the cache fits; each
+    /// one is counted in [`CacheStats::evictions`]. `0` is treated as `1`.
+    pub fn set_capacity(&self, capacity: usize) {
+        let capacity = capacity.max(1);
+        let mut inner = self.inner.lock().expect("BytecodeCache mutex poisoned");
+        let before = inner.len();
+        inner.resize(NonZeroUsize::new(capacity).expect("capacity >= 1"));
+        let evicted = before - inner.len();
+        self.capacity.store(capacity, Ordering::Relaxed);
+        self.evictions.fetch_add(evicted as u64, Ordering::Relaxed);
     }
 
     /// Remove all entries from the cache, leaving it empty.
@@ -122,6 +454,128 @@ impl BytecodeCache {
             .expect("BytecodeCache mutex poisoned")
             .clear();
     }
+
+    /// Remove every expired entry and return how many were removed.
+    ///
+    /// A no-op returning `0` for caches without a TTL.
+    pub fn purge_expired(&self) -> usize {
+        if self.ttl.is_none() {
+            return 0;
+        }
+        let now = Instant::now();
+        let mut inner = self.inner.lock().expect("BytecodeCache mutex poisoned");
+        let expired: Vec<CacheKey> = inner
+            .iter()
+            .filter(|(_, entry)| self.is_expired(entry, now))
+            .map(|(key, _)| *key)
+            .collect();
+        for key in &expired {
+            inner.pop(key);
+        }
+        self.expirations
+            .fetch_add(expired.len() as u64, Ordering::Relaxed);
+        expired.len()
+    }
+
+    /// Write every entry to `path`, replacing any existing file.
+    ///
+    /// Entries are written least-recently-used first so that
+    /// [`load_from`](Self::load_from) reproduces the recency order. Returns the
+    /// number of entries written.
+    pub fn save_to(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let entries: Vec<PersistedEntry> = self
+            .inner
+            .lock()
+            .expect("BytecodeCache mutex poisoned")
+            .iter()
+            .rev()
+            .map(|(key, entry)| PersistedEntry {
+                key: *key,
+                value: entry.value.clone(),
+            })
+            .collect();
+
+        let mut out = BufWriter::new(File::create(path)?);
+        writeln!(out, "{PERSIST_MAGIC} {PERSIST_FORMAT_VERSION}")?;
+        serde_json::to_writer(&mut out, &entries)?;
+        out.flush()?;
+        Ok(entries.len())
+    }
+
+    /// Insert every entry from a file written by [`save_to`](Self::save_to).
+    ///
+    /// Existing entries are kept; loaded entries become the most recently used,
+    /// and the usual LRU eviction applies if the file holds more entries than
+    /// the capacity. Loading does not count towards [`stats`](Self::stats).
+    /// Loaded entries start a fresh TTL. Returns the number of entries read.
+    ///
+    /// # Errors
+    ///
+    /// `io::ErrorKind::InvalidData` if the header is missing, names a different
+    /// format version, or the body is malformed.
+    pub fn load_from(&self, path: impl AsRef<Path>) -> io::Result<usize> {
+        let mut reader = BufReader::new(File::open(path)?);
+        let mut header = String::new();
+        reader.read_line(&mut header)?;
+        let version = header
+            .trim_end()
+            .strip_prefix(PERSIST_MAGIC)
+            .and_then(|rest| rest.strip_prefix(' '))
+            .and_then(|v| v.parse::<u32>().ok());
+        match version {
+            Some(PERSIST_FORMAT_VERSION) => {}
+            Some(other) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "unsupported bytecode cache format version {other} \
+                         (expected {PERSIST_FORMAT_VERSION})"
+                    ),
+                ))
+            }
+            None => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "missing llm-pyexec bytecode cache header",
+                ))
+            }
+        }
+
+        let entries: Vec<PersistedEntry> = serde_json::from_reader(reader)?;
+        let count = entries.len();
+        let now = Instant::now();
+        let mut inner = self.inner.lock().expect("BytecodeCache mutex poisoned");
+        for entry in entries {
+            let cached = CacheEntry {
+                value: entry.value,
+                touched: now,
+            };
+            inner.push(entry.key, cached);
+        }
+        Ok(count)
+    }
+
+    /// Return a snapshot of the hit/miss/insert/eviction counters.
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            inserts: self.inserts.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            expirations: self.expirations.load(Ordering::Relaxed),
+        }
+    }
+
+    /// Reset all counters reported by [`stats`](Self::stats) to zero.
+    ///
+    /// Cached entries are not affected.
+    pub fn reset_stats(&self) {
+        self.hits.store(0, Ordering::Relaxed);
+        self.misses.store(0, Ordering::Relaxed);
+        self.inserts.store(0, Ordering::Relaxed);
+        self.evictions.store(0, Ordering::Relaxed);
+        self.expirations.store(0, Ordering::Relaxed);
+    }
 }
 
 // ─── Unit tests ────────────────────────────────────────────────────────────────
@@ -153,6 +607,65 @@ mod tests {
         assert_eq!(key.len(), 32);
     }
 
+    // ── CacheKey hex / serde ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_cache_key_str_and_bytes_agree() {
+        assert_eq!(cache_key("x = 1"), cache_key(b"x = 1".as_slice()));
+        assert_eq!(cache_key("x = 1"), cache_key(String::from("x = 1")));
+    }
+
+    #[test]
+    fn test_cache_key_display_is_lowercase_hex() {
+        // SHA-256("") is a well-known constant.
+        let key = cache_key("");
+        assert_eq!(
+            key.to_string(),
+            "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
+        assert_eq!(key.to_string(), key.to_hex());
+    }
+
+    #[test]
+    fn test_cache_key_hex_round_trip() {
+        let key = cache_key("print('hello')");
+        assert_eq!(CacheKey::from_hex(&key.to_hex()), Ok(key));
+        assert_eq!(key.to_hex().to_uppercase().parse::<CacheKey>(), Ok(key));
+    }
+
+    #[test]
+    fn test_cache_key_serde_json_round_trip() {
+        let key = cache_key("x = 42");
+        let json = serde_json::to_string(&key).expect("serialize CacheKey");
+        assert_eq!(json, format!("\"{}\"", key.to_hex()));
+        let back: CacheKey = serde_json::from_str(&json).expect("deserialize CacheKey");
+        assert_eq!(back, key);
+    }
+
+    #[test]
+    fn test_cache_key_from_str_rejects_wrong_length() {
+        assert_eq!(
+            "abcd".parse::<CacheKey>(),
+            Err(CacheKeyParseError::InvalidLength { len: 4 })
+        );
+        let too_long = "0".repeat(65);
+        assert_eq!(
+            too_long.parse::<CacheKey>(),
+            Err(CacheKeyParseError::InvalidLength { len: 65 })
+        );
+    }
+
+    #[test]
+    fn test_cache_key_from_str_rejects_non_hex() {
+        let mut hex = cache_key("a").to_hex();
+        hex.replace_range(10..11, "g");
+        assert_eq!(
+            hex.parse::<CacheKey>(),
+            Err(CacheKeyParseError::InvalidHexChar { index: 10 })
+        );
+        assert!(serde_json::from_str::<CacheKey>(&format!("\"{hex}\"")).is_err());
+    }
+
     // ── get / insert / len round-trip ────────────────────────────────────────
 
     #[test]
@@ -241,6 +754,179 @@ mod tests {
         assert!(cache.get(&key_c).is_some(), "key_c should survive");
     }
 
+    // ── set_capacity ─────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_set_capacity_shrink_evicts_lru() {
+        let cache = BytecodeCache::new(2);
+        let key_a = cache_key("a");
+        let key_b = cache_key("b");
+        cache.insert(key_a, "A".to_string());
+        cache.insert(key_b, "B".to_string());
+
+        cache.set_capacity(1);
+
+        assert_eq!(cache.capacity(), 1);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.stats().evictions, 1);
+        assert_eq!(cache.get(&key_a), None, "key_a was least recently used");
+        assert!(cache.get(&key_b).is_some(), "key_b should survive");
+
+        // Further inserts respect the new capacity.
+        cache.insert(cache_key("c"), "C".to_string());
+        assert_eq!(cache.len(), 1);
+    }
+
+    #[test]
+    fn test_set_capacity_grow_keeps_entries() {
+        let cache = BytecodeCache::new(1);
+        cache.insert(cache_key("a"), "A".to_string());
+        cache.set_capacity(4);
+        cache.insert(cache_key("b"), "B".to_string());
+        assert_eq!(cache.capacity(), 4);
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.stats().evictions, 0);
+    }
+
+    #[test]
+    fn test_set_capacity_zero_treated_as_one() {
+        let cache = BytecodeCache::new(4);
+        cache.set_capacity(0);
+        assert_eq!(cache.capacity(), 1);
+    }
+
+    // ── TTL expiry ───────────────────────────────────────────────────────────
+
+    #[test]
+    fn test_new_has_no_ttl() {
+        assert_eq!(BytecodeCache::new(4).ttl(), None);
+        assert_eq!(BytecodeCache::new(4).purge_expired(), 0);
+    }
+
+    #[test]
+    fn test_ttl_expired_entry_is_a_miss_and_removed() {
+        let cache = BytecodeCache::with_ttl(4, Duration::from_millis(50));
+        let key = cache_key("x = 1");
+        cache.insert(key, "v".to_string());
+        assert!(cache.get(&key).is_some(), "fresh entry must hit");
+
+        std::thread::sleep(Duration::from_millis(120));
+
+        assert_eq!(cache.get(&key), None, "expired entry must miss");
+        assert_eq!(cache.len(), 0, "expired entry must be removed");
+        let stats = cache.stats();
+        assert_eq!(stats.hits, 1);
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.expirations, 1);
+    }
+
+    #[test]
+    fn test_ttl_hit_refreshes_entry() {
+        let cache = BytecodeCache::with_ttl(4, Duration::from_millis(150));
+        let key = cache_key("x = 1");
+        cache.insert(key, "v".to_string());
+        for _ in 0..3 {
+            std::thread::sleep(Duration::from_millis(60));
+            assert!(cache.get(&key).is_some(), "access must extend the TTL");
+        }
+    }
+
+    #[test]
+    fn test_purge_expired_removes_only_stale_entries() {
+        let cache = BytecodeCache::with_ttl(4, Duration::from_millis(50));
+        cache.insert(cache_key("old_a"), "A".to_string());
+        cache.insert(cache_key("old_b"), "B".to_string());
+        std::thread::sleep(Duration::from_millis(120));
+        let fresh = cache_key("fresh");
+        cache.insert(fresh, "F".to_string());
+
+        assert_eq!(cache.purge_expired(), 2);
+        assert_eq!(cache.len(), 1);
+        assert!(cache.get(&fresh).is_some());
+        assert_eq!(cache.stats().expirations, 2);
+    }
+
+    #[test]
+    fn test_ttl_cache_keeps_lru_order_for_live_entries() {
+        let cache = BytecodeCache::with_ttl(2, Duration::from_secs(60));
+        let key_a = cache_key("a");
+        let key_b = cache_key("b");
+        cache.insert(key_a, "A".to_string());
+        cache.insert(key_b, "B".to_string());
+        let _ = cache.get(&key_a);
+        cache.insert(cache_key("c"), "C".to_string());
+        assert_eq!(cache.get(&key_b), None, "key_b should be evicted (LRU)");
+        assert!(cache.get(&key_a).is_some());
+    }
+
+    // ── save_to / load_from ──────────────────────────────────────────────────
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("llm-pyexec-{}-{name}", std::process::id()))
+    }
+
+    #[test]
+    fn test_save_load_round_trip_preserves_entries() {
+        let path = temp_path("cache-round-trip");
+        let cache = BytecodeCache::new(8);
+        let key_a = cache_key("a");
+        let key_b = cache_key("b");
+        cache.insert(key_a, "A".to_string());
+        cache.insert(key_b, "B\nwith \"quotes\"".to_string());
+        assert_eq!(cache.save_to(&path).expect("save"), 2);
+
+        let restored = BytecodeCache::new(8);
+        assert_eq!(restored.load_from(&path).expect("load"), 2);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(restored.len(), 2);
+        assert_eq!(restored.get(&key_a), Some("A".to_string()));
+        assert_eq!(restored.get(&key_b), Some("B\nwith \"quotes\"".to_string()));
+    }
+
+    #[test]
+    fn test_load_preserves_recency_order() {
+        let path = temp_path("cache-recency");
+        let cache = BytecodeCache::new(2);
+        let key_a = cache_key("a");
+        let key_b = cache_key("b");
+        cache.insert(key_a, "A".to_string());
+        cache.insert(key_b, "B".to_string());
+        let _ = cache.get(&key_a); // key_b is now LRU
+        cache.save_to(&path).expect("save");
+
+        let restored = BytecodeCache::new(2);
+        restored.load_from(&path).expect("load");
+        let _ = std::fs::remove_file(&path);
+
+        restored.insert(cache_key("c"), "C".to_string());
+        assert_eq!(restored.get(&key_b), None, "key_b should be evicted first");
+        assert!(restored.get(&key_a).is_some());
+    }
+
+    #[test]
+    fn test_load_rejects_bad_version_header() {
+        let path = temp_path("cache-bad-version");
+        std::fs::write(&path, "llm-pyexec-bytecode-cache 999\n[]").expect("write");
+        let err = BytecodeCache::new(4)
+            .load_from(&path)
+            .expect_err("must reject");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+        assert!(err.to_string().contains("999"), "error: {err}");
+    }
+
+    #[test]
+    fn test_load_rejects_missing_header() {
+        let path = temp_path("cache-no-header");
+        std::fs::write(&path, "[]").expect("write");
+        let err = BytecodeCache::new(4)
+            .load_from(&path)
+            .expect_err("must reject");
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(err.kind(), io::ErrorKind::InvalidData);
+    }
+
     // ── Edge cases ───────────────────────────────────────────────────────────
 
     #[test]
@@ -296,6 +982,85 @@ mod tests {
         assert_eq!(cache.get(&key), Some("v2".to_string()));
     }
 
+    // ── stats / reset_stats ──────────────────────────────────────────────────
+
+    #[test]
+    fn test_stats_fresh_cache_is_zero() {
+        let cache = BytecodeCache::new(4);
+        assert_eq!(cache.stats(), CacheStats::default());
+    }
+
+    #[test]
+    fn test_stats_counts_hits_misses_and_inserts() {
+        let cache = BytecodeCache::new(4);
+        let key = cache_key("x = 1");
+        assert_eq!(cache.get(&key), None);
+        cache.insert(key, "v".to_string());
+        assert!(cache.get(&key).is_some());
+        assert!(cache.get(&key).is_some());
+
+        let stats = cache.stats();
+        assert_eq!(stats.misses, 1);
+        assert_eq!(stats.hits, 2);
+        assert_eq!(stats.inserts, 1);
+        assert_eq!(stats.evictions, 0);
+    }
+
+    #[test]
+    fn test_stats_overwrite_is_not_an_eviction() {
+        let cache = BytecodeCache::new(1);
+        let key = cache_key("same");
+        cache.insert(key, "v1".to_string());
+        cache.insert(key, "v2".to_string());
+        assert_eq!(cache.stats().inserts, 2);
+        assert_eq!(cache.stats().evictions, 0);
+    }
+
+    #[test]
+    fn test_stats_eviction_counted_with_capacity_one() {
+        let cache = BytecodeCache::new(1);
+        let key_a = cache_key("a");
+        let key_b = cache_key("b");
+
+        cache.insert(key_a, "A".to_string());
+        cache.insert(key_b, "B".to_string()); // evicts key_a
+        assert_eq!(cache.get(&key_a), None);
+        assert!(cache.get(&key_b).is_some());
+
+        assert_eq!(
+            cache.stats(),
+            CacheStats {
+                hits: 1,
+                misses: 1,
+                inserts: 2,
+                evictions: 1,
+                expirations: 0,
+            }
+        );
+    }
+
+    #[test]
+    fn test_hit_ratio() {
+        assert_eq!(CacheStats::default().hit_ratio(), 0.0);
+        let stats = CacheStats {
+            hits: 3,
+            misses: 1,
+            ..CacheStats::default()
+        };
+        assert_eq!(stats.hit_ratio(), 0.75);
+    }
+
+    #[test]
+    fn test_reset_stats_zeroes_counters_but_keeps_entries() {
+        let cache = BytecodeCache::new(4);
+        let key = cache_key("a");
+        cache.insert(key, "A".to_string());
+        let _ = cache.get(&key);
+        cache.reset_stats();
+        assert_eq!(cache.stats(), CacheStats::default());
+        assert_eq!(cache.len(), 1, "reset_stats must not clear entries");
+    }
+
     // ── Thread safety ────────────────────────────────────────────────────────
 
     #[test]
@@ -0,0 +1,190 @@
+//! Cancelling a running call from another thread.
+//!
+//! [`execute_cancellable`](crate::execute_cancellable) runs a snippet on a
+//! background thread and returns an [`ExecutionHandle`] alongside the
+//! [`PendingExecution`]. [`ExecutionHandle::cancel`] stops the snippet at its
+//! next bytecode instruction, and the call reports
+//! [`ExecutionError::Cancelled`](crate::ExecutionError::Cancelled).
+//!
+//! ## Mechanism
+//!
+//! Cancellation uses the interpreter's user-signal channel, like the CPU budget
+//! in [`crate::cpu_time`]. While the call runs, the interpreter's sender is
+//! registered with the handle; `cancel` sends a check that raises
+//! `KeyboardInterrupt` on the VM thread and keeps raising it at every
+//! following instruction, so neither `except Exception` nor
+//! `except BaseException` in user code can swallow it. Nothing is sent until
+//! `cancel` is called, so calls that are never cancelled pay nothing.
+//!
+//! The exception unwinds the snippet like any other uncaught error: a pooled
+//! interpreter finishes the work item normally and goes back to the pool. A
+//! call cancelled before it reaches an interpreter does not run at all.
+//!
+//! Checks only run between instructions, so a single long-running native call
+//! finishes before the snippet stops; the wall-clock timeout still bounds the
+//! call as a whole.
+
+use std::fmt;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::JoinHandle;
+
+use rustpython_vm::signal::UserSignalSender;
+
+use crate::types::ExecutionResult;
+
+/// State shared by every clone of an [`ExecutionHandle`] and the call it
+/// controls.
+#[derive(Default)]
+struct CancelState {
+    /// Set by [`ExecutionHandle::cancel`].
+    requested: AtomicBool,
+    /// Signal channel of the interpreter running the call, while it runs.
+    /// Cleared when the call ends, so checks still queued in a pooled
+    /// interpreter's channel do nothing on its next call.
+    running: Mutex<Option<UserSignalSender>>,
+    /// Set by the check that raised.
+    interrupted: AtomicBool,
+}
+
+impl CancelState {
+    fn running(&self) -> MutexGuard<'_, Option<UserSignalSender>> {
+        self.running.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+}
+
+/// Cancels the call started by [`execute_cancellable`](crate::execute_cancellable).
+///
+/// Clones control the same call, so the handle can be handed to another
+/// thread. Cancelling a call that already finished does nothing.
+#[derive(Clone)]
+pub struct ExecutionHandle {
+    state: Arc<CancelState>,
+}
+
+impl ExecutionHandle {
+    /// A handle for a call that has not started yet.
+    pub(crate) fn new() -> Self {
+        Self {
+            state: Arc::default(),
+        }
+    }
+
+    /// Stop the call at its next bytecode instruction, or before it runs if it
+    /// has not reached an interpreter yet.
+    pub fn cancel(&self) {
+        self.state.requested.store(true, Ordering::SeqCst);
+        if let Some(signals) = self.state.running().as_ref() {
+            interrupt(Arc::clone(&self.state), signals.clone());
+        }
+    }
+
+    /// Whether [`cancel`](Self::cancel) has been called.
+    pub fn is_cancelled(&self) -> bool {
+        self.state.requested.load(Ordering::SeqCst)
+    }
+
+    /// Register the interpreter about to run the call, interrupting it right
+    /// away if the call was already cancelled.
+    ///
+    /// Must be called on the thread that runs the code.
+    pub(crate) fn attach(&self, signals: &UserSignalSender) -> CancelGuard {
+        let mut running = self.state.running();
+        *running = Some(signals.clone());
+        if self.is_cancelled() {
+            interrupt(Arc::clone(&self.state), signals.clone());
+        }
+        CancelGuard {
+            state: Arc::clone(&self.state),
+        }
+    }
+}
+
+impl fmt::Debug for ExecutionHandle {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("ExecutionHandle")
+            .field("cancelled", &self.is_cancelled())
+            .finish()
+    }
+}
+
+/// Send a check that raises `KeyboardInterrupt` while the call is running and
+/// queues itself again, so the next instruction raises too.
+fn interrupt(state: Arc<CancelState>, signals: UserSignalSender) {
+    let _ = signals.clone().send(Box::new(move |vm| {
+        if state.running().is_none() {
+            return Ok(());
+        }
+        state.interrupted.store(true, Ordering::SeqCst);
+        interrupt(Arc::clone(&state), signals);
+        Err(vm.new_exception_msg(
+            vm.ctx.exceptions.keyboard_interrupt.to_owned(),
+            "execution cancelled".to_owned(),
+        ))
+    }));
+}
+
+/// A running call's registration with its [`ExecutionHandle`]; unregisters on
+/// drop.
+pub(crate) struct CancelGuard {
+    state: Arc<CancelState>,
+}
+
+impl CancelGuard {
+    /// Stop accepting cancellation. Returns `true` if the call was interrupted.
+    pub(crate) fn finish(self) -> bool {
+        self.state.interrupted.load(Ordering::SeqCst)
+    }
+}
+
+impl Drop for CancelGuard {
+    fn drop(&mut self) {
+        *self.state.running() = None;
+    }
+}
+
+/// The result of a call started by
+/// [`execute_cancellable`](crate::execute_cancellable), still being computed.
+#[derive(Debug)]
+pub struct PendingExecution {
+    thread: JoinHandle<ExecutionResult>,
+}
+
+impl PendingExecution {
+    pub(crate) fn new(thread: JoinHandle<ExecutionResult>) -> Self {
+        Self { thread }
+    }
+
+    /// Whether the call has finished, so [`join`](Self::join) will not block.
+    pub fn is_finished(&self) -> bool {
+        self.thread.is_finished()
+    }
+
+    /// Block until the call finishes and return its result.
+    ///
+    /// A cancelled call returns as soon as the snippet stops, with
+    /// [`ExecutionError::Cancelled`](crate::ExecutionError::Cancelled).
+    ///
+    /// # Panics
+    ///
+    /// Re-raises a panic from the thread running the call.
+    pub fn join(self) -> ExecutionResult {
+        self.thread
+            .join()
+            .unwrap_or_else(|payload| std::panic::resume_unwind(payload))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cancel_before_attach_is_remembered() {
+        let handle = ExecutionHandle::new();
+        assert!(!handle.is_cancelled());
+        handle.clone().cancel();
+        assert!(handle.is_cancelled());
+        assert_eq!(format!("{handle:?}"), "ExecutionHandle { cancelled: true }");
+    }
+}
@@ -0,0 +1,156 @@
+//! Two-phase execution: compile up front, run later.
+//!
+//! [`CompiledSnippet::compile`] does the work [`execute`](crate::execute) would
+//! do before touching the pool — last-expression wrapping, syntax validation,
+//! and bytecode cache insertion — on the calling thread, without building or
+//! checking out an interpreter. [`CompiledSnippet::execute`] then dispatches the
+//! prepared source through the normal pool path.
+//!
+//! Settings that shape compilation (currently `use_cache`, `compile_timeout_ns`,
+//! `result_capture` and `wrap_last_expr`) are fixed at compile time. Runtime limits can be
+//! overridden per run with [`ExecutionOverrides`].
+//!
+//! A `CompiledSnippet` is `Send + Sync` and cloning it only bumps a reference
+//! count, so it can be prepared on one thread and executed on others, including
+//! concurrently.
+//!
+//! The snippet also keeps the source's [`CacheKey`], so runs skip hashing. To
+//! replace the settings wholesale rather than override a few limits, use
+//! [`execute_compiled`](crate::execute_compiled); [`compile`](crate::compile)
+//! is the matching shorthand for compiling with default settings.
+
+use std::sync::Arc;
+use std::time::Instant;
+
+use crate::cache::{BytecodeCache, CacheKey};
+use crate::executor::{prepare_source, run_keyed, PreparedSource};
+use crate::types::{ExecutionError, ExecutionResult, ExecutionSettings};
+use crate::vm::compile_check;
+
+/// Runtime limits that may differ from the ones a snippet was compiled with.
+///
+/// `None` keeps the compile-time value.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ExecutionOverrides {
+    /// Replaces [`ExecutionSettings::timeout_ns`].
+    pub timeout_ns: Option<u64>,
+    /// Replaces [`ExecutionSettings::max_output_bytes`].
+    pub max_output_bytes: Option<usize>,
+    /// Replaces [`ExecutionSettings::allowed_modules`].
+    pub allowed_modules: Option<Vec<String>>,
+}
+
+/// A snippet that has been wrapped and syntax-checked, ready to run.
+#[derive(Debug, Clone)]
+pub struct CompiledSnippet {
+    inner: Arc<CompiledInner>,
+}
+
+#[derive(Debug)]
+struct CompiledInner {
+    prepared: PreparedSource,
+    key: CacheKey,
+    settings: ExecutionSettings,
+}
+
+impl CompiledSnippet {
+    /// Wrap and syntax-check `code`, and insert it into the global
+    /// [`BytecodeCache`] unless `settings.use_cache` is `false`.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`ExecutionError::SyntaxError`] if the source does not compile.
+    /// No interpreter is involved either way.
+    pub fn compile(code: &str, settings: ExecutionSettings) -> Result<Self, ExecutionError> {
+        let prepared = prepare_source(code, &settings);
+        compile_check(&prepared.wrapped)?;
+        let key = prepared.cache_key();
+        if settings.use_cache {
+            BytecodeCache::global().insert(key, prepared.wrapped.clone());
+        }
+        Ok(Self {
+            inner: Arc::new(CompiledInner {
+                prepared,
+                key,
+                settings,
+            }),
+        })
+    }
+
+    /// Run the snippet with the compile-time settings, adjusted by `overrides`.
+    ///
+    /// Behaves like [`execute`](crate::execute) on the original source;
+    /// `duration_ns` covers only this call.
+    pub fn execute(&self, overrides: ExecutionOverrides) -> ExecutionResult {
+        let start = Instant::now();
+        let mut settings = self.inner.settings.clone();
+        if let Some(timeout_ns) = overrides.timeout_ns {
+            settings.timeout_ns = timeout_ns;
+        }
+        if let Some(max_output_bytes) = overrides.max_output_bytes {
+            settings.max_output_bytes = max_output_bytes;
+        }
+        if let Some(allowed_modules) = overrides.allowed_modules {
+            settings.allowed_modules = allowed_modules;
+        }
+        let prepared = self.inner.prepared.clone();
+        run_keyed(prepared, self.inner.key, &settings, start)
+    }
+
+    /// The source as it will run, after last-expression wrapping (if any; see
+    /// [`ExecutionSettings::result_capture`]).
+    pub fn source(&self) -> &str {
+        &self.inner.prepared.wrapped
+    }
+
+    /// The wrapped source together with where its result variable was inserted.
+    pub(crate) fn prepared(&self) -> &PreparedSource {
+        &self.inner.prepared
+    }
+
+    /// The key [`source`](Self::source) is cached under, computed at compile
+    /// time: its [`cache_key`](crate::cache::cache_key), unless a final call's `None` result is
+    /// reported (see [`ExecutionSettings::capture_call_results`]).
+    pub fn cache_key(&self) -> CacheKey {
+        self.inner.key
+    }
+
+    /// The settings the snippet was compiled with.
+    pub fn settings(&self) -> &ExecutionSettings {
+        &self.inner.settings
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cache::cache_key;
+    use crate::executor::maybe_wrap_last_expr;
+
+    fn assert_send_sync<T: Send + Sync>() {}
+
+    #[test]
+    fn test_compiled_snippet_is_send_sync() {
+        assert_send_sync::<CompiledSnippet>();
+    }
+
+    #[test]
+    fn test_compile_wraps_last_expression() {
+        let snippet =
+            CompiledSnippet::compile("x = 2\nx * 3", ExecutionSettings::default()).unwrap();
+        assert_eq!(snippet.source(), maybe_wrap_last_expr("x = 2\nx * 3"));
+    }
+
+    #[test]
+    fn test_cache_key_matches_source() {
+        let snippet = CompiledSnippet::compile("1 + 1", ExecutionSettings::default()).unwrap();
+        assert_eq!(snippet.cache_key(), cache_key(snippet.source()));
+    }
+
+    #[test]
+    fn test_compile_rejects_syntax_error() {
+        let err = CompiledSnippet::compile("def f(:", ExecutionSettings::default())
+            .expect_err("syntax error must surface at compile time");
+        assert!(matches!(err, ExecutionError::SyntaxError { .. }));
+    }
+}
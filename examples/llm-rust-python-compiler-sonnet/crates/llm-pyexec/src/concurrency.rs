@@ -0,0 +1,135 @@
+//! Ceiling on interpreters running outside the pool.
+//!
+//! Pool slots are bounded by the pool size, but every call the pool cannot
+//! serve (fallback under [`ExecutionMode::PoolPreferred`](crate::ExecutionMode::PoolPreferred),
+//! or [`ExecutionMode::Isolated`](crate::ExecutionMode::Isolated)) builds a fresh
+//! interpreter on its own thread. [`ConcurrencyLimit::global`] caps how many of
+//! those exist at once; a call that would exceed the cap fails with
+//! [`ExecutionError::Overloaded`](crate::ExecutionError::Overloaded) without
+//! running.
+//!
+//! A permit is held by the interpreter thread, not the caller, so a call that
+//! timed out keeps counting until its abandoned thread actually finishes.
+//!
+//! # Environment variables
+//!
+//! `PYEXEC_MAX_CONCURRENCY` — the global ceiling, read once on the first call
+//! to [`ConcurrencyLimit::global`]. Defaults to twice the pool size
+//! (`PYEXEC_POOL_SIZE`, default 4). `0` is treated as `1`. Use
+//! [`ConcurrencyLimit::set_max`] to change it at runtime.
+
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::OnceLock;
+
+use crate::pool::InterpreterPoolBuilder;
+
+/// Counting semaphore for fresh (non-pooled) interpreters.
+#[derive(Debug)]
+pub struct ConcurrencyLimit {
+    max: AtomicUsize,
+    active: AtomicUsize,
+}
+
+/// Backing storage for [`ConcurrencyLimit::global`].
+static GLOBAL_LIMIT: OnceLock<ConcurrencyLimit> = OnceLock::new();
+
+impl ConcurrencyLimit {
+    /// Create a limit allowing `max` concurrent fresh interpreters. A `max` of
+    /// 0 is treated as 1.
+    pub fn new(max: usize) -> Self {
+        Self {
+            max: AtomicUsize::new(max.max(1)),
+            active: AtomicUsize::new(0),
+        }
+    }
+
+    /// Return the process-wide limit used by [`execute`](crate::execute) and
+    /// [`execute_with`](crate::execute_with).
+    pub fn global() -> &'static ConcurrencyLimit {
+        GLOBAL_LIMIT.get_or_init(|| {
+            let max = std::env::var("PYEXEC_MAX_CONCURRENCY")
+                .ok()
+                .and_then(|v| v.parse::<usize>().ok())
+                .unwrap_or_else(|| InterpreterPoolBuilder::from_env().target_size() * 2);
+            ConcurrencyLimit::new(max)
+        })
+    }
+
+    /// The ceiling on concurrent fresh interpreters.
+    pub fn max(&self) -> usize {
+        self.max.load(Ordering::SeqCst)
+    }
+
+    /// Change the ceiling. Interpreters already running are not interrupted;
+    /// lowering the ceiling below [`active`](Self::active) only refuses new
+    /// calls until enough of them finish. `0` is treated as `1`.
+    pub fn set_max(&self, max: usize) {
+        self.max.store(max.max(1), Ordering::SeqCst);
+    }
+
+    /// Number of fresh interpreters currently holding a permit.
+    pub fn active(&self) -> usize {
+        self.active.load(Ordering::SeqCst)
+    }
+
+    /// Take a permit if fewer than [`max`](Self::max) are held. The permit is
+    /// released when dropped.
+    pub(crate) fn try_acquire(&self) -> Option<ConcurrencyPermit<'_>> {
+        self.active
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |active| {
+                (active < self.max()).then_some(active + 1)
+            })
+            .ok()
+            .map(|_| ConcurrencyPermit { limit: self })
+    }
+}
+
+/// A held slot in a [`ConcurrencyLimit`]; releases it on drop.
+#[derive(Debug)]
+pub(crate) struct ConcurrencyPermit<'a> {
+    limit: &'a ConcurrencyLimit,
+}
+
+impl Drop for ConcurrencyPermit<'_> {
+    fn drop(&mut self) {
+        self.limit.active.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_acquire_up_to_max_then_refuse() {
+        let limit = ConcurrencyLimit::new(2);
+        let first = limit.try_acquire().expect("first permit");
+        let _second = limit.try_acquire().expect("second permit");
+        assert!(limit.try_acquire().is_none());
+        assert_eq!(limit.active(), 2);
+
+        drop(first);
+        assert_eq!(limit.active(), 1);
+        assert!(limit.try_acquire().is_some());
+    }
+
+    #[test]
+    fn test_zero_max_is_one() {
+        let limit = ConcurrencyLimit::new(0);
+        assert_eq!(limit.max(), 1);
+        limit.set_max(0);
+        assert_eq!(limit.max(), 1);
+    }
+
+    /// Lowering the ceiling refuses new permits but keeps existing ones.
+    #[test]
+    fn test_set_max_below_active() {
+        let limit = ConcurrencyLimit::new(3);
+        let permits: Vec<_> = (0..3).filter_map(|_| limit.try_acquire()).collect();
+        limit.set_max(1);
+        assert!(limit.try_acquire().is_none());
+        drop(permits);
+        assert_eq!(limit.active(), 0);
+        assert!(limit.try_acquire().is_some());
+    }
+}
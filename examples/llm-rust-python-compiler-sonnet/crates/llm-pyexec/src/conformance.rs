@@ -0,0 +1,334 @@
+//! Sandbox conformance probes for downstream embedders (feature `conformance`).
+//!
+//! Each probe is an adversarial Python snippet (or an attack/check pair of
+//! snippets) executed through the normal [`execute`] path with the caller's
+//! [`ExecutionSettings`]. The resulting [`ConformanceReport`] says which probes
+//! the configuration resisted, with a human-readable detail string for each,
+//! so CI in a downstream repository can gate on [`ConformanceReport::all_passed`].
+//!
+//! ## Probes
+//!
+//! | name | attack |
+//! |---|---|
+//! | `import_hook_restore` | replace `builtins.__import__`, then import a denied module in a later call |
+//! | `importlib_bypass` | `importlib.import_module("socket")` |
+//! | `name_spoofing` | forge `__name__` (module globals or `__import__` globals argument) |
+//! | `sys_modules_poisoning` | plant a fake `json` in `sys.modules` for a later call |
+//! | `builtins_escape` | call any other import-like callable reachable from builtins |
+//! | `environ_leak` | read the host `PATH` through `os.environ` |
+//! | `baseline_module_mutation` | mutate a pre-loaded module (`sys`) for a later call |
+//! | `io_file_escape` | read a host file through `io.open`, `io.open_code` or `io.FileIO` |
+//! | `denied_attribute_bypass` | reach a denied attribute, for `os` also through `posix` / `nt` in `sys.modules` |
+//!
+//! Cross-call probes run the attack and the check once per pool slot so that
+//! every warm interpreter is exercised.
+//!
+//! New sandbox hardening features must add a probe here.
+
+use serde::Serialize;
+
+use crate::executor::execute;
+use crate::modules::build_denied_attributes;
+use crate::pool::InterpreterPool;
+use crate::types::{ExecutionError, ExecutionResult, ExecutionSettings};
+
+/// The module every import probe tries to reach.
+const TARGET_MODULE: &str = "socket";
+
+/// A probe returns `Ok(detail)` if the configuration resisted it, `Err(detail)` otherwise.
+type Probe = fn(&ExecutionSettings) -> Result<String, String>;
+
+/// Outcome of a single probe.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ProbeOutcome {
+    /// Stable probe identifier (see the module-level table).
+    pub name: &'static str,
+    /// `true` if the configuration resisted the attack.
+    pub passed: bool,
+    /// What was observed, suitable for CI logs.
+    pub detail: String,
+}
+
+/// Results of running every probe against one configuration.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConformanceReport {
+    /// One entry per probe, in a stable order.
+    pub probes: Vec<ProbeOutcome>,
+}
+
+impl ConformanceReport {
+    /// Returns `true` if every probe passed.
+    pub fn all_passed(&self) -> bool {
+        self.probes.iter().all(|p| p.passed)
+    }
+
+    /// Iterate over the probes that failed.
+    pub fn failures(&self) -> impl Iterator<Item = &ProbeOutcome> {
+        self.probes.iter().filter(|p| !p.passed)
+    }
+
+    /// Look up a probe outcome by name.
+    pub fn get(&self, name: &str) -> Option<&ProbeOutcome> {
+        self.probes.iter().find(|p| p.name == name)
+    }
+}
+
+/// Run every probe against `settings` and collect the outcomes.
+pub fn run(settings: &ExecutionSettings) -> ConformanceReport {
+    let probes: &[(&'static str, Probe)] = &[
+        ("import_hook_restore", probe_import_hook_restore),
+        ("importlib_bypass", probe_importlib_bypass),
+        ("name_spoofing", probe_name_spoofing),
+        ("sys_modules_poisoning", probe_sys_modules_poisoning),
+        ("builtins_escape", probe_builtins_escape),
+        ("environ_leak", probe_environ_leak),
+        ("baseline_module_mutation", probe_baseline_module_mutation),
+        ("io_file_escape", probe_io_file_escape),
+        ("denied_attribute_bypass", probe_denied_attribute_bypass),
+    ];
+
+    let probes = probes
+        .iter()
+        .map(|(name, probe)| {
+            let (passed, detail) = match probe(settings) {
+                Ok(detail) => (true, detail),
+                Err(detail) => (false, detail),
+            };
+            ProbeOutcome {
+                name: *name,
+                passed,
+                detail,
+            }
+        })
+        .collect();
+
+    ConformanceReport { probes }
+}
+
+// ── Probes ────────────────────────────────────────────────────────────────────
+
+fn probe_import_hook_restore(settings: &ExecutionSettings) -> Result<String, String> {
+    let attack = "\
+b = __builtins__ if isinstance(__builtins__, dict) else __builtins__.__dict__
+b['__import__'] = lambda *a, **k: None
+";
+    let check = format!("import {TARGET_MODULE}");
+    for _ in 0..rounds() {
+        let _ = execute(attack, settings.clone());
+    }
+    for _ in 0..rounds() {
+        expect_denied(&execute(&check, settings.clone())).map_err(|got| {
+            format!("import of '{TARGET_MODULE}' not denied after __import__ replacement: {got}")
+        })?;
+    }
+    Ok(format!(
+        "import of '{TARGET_MODULE}' still denied after __import__ replacement"
+    ))
+}
+
+fn probe_importlib_bypass(settings: &ExecutionSettings) -> Result<String, String> {
+    let code = format!("import importlib\nimportlib.import_module('{TARGET_MODULE}')");
+    expect_denied(&execute(&code, settings.clone()))
+        .map(|denied| format!("importlib path denied: {denied}"))
+        .map_err(|got| format!("importlib.import_module('{TARGET_MODULE}') was not denied: {got}"))
+}
+
+fn probe_name_spoofing(settings: &ExecutionSettings) -> Result<String, String> {
+    let forged_module = format!("__name__ = 'json'\nimport {TARGET_MODULE}");
+    expect_denied(&execute(&forged_module, settings.clone()))
+        .map_err(|got| format!("forged module __name__ bypassed the allowlist: {got}"))?;
+
+    let forged_globals = format!("__import__('{TARGET_MODULE}', {{'__name__': 'json'}})");
+    expect_denied(&execute(&forged_globals, settings.clone()))
+        .map_err(|got| format!("forged __import__ globals bypassed the allowlist: {got}"))?;
+
+    Ok("forged __name__ in module and __import__ globals both denied".to_string())
+}
+
+fn probe_sys_modules_poisoning(settings: &ExecutionSettings) -> Result<String, String> {
+    let attack = "import sys\nsys.modules['json'] = type(sys)('json')";
+    let check = "import json\nprint(json.dumps([1]))";
+    for _ in 0..rounds() {
+        let _ = execute(attack, settings.clone());
+    }
+    for _ in 0..rounds() {
+        let result = execute(check, settings.clone());
+        if result.error.is_some() || result.stdout != "[1]\n" {
+            return Err(format!(
+                "planted sys.modules['json'] survived into a later call: {}",
+                describe(&result)
+            ));
+        }
+    }
+    Ok("sys.modules entries planted by one call do not reach the next".to_string())
+}
+
+fn probe_builtins_escape(settings: &ExecutionSettings) -> Result<String, String> {
+    let code = format!(
+        "\
+b = __builtins__ if isinstance(__builtins__, dict) else __builtins__.__dict__
+leaked = []
+for name, value in list(b.items()):
+    if 'import' in name and name != '__import__' and callable(value):
+        try:
+            mod = value('{TARGET_MODULE}')
+        except Exception:
+            continue
+        if getattr(mod, '__name__', None) == '{TARGET_MODULE}':
+            leaked.append(name)
+print(','.join(sorted(leaked)))
+"
+    );
+    let result = execute(&code, settings.clone());
+    match &result.error {
+        None if result.stdout.trim().is_empty() => {
+            Ok("no unfiltered importer reachable from builtins".to_string())
+        }
+        None => Err(format!(
+            "builtins expose unfiltered importer(s): {}",
+            result.stdout.trim()
+        )),
+        Some(_) => expect_denied(&result)
+            .map(|denied| format!("builtins scan denied: {denied}"))
+            .map_err(|got| format!("builtins scan failed unexpectedly: {got}")),
+    }
+}
+
+fn probe_environ_leak(settings: &ExecutionSettings) -> Result<String, String> {
+    let code = "import os\nprint(os.environ.get('PATH') is not None)";
+    let result = execute(code, settings.clone());
+    match &result.error {
+        None if result.stdout.trim() == "True" => {
+            Err("host environment readable via os.environ".to_string())
+        }
+        None => Ok("os.environ does not expose the host environment".to_string()),
+        Some(_) => Ok(format!("environment access blocked: {}", describe(&result))),
+    }
+}
+
+fn probe_baseline_module_mutation(settings: &ExecutionSettings) -> Result<String, String> {
+    let attack = "import sys\nsys.pyexec_conformance_marker = 1";
+    let check = "import sys\nprint(hasattr(sys, 'pyexec_conformance_marker'))";
+    for _ in 0..rounds() {
+        let _ = execute(attack, settings.clone());
+    }
+    for _ in 0..rounds() {
+        let result = execute(check, settings.clone());
+        if result.stdout.trim() == "True" {
+            return Err("attribute set on sys by one call is visible to the next".to_string());
+        }
+    }
+    Ok("baseline module mutations do not leak between calls".to_string())
+}
+
+fn probe_io_file_escape(settings: &ExecutionSettings) -> Result<String, String> {
+    // `os.path` is the host's posixpath / ntpath module, so its file exists.
+    let code = "\
+import io
+import os.path
+path = os.path.__file__
+leaked = []
+for name, read in (
+    ('open', lambda: io.open(path, 'rb').read(1)),
+    ('open_code', lambda: io.open_code(path).read(1)),
+    ('FileIO', lambda: io.FileIO(path).read(1)),
+):
+    try:
+        if read():
+            leaked.append(name)
+    except Exception:
+        pass
+print(','.join(leaked))
+";
+    let result = execute(code, settings.clone());
+    match &result.error {
+        None if result.stdout.trim().is_empty() => Ok("io cannot read host files".to_string()),
+        None => Err(format!(
+            "host file readable through io.{}",
+            result.stdout.trim()
+        )),
+        Some(_) => Ok(format!("io file access blocked: {}", describe(&result))),
+    }
+}
+
+fn probe_denied_attribute_bypass(settings: &ExecutionSettings) -> Result<String, String> {
+    let denied = build_denied_attributes(settings);
+    if denied.is_empty() {
+        return Ok("no attributes denied".to_string());
+    }
+    let pairs: Vec<String> = denied
+        .iter()
+        .map(|(module, attribute)| format!("({module:?}, {attribute:?})"))
+        .collect();
+    let code = format!(
+        "\
+import sys
+leaked = []
+for module_name, attribute in [{}]:
+    try:
+        __import__(module_name)
+    except ImportError:
+        continue
+    owners = [sys.modules.get(module_name)]
+    if module_name == 'os':
+        owners += [sys.modules.get('posix'), sys.modules.get('nt')]
+    for owner in owners:
+        if owner is None:
+            continue
+        try:
+            value = getattr(owner, attribute)
+            name = getattr(value, '__name__', None)
+        except (AttributeError, PermissionError):
+            continue
+        if name != '__pyexec_denied_attribute__':
+            leaked.append(owner.__name__ + '.' + attribute)
+print(','.join(leaked))
+",
+        pairs.join(", ")
+    );
+    let result = execute(&code, settings.clone());
+    match &result.error {
+        None if result.stdout.trim().is_empty() => Ok(format!(
+            "{} denied attribute(s) guarded, including through sys.modules",
+            denied.len()
+        )),
+        None => Err(format!(
+            "denied attribute(s) reachable: {}",
+            result.stdout.trim()
+        )),
+        Some(_) => Ok(format!(
+            "denied attribute probe blocked: {}",
+            describe(&result)
+        )),
+    }
+}
+
+// ── Helpers ───────────────────────────────────────────────────────────────────
+
+/// Number of repetitions for cross-call probes: one per pool slot.
+fn rounds() -> usize {
+    InterpreterPool::global().size()
+}
+
+/// `Ok(description)` if `result` was stopped by the module allowlist or as a
+/// forbidden module, `Err(description)` otherwise.
+fn expect_denied(result: &ExecutionResult) -> Result<String, String> {
+    match &result.error {
+        Some(ExecutionError::ModuleNotAllowed { module_name }) => {
+            Ok(format!("ModuleNotAllowed({module_name})"))
+        }
+        Some(ExecutionError::ModuleForbidden { module_name, .. }) => {
+            Ok(format!("ModuleForbidden({module_name})"))
+        }
+        _ => Err(describe(result)),
+    }
+}
+
+/// Short description of an execution outcome for detail strings.
+fn describe(result: &ExecutionResult) -> String {
+    match &result.error {
+        None => format!("succeeded with stdout {:?}", result.stdout),
+        Some(ExecutionError::RuntimeError { message, .. }) => format!("RuntimeError({message})"),
+        Some(other) => format!("{other:?}"),
+    }
+}
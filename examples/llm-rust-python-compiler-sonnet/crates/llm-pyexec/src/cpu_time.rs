@@ -0,0 +1,159 @@
+//! CPU-time budget enforcement for [`ExecutionSettings::cpu_timeout_ns`](crate::ExecutionSettings::cpu_timeout_ns).
+//!
+//! A wall-clock timeout also counts time the VM thread spent descheduled, so
+//! on an oversubscribed host it can fire on a snippet that was not looping at
+//! all. A CPU budget counts only time the thread actually ran.
+//!
+//! ## Mechanism
+//!
+//! RustPython checks for pending signals before every bytecode instruction.
+//! Each interpreter is built with a user-signal channel (see
+//! [`build_interpreter`](crate::vm::build_interpreter)); while a budgeted call
+//! runs, a [`CpuWatchdog`] thread sends a check into that channel every few
+//! milliseconds. The check runs on the VM thread itself, reads that thread's CPU
+//! clock, and once the budget is spent raises `KeyboardInterrupt` — a
+//! `BaseException`, so `except Exception` in user code does not swallow it. The
+//! caller reports [`ExecutionError::Timeout`](crate::ExecutionError::Timeout).
+//!
+//! Checks only run between instructions, so a single long-running native call
+//! (a huge `sorted()`, say) overshoots the budget; the wall-clock timeout still
+//! bounds the call as a whole.
+//!
+//! ## Platforms
+//!
+//! On Unix the clock is `CLOCK_THREAD_CPUTIME_ID`. Elsewhere there is no
+//! per-thread CPU clock to read, and the budget is measured in wall-clock time
+//! from the start of execution instead.
+
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
+
+use rustpython_vm::signal::UserSignalSender;
+
+/// Shortest and longest interval between two budget checks.
+const MIN_CHECK_INTERVAL: Duration = Duration::from_millis(1);
+const MAX_CHECK_INTERVAL: Duration = Duration::from_millis(10);
+
+/// CPU time consumed by the calling thread, in nanoseconds.
+#[cfg(unix)]
+pub(crate) fn thread_cpu_time_ns() -> u64 {
+    let mut ts = libc::timespec {
+        tv_sec: 0,
+        tv_nsec: 0,
+    };
+    // SAFETY: `ts` is a valid, writable timespec; CLOCK_THREAD_CPUTIME_ID is
+    // always supported for the calling thread on Linux and macOS.
+    let rc = unsafe { libc::clock_gettime(libc::CLOCK_THREAD_CPUTIME_ID, &mut ts) };
+    if rc != 0 {
+        return 0;
+    }
+    (ts.tv_sec as u64)
+        .saturating_mul(1_000_000_000)
+        .saturating_add(ts.tv_nsec as u64)
+}
+
+/// Wall-clock fallback: nanoseconds since the first call in this process.
+#[cfg(not(unix))]
+pub(crate) fn thread_cpu_time_ns() -> u64 {
+    use std::sync::OnceLock;
+    use std::time::Instant;
+
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    EPOCH.get_or_init(Instant::now).elapsed().as_nanos() as u64
+}
+
+/// State shared between a [`CpuWatchdog`] and the checks it sends.
+struct Budget {
+    /// Thread CPU time at which the budget runs out.
+    limit_at_ns: u64,
+    /// Cleared when the call ends, so checks still queued in a pooled
+    /// interpreter's channel do nothing on its next call.
+    active: AtomicBool,
+    /// Set by the check that raised.
+    exceeded: AtomicBool,
+}
+
+/// Enforces a CPU budget on the VM thread for the duration of one call.
+///
+/// Must be started on the thread that runs the code.
+pub(crate) struct CpuWatchdog {
+    budget: Arc<Budget>,
+    /// Dropping this stops the watchdog thread.
+    _stop: mpsc::Sender<()>,
+}
+
+impl CpuWatchdog {
+    /// Start enforcing `budget_ns` of CPU time, counted from now, through the
+    /// interpreter's user-signal channel.
+    pub(crate) fn start(signals: &UserSignalSender, budget_ns: u64) -> Self {
+        let budget = Arc::new(Budget {
+            limit_at_ns: thread_cpu_time_ns().saturating_add(budget_ns),
+            active: AtomicBool::new(true),
+            exceeded: AtomicBool::new(false),
+        });
+        let interval =
+            Duration::from_nanos(budget_ns / 4).clamp(MIN_CHECK_INTERVAL, MAX_CHECK_INTERVAL);
+        let (stop_tx, stop_rx) = mpsc::channel::<()>();
+        let signals = signals.clone();
+        let shared = Arc::clone(&budget);
+        let _ = thread::Builder::new()
+            .name("pyexec-cpu-watchdog".to_string())
+            .spawn(move || {
+                while let Err(mpsc::RecvTimeoutError::Timeout) = stop_rx.recv_timeout(interval) {
+                    let budget = Arc::clone(&shared);
+                    let sent = signals.send(Box::new(move |vm| {
+                        if !budget.active.load(Ordering::SeqCst)
+                            || thread_cpu_time_ns() < budget.limit_at_ns
+                        {
+                            return Ok(());
+                        }
+                        budget.exceeded.store(true, Ordering::SeqCst);
+                        Err(vm.new_exception_msg(
+                            vm.ctx.exceptions.keyboard_interrupt.to_owned(),
+                            "CPU time limit exceeded".to_owned(),
+                        ))
+                    }));
+                    if sent.is_err() {
+                        break; // Interpreter dropped.
+                    }
+                }
+            });
+        CpuWatchdog {
+            budget,
+            _stop: stop_tx,
+        }
+    }
+
+    /// Stop enforcing the budget. Returns `true` if it was exceeded.
+    pub(crate) fn finish(self) -> bool {
+        self.budget.active.store(false, Ordering::SeqCst);
+        self.budget.exceeded.load(Ordering::SeqCst)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_thread_cpu_time_advances_with_work() {
+        let before = thread_cpu_time_ns();
+        let mut x = 0u64;
+        for i in 0..5_000_000u64 {
+            x = std::hint::black_box(x.wrapping_add(i));
+        }
+        assert!(thread_cpu_time_ns() > before);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_thread_cpu_time_ignores_sleep() {
+        let before = thread_cpu_time_ns();
+        thread::sleep(Duration::from_millis(50));
+        let slept_ns = thread_cpu_time_ns() - before;
+        assert!(slept_ns < 25_000_000, "sleep counted as CPU: {slept_ns}ns");
+    }
+}
@@ -0,0 +1,76 @@
+//! Which allowed modules actually import on this host.
+//!
+//! Pure-Python stdlib modules (`collections`, `datetime`, `re`, ...) come from a
+//! host Python installation unless the `embedded-stdlib` feature is enabled, so
+//! a module can be on the allowlist and still fail to import. Such failures are
+//! reported as [`ExecutionError::StdlibUnavailable`]; [`environment_report`]
+//! checks every default module up front. The CLI prints the report with
+//! `llm-pyexec-cli doctor`.
+
+use serde::{Deserialize, Serialize};
+
+use crate::session::Session;
+use crate::types::{ExecutionError, ExecutionSettings};
+use crate::vm::{python_stdlib_paths, stdlib_found};
+
+/// Outcome of [`environment_report`].
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct EnvironmentReport {
+    /// Directories searched for the pure-Python stdlib.
+    pub stdlib_paths: Vec<String>,
+    /// Whether the stdlib is embedded or was found on `stdlib_paths`.
+    pub stdlib_found: bool,
+    /// One entry per module checked, in allowlist order.
+    pub modules: Vec<ModuleStatus>,
+}
+
+impl EnvironmentReport {
+    /// Whether every module checked imported.
+    pub fn all_importable(&self) -> bool {
+        self.modules.iter().all(ModuleStatus::importable)
+    }
+}
+
+/// Whether one module imports.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ModuleStatus {
+    /// Module name, as on the allowlist.
+    pub name: String,
+    /// Why `import <name>` failed, or `None` if it succeeded.
+    pub error: Option<ExecutionError>,
+}
+
+impl ModuleStatus {
+    /// Whether `import <name>` succeeded.
+    pub fn importable(&self) -> bool {
+        self.error.is_none()
+    }
+}
+
+/// Import each of [`DEFAULT_ALLOWED_MODULES`](crate::DEFAULT_ALLOWED_MODULES)
+/// in a fresh interpreter and report which succeed.
+pub fn environment_report() -> EnvironmentReport {
+    environment_report_for(&ExecutionSettings::default())
+}
+
+/// Like [`environment_report`], but checks the modules `settings` allows
+/// (`allowed_modules`, then `extra_allowed_modules`), with its stdlib paths
+/// and other options.
+pub fn environment_report_for(settings: &ExecutionSettings) -> EnvironmentReport {
+    let stdlib_paths = python_stdlib_paths(settings.stdlib_paths.as_deref());
+    let session = Session::new(settings.clone());
+    let modules = settings
+        .allowed_modules
+        .iter()
+        .chain(&settings.extra_allowed_modules)
+        .map(|name| ModuleStatus {
+            name: name.clone(),
+            error: session.eval(&format!("import {name}")).error,
+        })
+        .collect();
+    EnvironmentReport {
+        stdlib_found: stdlib_found(&stdlib_paths),
+        stdlib_paths,
+        modules,
+    }
+}
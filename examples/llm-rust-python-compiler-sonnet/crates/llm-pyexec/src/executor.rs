@@ -2,15 +2,62 @@
 //!
 //! This module is the top-level orchestrator for a single Python execution:
 //! 1. Applies [`maybe_wrap_last_expr`] to the source so bare expressions yield a
-//!    return value via the `__result__` convention.
+//!    return value via the `__result__` convention (skipped under
+//!    [`ResultCapture::Single`], which captures the value without rewriting).
+//!    Each run renames the inserted `__result__` to a fresh
+//!    `__pyexec_result_<hex>__`, so a `__result__` in user code is just a
+//!    variable.
 //! 2. Computes a SHA-256 cache key and warms the [`BytecodeCache`] LRU entry.
 //! 3. Creates a fresh [`OutputBuffer`] sized to `settings.max_output_bytes`.
 //! 4. Builds the module allowlist with [`build_allowed_set`].
-//! 5. Attempts to dispatch work to the [`InterpreterPool`] (warm path).
-//!    - On success: waits on per-call response channel with execution timeout.
-//!    - On pool exhaustion: falls back to [`run_with_timeout`] with a fresh interpreter.
+//! 5. Attempts to dispatch work to the [`InterpreterPool`] (warm path), unless
+//!    `settings.execution_mode` is [`ExecutionMode::Isolated`] or the json shim
+//!    is disabled.
+//!    - On success: waits on per-call response channel until the call's
+//!      deadline, `timeout_ns` after it started. Checkout waits count against
+//!      the same deadline; a call that runs out of time in checkout times out
+//!      without running.
+//!    - On pool exhaustion: falls back to [`run_with_timeout`] with a fresh interpreter,
+//!      or fails with `PoolUnavailable` under [`ExecutionMode::PoolOnly`].
+//!    - Isolated calls always use [`run_with_timeout`] with a fresh interpreter.
+//!    - A fresh interpreter is only started while fewer than
+//!      [`ConcurrencyLimit::global`] are running; otherwise the call fails
+//!      with `Overloaded`.
 //! 6. Maps the result into an [`ExecutionResult`], filling in `error = Some(Timeout { .. })`
-//!    on timeout, and inserts into the bytecode cache on non-SyntaxError results.
+//!    on timeout, and inserts into the bytecode cache when the source compiled
+//!    (no `SyntaxError` or `CompileTimeout`).
+//! 7. Records the duration of pool-served calls in the [`latency_histogram`].
+//!
+//! [`execute`] runs these steps against the process-global pool and cache;
+//! [`execute_with`] runs them against a caller-supplied pool and cache;
+//! [`execute_cancellable`] runs them on a background thread that an
+//! [`ExecutionHandle`] can stop; [`execute_batch`] runs many snippets in
+//! parallel and returns their results in input order.
+//!
+//! ## `wasm` feature
+//!
+//! WASM runtimes generally cannot spawn OS threads, so with the `wasm` feature
+//! [`execute`] skips steps 2 and 5–7: every call builds a fresh interpreter
+//! and runs it inline on the calling thread, with no pool, bytecode cache or
+//! watchdog thread. The allowlist and import hook are unchanged. Limitations:
+//!
+//! - The wall-clock timeout is cooperative. It is checked through a
+//!   `sys.settrace` hook, like [`ExecutionSettings::max_instructions`], so
+//!   only on Python-level calls, returns and reported lines. A loop that makes
+//!   no Python calls, or one long native call such as `sum(range(10**12))`,
+//!   runs past it and blocks the caller until it finishes.
+//! - [`ExecutionSettings::cpu_timeout_ns`] and
+//!   [`ExecutionSettings::execution_mode`] are ignored.
+//! - Every other entry point ([`execute_with`], [`execute_cancellable`],
+//!   [`execute_batch`], [`Session`](crate::Session), the pool) still spawns
+//!   threads and is unavailable on targets without them.
+//!
+//! ## Executor trait
+//!
+//! [`PyExecutor`] abstracts over the entry point so embedders can depend on a
+//! `Box<dyn PyExecutor>` and substitute a fake in tests. [`DefaultExecutor`]
+//! forwards to [`execute`] or [`execute_with`]; a canned-result
+//! `MockExecutor` is available behind the `test-util` feature.
 //!
 //! ## Thread safety
 //!
@@ -23,18 +70,40 @@
 //!
 //! This file contains no `unsafe` code.
 
-use std::sync::Arc;
+use std::collections::hash_map::RandomState;
+use std::convert::Infallible;
+use std::hash::{BuildHasher, Hasher};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
 use std::time::{Duration, Instant};
 
-use crate::cache::{BytecodeCache, cache_key};
+use rustpython_parser::ast::fold::Fold;
+use rustpython_parser::ast::{self, Ranged};
+use rustpython_parser::source_code::{LinearLocator, LocatedError};
+use rustpython_parser::text_size::TextRange;
+use rustpython_parser::{Parse, ParseErrorType};
+
+use crate::cache::{BytecodeCache, CacheKey, cache_key};
+use crate::cancel::{ExecutionHandle, PendingExecution};
+use crate::compiled::CompiledSnippet;
+use crate::concurrency::ConcurrencyLimit;
+use crate::latency::{latency_histogram, LatencyHistogram, LatencySlo};
 use crate::modules::build_allowed_set;
 use crate::output::OutputBuffer;
-use crate::pool::{InterpreterPool, WorkItem};
+use crate::pool::{DispatchOutcome, InterpreterPool, WorkItem};
 use crate::timeout::run_with_timeout;
-use crate::types::{ExecutionError, ExecutionResult, ExecutionSettings};
-use crate::vm::{build_interpreter, run_code, VmRunResult};
-
-/// Timeout used when waiting for an available pool slot.
+use crate::types::{
+    EncodingErrors, ExecutionDiagnostics, ExecutionError, ExecutionMode, ExecutionPath,
+    ExecutionResult, ExecutionSettings, ResultCapture, TimeoutKind, Timing,
+};
+use crate::vm::{
+    build_interpreter, compile_check, output_limit_message, panic_result, run_code, RunOptions,
+    VmRunResult, RESULT_NAME,
+};
+
+/// Default timeout used when waiting for an available pool slot
+/// (see [`ExecutionSettings::pool_checkout_timeout_ns`]).
 /// 30 seconds — gives all pool slots time to finish current work before falling back.
 const POOL_CHECKOUT_TIMEOUT: Duration = Duration::from_secs(30);
 
@@ -43,9 +112,14 @@ const POOL_CHECKOUT_TIMEOUT: Duration = Duration::from_secs(30);
 /// Execute a Python source string and return a structured result.
 ///
 /// # Parameters
-/// - `code`: Python source text.  The last statement, if it is a bare
-///   expression (not a keyword statement or a function call), is automatically
-///   wrapped as `__result__ = <expr>` so callers can retrieve a return value.
+/// - `code`: Python source text.  The last statement, if it is an expression
+///   statement, is automatically wrapped as `__result__ = <expr>` so callers
+///   can retrieve a return value. Calls are wrapped too (`print(x)` becomes
+///   `__result__ = print(x)`); only `yield` and `await` expressions are left
+///   alone (see [`maybe_wrap_last_expr`], and
+///   [`ExecutionSettings::result_capture`] for the alternative). A `None`
+///   result is not reported unless it comes from a call and
+///   [`ExecutionSettings::capture_call_results`] is set.
 /// - `settings`: timeout, output limit, and module allowlist configuration.
 ///
 /// # Returns
@@ -57,17 +131,331 @@ const POOL_CHECKOUT_TIMEOUT: Duration = Duration::from_secs(30);
 /// concurrent calls.
 pub fn execute(code: &str, settings: ExecutionSettings) -> ExecutionResult {
     let start = Instant::now();
+    let prepared = prepare_source(code, &settings);
+    #[cfg(feature = "wasm")]
+    {
+        run_inline(prepared, &settings, start)
+    }
+    #[cfg(not(feature = "wasm"))]
+    {
+        run_wrapped(prepared, &settings, start)
+    }
+}
+
+/// Like [`execute`], but runs on `pool` and caches in `cache` instead of the
+/// process-global [`InterpreterPool::global`] and [`BytecodeCache::global`].
+///
+/// Lets tests and embedders use a local pool (for example one built with a
+/// different default allowlist) without touching, or creating, the globals.
+/// Pool-served calls are still recorded in the global [`latency_histogram`].
+pub fn execute_with(
+    pool: &InterpreterPool,
+    cache: &BytecodeCache,
+    code: &str,
+    settings: ExecutionSettings,
+) -> ExecutionResult {
+    let start = Instant::now();
+    let prepared = prepare_source(code, &settings);
+    let key = prepared.cache_key();
+    run_wrapped_with(|| pool, cache, prepared, key, &settings, start, None)
+}
+
+/// Like [`execute`], but runs on a background thread and returns an
+/// [`ExecutionHandle`] that can stop the call, alongside the
+/// [`PendingExecution`] to wait on for its result.
+///
+/// A cancelled call reports [`ExecutionError::Cancelled`]; a pooled
+/// interpreter it ran on goes back to the pool. See [`crate::cancel`].
+///
+/// # Panics
+///
+/// Panics if the background thread cannot be spawned.
+pub fn execute_cancellable(
+    code: &str,
+    settings: ExecutionSettings,
+) -> (ExecutionHandle, PendingExecution) {
+    let start = Instant::now();
+    let handle = ExecutionHandle::new();
+    let code = code.to_owned();
+    let cancel = handle.clone();
+    let thread = std::thread::Builder::new()
+        .name("pyexec-cancellable".to_string())
+        .spawn(move || {
+            let prepared = prepare_source(&code, &settings);
+            let key = prepared.cache_key();
+            run_wrapped_with(
+                InterpreterPool::global,
+                BytecodeCache::global(),
+                prepared,
+                key,
+                &settings,
+                start,
+                Some(cancel),
+            )
+        })
+        .expect("Failed to spawn execution thread");
+    (handle, PendingExecution::new(thread))
+}
 
-    let wrapped = maybe_wrap_last_expr(code);
-    let timeout_ns = settings.timeout_ns;
+/// Run every snippet in `codes` with the same `settings`, returning their
+/// results in the order of `codes`.
+///
+/// Snippets run in parallel against the global pool and cache, on up to
+/// [`InterpreterPool::size`] worker threads. Each worker takes the next
+/// snippet not yet started as soon as its current one finishes, and reports
+/// its result tagged with the snippet's index, so the order snippets finish in
+/// does not matter. Every snippet has its own timeout, counted from when a
+/// worker picks it up: one that times out or fails does not hold up the rest.
+///
+/// # Panics
+///
+/// Panics if a worker thread cannot be spawned.
+pub fn execute_batch(codes: &[&str], settings: ExecutionSettings) -> Vec<ExecutionResult> {
+    if codes.is_empty() {
+        return Vec::new();
+    }
+    let workers = InterpreterPool::global().size().clamp(1, codes.len());
+    let next = AtomicUsize::new(0);
+    let (result_tx, result_rx) = mpsc::channel();
+    std::thread::scope(|scope| {
+        for _ in 0..workers {
+            let result_tx = result_tx.clone();
+            let (next, settings) = (&next, &settings);
+            std::thread::Builder::new()
+                .name("pyexec-batch".to_string())
+                .spawn_scoped(scope, move || loop {
+                    let index = next.fetch_add(1, Ordering::Relaxed);
+                    let Some(code) = codes.get(index) else {
+                        break;
+                    };
+                    let start = Instant::now();
+                    let prepared = prepare_source(code, settings);
+                    let _ = result_tx.send((index, run_wrapped(prepared, settings, start)));
+                })
+                .expect("Failed to spawn batch worker thread");
+        }
+    });
+    drop(result_tx);
+
+    let mut results: Vec<Option<ExecutionResult>> = codes.iter().map(|_| None).collect();
+    for (index, result) in result_rx {
+        results[index] = Some(result);
+    }
+    results
+        .into_iter()
+        .map(|result| result.expect("every batch snippet reports a result"))
+        .collect()
+}
+
+/// Wrap and syntax-check `code` once, for repeated runs with [`execute_compiled`].
+///
+/// Shorthand for [`CompiledSnippet::compile`] with default settings: the
+/// source is wrapped under [`ResultCapture::Wrap`] and inserted into the
+/// global [`BytecodeCache`].
+///
+/// # Errors
+///
+/// Returns [`ExecutionError::SyntaxError`] if the source does not compile.
+pub fn compile(code: &str) -> Result<CompiledSnippet, ExecutionError> {
+    CompiledSnippet::compile(code, ExecutionSettings::default())
+}
+
+/// Run a [`CompiledSnippet`] with `settings`, which replace the ones it was
+/// compiled with.
+///
+/// Behaves like [`execute`] on the original source, except that the source is
+/// not re-wrapped or re-hashed: the snippet's precomputed
+/// [`cache_key`](CompiledSnippet::cache_key) is used for the cache lookup.
+/// `settings.result_capture`, `settings.wrap_last_expr` and
+/// `settings.capture_call_results` are ignored, since wrapping happened at
+/// compile time.
+pub fn execute_compiled(
+    snippet: &CompiledSnippet,
+    mut settings: ExecutionSettings,
+) -> ExecutionResult {
+    let start = Instant::now();
+    settings.result_capture = snippet.settings().result_capture;
+    settings.wrap_last_expr = snippet.settings().wrap_last_expr;
+    settings.capture_call_results = snippet.settings().capture_call_results;
+    let prepared = snippet.prepared().clone();
+    run_keyed(prepared, snippet.cache_key(), &settings, start)
+}
+
+/// Step 1 of [`execute`]: the source as it will be compiled.
+///
+/// Wrapped by [`maybe_wrap_last_expr`] under [`ResultCapture::Wrap`],
+/// unchanged under [`ResultCapture::Single`] or when
+/// [`ExecutionSettings::wrap_last_expr`] is off.
+pub(crate) fn prepare_source(code: &str, settings: &ExecutionSettings) -> PreparedSource {
+    match settings.result_capture {
+        ResultCapture::Wrap if settings.wrap_last_expr => {
+            let (wrapped, result_at, final_call) = wrap_last_expr_at(code);
+            PreparedSource {
+                wrapped,
+                result_at,
+                report_none: final_call && settings.capture_call_results,
+            }
+        }
+        ResultCapture::Wrap | ResultCapture::Single => PreparedSource {
+            wrapped: code.to_string(),
+            result_at: None,
+            report_none: false,
+        },
+    }
+}
+
+/// Source after step 1 of [`execute`].
+///
+/// `wrapped` spells the result variable `__result__`; it is what gets hashed,
+/// cached and reported. Each run renames the inserted assignment to a fresh
+/// name (see [`fresh_result_name`]) so that a `__result__` the user assigns
+/// is never mistaken for the last expression's value.
+#[derive(Debug, Clone)]
+pub(crate) struct PreparedSource {
+    /// The source as wrapped by [`maybe_wrap_last_expr`], if at all.
+    pub(crate) wrapped: String,
+    /// Byte offset in `wrapped` of the inserted `__result__`, or `None` if
+    /// nothing was inserted.
+    pub(crate) result_at: Option<usize>,
+    /// Report a `None` result as `"None"`: set for a final call under
+    /// [`ExecutionSettings::capture_call_results`].
+    pub(crate) report_none: bool,
+}
+
+impl PreparedSource {
+    /// The [`cache_key`] this source is stored under: that of `wrapped`, kept
+    /// apart from the same source run without `report_none`.
+    pub(crate) fn cache_key(&self) -> CacheKey {
+        if self.report_none {
+            let marker: &[u8] = b"\0capture_call_results";
+            cache_key([self.wrapped.as_bytes(), marker].concat())
+        } else {
+            cache_key(&self.wrapped)
+        }
+    }
+
+    /// The source to execute: `wrapped` with the inserted `__result__`
+    /// renamed to `result_name`. A `__result__` written by the user is kept.
+    pub(crate) fn executable(&self, result_name: &str) -> String {
+        match self.result_at {
+            Some(at) => format!(
+                "{}{result_name}{}",
+                &self.wrapped[..at],
+                &self.wrapped[at + RESULT_NAME.len()..]
+            ),
+            None => self.wrapped.clone(),
+        }
+    }
+
+    /// Undo the effect of the rename on `error`'s column, so that locations
+    /// refer to `wrapped` no matter how long `result_name` is.
+    pub(crate) fn restore_error_column(&self, error: &mut ExecutionError, result_name: &str) {
+        let Some(at) = self.result_at else {
+            return;
+        };
+        let before = &self.wrapped[..at];
+        let line_start = before.rfind('\n').map_or(0, |i| i + 1);
+        let result_line = before.matches('\n').count() as u32 + 1;
+        // 1-based column just past the name in the executed source.
+        let name_end = (before[line_start..].chars().count() + result_name.len() + 1) as u32;
+        let (line, col) = match error {
+            ExecutionError::SyntaxError { line, col, .. } => (*line, col),
+            ExecutionError::RuntimeError {
+                line: Some(line),
+                col: Some(col),
+                ..
+            } => (*line, col),
+            _ => return,
+        };
+        if line == result_line && *col >= name_end {
+            *col -= (result_name.len() - RESULT_NAME.len()) as u32;
+        }
+    }
+}
+
+/// A result variable name for one run that user code will not collide with,
+/// of the form `__pyexec_result_<16 hex digits>__`.
+pub(crate) fn fresh_result_name() -> String {
+    static RUNS: AtomicU64 = AtomicU64::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_u64(RUNS.fetch_add(1, Ordering::Relaxed));
+    format!("__pyexec_result_{:016x}__", hasher.finish())
+}
+
+/// Steps 2–7 of [`execute`]: run already-wrapped source with `settings` on the
+/// global pool and cache.
+///
+/// `start` is when the caller began timing, so `duration_ns` covers any work
+/// done before this call.
+pub(crate) fn run_wrapped(
+    prepared: PreparedSource,
+    settings: &ExecutionSettings,
+    start: Instant,
+) -> ExecutionResult {
+    let key = prepared.cache_key();
+    run_keyed(prepared, key, settings, start)
+}
+
+/// [`run_wrapped`] with the source's [`cache_key`] already computed.
+pub(crate) fn run_keyed(
+    prepared: PreparedSource,
+    key: CacheKey,
+    settings: &ExecutionSettings,
+    start: Instant,
+) -> ExecutionResult {
+    run_wrapped_with(
+        InterpreterPool::global,
+        BytecodeCache::global(),
+        prepared,
+        key,
+        settings,
+        start,
+        None,
+    )
+}
+
+/// [`run_keyed`] against an explicit pool and cache.
+///
+/// The pool is only requested when the execution mode uses it, so an
+/// [`ExecutionMode::Isolated`] call never initializes the global pool.
+/// `cancel` is the handle of an [`execute_cancellable`] call.
+fn run_wrapped_with<'p>(
+    pool: impl FnOnce() -> &'p InterpreterPool,
+    cache: &BytecodeCache,
+    prepared: PreparedSource,
+    key: CacheKey,
+    settings: &ExecutionSettings,
+    start: Instant,
+    cancel: Option<ExecutionHandle>,
+) -> ExecutionResult {
+    #[cfg(feature = "tracing")]
+    let _span = tracing::debug_span!("execute", mode = ?settings.execution_mode).entered();
+
+    let timeout_ns = settings.effective_wall_timeout_ns();
     let max_output_bytes = settings.max_output_bytes;
+    let result_name = fresh_result_name();
+    let executable = prepared.executable(&result_name);
+    let options = RunOptions {
+        result_name: result_name.clone(),
+        report_none: prepared.report_none,
+        cancel: cancel.clone(),
+        ..RunOptions::from_settings(settings, start)
+    };
+    let wrapped = prepared.wrapped.clone();
+    let (wrapped_source, cache_key_hex) = if settings.debug {
+        (Some(wrapped.clone()), Some(key.to_hex()))
+    } else {
+        (None, None)
+    };
 
-    // Compute SHA-256 cache key and warm the LRU entry (AC: get() before execution).
-    let key = cache_key(&wrapped);
-    let _ = BytecodeCache::global().get(&key);
+    // Warm the LRU entry for the SHA-256 cache key (AC: get() before execution).
+    // The lookup is counted as a hit or miss in `BytecodeCache::stats()`.
+    // With `use_cache: false` the cache is neither read nor written.
+    let use_cache = settings.use_cache;
+    let cache_hit = use_cache && cache.get(&key).is_some();
 
     // Build the allowlist set once, before spawning the VM thread.
-    let allowed_set = Arc::new(build_allowed_set(&settings));
+    let allowed_set = Arc::new(build_allowed_set(settings));
 
     // Create the output buffer that will be shared between executor and VM.
     let output = OutputBuffer::new(max_output_bytes);
@@ -77,18 +465,105 @@ pub fn execute(code: &str, settings: ExecutionSettings) -> ExecutionResult {
 
     // Build WorkItem with all Send fields.
     let work = WorkItem {
-        wrapped_source: wrapped.clone(),
+        wrapped_source: executable.clone(),
         output: output.clone(),
         allowed_set: Arc::clone(&allowed_set),
+        options: options.clone(),
         response: response_tx,
     };
 
-    // Try to dispatch to the pool (warm path).
+    // Try to dispatch to the pool (warm path). Isolated calls never touch it.
+    let mode = settings.effective_execution_mode();
+    let checkout_timeout = settings
+        .pool_checkout_timeout_ns
+        .map_or(POOL_CHECKOUT_TIMEOUT, Duration::from_nanos);
+    let pool = match mode {
+        ExecutionMode::Isolated => None,
+        ExecutionMode::PoolPreferred | ExecutionMode::PoolOnly => Some(pool()),
+    };
+    // One deadline, counted from `start`, covers the whole call: every wait
+    // gets only what is left of it.
+    let deadline = options.deadline;
+    let remaining = || time_left(deadline);
+    let checkout_start = Instant::now();
+    let checkout_budget = remaining();
+    let pooled = pool.is_some_and(|pool| {
+        pool.dispatch_work(work, checkout_timeout.min(checkout_budget))
+            != DispatchOutcome::Unavailable
+    });
+    let pool_wait_ns = match pool {
+        Some(_) => checkout_start.elapsed().as_nanos() as u64,
+        None => 0,
+    };
+    #[cfg(feature = "tracing")]
+    trace_dispatch(mode, pooled, pool_wait_ns);
+    // The result for a call that never reached an interpreter.
+    let not_executed = |error: ExecutionError| {
+        let duration_ns = start.elapsed().as_nanos() as u64;
+        ExecutionResult {
+            stdout: String::new(),
+            stderr: String::new(),
+            warnings: Vec::new(),
+            denied_imports: Vec::new(),
+            return_value: None,
+            typed_value: None,
+            error: Some(error),
+            exit_code: None,
+            duration_ns,
+            queue_duration_ns: pool_wait_ns,
+            exec_duration_ns: 0,
+            output_bytes: 0,
+            output_lossy: false,
+            output_truncated: false,
+            secondary_error: None,
+            inspections: Vec::new(),
+            used_pool: false,
+            diagnostics: ExecutionDiagnostics {
+                pool_wait_ns,
+                cache_hit,
+                ..ExecutionDiagnostics::default()
+            },
+            timing: Timing::from_parts(duration_ns, 0, 0),
+            wrapped_source: wrapped_source.clone(),
+            cache_key_hex: cache_key_hex.clone(),
+        }
+    };
+    if !pooled && pool.is_some() && checkout_budget < checkout_timeout {
+        // The deadline, not the checkout timeout, ended the wait.
+        return not_executed(ExecutionError::Timeout {
+            limit_ns: timeout_ns,
+            kind: TimeoutKind::Wall,
+        });
+    }
+    // A pooled interpreter checks for cancellation itself before running.
+    if !pooled && cancel.as_ref().is_some_and(ExecutionHandle::is_cancelled) {
+        return not_executed(ExecutionError::Cancelled);
+    }
+    if !pooled && mode == ExecutionMode::PoolOnly {
+        return not_executed(ExecutionError::PoolUnavailable {
+            checkout_timeout_ns: checkout_timeout.as_nanos() as u64,
+        });
+    }
+    // Each fresh interpreter gets its own thread: refuse the call rather than
+    // exceed the global ceiling. The VM thread holds the permit until it exits.
+    let limit = ConcurrencyLimit::global();
+    let permit = if pooled {
+        None
+    } else {
+        match limit.try_acquire() {
+            Some(permit) => Some(permit),
+            None => {
+                return not_executed(ExecutionError::Overloaded {
+                    max_concurrency: limit.max(),
+                })
+            }
+        }
+    };
+    let exec_start = Instant::now();
     let vm_result: Option<VmRunResult> =
-        if InterpreterPool::global().dispatch_work(work, POOL_CHECKOUT_TIMEOUT) {
-            // Pool accepted the work item. Wait for the result with execution timeout.
-            let execution_timeout = Duration::from_nanos(timeout_ns);
-            match response_rx.recv_timeout(execution_timeout) {
+        if pooled {
+            // Pool accepted the work item. Wait for the result until the deadline.
+            match response_rx.recv_timeout(remaining()) {
                 Ok(result) => Some(result),
                 Err(_) => {
                     // Timeout (or channel disconnect): treat as a timeout.
@@ -96,47 +571,274 @@ pub fn execute(code: &str, settings: ExecutionSettings) -> ExecutionResult {
                 }
             }
         } else {
-            // Pool exhausted — fall back to a fresh interpreter on a new thread.
+            // Pool exhausted (or bypassed) — run a fresh interpreter on a new thread.
+            if let (ExecutionMode::PoolPreferred, Some(pool)) = (mode, pool) {
+                pool.record_fallback();
+            }
             // Clone output for the VM thread (executor retains its own handle).
             let output_for_vm = output.clone();
             let allowed_set_inner = (*allowed_set).clone();
-            let wrapped_for_vm = wrapped.clone();
+            let wrapped_for_vm = executable;
+            let json_shim = settings.use_builtin_json_shim;
+            let stdlib_paths = settings.stdlib_paths.clone();
+            let exec_timeout_ns = u64::try_from(remaining().as_nanos()).unwrap_or(u64::MAX);
             run_with_timeout(
                 move || {
-                    let interp = build_interpreter(allowed_set_inner, output_for_vm.clone());
-                    run_code(&interp, &wrapped_for_vm, output_for_vm)
+                    let _permit = permit;
+                    // A panic inside the VM is reported rather than surfacing
+                    // as a timeout; the interpreter is discarded either way.
+                    std::panic::catch_unwind(AssertUnwindSafe(|| {
+                        let interp = build_interpreter(
+                            allowed_set_inner,
+                            output_for_vm.clone(),
+                            json_shim,
+                            stdlib_paths.as_deref(),
+                        );
+                        run_code(&interp, &wrapped_for_vm, output_for_vm, &options)
+                    }))
+                    .unwrap_or_else(|payload| panic_result(payload.as_ref()))
                 },
-                timeout_ns,
+                exec_timeout_ns,
             )
         };
 
+    let exec_duration_ns = exec_start.elapsed().as_nanos() as u64;
     let duration_ns = start.elapsed().as_nanos() as u64;
+    let slo_breached = record_latency(
+        latency_histogram(),
+        pooled,
+        duration_ns,
+        settings.latency_slo,
+    );
+    // A call that timed out ran, as far as anyone can tell, until it did.
+    let timing = match &vm_result {
+        Some(result) => Timing::from_parts(duration_ns, result.compile_ns, result.execute_ns),
+        None => Timing::from_parts(duration_ns, 0, exec_duration_ns),
+    };
+    let diagnostics = ExecutionDiagnostics {
+        path: if pooled {
+            ExecutionPath::Pool
+        } else {
+            ExecutionPath::Fallback
+        },
+        pool_slot_id: vm_result.as_ref().and_then(|result| result.slot_id),
+        pool_wait_ns,
+        cache_hit,
+        slo_breached,
+    };
+
+    if let Some(result) = &vm_result {
+        // Cache the wrapped source only if it compiled (no SyntaxError or
+        // CompileTimeout). A hit has already refreshed the LRU position, so
+        // only misses insert.
+        let compiled = !matches!(
+            result.error,
+            Some(ExecutionError::SyntaxError { .. } | ExecutionError::CompileTimeout { .. })
+        );
+        if use_cache && compiled && !cache_hit {
+            cache.insert(key, wrapped);
+        }
+    }
+
+    let mut result = ExecutionResult {
+        used_pool: pooled,
+        queue_duration_ns: pool_wait_ns,
+        exec_duration_ns,
+        diagnostics,
+        timing,
+        wrapped_source,
+        cache_key_hex,
+        ..into_execution_result(
+            vm_result,
+            output,
+            timeout_ns,
+            max_output_bytes,
+            settings.output_encoding_errors,
+            duration_ns,
+        )
+    };
+    if let Some(error) = result.error.as_mut() {
+        prepared.restore_error_column(error, &result_name);
+    }
+    #[cfg(feature = "tracing")]
+    tracing::debug!(
+        exec_ns = exec_duration_ns,
+        duration_ns,
+        error = error_kind(result.error.as_ref()),
+        "execution finished"
+    );
+    result
+}
+
+/// [`execute`] under the `wasm` feature: run `prepared` in a fresh interpreter
+/// on the calling thread, stopping it cooperatively at the call's deadline.
+#[cfg(feature = "wasm")]
+fn run_inline(
+    prepared: PreparedSource,
+    settings: &ExecutionSettings,
+    start: Instant,
+) -> ExecutionResult {
+    let timeout_ns = settings.effective_wall_timeout_ns();
+    let result_name = fresh_result_name();
+    let options = RunOptions {
+        result_name: result_name.clone(),
+        report_none: prepared.report_none,
+        cpu_timeout_ns: None,
+        cooperative_timeout_ns: Some(timeout_ns),
+        ..RunOptions::from_settings(settings, start)
+    };
+    let output = OutputBuffer::new(settings.max_output_bytes);
+    let vm_result = std::panic::catch_unwind(AssertUnwindSafe(|| {
+        let interp = build_interpreter(
+            build_allowed_set(settings),
+            output.clone(),
+            settings.use_builtin_json_shim,
+            settings.stdlib_paths.as_deref(),
+        );
+        run_code(
+            &interp,
+            &prepared.executable(&result_name),
+            output.clone(),
+            &options,
+        )
+    }))
+    .unwrap_or_else(|payload| panic_result(payload.as_ref()));
+
+    let mut result = ExecutionResult {
+        wrapped_source: settings.debug.then(|| prepared.wrapped.clone()),
+        cache_key_hex: settings.debug.then(|| prepared.cache_key().to_hex()),
+        ..into_execution_result(
+            Some(vm_result),
+            output,
+            timeout_ns,
+            settings.max_output_bytes,
+            settings.output_encoding_errors,
+            start.elapsed().as_nanos() as u64,
+        )
+    };
+    if let Some(error) = result.error.as_mut() {
+        prepared.restore_error_column(error, &result_name);
+    }
+    result
+}
+
+/// Time left until `deadline`, or [`Duration::MAX`] without one.
+fn time_left(deadline: Option<Instant>) -> Duration {
+    deadline.map_or(Duration::MAX, |d| {
+        d.saturating_duration_since(Instant::now())
+    })
+}
+
+/// Emit the dispatch decision for one call: pool slot, fallback to a fresh
+/// interpreter, or neither.
+#[cfg(feature = "tracing")]
+fn trace_dispatch(mode: ExecutionMode, pooled: bool, wait_ns: u64) {
+    match (pooled, mode) {
+        (true, _) => tracing::debug!(path = "pool", wait_ns, "dispatched to a pool slot"),
+        (false, ExecutionMode::PoolPreferred) => tracing::info!(
+            path = "fallback",
+            wait_ns,
+            "no pool slot free; running on a fresh interpreter"
+        ),
+        (false, ExecutionMode::PoolOnly) => tracing::warn!(
+            path = "unavailable",
+            wait_ns,
+            "no pool slot free; not executed"
+        ),
+        (false, ExecutionMode::Isolated) => {
+            tracing::debug!(path = "isolated", "running on a fresh interpreter")
+        }
+    }
+}
+
+/// The `type` tag [`ExecutionError`] serializes with, or `"none"` on success.
+#[cfg(feature = "tracing")]
+fn error_kind(error: Option<&ExecutionError>) -> &'static str {
+    match error {
+        None => "none",
+        Some(ExecutionError::SyntaxError { .. }) => "SyntaxError",
+        Some(ExecutionError::RuntimeError { .. }) => "RuntimeError",
+        Some(ExecutionError::NonZeroExit { .. }) => "NonZeroExit",
+        Some(ExecutionError::RecursionLimitExceeded { .. }) => "RecursionLimitExceeded",
+        Some(ExecutionError::InstructionLimitExceeded { .. }) => "InstructionLimitExceeded",
+        Some(ExecutionError::PoolUnavailable { .. }) => "PoolUnavailable",
+        Some(ExecutionError::Overloaded { .. }) => "Overloaded",
+        Some(ExecutionError::Timeout { .. }) => "Timeout",
+        Some(ExecutionError::Cancelled) => "Cancelled",
+        Some(ExecutionError::CompileTimeout { .. }) => "CompileTimeout",
+        Some(ExecutionError::OutputLimitExceeded { .. }) => "OutputLimitExceeded",
+        Some(ExecutionError::ModuleNotAllowed { .. }) => "ModuleNotAllowed",
+        Some(ExecutionError::ModuleForbidden { .. }) => "ModuleForbidden",
+        Some(ExecutionError::StdlibUnavailable { .. }) => "StdlibUnavailable",
+        Some(ExecutionError::InvalidOutputEncoding { .. }) => "InvalidOutputEncoding",
+        Some(ExecutionError::Internal { .. }) => "Internal",
+    }
+}
+
+/// Map a VM outcome (`None` on timeout) into the public [`ExecutionResult`].
+///
+/// Shared by [`execute`] and [`Session::eval`](crate::Session::eval). All of
+/// `duration_ns` is reported as execution time; `execute` overwrites the split.
+pub(crate) fn into_execution_result(
+    vm_result: Option<VmRunResult>,
+    output: OutputBuffer,
+    timeout_ns: u64,
+    max_output_bytes: usize,
+    encoding_errors: EncodingErrors,
+    duration_ns: u64,
+) -> ExecutionResult {
+    let output_bytes = output.total_written();
+    let invalid_stream = output.invalid_utf8_stream();
+    let output_lossy = invalid_stream.is_some();
+    let output_truncated = output.is_limit_exceeded();
 
     match vm_result {
         Some(result) => {
-            // Cache the wrapped source on successful (non-SyntaxError) results.
-            let is_syntax_error = matches!(result.error, Some(ExecutionError::SyntaxError { .. }));
-            if !is_syntax_error {
-                BytecodeCache::global().insert(key, wrapped);
-            }
-
-            // Check if the output buffer limit was exceeded.
-            let limit_exceeded = output.is_limit_exceeded();
-            let error = if limit_exceeded {
-                // An output limit was hit; return the canonical error variant
-                // regardless of what runtime error the VM produced internally.
-                Some(ExecutionError::OutputLimitExceeded {
+            let (error, secondary_error) = if output_truncated {
+                // An output limit was hit; return the canonical error variant,
+                // and keep the VM's own error unless it is just the exception
+                // raised by the overflowing write.
+                let secondary = result.error.filter(|error| {
+                    !matches!(
+                        error,
+                        ExecutionError::RuntimeError { message, .. }
+                            if *message == output_limit_message(max_output_bytes)
+                    )
+                });
+                let error = ExecutionError::OutputLimitExceeded {
                     limit_bytes: max_output_bytes,
-                })
+                };
+                (Some(error), secondary)
+            } else if encoding_errors == EncodingErrors::Strict {
+                let invalid = invalid_stream.map(|stream| ExecutionError::InvalidOutputEncoding {
+                    stream: stream.to_string(),
+                });
+                (result.error.or(invalid), None)
             } else {
-                result.error
+                (result.error, None)
             };
             ExecutionResult {
                 stdout: result.stdout,
                 stderr: result.stderr,
+                warnings: result.warnings,
+                denied_imports: result.denied_imports,
                 return_value: result.return_value,
+                typed_value: result.typed_value,
                 error,
+                exit_code: result.exit_code,
                 duration_ns,
+                queue_duration_ns: 0,
+                exec_duration_ns: duration_ns,
+                output_bytes,
+                output_lossy,
+                output_truncated,
+                secondary_error,
+                inspections: result.inspections,
+                used_pool: false,
+                diagnostics: ExecutionDiagnostics::default(),
+                timing: Timing::from_parts(duration_ns, result.compile_ns, result.execute_ns),
+                wrapped_source: None,
+                cache_key_hex: None,
             }
         }
         None => {
@@ -145,19 +847,292 @@ pub fn execute(code: &str, settings: ExecutionSettings) -> ExecutionResult {
             ExecutionResult {
                 stdout,
                 stderr,
+                warnings: Vec::new(),
+                denied_imports: Vec::new(),
                 return_value: None,
-                error: Some(ExecutionError::Timeout { limit_ns: timeout_ns }),
+                typed_value: None,
+                error: Some(ExecutionError::Timeout {
+                    limit_ns: timeout_ns,
+                    kind: TimeoutKind::Wall,
+                }),
+                exit_code: None,
                 duration_ns,
+                queue_duration_ns: 0,
+                exec_duration_ns: duration_ns,
+                output_bytes,
+                output_lossy,
+                output_truncated,
+                secondary_error: None,
+                inspections: Vec::new(),
+                used_pool: false,
+                diagnostics: ExecutionDiagnostics::default(),
+                timing: Timing::from_parts(duration_ns, 0, duration_ns),
+                wrapped_source: None,
+                cache_key_hex: None,
+            }
+        }
+    }
+}
+
+/// Record a call's duration if it was served by the pool, and return whether
+/// `slo`, which is configured on `histogram` first, is breached.
+///
+/// Fallback executions include interpreter construction and would skew the
+/// warm-path distribution, so they are not recorded.
+fn record_latency(
+    histogram: &LatencyHistogram,
+    pooled: bool,
+    duration_ns: u64,
+    slo: Option<LatencySlo>,
+) -> bool {
+    if let Some(slo) = slo {
+        histogram.apply_slo(slo);
+    }
+    if pooled {
+        histogram.record(duration_ns);
+    }
+    slo.is_some() && histogram.slo_breached()
+}
+
+/// Outcome counts returned by [`precompile`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PrecompileSummary {
+    /// Sources that compiled and were inserted into the cache.
+    pub compiled: usize,
+    /// Sources rejected with a `SyntaxError` (not cached).
+    pub rejected: usize,
+}
+
+/// Prime the global [`BytecodeCache`] with a known set of snippets.
+///
+/// Each source is wrapped and keyed exactly as [`execute`] does with default
+/// settings, so a later `execute()` of the same text is a cache hit. Sources
+/// that fail to compile are skipped, mirroring `execute()`, which never caches
+/// a `SyntaxError`.
+pub fn precompile(sources: &[&str]) -> PrecompileSummary {
+    let settings = ExecutionSettings::default();
+    let mut summary = PrecompileSummary::default();
+    for source in sources {
+        let prepared = prepare_source(source, &settings);
+        if compile_check(&prepared.wrapped).is_err() {
+            summary.rejected += 1;
+            continue;
+        }
+        BytecodeCache::global().insert(prepared.cache_key(), prepared.wrapped);
+        summary.compiled += 1;
+    }
+    summary
+}
+
+/// Modules a snippet imports, as found by [`analyze_imports`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ImportAnalysis {
+    /// Module names from `import` and `from ... import` statements and from
+    /// `__import__` calls with a string-literal name, in order of first
+    /// appearance and without duplicates. Relative imports keep their leading
+    /// dots (`from .b import c` gives `".b"`).
+    pub modules: Vec<String>,
+    /// `true` if some `__import__` call names its module with anything but a
+    /// string literal, in which case `modules` may be incomplete.
+    pub has_dynamic_imports: bool,
+}
+
+/// List the modules `code` would try to import, without executing it.
+///
+/// Every statement is inspected, including ones inside functions, classes and
+/// branches that would never run, so the result can be used to approve or
+/// reject a snippet up front. For `from b.c import d`, only `b.c` is reported,
+/// even if `d` is itself a submodule. Imports made by the imported modules
+/// are not seen.
+///
+/// # Errors
+///
+/// Returns [`ExecutionError::SyntaxError`] if the source does not parse.
+pub fn analyze_imports(code: &str) -> Result<ImportAnalysis, ExecutionError> {
+    let suite = ast::Suite::parse(code, "<string>").map_err(|err| {
+        let err: LocatedError<ParseErrorType> = LinearLocator::new(code).locate_error(err);
+        let (line, col) = err.python_location();
+        ExecutionError::SyntaxError {
+            message: err.to_string(),
+            line: line as u32,
+            col: col as u32,
+        }
+    })?;
+    let mut collector = ImportCollector::default();
+    collector.fold(suite).unwrap_or_else(|never| match never {});
+    Ok(collector.analysis)
+}
+
+/// A [`Fold`] that records imports while rebuilding the tree unchanged.
+#[derive(Default)]
+struct ImportCollector {
+    analysis: ImportAnalysis,
+}
+
+impl ImportCollector {
+    fn record(&mut self, module: String) {
+        if !self.analysis.modules.contains(&module) {
+            self.analysis.modules.push(module);
+        }
+    }
+}
+
+impl Fold<TextRange> for ImportCollector {
+    type TargetU = TextRange;
+    type Error = Infallible;
+    type UserContext = ();
+
+    fn will_map_user(&mut self, _user: &TextRange) -> Self::UserContext {}
+
+    fn map_user(&mut self, user: TextRange, _context: ()) -> Result<TextRange, Infallible> {
+        Ok(user)
+    }
+
+    fn fold_stmt(&mut self, node: ast::Stmt) -> Result<ast::Stmt, Infallible> {
+        match &node {
+            ast::Stmt::Import(import) => {
+                for alias in &import.names {
+                    self.record(alias.name.to_string());
+                }
+            }
+            ast::Stmt::ImportFrom(import) => {
+                let dots = ".".repeat(import.level.map_or(0, |level| level.to_usize()));
+                let module = import.module.as_ref().map_or("", |module| module.as_str());
+                self.record(format!("{dots}{module}"));
             }
+            _ => {}
+        }
+        ast::fold::fold_stmt(self, node)
+    }
+
+    fn fold_expr(&mut self, node: ast::Expr) -> Result<ast::Expr, Infallible> {
+        if let ast::Expr::Call(call) = &node {
+            if matches!(&*call.func, ast::Expr::Name(name) if name.id.as_str() == "__import__") {
+                let name_arg = call.args.first().or_else(|| {
+                    call.keywords
+                        .iter()
+                        .find(|keyword| keyword.arg.as_deref() == Some("name"))
+                        .map(|keyword| &keyword.value)
+                });
+                match name_arg {
+                    Some(ast::Expr::Constant(ast::ExprConstant {
+                        value: ast::Constant::Str(module),
+                        ..
+                    })) => self.record(module.clone()),
+                    _ => self.analysis.has_dynamic_imports = true,
+                }
+            }
+        }
+        ast::fold::fold_expr(self, node)
+    }
+}
+
+// ── Executor trait ───────────────────────────────────────────────────────────
+
+/// Something that executes Python source like [`execute`].
+///
+/// Object-safe, so services can hold a `Box<dyn PyExecutor>` or
+/// `Arc<dyn PyExecutor>` and swap in a fake during tests.
+pub trait PyExecutor: Send + Sync {
+    /// Execute `code` with `settings`; see [`execute`].
+    fn execute(&self, code: &str, settings: ExecutionSettings) -> ExecutionResult;
+}
+
+/// The real executor: runs on the global pool and cache, or on the pool and
+/// cache given to [`with_resources`](Self::with_resources).
+#[derive(Clone, Default)]
+pub struct DefaultExecutor {
+    resources: Option<(Arc<InterpreterPool>, Arc<BytecodeCache>)>,
+}
+
+impl DefaultExecutor {
+    /// An executor that calls [`execute`].
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An executor that calls [`execute_with`] on `pool` and `cache`.
+    pub fn with_resources(pool: Arc<InterpreterPool>, cache: Arc<BytecodeCache>) -> Self {
+        DefaultExecutor {
+            resources: Some((pool, cache)),
+        }
+    }
+}
+
+impl PyExecutor for DefaultExecutor {
+    fn execute(&self, code: &str, settings: ExecutionSettings) -> ExecutionResult {
+        match &self.resources {
+            Some((pool, cache)) => execute_with(pool, cache, code, settings),
+            None => execute(code, settings),
         }
     }
 }
 
 // ── Source-level expression wrapper ──────────────────────────────────────────
 
+/// Rewrite `code` so that the value of a final expression statement is
+/// captured: the last top-level statement becomes `__result__ = <expr>` if it
+/// is a bare expression, and `code` is returned unchanged otherwise.
+///
+/// The source is parsed with `rustpython-parser`, so the decision does not
+/// depend on surface syntax: calls, string literals containing `=`,
+/// expressions spanning several lines, trailing comments and `;`-separated
+/// statements are all handled. Only the final statement's own text is
+/// prefixed, so line numbers are unchanged. `yield` and `await` expressions
+/// are left alone, since they cannot be assigned at module level.
+///
+/// Source that does not parse goes through the older line-based heuristic
+/// (see [`heuristic_wrap_last_expr`]) and then fails to compile exactly as
+/// before, so `SyntaxError` reporting is unaffected.
+///
+/// # Examples
+/// ```
+/// use llm_pyexec::executor::maybe_wrap_last_expr;
+/// assert_eq!(maybe_wrap_last_expr("1 + 1"), "__result__ = 1 + 1");
+/// assert_eq!(maybe_wrap_last_expr("x = [3, 1]\nsorted(x)"), "x = [3, 1]\n__result__ = sorted(x)");
+/// assert_eq!(maybe_wrap_last_expr("x = 1"), "x = 1");
+/// assert_eq!(maybe_wrap_last_expr(""), "");
+/// ```
+pub fn maybe_wrap_last_expr(code: &str) -> String {
+    wrap_last_expr_at(code).0
+}
+
+/// [`maybe_wrap_last_expr`], also returning the byte offset of the inserted
+/// `__result__` in the wrapped source (`None` if `code` was left unchanged)
+/// and whether the wrapped expression is a call.
+fn wrap_last_expr_at(code: &str) -> (String, Option<usize>, bool) {
+    let suite = match ast::Suite::parse(code, "<string>") {
+        Ok(suite) => suite,
+        Err(_) => {
+            let wrapped = heuristic_wrap_last_expr(code);
+            // The heuristic only rewrites the last non-blank line, from its
+            // first column.
+            let result_at =
+                (wrapped != code).then(|| wrapped.trim_end().rfind('\n').map_or(0, |i| i + 1));
+            // It leaves calls alone.
+            return (wrapped, result_at, false);
+        }
+    };
+    match suite.last() {
+        Some(ast::Stmt::Expr(stmt))
+            if !matches!(
+                *stmt.value,
+                ast::Expr::Yield(_) | ast::Expr::YieldFrom(_) | ast::Expr::Await(_)
+            ) =>
+        {
+            let start = usize::from(stmt.range().start());
+            let wrapped = format!("{}{RESULT_NAME} = {}", &code[..start], &code[start..]);
+            let is_call = matches!(*stmt.value, ast::Expr::Call(_));
+            (wrapped, Some(start), is_call)
+        }
+        _ => (code.to_string(), None, false),
+    }
+}
+
 /// Heuristically wrap the last line of `code` as `__result__ = <last_line>`
 /// if the last line looks like a bare value-producing expression rather than a
-/// statement or a side-effecting call.
+/// statement or a side-effecting call. Used by [`maybe_wrap_last_expr`] for
+/// source that does not parse.
 ///
 /// # Rules (in order of evaluation)
 ///
@@ -176,15 +1151,7 @@ pub fn execute(code: &str, settings: ExecutionSettings) -> ExecutionResult {
 ///   line ends with `)` at balanced nesting depth).
 ///
 /// Otherwise the line is wrapped as `__result__ = <line>`.
-///
-/// # Examples
-/// ```
-/// use llm_pyexec::executor::maybe_wrap_last_expr;
-/// assert_eq!(maybe_wrap_last_expr("1 + 1"), "__result__ = 1 + 1");
-/// assert_eq!(maybe_wrap_last_expr("x = 1\nprint(x)"), "x = 1\nprint(x)");
-/// assert_eq!(maybe_wrap_last_expr(""), "");
-/// ```
-pub fn maybe_wrap_last_expr(code: &str) -> String {
+fn heuristic_wrap_last_expr(code: &str) -> String {
     // Statement-keyword prefixes that indicate the last line is NOT a bare expr.
     // Architecture §4.7 list.
     const STATEMENT_PREFIXES: &[&str] = &[
@@ -293,19 +1260,30 @@ pub fn maybe_wrap_last_expr(code: &str) -> String {
 /// Returns `true` if `line` looks like an assignment statement.
 ///
 /// Detects:
-/// - Simple assignment: `x = expr` (bare `=` not preceded by `!<>=+-*/&|^~`)
+/// - Simple assignment: `x = expr` (bare `=` not preceded by `!<>=:`)
 /// - Augmented assignment: `x += expr`, `x -= expr`, `x *= expr`, etc.
-///   (a `=` preceded by `+`, `-`, `*`, `/`, `%`, `&`, `|`, `^`, `~` counts as
-///   augmented assignment, which is still an assignment statement)
+///   (a `=` preceded by an operator character is still an assignment
+///   statement)
 ///
 /// Does NOT match:
 /// - `==`, `!=`, `<=`, `>=` comparisons
+/// - the walrus operator `:=`, which is an expression
+/// - any `=` inside a string literal (see [`mask_string_literals`]), inside
+///   brackets (keyword arguments, `[x := 5]`), or after a `#` comment marker
 fn looks_like_assignment(line: &str) -> bool {
-    let chars: Vec<char> = line.chars().collect();
+    let chars: Vec<char> = mask_string_literals(line).chars().collect();
     let n = chars.len();
+    let mut depth: i32 = 0;
 
     for i in 0..n {
-        if chars[i] != '=' {
+        let c = chars[i];
+        match c {
+            '#' => break,
+            '(' | '[' | '{' => depth += 1,
+            ')' | ']' | '}' => depth -= 1,
+            _ => {}
+        }
+        if c != '=' || depth != 0 {
             continue;
         }
         // '==' — skip (comparison, not assignment)
@@ -318,10 +1296,9 @@ fn looks_like_assignment(line: &str) -> bool {
             match prev {
                 // '!', '<', '>' or '=' before '=' → comparison operator, skip.
                 '!' | '<' | '>' | '=' => continue,
-                // '+', '-', '*', '/', '%', '&', '|', '^', '~' before '=' → augmented assignment.
-                // Augmented assignment IS a statement — return true.
-                '+' | '-' | '*' | '/' | '%' | '&' | '|' | '^' | '~' => return true,
-                // Anything else before '=' → simple assignment.
+                // ':' before '=' → walrus operator, an expression; skip.
+                ':' => continue,
+                // Anything else before '=' → simple or augmented assignment.
                 _ => return true,
             }
         } else {
@@ -339,13 +1316,15 @@ fn looks_like_assignment(line: &str) -> bool {
 /// It does NOT catch expressions like `(1 + 2)` — those should be wrapped.
 ///
 /// The rule: if the line ends with `)` at balanced depth AND there is a `(`
-/// somewhere in the line, it's treated as a call statement.
+/// somewhere in the line, it's treated as a call statement. Parentheses inside
+/// string literals are ignored, so `f(")")` is a call and `")" * 3` is not.
 fn is_call_statement(line: &str) -> bool {
+    let line = mask_string_literals(line);
     if !line.ends_with(')') {
         return false;
     }
 
-    // Check parentheses are balanced.
+    // Check parentheses are balanced (outside string literals).
     let mut depth: i32 = 0;
     for ch in line.chars() {
         match ch {
@@ -370,6 +1349,48 @@ fn is_call_statement(line: &str) -> bool {
     true
 }
 
+/// `line` with every string literal replaced by an empty `""`, so that quotes,
+/// brackets, `=` and `#` inside strings do not sway the line heuristics.
+///
+/// Handles single-, double- and triple-quoted literals and backslash escapes;
+/// prefixes such as `r` or `f` are left in place. An unterminated literal runs
+/// to the end of the line. The result depends only on `line`, so wrapping
+/// decisions (and hence cache keys) stay deterministic.
+fn mask_string_literals(line: &str) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    let n = chars.len();
+    let mut masked = String::with_capacity(line.len());
+    let mut i = 0;
+
+    while i < n {
+        let quote = chars[i];
+        if quote != '\'' && quote != '"' {
+            masked.push(quote);
+            i += 1;
+            continue;
+        }
+        let triple = [quote; 3];
+        let delimiter = if chars[i..].starts_with(&triple) {
+            &triple[..]
+        } else {
+            &triple[..1]
+        };
+        i += delimiter.len();
+        while i < n {
+            if chars[i] == '\\' {
+                i += 2;
+            } else if chars[i..].starts_with(delimiter) {
+                i += delimiter.len();
+                break;
+            } else {
+                i += 1;
+            }
+        }
+        masked.push_str("\"\"");
+    }
+    masked
+}
+
 // ── Tests ─────────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -400,15 +1421,14 @@ mod tests {
         assert_eq!(result, "x = 5\n__result__ = x * 2");
     }
 
-    /// Multiline code where last line is a call (e.g. print(x)) — unchanged.
-    /// Per AC: "maybe_wrap_last_expr leaves 'x = 1\nprint(x)' unchanged
-    ///  (last line is a call, not a bare expression with assignment context
-    ///   — behavior per heuristic)"
+    /// A final call is an expression statement like any other and is wrapped;
+    /// `print(x)` evaluates to `None`, which yields no return value unless
+    /// `capture_call_results` is set.
     #[test]
-    fn test_no_wrap_call_print() {
+    fn test_wrap_call_print() {
         let code = "x = 1\nprint(x)";
         let result = maybe_wrap_last_expr(code);
-        assert_eq!(result, "x = 1\nprint(x)");
+        assert_eq!(result, "x = 1\n__result__ = print(x)");
     }
 
     /// Empty string is unchanged.
@@ -569,6 +1589,283 @@ mod tests {
         assert_eq!(maybe_wrap_last_expr(code), "x = 42\n__result__ = x");
     }
 
+    // ── AST-based cases the line heuristic got wrong ──────────────────────────
+
+    /// A call returning a value is captured.
+    #[test]
+    fn test_wrap_call_expression() {
+        assert_eq!(
+            maybe_wrap_last_expr("xs = [3, 1, 2]\nsorted(xs)"),
+            "xs = [3, 1, 2]\n__result__ = sorted(xs)"
+        );
+        assert_eq!(
+            maybe_wrap_last_expr("'a,b'.split(',')"),
+            "__result__ = 'a,b'.split(',')"
+        );
+    }
+
+    /// An `=` inside a string literal does not make it an assignment.
+    #[test]
+    fn test_wrap_string_containing_equals() {
+        assert_eq!(maybe_wrap_last_expr("\"a=b\""), "__result__ = \"a=b\"");
+    }
+
+    /// A keyword argument is not an assignment either.
+    #[test]
+    fn test_wrap_call_with_keyword_argument() {
+        assert_eq!(maybe_wrap_last_expr("dict(a=1)"), "__result__ = dict(a=1)");
+    }
+
+    /// An expression spanning several lines is wrapped at its first line.
+    #[test]
+    fn test_wrap_multiline_expression() {
+        let code = "total = (\n    1 +\n    2\n)";
+        assert_eq!(maybe_wrap_last_expr(code), code);
+        let code = "(\n    1 +\n    2\n)";
+        assert_eq!(
+            maybe_wrap_last_expr(code),
+            "__result__ = (\n    1 +\n    2\n)"
+        );
+    }
+
+    /// A trailing comment is kept and does not hide the expression.
+    #[test]
+    fn test_wrap_with_trailing_comment() {
+        assert_eq!(
+            maybe_wrap_last_expr("x = 2\nx * 3  # six\n# done\n"),
+            "x = 2\n__result__ = x * 3  # six\n# done\n"
+        );
+    }
+
+    /// Only the final `;`-separated statement is wrapped.
+    #[test]
+    fn test_wrap_after_semicolon() {
+        assert_eq!(
+            maybe_wrap_last_expr("x = 2; x * 3"),
+            "x = 2; __result__ = x * 3"
+        );
+        assert_eq!(maybe_wrap_last_expr("x = 2; y = 3;"), "x = 2; y = 3;");
+    }
+
+    /// A parenthesized walrus is an expression and is wrapped.
+    #[test]
+    fn test_wrap_walrus() {
+        assert_eq!(maybe_wrap_last_expr("(y := 5)"), "__result__ = (y := 5)");
+    }
+
+    /// Walrus expressions and `=` inside string values are wrapped.
+    #[test]
+    fn test_wrap_walrus_and_equals_in_strings() {
+        assert_eq!(maybe_wrap_last_expr("(n := 10)"), "__result__ = (n := 10)");
+        assert_eq!(maybe_wrap_last_expr("[x := 5]"), "__result__ = [x := 5]");
+        assert_eq!(
+            maybe_wrap_last_expr(r#"{"query": "a=b"}"#),
+            r#"__result__ = {"query": "a=b"}"#
+        );
+    }
+
+    /// The fallback heuristic ignores `:=` and any `=` in strings or brackets.
+    #[test]
+    fn test_looks_like_assignment_skips_walrus_strings_and_brackets() {
+        assert!(!looks_like_assignment("(n := 10)"));
+        assert!(!looks_like_assignment("[x := 5]"));
+        assert!(!looks_like_assignment("total := sum(xs)"));
+        assert!(!looks_like_assignment(r#"{"query": "a=b"}"#));
+        assert!(!looks_like_assignment(r#"'it\'s a=b'"#));
+        assert!(!looks_like_assignment("dict(a=1)"));
+        assert!(!looks_like_assignment("x  # y = 1"));
+        assert!(!looks_like_assignment(r#"f"{x=}""#));
+        assert!(!looks_like_assignment(r#"f'{a=} and {b = }'"#));
+
+        assert!(looks_like_assignment("x = 1"));
+        assert!(looks_like_assignment("x[0] += 1"));
+        assert!(looks_like_assignment(r#"s = "a=b""#));
+        assert!(looks_like_assignment(r#"s = f"{x=}""#));
+    }
+
+    /// The `=` of an f-string's `{x=}` is not an assignment, with or without
+    /// the parser: the fallback heuristic agrees for unparsable source.
+    #[test]
+    fn test_wrap_fstring_debug_specifier() {
+        assert_eq!(
+            maybe_wrap_last_expr("x = 1\nf\"{x=}\""),
+            "x = 1\n__result__ = f\"{x=}\""
+        );
+        let cases = [
+            ("x = (\nf\"{x=}\"", "x = (\n__result__ = f\"{x=}\""),
+            ("x = (\n(n := 10)", "x = (\n__result__ = (n := 10)"),
+            ("x = (\nx = 1", "x = (\nx = 1"),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(heuristic_wrap_last_expr(code), expected, "{code:?}");
+        }
+    }
+
+    #[test]
+    fn test_mask_string_literals() {
+        assert_eq!(mask_string_literals(r#"len(") (")"#), r#"len("")"#);
+        assert_eq!(mask_string_literals(r#"")" * 3"#), r#""" * 3"#);
+        assert_eq!(mask_string_literals(r#"f('a\')', "=")"#), r#"f("", "")"#);
+        assert_eq!(mask_string_literals(r#"'''a ' = (''' + x"#), r#""" + x"#);
+        assert_eq!(mask_string_literals(r#"x + "unterminated ("#), r#"x + """#);
+        assert_eq!(mask_string_literals("''"), r#""""#);
+    }
+
+    /// Parentheses inside strings do not affect the call check.
+    #[test]
+    fn test_is_call_statement_ignores_parens_in_strings() {
+        assert!(is_call_statement(r#"len(") (")"#));
+        assert!(is_call_statement(r#"f(")")"#));
+        assert!(is_call_statement(r#"print("(")"#));
+        assert!(!is_call_statement(r#"")" * 3"#));
+        assert!(!is_call_statement(r#"x + "f()""#));
+    }
+
+    /// The fallback heuristic, fed unparsable source, decides on the masked
+    /// last line, and gives the same answer every time.
+    #[test]
+    fn test_heuristic_wrap_with_strings() {
+        let cases = [
+            ("x = (\n\")\" * 3", "x = (\n__result__ = \")\" * 3"),
+            ("x = (\n\"a=b\" + 'c'", "x = (\n__result__ = \"a=b\" + 'c'"),
+            ("x = (\nf(\")\")", "x = (\nf(\")\")"),
+            ("x = (\ns = \"(\"", "x = (\ns = \"(\""),
+        ];
+        for (code, expected) in cases {
+            assert_eq!(heuristic_wrap_last_expr(code), expected, "{code:?}");
+            assert_eq!(
+                heuristic_wrap_last_expr(code),
+                heuristic_wrap_last_expr(code)
+            );
+        }
+    }
+
+    /// Identical input always produces identical wrapped output, so the cache
+    /// key is stable.
+    #[test]
+    fn test_wrapping_is_deterministic() {
+        for code in [
+            r#"len(") (")"#,
+            r#"")" * 3"#,
+            r#"{"query": "a=b"}"#,
+            "x = (\n')'",
+        ] {
+            let first = maybe_wrap_last_expr(code);
+            assert_eq!(maybe_wrap_last_expr(code), first, "{code:?}");
+            assert_eq!(
+                cache_key(maybe_wrap_last_expr(code)),
+                cache_key(&first),
+                "{code:?}"
+            );
+        }
+    }
+
+    /// Only the inserted `__result__` is renamed, on both wrapping paths.
+    #[test]
+    fn test_executable_renames_only_inserted_result() {
+        let settings = ExecutionSettings::default();
+        for (code, expected) in [
+            (
+                "__result__ = 1\n__result__ + 1",
+                "__result__ = 1\nR = __result__ + 1",
+            ),
+            ("x = 1; x", "x = 1; R = x"),
+            ("x = (\n1 + 1\n\n", "x = (\nR = 1 + 1\n\n"),
+            ("__result__ = 1", "__result__ = 1"),
+        ] {
+            let prepared = prepare_source(code, &settings);
+            assert_eq!(prepared.wrapped, maybe_wrap_last_expr(code), "{code:?}");
+            assert_eq!(prepared.executable("R"), expected, "{code:?}");
+        }
+    }
+
+    /// Only a final call reports a `None` result, and only when asked to; its
+    /// cache key then differs from the same source run without the setting.
+    #[test]
+    fn test_prepare_source_reports_none_for_final_calls() {
+        let capture = ExecutionSettings {
+            capture_call_results: true,
+            ..ExecutionSettings::default()
+        };
+        for (code, report_none) in [
+            ("print(\"x\")", true),
+            ("words = ['a']\n\" \".join(words).upper()", true),
+            ("x = None\nx", false),
+            ("(1 + 2)", false),
+            ("print(\"x\")\nx = 1", false),
+        ] {
+            let prepared = prepare_source(code, &capture);
+            assert_eq!(prepared.report_none, report_none, "{code:?}");
+            let default = prepare_source(code, &ExecutionSettings::default());
+            assert!(!default.report_none, "{code:?}");
+            assert_eq!(prepared.wrapped, default.wrapped, "{code:?}");
+            assert_eq!(
+                prepared.cache_key() != default.cache_key(),
+                report_none,
+                "{code:?}"
+            );
+            assert_eq!(default.cache_key(), cache_key(&default.wrapped));
+        }
+    }
+
+    #[test]
+    fn test_fresh_result_names_differ() {
+        let name = fresh_result_name();
+        assert!(name.starts_with("__pyexec_result_") && name.ends_with("__"));
+        assert_ne!(name, fresh_result_name());
+    }
+
+    /// A compound statement ending in an expression is not wrapped.
+    #[test]
+    fn test_no_wrap_expression_inside_block() {
+        let code = "if True:\n    1 + 1";
+        assert_eq!(maybe_wrap_last_expr(code), code);
+    }
+
+    /// Source that does not parse falls back to the line heuristic.
+    #[test]
+    fn test_unparsable_source_uses_heuristic() {
+        assert_eq!(
+            maybe_wrap_last_expr("def f(:\n1 + 1"),
+            "def f(:\n__result__ = 1 + 1"
+        );
+        assert_eq!(maybe_wrap_last_expr("x +* 2\nx = 1"), "x +* 2\nx = 1");
+    }
+
+    // ── precompile ────────────────────────────────────────────────────────────
+
+    /// Valid sources are counted as compiled, syntax errors as rejected.
+    #[test]
+    fn test_precompile_counts_compiled_and_rejected() {
+        let summary = precompile(&["1 + 1", "def f(:", "x = 3\nx * 2"]);
+        assert_eq!(summary, PrecompileSummary { compiled: 2, rejected: 1 });
+    }
+
+    #[test]
+    fn test_record_latency_only_counts_pool_path() {
+        let histogram = LatencyHistogram::new();
+        record_latency(&histogram, true, 1_000, None);
+        record_latency(&histogram, false, 5_000_000, None);
+        record_latency(&histogram, true, 2_000, None);
+        assert_eq!(histogram.count(), 2);
+        assert_eq!(histogram.max_ns(), 2_000, "fallback duration must not be recorded");
+    }
+
+    #[test]
+    fn test_record_latency_reports_slo_breach() {
+        let histogram = LatencyHistogram::new();
+        let slo = Some(LatencySlo {
+            p95_ns: 100_000,
+            window: 2,
+        });
+        assert!(!record_latency(&histogram, true, 500_000, slo));
+        assert!(record_latency(&histogram, true, 500_000, slo));
+        // Repeating the target keeps the flag; without one nothing is reported.
+        assert!(record_latency(&histogram, false, 1_000, slo));
+        assert!(!record_latency(&histogram, true, 1_000, None));
+        assert_eq!(histogram.slo(), slo);
+    }
+
     // ── execute() functional tests ────────────────────────────────────────────
 
     /// AC-11: execute('print("hello world")', Default::default()).stdout == 'hello world\n'
@@ -594,7 +1891,7 @@ mod tests {
         let elapsed_ms = start.elapsed().as_millis();
 
         match result.error {
-            Some(ExecutionError::Timeout { limit_ns }) => {
+            Some(ExecutionError::Timeout { limit_ns, .. }) => {
                 assert_eq!(limit_ns, 200_000_000, "limit_ns should match timeout setting");
             }
             other => panic!("Expected Timeout error, got: {:?}", other),
@@ -659,4 +1956,94 @@ mod tests {
             assert!(dur > 0, "duration_ns should be > 0");
         }
     }
+
+    // ── VM panics ─────────────────────────────────────────────────────────────
+
+    fn assert_internal_error_promptly(result: &ExecutionResult) {
+        assert!(
+            matches!(result.error, Some(ExecutionError::Internal { .. })),
+            "expected Internal error, got {:?}",
+            result.error
+        );
+        assert!(
+            result.duration_ns < 10_000_000_000,
+            "panic surfaced only after {}ns",
+            result.duration_ns
+        );
+    }
+
+    /// A VM panic on the fresh-interpreter path is reported as `Internal`
+    /// long before the 60s timeout.
+    #[test]
+    #[ignore = "slow: VM init per test"]
+    fn test_vm_panic_on_fallback_path_reports_internal() {
+        let settings = ExecutionSettings {
+            timeout_ns: 60_000_000_000,
+            execution_mode: ExecutionMode::Isolated,
+            ..ExecutionSettings::default()
+        };
+        let result = execute(crate::vm::PANIC_HOOK, settings);
+        assert_internal_error_promptly(&result);
+    }
+
+    /// A VM panic on a pool slot is reported as `Internal`, and the slot keeps
+    /// serving calls afterwards.
+    #[test]
+    #[ignore = "slow: VM init per test"]
+    fn test_vm_panic_on_pool_path_reports_internal_and_slot_recovers() {
+        let pool = InterpreterPool::new(1);
+        let cache = BytecodeCache::new(8);
+        let settings = ExecutionSettings {
+            timeout_ns: 60_000_000_000,
+            ..ExecutionSettings::default()
+        };
+
+        let result = execute_with(&pool, &cache, crate::vm::PANIC_HOOK, settings.clone());
+        assert!(result.used_pool);
+        assert_internal_error_promptly(&result);
+
+        let result = execute_with(&pool, &cache, "6 * 7", settings);
+        assert!(
+            result.error.is_none(),
+            "unexpected error: {:?}",
+            result.error
+        );
+        assert_eq!(result.return_value.as_deref(), Some("42"));
+        assert!(result.used_pool, "slot should still be in the pool");
+    }
+
+    // ── wasm feature ──────────────────────────────────────────────────────────
+
+    /// Under `wasm`, `execute` runs the interpreter on the calling thread.
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_execute_runs_on_calling_thread() {
+        use crate::vm::RUNS_ON_THIS_THREAD;
+
+        let before = RUNS_ON_THIS_THREAD.with(|runs| runs.get());
+        let result = execute("1+1", ExecutionSettings::default());
+        assert_eq!(result.error, None);
+        assert_eq!(result.return_value.as_deref(), Some("2"));
+        assert!(!result.used_pool);
+        assert_eq!(RUNS_ON_THIS_THREAD.with(|runs| runs.get()), before + 1);
+    }
+
+    /// Under `wasm`, a runaway loop of Python calls is stopped at the wall
+    /// timeout by the trace hook.
+    #[cfg(feature = "wasm")]
+    #[test]
+    fn test_wasm_timeout_is_cooperative() {
+        let settings = ExecutionSettings {
+            timeout_ns: 200_000_000,
+            ..ExecutionSettings::default()
+        };
+        let result = execute("def f():\n    pass\nwhile True:\n    f()", settings);
+        assert_eq!(
+            result.error,
+            Some(ExecutionError::Timeout {
+                limit_ns: 200_000_000,
+                kind: TimeoutKind::Wall,
+            })
+        );
+    }
 }
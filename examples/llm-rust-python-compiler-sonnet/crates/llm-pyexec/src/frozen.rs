@@ -0,0 +1,264 @@
+//! Registry of Python modules frozen into (or injected into) every interpreter.
+//!
+//! Security review needs to see exactly which Python code ships inside the
+//! binary without reading `vm.rs`. Every module `build_interpreter` installs
+//! is listed here, and [`frozen_modules`] reports each one with a SHA-256 of its
+//! source:
+//!
+//! - **Built-in** modules are compiled into the binary with `py_freeze!`. Their
+//!   sources live under `src/frozen/` and are `include_str!`-ed here, so the
+//!   reported hash always matches the frozen bytecode.
+//! - **Embedder** modules are registered at runtime with
+//!   [`register_frozen_module`] and installed into `sys.modules` by every
+//!   interpreter built afterwards. Register them before the first
+//!   [`execute`](crate::execute) call: pool slots that are already warm do not
+//!   pick up later registrations.
+//!
+//! Frozen modules are still subject to the import allowlist when imported by
+//! user code.
+//!
+//! The `embedded-stdlib` feature additionally freezes RustPython's own
+//! pure-Python standard library. That is pinned by the `rustpython-pylib`
+//! version rather than shipped by llm-pyexec, so it is not listed here; the
+//! shims above replace its `json`, `random` and `io`.
+
+use std::sync::{Mutex, OnceLock};
+
+use rustpython_vm::VirtualMachine;
+use serde::{Deserialize, Serialize};
+
+use crate::cache::cache_key;
+use crate::types::ExecutionError;
+use crate::vm::compile_check;
+
+/// Where a frozen module's source came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum FrozenOrigin {
+    /// Shipped with llm-pyexec (e.g. the `json`, `random` and `io` shims).
+    BuiltIn,
+    /// Registered by the embedding application via [`register_frozen_module`].
+    Embedder,
+}
+
+/// Audit record for one frozen module.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct FrozenModuleInfo {
+    /// Importable module name.
+    pub name: String,
+    /// Lowercase hex SHA-256 of the module source.
+    pub source_sha256: String,
+    /// Source length in bytes.
+    pub source_len: usize,
+    /// Built-in shim or embedder-supplied module.
+    pub origin: FrozenOrigin,
+}
+
+/// A module compiled into the binary.
+pub(crate) struct BuiltinFrozen {
+    pub name: &'static str,
+    pub source: &'static str,
+    /// Adds the pre-compiled bytecode to a VM under construction.
+    pub install: fn(&mut VirtualMachine),
+}
+
+/// Every built-in frozen module, in installation order.
+///
+/// `source` and the `py_freeze!` file must name the same file.
+pub(crate) static BUILTIN_FROZEN: &[BuiltinFrozen] = &[
+    BuiltinFrozen {
+        // Python-level json wrapper over the native _json module, which works
+        // around RustPython's own json package.
+        name: "json",
+        source: include_str!("frozen/json.py"),
+        install: |vm| {
+            vm.add_frozen(rustpython_vm::py_freeze!(
+                file = "src/frozen/json.py",
+                module_name = "json"
+            ))
+        },
+    },
+    BuiltinFrozen {
+        // Pure-Python Mersenne Twister seeded like CPython's. RustPython's
+        // random.py imports _sha2 / hashlib, which RustPython 0.3 lacks.
+        name: "random",
+        source: include_str!("frozen/random.py"),
+        install: |vm| {
+            vm.add_frozen(rustpython_vm::py_freeze!(
+                file = "src/frozen/random.py",
+                module_name = "random"
+            ))
+        },
+    },
+    BuiltinFrozen {
+        // The stdlib io module with `open` routed through the sandbox's
+        // virtual `open()` and `FileIO` disabled, so allowing `io` does not
+        // open the host filesystem.
+        name: "io",
+        source: include_str!("frozen/io.py"),
+        install: |vm| {
+            vm.add_frozen(rustpython_vm::py_freeze!(
+                file = "src/frozen/io.py",
+                module_name = "io"
+            ))
+        },
+    },
+];
+
+fn embedder_registry() -> &'static Mutex<Vec<(String, String)>> {
+    static REGISTRY: OnceLock<Mutex<Vec<(String, String)>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(Vec::new()))
+}
+
+/// Register an embedder-supplied module to be installed in every new interpreter.
+///
+/// Registering a name again replaces the earlier source. The source is
+/// syntax-checked up front so that a bad module fails here rather than
+/// silently at interpreter startup.
+///
+/// # Errors
+///
+/// Returns [`ExecutionError::SyntaxError`] if `source` does not compile.
+pub fn register_frozen_module(
+    name: impl Into<String>,
+    source: impl Into<String>,
+) -> Result<(), ExecutionError> {
+    let (name, source) = (name.into(), source.into());
+    compile_check(&source)?;
+
+    let mut registry = embedder_registry()
+        .lock()
+        .expect("frozen module registry poisoned");
+    match registry.iter_mut().find(|(n, _)| *n == name) {
+        Some(entry) => entry.1 = source,
+        None => registry.push((name, source)),
+    }
+    Ok(())
+}
+
+/// Snapshot of the embedder-registered `(name, source)` pairs.
+pub(crate) fn embedder_modules() -> Vec<(String, String)> {
+    embedder_registry()
+        .lock()
+        .expect("frozen module registry poisoned")
+        .clone()
+}
+
+/// List every frozen module new interpreters install: built-ins first, then
+/// embedder modules in registration order.
+pub fn frozen_modules() -> Vec<FrozenModuleInfo> {
+    let builtin = BUILTIN_FROZEN
+        .iter()
+        .map(|m| info(m.name, m.source, FrozenOrigin::BuiltIn));
+    let embedder = embedder_modules()
+        .into_iter()
+        .map(|(name, source)| info(&name, &source, FrozenOrigin::Embedder));
+    builtin.chain(embedder).collect()
+}
+
+/// Source text of the frozen module `name`, if one is registered.
+///
+/// Embedder modules take precedence over a built-in of the same name, matching
+/// what user code would import.
+pub fn frozen_module_source(name: &str) -> Option<String> {
+    embedder_modules()
+        .into_iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, source)| source)
+        .or_else(|| {
+            BUILTIN_FROZEN
+                .iter()
+                .find(|m| m.name == name)
+                .map(|m| m.source.to_string())
+        })
+}
+
+fn info(name: &str, source: &str, origin: FrozenOrigin) -> FrozenModuleInfo {
+    FrozenModuleInfo {
+        name: name.to_string(),
+        source_sha256: cache_key(source).to_hex(),
+        source_len: source.len(),
+        origin,
+    }
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Golden hash of `src/frozen/json.py`. If this fails, the json shim was
+    /// edited: review the change, then update the hash.
+    const JSON_SHIM_SHA256: &str =
+        "0337dd5e6e304c1c9846ca5e7ff77bd80a71c11870f32059730ed630f1465949";
+
+    #[test]
+    fn test_json_shim_golden_hash() {
+        let json = frozen_modules()
+            .into_iter()
+            .find(|m| m.name == "json")
+            .expect("json shim must be listed");
+        assert_eq!(json.origin, FrozenOrigin::BuiltIn);
+        assert_eq!(json.source_sha256, JSON_SHIM_SHA256);
+        assert_eq!(json.source_len, include_str!("frozen/json.py").len());
+    }
+
+    /// Golden hash of `src/frozen/random.py`.
+    const RANDOM_SHIM_SHA256: &str =
+        "3788b767eb1335e22c8e1f35a9912564d1fa4db3dc31f60530b3f4dd737c7222";
+
+    #[test]
+    fn test_random_shim_golden_hash() {
+        let random = frozen_modules()
+            .into_iter()
+            .find(|m| m.name == "random")
+            .expect("random shim must be listed");
+        assert_eq!(random.origin, FrozenOrigin::BuiltIn);
+        assert_eq!(random.source_sha256, RANDOM_SHIM_SHA256);
+        assert_eq!(random.source_len, include_str!("frozen/random.py").len());
+    }
+
+    /// Golden hash of `src/frozen/io.py`.
+    const IO_SHIM_SHA256: &str = "4784d3974d08e010efa37c92af062b8a9fecb7cb59989a31029dd6cb7d57e7cf";
+
+    #[test]
+    fn test_io_shim_golden_hash() {
+        let io = frozen_modules()
+            .into_iter()
+            .find(|m| m.name == "io")
+            .expect("io shim must be listed");
+        assert_eq!(io.origin, FrozenOrigin::BuiltIn);
+        assert_eq!(io.source_sha256, IO_SHIM_SHA256);
+        assert_eq!(io.source_len, include_str!("frozen/io.py").len());
+    }
+
+    #[test]
+    fn test_registered_module_listed_as_embedder() {
+        register_frozen_module("pyexec_test_helpers", "def double(x):\n    return x * 2\n")
+            .expect("valid module source");
+
+        let info = frozen_modules()
+            .into_iter()
+            .find(|m| m.name == "pyexec_test_helpers")
+            .expect("registered module must be listed");
+        assert_eq!(info.origin, FrozenOrigin::Embedder);
+        assert_eq!(
+            info.source_sha256,
+            cache_key("def double(x):\n    return x * 2\n").to_hex()
+        );
+        assert_eq!(
+            frozen_module_source("pyexec_test_helpers").as_deref(),
+            Some("def double(x):\n    return x * 2\n")
+        );
+    }
+
+    #[test]
+    fn test_register_rejects_syntax_error() {
+        let err = register_frozen_module("pyexec_test_broken", "def f(:")
+            .expect_err("syntax error must be rejected");
+        assert!(matches!(err, ExecutionError::SyntaxError { .. }));
+        assert!(frozen_modules()
+            .iter()
+            .all(|m| m.name != "pyexec_test_broken"));
+    }
+}
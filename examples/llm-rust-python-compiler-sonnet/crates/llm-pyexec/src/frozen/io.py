@@ -0,0 +1,64 @@
+# The stdlib io module, minus direct file access: open() and open_code() go
+# through builtins.open, which the sandbox replaces with its virtual open()
+# on every call, and FileIO cannot be constructed. StringIO, BytesIO and the
+# buffered / text wrappers are the native _io classes.
+
+from _io import (DEFAULT_BUFFER_SIZE, BlockingIOError, UnsupportedOperation,
+                 BytesIO, StringIO, BufferedReader, BufferedWriter,
+                 BufferedRWPair, BufferedRandom, IncrementalNewlineDecoder,
+                 text_encoding, TextIOWrapper)
+from _io import _IOBase, _RawIOBase, _BufferedIOBase, _TextIOBase
+import abc
+
+__all__ = ["BlockingIOError", "open", "open_code", "IOBase", "RawIOBase",
+           "FileIO", "BytesIO", "StringIO", "BufferedIOBase",
+           "BufferedReader", "BufferedWriter", "BufferedRWPair",
+           "BufferedRandom", "TextIOBase", "TextIOWrapper",
+           "UnsupportedOperation", "SEEK_SET", "SEEK_CUR", "SEEK_END",
+           "DEFAULT_BUFFER_SIZE", "text_encoding", "IncrementalNewlineDecoder"]
+
+UnsupportedOperation.__module__ = "io"
+
+SEEK_SET = 0
+SEEK_CUR = 1
+SEEK_END = 2
+
+
+def open(*args, **kwargs):
+    # Looked up on every call, so the sandbox's open() of the current
+    # execution is the one used.
+    import builtins
+    return builtins.open(*args, **kwargs)
+
+
+def open_code(path):
+    return open(path, "rb")
+
+
+class IOBase(_IOBase, metaclass=abc.ABCMeta):
+    __doc__ = _IOBase.__doc__
+
+class RawIOBase(_RawIOBase, IOBase):
+    __doc__ = _RawIOBase.__doc__
+
+class BufferedIOBase(_BufferedIOBase, IOBase):
+    __doc__ = _BufferedIOBase.__doc__
+
+class TextIOBase(_TextIOBase, IOBase):
+    __doc__ = _TextIOBase.__doc__
+
+
+class FileIO(RawIOBase):
+    """Raw file access is not available in the sandbox; use open()."""
+
+    def __init__(self, file, *args, **kwargs):
+        raise PermissionError(1, "Operation not permitted", file)
+
+
+for klass in (BytesIO, BufferedReader, BufferedWriter, BufferedRandom,
+              BufferedRWPair):
+    BufferedIOBase.register(klass)
+
+for klass in (StringIO, TextIOWrapper):
+    TextIOBase.register(klass)
+del klass, abc, _IOBase, _RawIOBase, _BufferedIOBase, _TextIOBase
@@ -0,0 +1,170 @@
+import _json
+import sys
+
+# Deepest nesting dumps() will encode; lowered to sys.getrecursionlimit().
+_MAX_DEPTH = 1000
+
+class JSONDecodeError(ValueError):
+    def __init__(self, msg, doc, pos):
+        errmsg = '%s: line %d column %d (char %d)' % (
+            msg,
+            doc.count('\n', 0, pos) + 1,
+            pos - doc.rfind('\n', 0, pos),
+            pos,
+        )
+        ValueError.__init__(self, errmsg)
+        self.msg = msg
+        self.doc = doc
+        self.pos = pos
+        self.lineno = doc.count('\n', 0, pos) + 1
+        self.colno = pos - doc.rfind('\n', 0, pos)
+
+class JSONEncoder:
+    def __init__(self, skipkeys=False, ensure_ascii=True,
+                 check_circular=True, allow_nan=True, sort_keys=False,
+                 indent=None, separators=None, default=None):
+        self.skipkeys = skipkeys
+        self.ensure_ascii = ensure_ascii
+        self.check_circular = check_circular
+        self.allow_nan = allow_nan
+        self.sort_keys = sort_keys
+        self.indent = indent
+        if separators is not None:
+            self.item_separator, self.key_separator = separators
+        elif indent is not None:
+            self.item_separator = ','
+            self.key_separator = ': '
+        else:
+            self.item_separator = ', '
+            self.key_separator = ': '
+        self.default = default if default is not None else self._default
+
+    def _default(self, obj):
+        raise TypeError(f'Object of type {type(obj).__name__} is not JSON serializable')
+
+    def encode(self, o):
+        markers = {} if self.check_circular else None
+        return _json.encode_basestring_ascii(str(o)) if False else _simple_encode(o, self, 0, markers)
+
+    def iterencode(self, o, _one_shot=False):
+        return iter([self.encode(o)])
+
+def _enter_container(obj, depth, markers):
+    if depth >= min(_MAX_DEPTH, sys.getrecursionlimit()):
+        raise RecursionError('maximum recursion depth exceeded while encoding a JSON object')
+    if markers is not None:
+        if id(obj) in markers:
+            raise ValueError('Circular reference detected')
+        markers[id(obj)] = obj
+
+def _leave_container(obj, markers):
+    if markers is not None:
+        del markers[id(obj)]
+
+def _simple_encode(obj, encoder, depth=0, markers=None):
+    if obj is None:
+        return 'null'
+    elif obj is True:
+        return 'true'
+    elif obj is False:
+        return 'false'
+    elif isinstance(obj, int):
+        return str(obj)
+    elif isinstance(obj, float):
+        if obj != obj:
+            return 'NaN'
+        elif obj == float('inf'):
+            return 'Infinity'
+        elif obj == float('-inf'):
+            return '-Infinity'
+        return repr(obj)
+    elif isinstance(obj, str):
+        return _encode_str(obj)
+    elif isinstance(obj, (list, tuple)):
+        if not obj:
+            return '[]'
+        _enter_container(obj, depth, markers)
+        items = []
+        for v in obj:
+            items.append(_simple_encode(v, encoder, depth + 1, markers))
+        _leave_container(obj, markers)
+        return '[' + ', '.join(items) + ']'
+    elif isinstance(obj, dict):
+        if not obj:
+            return '{}'
+        _enter_container(obj, depth, markers)
+        keys = sorted(obj.keys()) if encoder.sort_keys else obj.keys()
+        items = []
+        for k in keys:
+            items.append(_encode_str(str(k)) + ': ' + _simple_encode(obj[k], encoder, depth + 1, markers))
+        _leave_container(obj, markers)
+        return '{' + ', '.join(items) + '}'
+    else:
+        return encoder.default(obj)
+
+def _encode_str(s):
+    result = ['"']
+    for c in s:
+        if c == '"':
+            result.append('\\"')
+        elif c == '\\':
+            result.append('\\\\')
+        elif c == '\n':
+            result.append('\\n')
+        elif c == '\r':
+            result.append('\\r')
+        elif c == '\t':
+            result.append('\\t')
+        elif ord(c) < 0x20:
+            result.append('\\u{:04x}'.format(ord(c)))
+        else:
+            result.append(c)
+    result.append('"')
+    return ''.join(result)
+
+def dumps(obj, *, skipkeys=False, ensure_ascii=True, check_circular=True,
+          allow_nan=True, cls=None, indent=None, separators=None, default=None,
+          sort_keys=False, **kw):
+    encoder = (cls or JSONEncoder)(
+        skipkeys=skipkeys, ensure_ascii=ensure_ascii,
+        check_circular=check_circular, allow_nan=allow_nan,
+        indent=indent, separators=separators, default=default,
+        sort_keys=sort_keys, **kw
+    )
+    return encoder.encode(obj)
+
+def dump(obj, fp, **kwargs):
+    fp.write(dumps(obj, **kwargs))
+
+def loads(s, *, cls=None, object_hook=None, parse_float=None,
+          parse_int=None, parse_constant=None, object_pairs_hook=None, **kw):
+    if isinstance(s, (bytes, bytearray)):
+        s = s.decode('utf-8')
+    decoder = JSONDecoder(object_hook=object_hook, object_pairs_hook=object_pairs_hook,
+                          parse_float=parse_float, parse_int=parse_int, strict=True)
+    return decoder.decode(s)
+
+def load(fp, **kwargs):
+    return loads(fp.read(), **kwargs)
+
+class JSONDecoder:
+    def __init__(self, *, object_hook=None, parse_float=None, parse_int=None,
+                 parse_constant=None, strict=True, object_pairs_hook=None):
+        self.object_hook = object_hook
+        self.object_pairs_hook = object_pairs_hook
+        self.parse_float = parse_float or float
+        self.parse_int = parse_int or int
+        self.strict = strict
+        self.scan_once = _json.make_scanner(self)
+
+    def decode(self, s, _w=None):
+        obj, end = self.raw_decode(s, 0)
+        end = len(s.lstrip()) if not s else end
+        return obj
+
+    def raw_decode(self, s, idx=0):
+        try:
+            obj, end = self.scan_once(s, idx)
+        except StopIteration as err:
+            raise JSONDecodeError("Expecting value", s, err.value) from None
+        return obj, end
@@ -0,0 +1,313 @@
+"""Self-contained replacement for the stdlib random module.
+
+RustPython's random.py needs _sha2 / hashlib, which RustPython 0.3 lacks.
+This is a pure-Python MT19937 seeded exactly like CPython's, so an int seed
+produces the same sequence as CPython for random(), getrandbits() and
+everything built on them. str / bytes seeds are deterministic but do not
+match CPython, which hashes them with SHA-512 first.
+"""
+
+from math import ceil as _ceil, floor as _floor, isfinite as _isfinite, log as _log
+from operator import index as _index
+from itertools import accumulate as _accumulate, repeat as _repeat
+from bisect import bisect as _bisect
+
+__all__ = [
+    "Random",
+    "seed",
+    "random",
+    "uniform",
+    "randint",
+    "randrange",
+    "choice",
+    "choices",
+    "shuffle",
+    "sample",
+    "getrandbits",
+    "getstate",
+    "setstate",
+]
+
+_N = 624
+_M = 397
+_MATRIX_A = 0x9908B0DF
+_UPPER_MASK = 0x80000000
+_LOWER_MASK = 0x7FFFFFFF
+_MASK32 = 0xFFFFFFFF
+
+VERSION = 3
+
+
+def _entropy():
+    try:
+        from os import urandom
+        return int.from_bytes(urandom(32), 'big')
+    except Exception:
+        from time import time_ns
+        return time_ns() ^ id(object())
+
+
+class Random:
+    """Mersenne Twister generator with CPython's seeding and sampling."""
+
+    VERSION = VERSION
+
+    def __init__(self, x=None):
+        self._mt = [0] * _N
+        self._mti = _N + 1
+        self.seed(x)
+
+    # ── Core generator ──────────────────────────────────────────────────────
+
+    def _init_genrand(self, s):
+        mt = self._mt
+        mt[0] = s & _MASK32
+        for i in range(1, _N):
+            prev = mt[i - 1]
+            mt[i] = (1812433253 * (prev ^ (prev >> 30)) + i) & _MASK32
+        self._mti = _N
+
+    def _init_by_array(self, key):
+        self._init_genrand(19650218)
+        mt = self._mt
+        i, j = 1, 0
+        length = len(key)
+        for _ in range(max(_N, length)):
+            prev = mt[i - 1]
+            mt[i] = ((mt[i] ^ ((prev ^ (prev >> 30)) * 1664525)) + key[j] + j) & _MASK32
+            i += 1
+            j += 1
+            if i >= _N:
+                mt[0] = mt[_N - 1]
+                i = 1
+            if j >= length:
+                j = 0
+        for _ in range(_N - 1):
+            prev = mt[i - 1]
+            mt[i] = ((mt[i] ^ ((prev ^ (prev >> 30)) * 1566083941)) - i) & _MASK32
+            i += 1
+            if i >= _N:
+                mt[0] = mt[_N - 1]
+                i = 1
+        mt[0] = 0x80000000
+
+    def _twist(self):
+        mt = self._mt
+        for kk in range(_N):
+            y = (mt[kk] & _UPPER_MASK) | (mt[(kk + 1) % _N] & _LOWER_MASK)
+            value = mt[(kk + _M) % _N] ^ (y >> 1)
+            if y & 1:
+                value ^= _MATRIX_A
+            mt[kk] = value
+        self._mti = 0
+
+    def _genrand_uint32(self):
+        if self._mti >= _N:
+            self._twist()
+        y = self._mt[self._mti]
+        self._mti += 1
+        y ^= y >> 11
+        y ^= (y << 7) & 0x9D2C5680
+        y ^= (y << 15) & 0xEFC60000
+        y ^= y >> 18
+        return y
+
+    def seed(self, a=None, version=2):
+        """Initialize the generator from an int, float, str, bytes or None."""
+        if a is None:
+            a = _entropy()
+        elif isinstance(a, (str, bytes, bytearray)):
+            if isinstance(a, str):
+                a = a.encode()
+            a = int.from_bytes(bytes(a), 'big')
+        elif isinstance(a, float):
+            a = hash(a)
+        elif not isinstance(a, int):
+            raise TypeError('The only supported seed types are: None,\n'
+                            'int, float, str, bytes, and bytearray.')
+        a = abs(a)
+        key = []
+        while a:
+            key.append(a & _MASK32)
+            a >>= 32
+        self._init_by_array(key or [0])
+        self.gauss_next = None
+
+    def getstate(self):
+        """Return an object capturing the current internal state."""
+        return self.VERSION, tuple(self._mt) + (self._mti,), self.gauss_next
+
+    def setstate(self, state):
+        """Restore the internal state from an object returned by getstate()."""
+        version, internal, self.gauss_next = state
+        if version != self.VERSION or len(internal) != _N + 1:
+            raise ValueError("state with version %r passed to Random.setstate() "
+                             "of version %r" % (version, self.VERSION))
+        self._mt = [int(word) & _MASK32 for word in internal[:_N]]
+        self._mti = int(internal[_N])
+
+    def random(self):
+        """Return the next float in the interval [0.0, 1.0)."""
+        a = self._genrand_uint32() >> 5
+        b = self._genrand_uint32() >> 6
+        return (a * 67108864.0 + b) * (1.0 / 9007199254740992.0)
+
+    def getrandbits(self, k):
+        """Return a non-negative int with k random bits."""
+        k = _index(k)
+        if k < 0:
+            raise ValueError('number of bits must be non-negative')
+        if k <= 32:
+            return self._genrand_uint32() >> (32 - k) if k else 0
+        result = 0
+        shift = 0
+        while k > 0:
+            word = self._genrand_uint32()
+            if k < 32:
+                word >>= 32 - k
+            result |= word << shift
+            shift += 32
+            k -= 32
+        return result
+
+    # ── Integers ────────────────────────────────────────────────────────────
+
+    def _randbelow(self, n):
+        """Return a random int in the range [0, n). Defined for n > 0."""
+        getrandbits = self.getrandbits
+        k = n.bit_length()
+        r = getrandbits(k)
+        while r >= n:
+            r = getrandbits(k)
+        return r
+
+    def randrange(self, start, stop=None, step=1):
+        """Choose a random item from range(start, stop[, step])."""
+        istart = _index(start)
+        if stop is None:
+            if step != 1:
+                raise TypeError("Missing a non-None stop argument")
+            if istart > 0:
+                return self._randbelow(istart)
+            raise ValueError("empty range for randrange()")
+
+        istop = _index(stop)
+        width = istop - istart
+        istep = _index(step)
+        if istep == 1:
+            if width > 0:
+                return istart + self._randbelow(width)
+            raise ValueError("empty range for randrange() (%d, %d, %d)"
+                             % (istart, istop, width))
+        if istep > 0:
+            n = (width + istep - 1) // istep
+        elif istep < 0:
+            n = (width + istep + 1) // istep
+        else:
+            raise ValueError("zero step for randrange()")
+        if n <= 0:
+            raise ValueError("empty range for randrange()")
+        return istart + istep * self._randbelow(n)
+
+    def randint(self, a, b):
+        """Return a random int in the range [a, b], including both end points."""
+        return self.randrange(a, b + 1)
+
+    # ── Sequences ───────────────────────────────────────────────────────────
+
+    def choice(self, seq):
+        """Choose a random element from a non-empty sequence."""
+        if not len(seq):
+            raise IndexError('Cannot choose from an empty sequence')
+        return seq[self._randbelow(len(seq))]
+
+    def shuffle(self, x):
+        """Shuffle list x in place, and return None."""
+        randbelow = self._randbelow
+        for i in reversed(range(1, len(x))):
+            j = randbelow(i + 1)
+            x[i], x[j] = x[j], x[i]
+
+    def sample(self, population, k, *, counts=None):
+        """Choose k unique random elements from a population sequence."""
+        if isinstance(population, (set, frozenset, dict)):
+            raise TypeError("Population must be a sequence.  "
+                            "For dicts or sets, use sorted(d).")
+        n = len(population)
+        if counts is not None:
+            cum_counts = list(_accumulate(counts))
+            if len(cum_counts) != n:
+                raise ValueError('The number of counts does not match the population')
+            total = cum_counts.pop()
+            if not isinstance(total, int):
+                raise TypeError('Counts must be integers')
+            if total <= 0:
+                raise ValueError('Total of counts must be greater than zero')
+            selections = self.sample(range(total), k=k)
+            bisect = _bisect
+            return [population[bisect(cum_counts, s)] for s in selections]
+        randbelow = self._randbelow
+        if not 0 <= k <= n:
+            raise ValueError("Sample larger than population or is negative")
+        result = [None] * k
+        setsize = 21
+        if k > 5:
+            setsize += 4 ** _ceil(_log(k * 3, 4))
+        if n <= setsize:
+            pool = list(population)
+            for i in range(k):
+                j = randbelow(n - i)
+                result[i] = pool[j]
+                pool[j] = pool[n - i - 1]
+        else:
+            selected = set()
+            for i in range(k):
+                j = randbelow(n)
+                while j in selected:
+                    j = randbelow(n)
+                selected.add(j)
+                result[i] = population[j]
+        return result
+
+    def choices(self, population, weights=None, *, cum_weights=None, k=1):
+        """Return a k sized list of population elements chosen with replacement."""
+        random = self.random
+        n = len(population)
+        if cum_weights is None:
+            if weights is None:
+                n += 0.0
+                return [population[_floor(random() * n)] for _ in _repeat(None, k)]
+            cum_weights = list(_accumulate(weights))
+        elif weights is not None:
+            raise TypeError('Cannot specify both weights and cumulative weights')
+        if len(cum_weights) != n:
+            raise ValueError('The number of weights does not match the population')
+        total = cum_weights[-1] + 0.0
+        if total <= 0.0:
+            raise ValueError('Total of weights must be greater than zero')
+        if not _isfinite(total):
+            raise ValueError('Total of weights must be finite')
+        hi = n - 1
+        return [population[_bisect(cum_weights, random() * total, 0, hi)]
+                for _ in _repeat(None, k)]
+
+    # ── Real-valued distributions ───────────────────────────────────────────
+
+    def uniform(self, a, b):
+        """Get a random number in the range [a, b) or [a, b] depending on rounding."""
+        return a + (b - a) * self.random()
+
+
+_inst = Random()
+seed = _inst.seed
+random = _inst.random
+uniform = _inst.uniform
+randint = _inst.randint
+randrange = _inst.randrange
+choice = _inst.choice
+choices = _inst.choices
+shuffle = _inst.shuffle
+sample = _inst.sample
+getrandbits = _inst.getrandbits
+getstate = _inst.getstate
+setstate = _inst.setstate
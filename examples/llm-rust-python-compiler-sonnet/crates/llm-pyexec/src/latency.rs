@@ -0,0 +1,374 @@
+//! Warm-path latency histogram for the llm-pyexec library.
+//!
+//! [`LatencyHistogram`] records execution durations into fixed log-scale
+//! buckets backed by atomics: memory is constant and every update is a bounded
+//! number of atomic operations (wait-free). [`execute`](crate::execute) records
+//! the wall-clock duration of every call served by the interpreter pool into the
+//! process-wide instance returned by [`latency_histogram`]; calls that fall back
+//! to a fresh interpreter are not recorded.
+//!
+//! ## Bucket layout
+//!
+//! Values below 8 ns get one bucket each. Above that, every power of two is split
+//! into 8 equal sub-buckets, so a percentile estimate (the midpoint of the bucket
+//! containing it) is within 1/16 (6.25%) of any value in that bucket.
+//!
+//! ## Latency SLO
+//!
+//! [`LatencyHistogram::set_slo`] configures a p95 target evaluated over rolling
+//! windows of `window` samples. When a window fills, its p95 is compared to the
+//! target and [`LatencyHistogram::slo_breached`] is updated; the window is then
+//! cleared. The flag reflects the most recently completed window.
+//!
+//! A call with [`ExecutionSettings::latency_slo`](crate::ExecutionSettings::latency_slo)
+//! set configures that target on the global histogram and reports the flag in
+//! [`ExecutionDiagnostics::slo_breached`](crate::ExecutionDiagnostics::slo_breached).
+
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::OnceLock;
+
+use serde::{Deserialize, Serialize};
+
+/// Sub-buckets per power of two, as a bit count (2^3 = 8).
+const SUB_BITS: u32 = 3;
+const SUB_COUNT: usize = 1 << SUB_BITS;
+/// Enough buckets to cover the full `u64` range.
+const BUCKETS: usize = (64 - SUB_BITS as usize + 1) * SUB_COUNT;
+
+/// Target for [`LatencyHistogram::set_slo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySlo {
+    /// Maximum acceptable p95 latency, in nanoseconds.
+    pub p95_ns: u64,
+    /// Number of samples per evaluation window (minimum 1).
+    pub window: u64,
+}
+
+/// Point-in-time view of a [`LatencyHistogram`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LatencySummary {
+    /// Number of recorded samples.
+    pub count: u64,
+    /// Estimated median, in nanoseconds.
+    pub p50_ns: u64,
+    /// Estimated 95th percentile, in nanoseconds.
+    pub p95_ns: u64,
+    /// Estimated 99th percentile, in nanoseconds.
+    pub p99_ns: u64,
+    /// Largest recorded sample (exact), in nanoseconds.
+    pub max_ns: u64,
+    /// Whether the last completed SLO window exceeded its p95 target.
+    pub slo_breached: bool,
+}
+
+/// Fixed-size, lock-free histogram of durations in nanoseconds.
+pub struct LatencyHistogram {
+    buckets: [AtomicU64; BUCKETS],
+    count: AtomicU64,
+    max_ns: AtomicU64,
+    window_buckets: [AtomicU64; BUCKETS],
+    window_count: AtomicU64,
+    /// `0` disables SLO evaluation.
+    slo_p95_ns: AtomicU64,
+    slo_window: AtomicU64,
+    slo_breached: AtomicBool,
+}
+
+impl Default for LatencyHistogram {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LatencyHistogram {
+    /// Create an empty histogram with no SLO configured.
+    pub fn new() -> Self {
+        Self {
+            buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            count: AtomicU64::new(0),
+            max_ns: AtomicU64::new(0),
+            window_buckets: std::array::from_fn(|_| AtomicU64::new(0)),
+            window_count: AtomicU64::new(0),
+            slo_p95_ns: AtomicU64::new(0),
+            slo_window: AtomicU64::new(0),
+            slo_breached: AtomicBool::new(false),
+        }
+    }
+
+    /// Record one duration sample.
+    pub fn record(&self, duration_ns: u64) {
+        let index = bucket_index(duration_ns);
+        self.buckets[index].fetch_add(1, Ordering::Relaxed);
+        self.count.fetch_add(1, Ordering::Relaxed);
+        self.max_ns.fetch_max(duration_ns, Ordering::Relaxed);
+
+        let target = self.slo_p95_ns.load(Ordering::Relaxed);
+        if target == 0 {
+            return;
+        }
+        self.window_buckets[index].fetch_add(1, Ordering::Relaxed);
+        let window = self.slo_window.load(Ordering::Relaxed).max(1);
+        let seen = self.window_count.fetch_add(1, Ordering::Relaxed) + 1;
+        // Several concurrent recorders can see the window full; only the one
+        // whose count is still current resets it, then evaluates and clears
+        // the window. Concurrent samples may straddle the reset, which only
+        // shifts them into the next window.
+        if seen >= window
+            && self
+                .window_count
+                .compare_exchange(seen, 0, Ordering::AcqRel, Ordering::Relaxed)
+                .is_ok()
+        {
+            let p95 = estimate(&self.window_buckets, seen, 0.95);
+            self.slo_breached.store(p95 > target, Ordering::Relaxed);
+            for bucket in &self.window_buckets {
+                bucket.store(0, Ordering::Relaxed);
+            }
+        }
+    }
+
+    /// Number of recorded samples.
+    pub fn count(&self) -> u64 {
+        self.count.load(Ordering::Relaxed)
+    }
+
+    /// Estimate the `q` quantile (`0.0..=1.0`) in nanoseconds.
+    ///
+    /// Returns `0` when no samples have been recorded.
+    pub fn percentile(&self, q: f64) -> u64 {
+        estimate(&self.buckets, self.count(), q)
+    }
+
+    /// Largest recorded sample, in nanoseconds.
+    pub fn max_ns(&self) -> u64 {
+        self.max_ns.load(Ordering::Relaxed)
+    }
+
+    /// Configure (or with `None`, disable) the rolling p95 SLO.
+    ///
+    /// Resets the current window and clears the breached flag.
+    pub fn set_slo(&self, slo: Option<LatencySlo>) {
+        let (p95_ns, window) = slo.map_or((0, 0), |s| (s.p95_ns.max(1), s.window.max(1)));
+        self.slo_p95_ns.store(0, Ordering::Relaxed);
+        for bucket in &self.window_buckets {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.window_count.store(0, Ordering::Relaxed);
+        self.slo_breached.store(false, Ordering::Relaxed);
+        self.slo_window.store(window, Ordering::Relaxed);
+        self.slo_p95_ns.store(p95_ns, Ordering::Relaxed);
+    }
+
+    /// Configure `slo` unless it is already the configured target, so that
+    /// calls repeating the same target keep the current window.
+    pub(crate) fn apply_slo(&self, slo: LatencySlo) {
+        let slo = LatencySlo {
+            p95_ns: slo.p95_ns.max(1),
+            window: slo.window.max(1),
+        };
+        if self.slo() != Some(slo) {
+            self.set_slo(Some(slo));
+        }
+    }
+
+    /// The configured SLO, if any.
+    pub fn slo(&self) -> Option<LatencySlo> {
+        match self.slo_p95_ns.load(Ordering::Relaxed) {
+            0 => None,
+            p95_ns => Some(LatencySlo {
+                p95_ns,
+                window: self.slo_window.load(Ordering::Relaxed),
+            }),
+        }
+    }
+
+    /// Whether the most recently completed SLO window exceeded its p95 target.
+    pub fn slo_breached(&self) -> bool {
+        self.slo_breached.load(Ordering::Relaxed)
+    }
+
+    /// Snapshot the common percentiles and the SLO flag.
+    pub fn summary(&self) -> LatencySummary {
+        LatencySummary {
+            count: self.count(),
+            p50_ns: self.percentile(0.50),
+            p95_ns: self.percentile(0.95),
+            p99_ns: self.percentile(0.99),
+            max_ns: self.max_ns(),
+            slo_breached: self.slo_breached(),
+        }
+    }
+
+    /// Discard all samples. The SLO configuration is kept.
+    pub fn reset(&self) {
+        for bucket in self.buckets.iter().chain(&self.window_buckets) {
+            bucket.store(0, Ordering::Relaxed);
+        }
+        self.count.store(0, Ordering::Relaxed);
+        self.max_ns.store(0, Ordering::Relaxed);
+        self.window_count.store(0, Ordering::Relaxed);
+        self.slo_breached.store(false, Ordering::Relaxed);
+    }
+}
+
+/// Return the process-wide warm-path latency histogram.
+pub fn latency_histogram() -> &'static LatencyHistogram {
+    static INSTANCE: OnceLock<LatencyHistogram> = OnceLock::new();
+    INSTANCE.get_or_init(LatencyHistogram::new)
+}
+
+// ── Bucket math ───────────────────────────────────────────────────────────────
+
+fn bucket_index(value: u64) -> usize {
+    if value < SUB_COUNT as u64 {
+        return value as usize;
+    }
+    let msb = 63 - value.leading_zeros();
+    let shift = msb - SUB_BITS;
+    let sub = ((value >> shift) as usize) & (SUB_COUNT - 1);
+    (shift as usize + 1) * SUB_COUNT + sub
+}
+
+/// Inclusive lower and exclusive upper bound of bucket `index`.
+fn bucket_bounds(index: usize) -> (u64, u64) {
+    if index < SUB_COUNT {
+        return (index as u64, index as u64 + 1);
+    }
+    let shift = (index / SUB_COUNT - 1) as u32;
+    let sub = (index % SUB_COUNT) as u128;
+    let lower = (SUB_COUNT as u128 + sub) << shift;
+    let upper = (SUB_COUNT as u128 + sub + 1) << shift;
+    (lower as u64, upper.min(u64::MAX as u128) as u64)
+}
+
+/// Midpoint of the bucket containing the `q` quantile of `count` samples.
+fn estimate(buckets: &[AtomicU64; BUCKETS], count: u64, q: f64) -> u64 {
+    if count == 0 {
+        return 0;
+    }
+    let rank = ((q.clamp(0.0, 1.0) * count as f64).ceil() as u64).max(1);
+    let mut seen = 0u64;
+    for (index, bucket) in buckets.iter().enumerate() {
+        seen += bucket.load(Ordering::Relaxed);
+        if seen >= rank {
+            let (lower, upper) = bucket_bounds(index);
+            return lower + (upper - lower) / 2;
+        }
+    }
+    // Concurrent updates can make the bucket total lag `count`; fall back to
+    // the highest non-empty bucket.
+    buckets
+        .iter()
+        .rposition(|b| b.load(Ordering::Relaxed) > 0)
+        .map_or(0, |index| bucket_bounds(index).0)
+}
+
+// ── Tests ─────────────────────────────────────────────────────────────────────
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(estimate: u64, actual: u64) {
+        let error = estimate.abs_diff(actual) as f64 / actual as f64;
+        assert!(
+            error <= 1.0 / 16.0,
+            "estimate {estimate} not within bucket resolution of {actual} ({error:.3})"
+        );
+    }
+
+    #[test]
+    fn test_bucket_index_and_bounds_are_consistent() {
+        for value in [
+            0,
+            1,
+            7,
+            8,
+            9,
+            15,
+            16,
+            1_000,
+            123_456_789,
+            u64::MAX / 3,
+            u64::MAX,
+        ] {
+            let (lower, upper) = bucket_bounds(bucket_index(value));
+            assert!(lower <= value, "{value} below bucket [{lower}, {upper})");
+            assert!(
+                value < upper || upper == u64::MAX,
+                "{value} above [{lower}, {upper})"
+            );
+        }
+        assert_eq!(bucket_index(u64::MAX), BUCKETS - 1);
+    }
+
+    #[test]
+    fn test_empty_histogram() {
+        let h = LatencyHistogram::new();
+        assert_eq!(h.count(), 0);
+        assert_eq!(h.percentile(0.95), 0);
+        assert_eq!(h.summary(), LatencySummary::default());
+    }
+
+    #[test]
+    fn test_percentiles_within_bucket_resolution() {
+        let h = LatencyHistogram::new();
+        // 1..=1000 µs, uniformly.
+        for us in 1..=1000u64 {
+            h.record(us * 1_000);
+        }
+        assert_eq!(h.count(), 1000);
+        assert_eq!(h.max_ns(), 1_000_000);
+        assert_close(h.percentile(0.50), 500_000);
+        assert_close(h.percentile(0.95), 950_000);
+        assert_close(h.percentile(0.99), 990_000);
+    }
+
+    #[test]
+    fn test_reset_discards_samples() {
+        let h = LatencyHistogram::new();
+        h.record(42);
+        h.reset();
+        assert_eq!(h.count(), 0);
+        assert_eq!(h.max_ns(), 0);
+    }
+
+    #[test]
+    fn test_slo_breach_flips_after_window() {
+        let h = LatencyHistogram::new();
+        h.set_slo(Some(LatencySlo {
+            p95_ns: 100_000,
+            window: 20,
+        }));
+
+        for _ in 0..20 {
+            h.record(50_000);
+        }
+        assert!(!h.slo_breached(), "fast window must not breach");
+
+        for _ in 0..19 {
+            h.record(500_000);
+        }
+        assert!(
+            !h.slo_breached(),
+            "flag only changes when a window completes"
+        );
+        h.record(500_000);
+        assert!(h.slo_breached(), "slow window must breach");
+
+        for _ in 0..20 {
+            h.record(10_000);
+        }
+        assert!(!h.slo_breached(), "recovered window must clear the flag");
+    }
+
+    #[test]
+    fn test_slo_disabled_never_breaches() {
+        let h = LatencyHistogram::new();
+        for _ in 0..100 {
+            h.record(u64::MAX / 2);
+        }
+        assert!(!h.slo_breached());
+        assert_eq!(h.slo(), None);
+    }
+}
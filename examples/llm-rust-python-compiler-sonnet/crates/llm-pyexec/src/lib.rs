@@ -1,18 +1,49 @@
 // llm-pyexec: Rust library for executing Python source strings via RustPython VM.
 
 pub mod cache;
+pub mod cancel;
+pub mod compiled;
+pub mod concurrency;
+#[cfg(feature = "conformance")]
+pub mod conformance;
+pub(crate) mod cpu_time;
+pub mod environment;
 pub mod executor;
+pub mod frozen;
+pub mod latency;
+#[cfg(feature = "test-util")]
+pub mod mock;
 pub mod modules;
 pub mod output;
 pub mod pool;
+pub mod session;
 pub mod timeout;
 pub mod types;
 pub(crate) mod vm;
 
-pub use cache::BytecodeCache;
-pub use executor::{execute, maybe_wrap_last_expr};
+pub use cache::{BytecodeCache, CacheKey, CacheStats};
+pub use cancel::{ExecutionHandle, PendingExecution};
+pub use compiled::{CompiledSnippet, ExecutionOverrides};
+pub use concurrency::ConcurrencyLimit;
+pub use environment::{
+    environment_report, environment_report_for, EnvironmentReport, ModuleStatus,
+};
+pub use executor::{
+    analyze_imports, compile, execute, execute_batch, execute_cancellable, execute_compiled,
+    execute_with, maybe_wrap_last_expr, precompile, DefaultExecutor, ImportAnalysis,
+    PrecompileSummary, PyExecutor,
+};
+pub use frozen::{
+    frozen_module_source, frozen_modules, register_frozen_module, FrozenModuleInfo, FrozenOrigin,
+};
+pub use latency::{latency_histogram, LatencyHistogram, LatencySlo, LatencySummary};
 pub use output::OutputBuffer;
-pub use pool::InterpreterPool;
+pub use pool::{InterpreterPool, InterpreterPoolBuilder, PoolMetrics};
+pub use session::Session;
 pub use types::{
-    ExecutionError, ExecutionResult, ExecutionSettings, DEFAULT_ALLOWED_MODULES,
+    AlreadyInitialized, DeterministicConfig, EncodingErrors, ExecutionDiagnostics, ExecutionError,
+    ExecutionMode, ExecutionPath, ExecutionResult, ExecutionSettings, InspectOutcome, InspectQuery,
+    InspectResult, PyValue, ResultCapture, SettingsError, TimeoutKind, Timing, TracebackFrame,
+    CPU_MODE_WALL_TIMEOUT_MULTIPLE, DEFAULT_ALLOWED_MODULES, DEFAULT_RECURSION_LIMIT,
+    STRICT_DENIED_ATTRIBUTES, UNGUARDABLE_ATTRIBUTES,
 };
@@ -0,0 +1,91 @@
+//! Canned-result executor for downstream unit tests (feature `test-util`).
+//!
+//! [`MockExecutor`] implements [`PyExecutor`] without a VM: each call returns
+//! the result registered for the first source substring that matches, and the
+//! sources it was called with are recorded for later assertions.
+//!
+//! ```
+//! use llm_pyexec::mock::MockExecutor;
+//! use llm_pyexec::{ExecutionResult, ExecutionSettings, PyExecutor};
+//!
+//! let mock = MockExecutor::new().on(
+//!     "6 * 7",
+//!     ExecutionResult {
+//!         return_value: Some("42".to_string()),
+//!         ..ExecutionResult::default()
+//!     },
+//! );
+//! let executor: Box<dyn PyExecutor> = Box::new(mock);
+//! let result = executor.execute("6 * 7", ExecutionSettings::default());
+//! assert_eq!(result.return_value.as_deref(), Some("42"));
+//! ```
+
+use std::sync::Mutex;
+
+use crate::executor::PyExecutor;
+use crate::types::{ExecutionError, ExecutionResult, ExecutionSettings};
+
+/// A [`PyExecutor`] that returns canned [`ExecutionResult`]s keyed by source
+/// substring.
+#[derive(Debug, Default)]
+pub struct MockExecutor {
+    /// `(substring, result)` pairs, checked in registration order.
+    responses: Vec<(String, ExecutionResult)>,
+    /// Returned when no substring matches.
+    fallback: Option<ExecutionResult>,
+    /// Sources passed to `execute`, in call order.
+    calls: Mutex<Vec<String>>,
+}
+
+impl MockExecutor {
+    /// A mock with no canned results.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Return `result` for any source containing `substring`.
+    ///
+    /// When several substrings match, the one registered first wins.
+    pub fn on(mut self, substring: impl Into<String>, result: ExecutionResult) -> Self {
+        self.responses.push((substring.into(), result));
+        self
+    }
+
+    /// Return `result` for sources that match no registered substring.
+    ///
+    /// Without a fallback, such calls return a `RuntimeError` naming the source.
+    pub fn otherwise(mut self, result: ExecutionResult) -> Self {
+        self.fallback = Some(result);
+        self
+    }
+
+    /// The sources `execute` has been called with, in call order.
+    pub fn calls(&self) -> Vec<String> {
+        self.calls.lock().expect("mock calls poisoned").clone()
+    }
+}
+
+impl PyExecutor for MockExecutor {
+    fn execute(&self, code: &str, _settings: ExecutionSettings) -> ExecutionResult {
+        self.calls
+            .lock()
+            .expect("mock calls poisoned")
+            .push(code.to_string());
+
+        self.responses
+            .iter()
+            .find(|(substring, _)| code.contains(substring.as_str()))
+            .map(|(_, result)| result.clone())
+            .or_else(|| self.fallback.clone())
+            .unwrap_or_else(|| ExecutionResult {
+                error: Some(ExecutionError::RuntimeError {
+                    message: format!("MockExecutor: no canned result for {code:?}"),
+                    traceback: String::new(),
+                    line: None,
+                    col: None,
+                    frames: Vec::new(),
+                }),
+                ..ExecutionResult::default()
+            })
+    }
+}
@@ -1,9 +1,19 @@
 //! Module allowlist checker for the llm-pyexec library.
 //!
-//! Provides two public functions:
+//! Provides these public functions:
 //! - [`check_module_allowed`] — verifies a module name against an allowlist `HashSet`.
-//! - [`build_allowed_set`] — converts [`ExecutionSettings::allowed_modules`] into a
-//!   `HashSet<String>` for O(1) per-import lookup.
+//! - [`check_module_allowed_verbose`] — the check the import hook applies,
+//!   which also covers submodules and [`ExecutionSettings::denied_modules`],
+//!   reporting a [`DenialReason`] on failure.
+//! - [`build_allowed_set`] — converts [`ExecutionSettings::allowed_modules`] (plus the
+//!   names of any [`ExecutionSettings::virtual_modules`]) into a `HashSet<String>`
+//!   for O(1) per-import lookup.
+//! - [`check_module_forbidden`] — rejects [`FORBIDDEN_MODULES`], which no
+//!   allowlist can permit unless [`ExecutionSettings::allow_forbidden`] is set.
+//! - [`build_denied_set`] — the same for [`ExecutionSettings::denied_modules`].
+//! - [`build_denied_attributes`] — the `(module, attribute)` pairs withheld by
+//!   [`ExecutionSettings::denied_attributes`] and
+//!   [`ExecutionSettings::strict_attributes`].
 //!
 //! ## Special case: `os` / `os.path`
 //!
@@ -13,8 +23,11 @@
 //! `"os"` whenever `"os.path"` is present in the allowlist.
 
 use std::collections::HashSet;
+use std::fmt;
 
-use crate::types::{ExecutionError, ExecutionSettings};
+use crate::types::{
+    ExecutionError, ExecutionSettings, STRICT_DENIED_ATTRIBUTES, UNGUARDABLE_ATTRIBUTES,
+};
 
 /// Checks whether `module_name` is permitted by the given allowlist.
 ///
@@ -45,10 +58,167 @@ pub fn check_module_allowed(
     })
 }
 
+/// Modules user code may never import, whatever the allowlist says, with the
+/// reason reported in [`ExecutionError::ModuleForbidden`]. Their submodules
+/// are forbidden too.
+pub const FORBIDDEN_MODULES: &[(&str, &str)] = &[
+    ("threading", SPAWN_REASON),
+    ("_thread", SPAWN_REASON),
+    ("multiprocessing", SPAWN_REASON),
+    ("subprocess", SPAWN_REASON),
+    (
+        "ctypes",
+        "calling native code is not permitted in the sandbox",
+    ),
+    ("socket", "network access is not permitted in the sandbox"),
+];
+
+const SPAWN_REASON: &str = "spawning threads/processes is not permitted in the sandbox";
+
+/// Why [`check_module_allowed_verbose`] or [`check_module_forbidden`] denied
+/// a module.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DenialReason {
+    /// Neither the module nor its top-level package is allowed.
+    NotInAllowlist,
+    /// The module itself is in the deny set.
+    ExplicitlyDenied,
+    /// A package containing the module is in the deny set.
+    ParentDenied {
+        /// The denied package, e.g. `"xml.etree"` for `"xml.etree.ElementTree"`.
+        parent: String,
+    },
+    /// The module or its top-level package is in [`FORBIDDEN_MODULES`].
+    Forbidden {
+        /// Why such modules are never allowed.
+        reason: &'static str,
+    },
+}
+
+impl fmt::Display for DenialReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DenialReason::NotInAllowlist => write!(f, "not in the allowlist"),
+            DenialReason::ExplicitlyDenied => write!(f, "explicitly denied"),
+            DenialReason::ParentDenied { parent } => {
+                write!(f, "package '{parent}' is denied")
+            }
+            DenialReason::Forbidden { reason } => f.write_str(reason),
+        }
+    }
+}
+
+/// Checks whether user code may import `module_name`, explaining a denial.
+///
+/// Deny entries win: the module is denied if it, or any package containing
+/// it, is in `denied_set`. Otherwise it is allowed if [`check_module_allowed`]
+/// accepts the module itself or its top-level package, so allowing `"json"`
+/// also allows `"json.decoder"`.
+pub fn check_module_allowed_verbose(
+    module_name: &str,
+    allowed_set: &HashSet<String>,
+    denied_set: &HashSet<String>,
+) -> Result<(), DenialReason> {
+    if denied_set.contains(module_name) {
+        return Err(DenialReason::ExplicitlyDenied);
+    }
+    let mut parents = module_name
+        .match_indices('.')
+        .map(|(i, _)| &module_name[..i]);
+    if let Some(parent) = parents.clone().find(|p| denied_set.contains(*p)) {
+        return Err(DenialReason::ParentDenied {
+            parent: parent.to_string(),
+        });
+    }
+
+    let top_level = parents.next();
+    if check_module_allowed(module_name, allowed_set).is_ok()
+        || top_level.is_some_and(|p| check_module_allowed(p, allowed_set).is_ok())
+    {
+        Ok(())
+    } else {
+        Err(DenialReason::NotInAllowlist)
+    }
+}
+
+/// Checks that `module_name` is not one of [`FORBIDDEN_MODULES`] or a
+/// submodule of one. The import hook applies this before the allowlist, so a
+/// permissive allowlist cannot let these modules through.
+pub fn check_module_forbidden(module_name: &str) -> Result<(), DenialReason> {
+    let top_level = module_name.split('.').next().unwrap_or(module_name);
+    let forbidden = FORBIDDEN_MODULES
+        .iter()
+        .find(|(name, _)| *name == top_level);
+    match forbidden {
+        Some((_, reason)) => Err(DenialReason::Forbidden { reason }),
+        None => Ok(()),
+    }
+}
+
 /// Builds a `HashSet<String>` from [`ExecutionSettings::allowed_modules`] for
 /// O(1) per-import lookup during Python execution.
+///
+/// [`ExecutionSettings::extra_allowed_modules`] and the names of
+/// [`ExecutionSettings::virtual_modules`] are always included.
 pub fn build_allowed_set(settings: &ExecutionSettings) -> HashSet<String> {
-    settings.allowed_modules.iter().cloned().collect()
+    settings
+        .allowed_modules
+        .iter()
+        .chain(&settings.extra_allowed_modules)
+        .chain(settings.virtual_modules.keys())
+        .cloned()
+        .collect()
+}
+
+/// Builds a `HashSet<String>` from [`ExecutionSettings::denied_modules`].
+pub fn build_denied_set(settings: &ExecutionSettings) -> HashSet<String> {
+    settings.denied_modules.iter().cloned().collect()
+}
+
+/// Splits a dotted attribute path at its last dot: `"os.path.join"` is
+/// `("os.path", "join")`. `None` unless both parts are non-empty.
+pub(crate) fn split_attribute_path(path: &str) -> Option<(&str, &str)> {
+    path.rsplit_once('.')
+        .filter(|(module, attribute)| !module.is_empty() && !attribute.is_empty())
+}
+
+/// Modules that back `os`: it re-exports their functions, which user code can
+/// reach through `sys.modules` without going through `os`.
+const OS_BACKING_MODULES: &[&str] = &["posix", "nt"];
+
+/// The `(module, attribute)` pairs withheld from user code: the entries of
+/// [`ExecutionSettings::denied_attributes`], plus [`STRICT_DENIED_ATTRIBUTES`]
+/// if [`ExecutionSettings::strict_attributes`] is set. Each `os` pair is
+/// followed by the same attribute of [`OS_BACKING_MODULES`].
+///
+/// Each pair is listed once. Entries that are not dotted paths or are in
+/// [`UNGUARDABLE_ATTRIBUTES`] are skipped (see [`ExecutionSettings::validate`]).
+pub fn build_denied_attributes(settings: &ExecutionSettings) -> Vec<(String, String)> {
+    let strict = if settings.strict_attributes {
+        STRICT_DENIED_ATTRIBUTES
+    } else {
+        &[]
+    };
+    let mut pairs: Vec<(String, String)> = Vec::new();
+    let entries = settings
+        .denied_attributes
+        .iter()
+        .map(String::as_str)
+        .chain(strict.iter().copied());
+    let entries = entries.filter(|entry| !UNGUARDABLE_ATTRIBUTES.contains(entry));
+    for (module, attribute) in entries.filter_map(split_attribute_path) {
+        let backing: &[&str] = if module == "os" {
+            OS_BACKING_MODULES
+        } else {
+            &[]
+        };
+        for module in std::iter::once(module).chain(backing.iter().copied()) {
+            if !pairs.iter().any(|(m, a)| m == module && a == attribute) {
+                pairs.push((module.to_string(), attribute.to_string()));
+            }
+        }
+    }
+    pairs
 }
 
 #[cfg(test)]
@@ -122,6 +292,140 @@ mod tests {
         );
     }
 
+    // ── check_module_allowed_verbose ───────────────────────────────────────────
+
+    fn set(names: &[&str]) -> HashSet<String> {
+        names.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_verbose_allows_module_and_submodules_of_allowed_package() {
+        let allowed = set(&["json"]);
+        let denied = HashSet::new();
+        assert_eq!(
+            check_module_allowed_verbose("json", &allowed, &denied),
+            Ok(())
+        );
+        assert_eq!(
+            check_module_allowed_verbose("json.decoder", &allowed, &denied),
+            Ok(())
+        );
+    }
+
+    #[test]
+    fn test_verbose_not_in_allowlist() {
+        let allowed = set(&["json"]);
+        let denied = HashSet::new();
+        assert_eq!(
+            check_module_allowed_verbose("socket", &allowed, &denied),
+            Err(DenialReason::NotInAllowlist)
+        );
+        assert_eq!(
+            check_module_allowed_verbose("xml.dom", &allowed, &denied),
+            Err(DenialReason::NotInAllowlist)
+        );
+    }
+
+    #[test]
+    fn test_verbose_explicitly_denied_beats_allowlist() {
+        let allowed = set(&["json"]);
+        let denied = set(&["json"]);
+        assert_eq!(
+            check_module_allowed_verbose("json", &allowed, &denied),
+            Err(DenialReason::ExplicitlyDenied)
+        );
+    }
+
+    #[test]
+    fn test_verbose_denied_submodule_of_allowed_parent() {
+        let allowed = set(&["xml"]);
+        let denied = set(&["xml.etree"]);
+        assert_eq!(
+            check_module_allowed_verbose("xml.dom", &allowed, &denied),
+            Ok(())
+        );
+        assert_eq!(
+            check_module_allowed_verbose("xml.etree", &allowed, &denied),
+            Err(DenialReason::ExplicitlyDenied)
+        );
+        assert_eq!(
+            check_module_allowed_verbose("xml.etree.ElementTree", &allowed, &denied),
+            Err(DenialReason::ParentDenied {
+                parent: "xml.etree".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_verbose_os_path_special_case() {
+        let allowed = set(&["os.path"]);
+        assert_eq!(
+            check_module_allowed_verbose("os", &allowed, &HashSet::new()),
+            Ok(()),
+            "'os' is granted by 'os.path', as in check_module_allowed"
+        );
+        assert_eq!(
+            check_module_allowed_verbose("os", &allowed, &set(&["os"])),
+            Err(DenialReason::ExplicitlyDenied)
+        );
+        assert_eq!(
+            check_module_allowed_verbose("os.path", &allowed, &set(&["os"])),
+            Err(DenialReason::ParentDenied {
+                parent: "os".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_denial_reason_display() {
+        assert_eq!(
+            DenialReason::NotInAllowlist.to_string(),
+            "not in the allowlist"
+        );
+        assert_eq!(
+            DenialReason::ExplicitlyDenied.to_string(),
+            "explicitly denied"
+        );
+        assert_eq!(
+            DenialReason::ParentDenied {
+                parent: "xml.etree".to_string()
+            }
+            .to_string(),
+            "package 'xml.etree' is denied"
+        );
+    }
+
+    // ── check_module_forbidden ─────────────────────────────────────────────────
+
+    #[test]
+    fn test_forbidden_modules_and_submodules_are_rejected() {
+        for name in [
+            "threading",
+            "_thread",
+            "ctypes.util",
+            "multiprocessing.pool",
+        ] {
+            assert!(
+                matches!(
+                    check_module_forbidden(name),
+                    Err(DenialReason::Forbidden { .. })
+                ),
+                "{name} must be forbidden"
+            );
+        }
+        assert_eq!(
+            check_module_forbidden("socket").unwrap_err().to_string(),
+            "network access is not permitted in the sandbox"
+        );
+    }
+
+    #[test]
+    fn test_other_modules_are_not_forbidden() {
+        for name in ["json", "os.path", "threadingx", "socketserver", "sys"] {
+            assert_eq!(check_module_forbidden(name), Ok(()), "{name}");
+        }
+    }
+
     // ── build_allowed_set ──────────────────────────────────────────────────────
 
     #[test]
@@ -161,4 +465,104 @@ mod tests {
         assert!(set.contains("json"));
         assert!(!set.contains("re"));
     }
+
+    #[test]
+    fn test_build_allowed_set_includes_virtual_module_names() {
+        let settings = ExecutionSettings {
+            allowed_modules: vec!["math".to_string()],
+            virtual_modules: [("helpers".to_string(), "X = 1\n".to_string())].into(),
+            ..ExecutionSettings::default()
+        };
+        let set = build_allowed_set(&settings);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("helpers"));
+    }
+
+    #[test]
+    fn test_build_allowed_set_adds_extra_modules_to_defaults() {
+        let settings = ExecutionSettings {
+            extra_allowed_modules: vec!["time".to_string(), "json".to_string()],
+            ..ExecutionSettings::default()
+        };
+        let set = build_allowed_set(&settings);
+        // "json" is already a default; only "time" is new.
+        assert_eq!(set.len(), DEFAULT_ALLOWED_MODULES.len() + 1);
+        assert!(set.contains("time"));
+        for module in DEFAULT_ALLOWED_MODULES {
+            assert!(set.contains(*module), "default '{module}' missing");
+        }
+    }
+
+    #[test]
+    fn test_build_allowed_set_adds_extra_modules_to_explicit_list() {
+        let settings = ExecutionSettings {
+            allowed_modules: vec!["math".to_string()],
+            extra_allowed_modules: vec!["time".to_string()],
+            ..ExecutionSettings::default()
+        };
+        let set = build_allowed_set(&settings);
+        assert_eq!(set.len(), 2);
+        assert!(set.contains("math"));
+        assert!(set.contains("time"));
+        assert!(!set.contains("json"), "explicit list replaces the defaults");
+    }
+
+    // ── build_denied_attributes ────────────────────────────────────────────────
+
+    #[test]
+    fn test_split_attribute_path_splits_at_last_dot() {
+        assert_eq!(split_attribute_path("os.system"), Some(("os", "system")));
+        assert_eq!(
+            split_attribute_path("os.path.join"),
+            Some(("os.path", "join"))
+        );
+        assert_eq!(split_attribute_path("system"), None);
+        assert_eq!(split_attribute_path("os."), None);
+        assert_eq!(split_attribute_path(".system"), None);
+    }
+
+    #[test]
+    fn test_build_denied_attributes_empty_by_default() {
+        assert!(build_denied_attributes(&ExecutionSettings::default()).is_empty());
+    }
+
+    #[test]
+    fn test_build_denied_attributes_strict_adds_defaults_once() {
+        let settings = ExecutionSettings {
+            denied_attributes: vec!["os.system".to_string(), "json.dumps".to_string()],
+            strict_attributes: true,
+            ..ExecutionSettings::default()
+        };
+        let pairs = build_denied_attributes(&settings);
+        let os_entries = STRICT_DENIED_ATTRIBUTES
+            .iter()
+            .filter(|entry| entry.starts_with("os."))
+            .count();
+        assert_eq!(
+            pairs.len(),
+            STRICT_DENIED_ATTRIBUTES.len() + 2 * os_entries + 1
+        );
+        assert_eq!(pairs[0], ("os".to_string(), "system".to_string()));
+        assert_eq!(pairs[3], ("json".to_string(), "dumps".to_string()));
+    }
+
+    #[test]
+    fn test_build_denied_attributes_covers_os_backing_modules() {
+        let settings = ExecutionSettings {
+            denied_attributes: vec![
+                "os.environ".to_string(),
+                "os.path.join".to_string(),
+                "sys.modules".to_string(),
+            ],
+            ..ExecutionSettings::default()
+        };
+        let pairs: Vec<String> = build_denied_attributes(&settings)
+            .into_iter()
+            .map(|(module, attribute)| format!("{module}.{attribute}"))
+            .collect();
+        assert_eq!(
+            pairs,
+            ["os.environ", "posix.environ", "nt.environ", "os.path.join"]
+        );
+    }
 }
@@ -9,7 +9,8 @@
 //! # Timeout path
 //!
 //! When the VM thread is abandoned on timeout, it may still hold a clone of the
-//! `OutputBuffer`.  [`into_strings`](OutputBuffer::into_strings) handles this
+//! `OutputBuffer`.  [`into_bytes`](OutputBuffer::into_bytes) (and
+//! [`into_strings`](OutputBuffer::into_strings), built on it) handles this
 //! gracefully: it tries `Arc::try_unwrap` first (fast path when no other clone
 //! exists) and falls back to locking the `Mutex` and cloning the inner data.
 
@@ -24,6 +25,8 @@ struct OutputBufferInner {
     stderr: Vec<u8>,
     max_bytes: usize,
     limit_exceeded: bool,
+    /// Bytes offered by every write, including rejected ones.
+    produced: usize,
 }
 
 impl OutputBufferInner {
@@ -33,6 +36,7 @@ impl OutputBufferInner {
             stderr: Vec::new(),
             max_bytes,
             limit_exceeded: false,
+            produced: 0,
         }
     }
 
@@ -40,6 +44,58 @@ impl OutputBufferInner {
     fn total_len(&self) -> usize {
         self.stdout.len() + self.stderr.len()
     }
+
+    /// Appends as much of `data` as fits under the limit to stdout or stderr.
+    ///
+    /// A write that does not fit keeps the longest prefix that fits without
+    /// splitting a UTF-8 sequence, and marks the limit exceeded; every later
+    /// write is rejected, so the captured output is a prefix of what the
+    /// program wrote.
+    fn append(&mut self, data: &[u8], to_stdout: bool) -> Result<(), ExecutionError> {
+        if self.append_prefix(data, to_stdout) {
+            return Ok(());
+        }
+        self.limit_exceeded = true;
+        Err(ExecutionError::OutputLimitExceeded {
+            limit_bytes: self.max_bytes,
+        })
+    }
+
+    /// Appends the longest prefix of `data` that fits under the limit without
+    /// splitting a UTF-8 sequence, returning whether all of it fit. Nothing is
+    /// kept once the limit has been exceeded.
+    fn append_prefix(&mut self, data: &[u8], to_stdout: bool) -> bool {
+        self.produced += data.len();
+        let room = if self.limit_exceeded {
+            0
+        } else {
+            self.max_bytes.saturating_sub(self.total_len())
+        };
+        let kept = if data.len() <= room {
+            data.len()
+        } else {
+            utf8_floor(data, room)
+        };
+        let stream = if to_stdout {
+            &mut self.stdout
+        } else {
+            &mut self.stderr
+        };
+        stream.extend_from_slice(&data[..kept]);
+        kept == data.len()
+    }
+}
+
+/// Largest `cut <= max` that does not fall inside a UTF-8 sequence of `data`.
+///
+/// Only continuation bytes are skipped, and at most three of them (the tail of
+/// a 4-byte sequence), so binary data is cut at `max` or just before it.
+fn utf8_floor(data: &[u8], max: usize) -> usize {
+    let mut cut = max;
+    while cut > max.saturating_sub(3) && cut > 0 && data[cut] & 0xC0 == 0x80 {
+        cut -= 1;
+    }
+    cut
 }
 
 // ── Public API ────────────────────────────────────────────────────────────────
@@ -66,18 +122,12 @@ impl OutputBuffer {
     ///
     /// Returns `Err(ExecutionError::OutputLimitExceeded { limit_bytes })` if
     /// accepting `data` would push the combined stdout+stderr total over
-    /// `max_bytes`.  On error the buffer state is *not* modified and
-    /// `is_limit_exceeded()` is set to `true`.
+    /// `max_bytes`.  On error the part of `data` that fits is still kept (cut
+    /// on a UTF-8 character boundary), `is_limit_exceeded()` is set to `true`,
+    /// and every later write is rejected outright.
     pub fn write_stdout(&self, data: &[u8]) -> Result<(), ExecutionError> {
         let mut inner = self.inner.lock().expect("OutputBuffer mutex poisoned");
-        if inner.total_len() + data.len() > inner.max_bytes {
-            inner.limit_exceeded = true;
-            return Err(ExecutionError::OutputLimitExceeded {
-                limit_bytes: inner.max_bytes,
-            });
-        }
-        inner.stdout.extend_from_slice(data);
-        Ok(())
+        inner.append(data, true)
     }
 
     /// Appends `data` to the stderr stream.
@@ -85,52 +135,103 @@ impl OutputBuffer {
     /// Same limit semantics as [`write_stdout`](Self::write_stdout).
     pub fn write_stderr(&self, data: &[u8]) -> Result<(), ExecutionError> {
         let mut inner = self.inner.lock().expect("OutputBuffer mutex poisoned");
-        if inner.total_len() + data.len() > inner.max_bytes {
-            inner.limit_exceeded = true;
-            return Err(ExecutionError::OutputLimitExceeded {
-                limit_bytes: inner.max_bytes,
-            });
-        }
-        inner.stderr.extend_from_slice(data);
-        Ok(())
+        inner.append(data, false)
+    }
+
+    /// Appends as much of `data` to the stderr stream as fits, for output
+    /// written on the program's behalf rather than by it (the traceback of an
+    /// uncaught exception).
+    ///
+    /// Unlike [`write_stderr`](Self::write_stderr), a cut is not an overflow:
+    /// [`is_limit_exceeded`](Self::is_limit_exceeded) is left unchanged, so
+    /// such output can never turn a run into an output-limit failure.
+    pub(crate) fn write_stderr_prefix(&self, data: &[u8]) {
+        let mut inner = self.inner.lock().expect("OutputBuffer mutex poisoned");
+        inner.append_prefix(data, false);
     }
 
-    /// Returns `true` if any write has been rejected due to the byte limit.
+    /// Returns `true` if any write has been cut short or rejected due to the
+    /// byte limit.
     pub fn is_limit_exceeded(&self) -> bool {
         let inner = self.inner.lock().expect("OutputBuffer mutex poisoned");
         inner.limit_exceeded
     }
 
+    /// Returns the combined stdout + stderr bytes the program tried to write,
+    /// including writes rejected by the limit.
+    ///
+    /// Equals the captured length when the limit was never hit, and exceeds
+    /// `max_bytes` once it was.
+    pub fn total_written(&self) -> usize {
+        let inner = self.inner.lock().expect("OutputBuffer mutex poisoned");
+        inner.produced
+    }
+
+    /// Returns `true` if stdout or stderr holds bytes that are not valid UTF-8,
+    /// i.e. if [`into_strings`](Self::into_strings) will replace some of them.
+    ///
+    /// Snippets can only produce such output by writing `bytes` to
+    /// `sys.stdout.buffer` / `sys.stderr.buffer`.
+    pub fn has_invalid_utf8(&self) -> bool {
+        self.invalid_utf8_stream().is_some()
+    }
+
+    /// The first stream, `"stdout"` or `"stderr"`, holding bytes that are not
+    /// valid UTF-8, or `None` if both are valid.
+    pub fn invalid_utf8_stream(&self) -> Option<&'static str> {
+        let inner = self.inner.lock().expect("OutputBuffer mutex poisoned");
+        if std::str::from_utf8(&inner.stdout).is_err() {
+            Some("stdout")
+        } else if std::str::from_utf8(&inner.stderr).is_err() {
+            Some("stderr")
+        } else {
+            None
+        }
+    }
+
     /// Consumes this handle and returns `(stdout, stderr)` as UTF-8 strings.
     ///
-    /// Invalid UTF-8 sequences are replaced with the Unicode replacement
-    /// character (`\u{FFFD}`) via [`String::from_utf8_lossy`].
+    /// The conversion is lossy: invalid UTF-8 sequences are replaced with the
+    /// Unicode replacement character (`\u{FFFD}`) via [`String::from_utf8_lossy`].
+    /// Use [`into_bytes`](Self::into_bytes) to get the output exactly as
+    /// written, or [`has_invalid_utf8`](Self::has_invalid_utf8) to detect the
+    /// replacement beforehand.
+    ///
+    /// Same fallback as [`into_bytes`](Self::into_bytes) when another clone is
+    /// still alive.
+    pub fn into_strings(self) -> (String, String) {
+        let (stdout, stderr) = self.into_bytes();
+        (lossy_string(stdout), lossy_string(stderr))
+    }
+
+    /// Consumes this handle and returns the raw `(stdout, stderr)` bytes.
     ///
     /// If another `Arc` clone exists (e.g. the VM thread is still running after
     /// a timeout), this method falls back to locking the `Mutex` and cloning
     /// the byte vectors rather than panicking.
-    pub fn into_strings(self) -> (String, String) {
+    pub fn into_bytes(self) -> (Vec<u8>, Vec<u8>) {
         match Arc::try_unwrap(self.inner) {
             Ok(mutex) => {
                 // We are the sole owner — unwrap without locking.
                 let inner = mutex.into_inner().expect("OutputBuffer mutex poisoned");
-                (
-                    String::from_utf8_lossy(&inner.stdout).into_owned(),
-                    String::from_utf8_lossy(&inner.stderr).into_owned(),
-                )
+                (inner.stdout, inner.stderr)
             }
             Err(arc) => {
                 // Another clone exists (timeout path) — lock and clone the data.
                 let inner = arc.lock().expect("OutputBuffer mutex poisoned");
-                (
-                    String::from_utf8_lossy(&inner.stdout).into_owned(),
-                    String::from_utf8_lossy(&inner.stderr).into_owned(),
-                )
+                (inner.stdout.clone(), inner.stderr.clone())
             }
         }
     }
 }
 
+/// Converts `bytes` to a `String`, replacing invalid UTF-8 with `\u{FFFD}`.
+/// Valid input is reused without copying.
+fn lossy_string(bytes: Vec<u8>) -> String {
+    String::from_utf8(bytes)
+        .unwrap_or_else(|err| String::from_utf8_lossy(err.as_bytes()).into_owned())
+}
+
 // ── Unit tests ────────────────────────────────────────────────────────────────
 
 #[cfg(test)]
@@ -191,6 +292,8 @@ mod tests {
         // Overflow immediately with a write larger than the limit.
         let _ = buf.write_stdout(b"toolong");
         assert!(buf.is_limit_exceeded());
+        // The part that fits is kept.
+        assert_eq!(buf.into_strings().0, "too");
     }
 
     // (6) clone() shares state — write via clone is visible through original
@@ -247,5 +350,81 @@ mod tests {
             other => panic!("expected OutputLimitExceeded, got {:?}", other),
         }
         assert!(buf.is_limit_exceeded());
+        assert_eq!(
+            buf.into_strings(),
+            ("123456".to_string(), "abcd".to_string())
+        );
+    }
+
+    // (10) total_written counts accepted and rejected bytes
+    #[test]
+    fn test_total_written_includes_rejected_writes() {
+        let buf = OutputBuffer::new(10);
+        buf.write_stdout(b"123456").unwrap();
+        buf.write_stderr(b"ab").unwrap();
+        assert_eq!(buf.total_written(), 8);
+        assert!(buf.write_stdout(b"xyz").is_err());
+        assert_eq!(buf.total_written(), 11);
+    }
+
+    // (11) into_bytes keeps invalid UTF-8 exactly as written
+    #[test]
+    fn test_into_bytes_preserves_invalid_utf8() {
+        let buf = OutputBuffer::new(64);
+        buf.write_stdout(b"ok \xff").unwrap();
+        buf.write_stderr(b"fine").unwrap();
+        assert!(buf.has_invalid_utf8());
+        assert_eq!(buf.invalid_utf8_stream(), Some("stdout"));
+        let (stdout, stderr) = buf.into_bytes();
+        assert_eq!(stdout, b"ok \xff");
+        assert_eq!(stderr, b"fine");
+    }
+
+    // (12) has_invalid_utf8 is false for text output
+    #[test]
+    fn test_has_invalid_utf8_false_for_text() {
+        let buf = OutputBuffer::new(64);
+        buf.write_stdout("héllo".as_bytes()).unwrap();
+        assert!(!buf.has_invalid_utf8());
+        assert_eq!(buf.invalid_utf8_stream(), None);
+    }
+
+    // (13) A cut never splits a multi-byte character
+    #[test]
+    fn test_cut_respects_utf8_boundaries() {
+        // "€" is 3 bytes and "🎉" 4; a 5-byte limit falls inside either.
+        for (text, kept) in [("abcd€", "abcd"), ("ab🎉", "ab"), ("ab€", "ab€")] {
+            let buf = OutputBuffer::new(5);
+            let _ = buf.write_stdout(text.as_bytes());
+            assert!(!buf.has_invalid_utf8(), "{text:?}");
+            assert_eq!(buf.into_strings().0, kept, "{text:?}");
+        }
+    }
+
+    // (14) Nothing is appended once the limit has been hit
+    #[test]
+    fn test_writes_after_limit_are_rejected() {
+        let buf = OutputBuffer::new(5);
+        assert!(buf.write_stdout("abcd€".as_bytes()).is_err());
+        // One byte of room is left, but the output must stay a prefix.
+        assert!(buf.write_stderr(b"x").is_err());
+        assert_eq!(buf.into_strings(), ("abcd".to_string(), String::new()));
+    }
+
+    // (15) write_stderr_prefix cuts without marking the limit exceeded
+    #[test]
+    fn test_write_stderr_prefix_is_not_an_overflow() {
+        let buf = OutputBuffer::new(6);
+        buf.write_stdout(b"ok\n").unwrap();
+        buf.write_stderr_prefix("Err€".as_bytes());
+        assert!(!buf.is_limit_exceeded());
+        assert_eq!(buf.total_written(), 9);
+        assert_eq!(buf.into_strings(), ("ok\n".to_string(), "Err".to_string()));
+
+        // After a real overflow nothing more is kept.
+        let buf = OutputBuffer::new(4);
+        assert!(buf.write_stdout(b"hello").is_err());
+        buf.write_stderr_prefix(b"E");
+        assert_eq!(buf.into_strings().1, "");
     }
 }
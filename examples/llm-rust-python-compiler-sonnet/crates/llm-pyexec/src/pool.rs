@@ -12,42 +12,132 @@
 //!
 //! ## Thread safety
 //!
-//! The pool itself (slot dispatch) uses `Mutex<VecDeque<Sender<WorkItem>>>` +
-//! `Condvar` to hand work channels to calling threads. Only the `Sender` end
+//! The pool itself (slot dispatch) uses a `Mutex` over a `VecDeque` of slot
+//! senders to hand work channels to calling threads. Only the `Sender` end
 //! of the work channel (which is `Send`) crosses thread boundaries. The
 //! `PyInterp` stays on its dedicated slot thread.
 //!
+//! ## Checkout order
+//!
+//! Checkout is first come, first served. A caller that finds no idle slot
+//! takes a ticket: it appends a one-shot handoff channel to a waiters queue
+//! (under the same mutex as the idle slots) and blocks on it. A slot that
+//! finishes its work hands its sender straight to the oldest waiter, and only
+//! becomes idle when nobody is waiting, so a newly arriving caller can never
+//! overtake one that has been waiting longer. A waiter whose checkout timeout
+//! expires removes its ticket under the mutex, after a last look for a slot
+//! handed over in the meantime.
+//!
 //! ## Pool size
 //!
 //! Configured via `PYEXEC_POOL_SIZE` env var at first call to `InterpreterPool::global()`.
-//! Default: 4.
+//! Default: 4. An embedder can instead install its own pool with
+//! [`InterpreterPool::init_global`] before anything calls `global()`.
+//!
+//! ## Pre-imports
+//!
+//! [`InterpreterPoolBuilder::preimport`] or [`InterpreterPool::new_with_warmup`]
+//! (or `PYEXEC_PREIMPORT` / `PYEXEC_WARM_MODULES`, comma-separated lists, for the
+//! global pool) names modules each slot imports while warming up,
+//! before it captures its `sys.modules` baseline. They therefore survive the
+//! per-call reset, and the first `import json` of a call finds the module
+//! already loaded. Pre-importing does not allowlist a module: the import hook
+//! checks each call's allowlist before it looks in `sys.modules`.
+//!
+//! ## Resizing
+//!
+//! [`InterpreterPool::resize`] changes the target size at runtime. Growing spawns
+//! new slot threads, which join the available queue once their interpreter is
+//! warm. Shrinking retires idle slots immediately and records the remainder as
+//! pending retirements; a busy slot that finishes its current work item while a
+//! retirement is pending exits instead of rejoining the queue. Retirement
+//! bookkeeping lives under the same mutex as the available queue, so a slot can
+//! never rejoin the queue and miss a pending retirement.
+//!
+//! ## Work queue
+//!
+//! By default a caller that finds no idle slot waits for one (up to its
+//! checkout timeout) and then falls back to a fresh interpreter. With a queue
+//! (see [`InterpreterPool::with_queue`]), such a work item is instead appended
+//! to a bounded FIFO and `dispatch_work` returns immediately; a slot that
+//! finishes its work item takes the oldest queued item before rejoining the
+//! available set. Only when the queue is full does the caller wait and fall
+//! back as before. Queued items whose deadline passed while waiting are dropped
+//! unexecuted — their callers have already given up. The queue lives under the
+//! same mutex as the available set, so an item can never be queued while a slot
+//! goes idle.
+//!
+//! ## Recycling
+//!
+//! RustPython interpreters accumulate interned strings and small leaks over
+//! thousands of executions. With `recycle_after` set (see
+//! [`InterpreterPoolBuilder::recycle_after`], or `PYEXEC_SLOT_RECYCLE_AFTER` for
+//! the global pool), a slot that has completed that many work items drops its
+//! interpreter and builds a fresh one, repeating its pre-imports and recapturing
+//! the `sys.modules` baseline, before rejoining the available queue. The slot
+//! is out of the queue while it rebuilds, so no work is ever sent to it
+//! mid-recycle.
+//!
+//! ## Panics and stack size
+//!
+//! Slot threads are spawned with a large stack (see `VM_THREAD_STACK_SIZE`)
+//! so deep-but-legal recursion stays within it; the Python recursion limit
+//! stops runaway recursion well before that. A native stack overflow would
+//! still abort the whole process, so the stack size is the only defence there.
+//! A *panic* while running a work item is caught: the caller receives an
+//! [`ExecutionError::Internal`](crate::ExecutionError::Internal), and the slot
+//! replaces its interpreter before rejoining the available queue, so
+//! `idle_count()` recovers. A slot thread that dies anyway (a panic outside
+//! the work item, e.g. while resetting state) can leave its sender in the
+//! available queue; sending to it then fails, and `dispatch_work` discards it,
+//! replaces the slot, and tries the next one rather than leaving the caller
+//! waiting for a result that never comes.
+//!
+//! ## Metrics
+//!
+//! [`InterpreterPool::metrics`] returns a [`PoolMetrics`] snapshot built from
+//! atomic counters updated in `dispatch_work`, in the slot loop, and by the
+//! executor when it falls back to a fresh interpreter.
 //!
 //! ## Timeout handling
 //!
 //! If the caller's `recv_timeout` on the response channel times out, the work
 //! item has already been sent to (and is being executed by) the slot thread.
-//! The slot thread will complete execution eventually and send the result —
-//! but no one is listening. The slot's result channel disconnects, the slot
-//! thread discards the result, resets interpreter state, and returns its sender
-//! to the available queue. Pool size remains stable. No replacement thread needed.
+//! If the code does finish, the slot thread sends the result — but no one is
+//! listening. `SyncSender::send()` on the disconnected channel returns
+//! `Err(SendError)`, which the slot thread ignores before resetting interpreter
+//! state and returning its sender to the available queue.
 //!
-//! This is possible because `std::sync::mpsc::SyncSender::send()` on a
-//! disconnected channel returns `Err(SendError)`, which the slot thread
-//! handles by simply continuing its loop.
+//! Code that never finishes (e.g. `while True: pass`) would hold its slot
+//! forever. Each slot therefore records when it started its current work item,
+//! and `dispatch_work`, while waiting for a free slot, looks for slots that
+//! have been busy longer than a multiple (see
+//! [`InterpreterPool::set_stuck_timeout_multiple`]) of the largest execution
+//! timeout seen so far. Such a slot is written off — it no longer counts
+//! towards [`InterpreterPool::active_slots`] or `busy_count`, and exits
+//! without rejoining the queue if it ever finishes — and a replacement slot is
+//! spawned so that capacity recovers to [`InterpreterPool::size`]. The stuck
+//! thread itself cannot be interrupted and is left to die with the process.
 //!
 //! ## Zero unsafe blocks (AC-18)
 //!
 //! This file contains no `unsafe` code. All concurrency uses safe Rust APIs
 //! (`Mutex`, `Condvar`, `mpsc::sync_channel`, `Arc`).
 
-use std::collections::HashSet;
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, HashSet, VecDeque};
+use std::panic::AssertUnwindSafe;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
+use std::sync::mpsc::{RecvTimeoutError, SendError, TrySendError};
 use std::sync::{Arc, Condvar, Mutex, OnceLock};
-use std::time::Duration;
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
 
 use crate::output::OutputBuffer;
-use crate::types::DEFAULT_ALLOWED_MODULES;
-use crate::vm::{build_interpreter, run_code, VmRunResult};
+use crate::types::{AlreadyInitialized, DEFAULT_ALLOWED_MODULES};
+use crate::vm::{
+    build_interpreter, panic_result, run_code, RunOptions, VmRunResult, VM_THREAD_STACK_SIZE,
+};
 
 // ── Work item types ──────────────────────────────────────────────────────────
 
@@ -67,78 +157,374 @@ pub(crate) struct WorkItem {
     pub output: OutputBuffer,
     /// The allowlist for this specific call (may differ from pool default).
     pub allowed_set: Arc<HashSet<String>>,
+    /// Compile budget, inspection queries and deadline for this call.
+    pub options: RunOptions,
     /// One-shot channel to send the result back to the calling thread.
     pub response: std::sync::mpsc::SyncSender<VmRunResult>,
 }
 
+/// How [`InterpreterPool::dispatch_work`] disposed of a work item.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DispatchOutcome {
+    /// Sent to an idle slot.
+    Dispatched,
+    /// Appended to the work queue; the next free slot will run it.
+    Queued,
+    /// Not accepted: no slot freed up in time and the queue was full.
+    Unavailable,
+}
+
+/// Message delivered to a slot thread over its work channel.
+enum SlotMessage {
+    /// Execute a work item and rejoin the available queue.
+    Work(WorkItem),
+    /// Exit without rejoining the available queue (sent to idle slots on shrink).
+    Retire,
+    /// Test hook: panic outside the work loop's `catch_unwind`, leaving a dead
+    /// slot whose sender is still in the available queue.
+    #[cfg(test)]
+    Die,
+}
+
+// ── Metrics ──────────────────────────────────────────────────────────────────
+
+/// Snapshot of pool utilization returned by [`InterpreterPool::metrics`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PoolMetrics {
+    /// Work items handed to a slot, or queued for one, by `dispatch_work`.
+    pub dispatches: u64,
+    /// Calls that found no free slot in time and ran on a fresh interpreter.
+    pub fallbacks: u64,
+    /// Total time dispatched calls spent waiting for a free slot, in nanoseconds.
+    pub total_wait_ns: u64,
+    /// Longest single wait for a free slot, in nanoseconds.
+    pub max_wait_ns: u64,
+    /// Work items executed by each slot, indexed by slot id (retired slots included).
+    pub slot_executions: Vec<u64>,
+    /// Slots currently checked out for a work item.
+    pub busy_count: usize,
+    /// Times a slot rebuilt its interpreter after reaching `recycle_after` executions.
+    #[serde(default)]
+    pub recycles: u64,
+    /// Slots written off as stuck on abandoned work and replaced.
+    #[serde(default)]
+    pub stuck_replacements: u64,
+    /// Work items that waited in the work queue (included in `dispatches`).
+    #[serde(default)]
+    pub queued: u64,
+    /// Work items in the work queue right now.
+    #[serde(default)]
+    pub queue_len: usize,
+    /// Slots found dead (thread gone) when work was sent to them, and replaced.
+    #[serde(default)]
+    pub dead_replacements: u64,
+}
+
+/// Atomic counters shared between the pool and its slot threads.
+#[derive(Default)]
+struct PoolCounters {
+    /// Number of slot threads currently alive (warming, idle, busy or retiring).
+    live: AtomicUsize,
+    busy: AtomicUsize,
+    dispatches: AtomicU64,
+    fallbacks: AtomicU64,
+    total_wait_ns: AtomicU64,
+    max_wait_ns: AtomicU64,
+    recycles: AtomicU64,
+    stuck_replacements: AtomicU64,
+    queued: AtomicU64,
+    dead_replacements: AtomicU64,
+    /// Largest execution budget of any dispatched work item, in nanoseconds.
+    max_timeout_ns: AtomicU64,
+    /// Per-slot state; the map is only locked when a slot starts, when a
+    /// snapshot is taken, and when looking for stuck slots.
+    slots: Mutex<BTreeMap<usize, Arc<SlotState>>>,
+}
+
+/// Per-slot state shared between a slot thread and the pool.
+#[derive(Default)]
+struct SlotState {
+    /// Work items this slot has executed.
+    executed: AtomicU64,
+    /// [`monotonic_ns`] when the current work item started, [`SLOT_IDLE`] when
+    /// the slot is not executing, or [`SLOT_ABANDONED`] once written off as stuck.
+    busy_since_ns: AtomicU64,
+}
+
+const SLOT_IDLE: u64 = 0;
+const SLOT_ABANDONED: u64 = u64::MAX;
+
+/// Default multiple of the largest seen timeout after which a busy slot is
+/// considered stuck.
+const DEFAULT_STUCK_TIMEOUT_MULTIPLE: u32 = 2;
+
+/// How often a caller waiting in `dispatch_work` re-checks for stuck slots.
+const STUCK_CHECK_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Checkouts that wait at least this long are logged (`tracing` feature).
+#[cfg(feature = "tracing")]
+const SLOW_CHECKOUT: Duration = Duration::from_millis(100);
+
+/// Nanoseconds since a process-wide epoch; never returns [`SLOT_IDLE`].
+fn monotonic_ns() -> u64 {
+    static EPOCH: OnceLock<Instant> = OnceLock::new();
+    let epoch = *EPOCH.get_or_init(Instant::now);
+    (epoch.elapsed().as_nanos() as u64).max(1)
+}
+
 // ── Pool slot ────────────────────────────────────────────────────────────────
 
+/// The pool's end of a slot's work channel.
+type SlotSender = std::sync::mpsc::SyncSender<SlotMessage>;
+
+/// Available slot senders, the number of busy slots still owed a retirement,
+/// and work items queued for the next free slot.
+///
+/// All fields are guarded by one mutex so that a slot rejoining the queue and a
+/// shrinking [`InterpreterPool::resize`] always agree on who retires, and so
+/// that no work item is queued, and no caller waits, while a slot is idle.
+struct SlotQueue {
+    idle: VecDeque<SlotSender>,
+    retiring: usize,
+    /// Work items waiting for a slot, oldest first.
+    pending: VecDeque<WorkItem>,
+    /// Maximum length of `pending`; 0 disables queueing.
+    max_pending: usize,
+    /// Callers blocked in `dispatch_work`, oldest first, by ticket number.
+    waiters: VecDeque<(u64, std::sync::mpsc::SyncSender<SlotSender>)>,
+    /// Ticket number for the next waiter.
+    next_ticket: u64,
+}
+
+impl SlotQueue {
+    /// Hands a free slot to the oldest waiting caller, or makes it idle.
+    fn release(&mut self, mut tx: SlotSender) {
+        while let Some((_, waiter)) = self.waiters.pop_front() {
+            // Each handoff channel has room for exactly one slot; a failed send
+            // means the waiter is gone, so try the next one.
+            match waiter.try_send(tx) {
+                Ok(()) => return,
+                Err(TrySendError::Full(returned) | TrySendError::Disconnected(returned)) => {
+                    tx = returned;
+                }
+            }
+        }
+        self.idle.push_back(tx);
+    }
+}
+
+type SharedQueue = Arc<(Mutex<SlotQueue>, Condvar)>;
+
+/// Interpreter configuration shared by every slot of one pool.
+struct SlotConfig {
+    /// Allowlist each slot's interpreter is built with.
+    default_allowed: Arc<HashSet<String>>,
+    /// Modules imported at warm-up, before the baseline capture.
+    preimport: Vec<String>,
+    /// Executions after which a slot rebuilds its interpreter.
+    recycle_after: Option<u32>,
+}
+
+/// What a slot does after warming up or finishing a work item.
+enum Rejoin {
+    /// Wait on the work channel.
+    Idle,
+    /// Exit.
+    Retire,
+    /// Run this queued work item next.
+    Work(WorkItem),
+}
+
+/// Consumes a pending retirement, takes the oldest queued work item, or hands
+/// the slot to the oldest waiting caller (or the available queue), in that
+/// order of preference.
+///
+/// `busy` is the pool's busy counter and `was_busy` whether this slot is
+/// finishing a work item; the counter is updated in the same critical section,
+/// so a slot that goes on to a queued item is counted as busy throughout.
+fn rejoin_or_retire(
+    pool_available: &SharedQueue,
+    tx: &SlotSender,
+    busy: &AtomicUsize,
+    was_busy: bool,
+) -> Rejoin {
+    let (lock, cvar) = &**pool_available;
+    let mut queue = lock.lock().expect("pool slot queue poisoned");
+    let next = if queue.retiring > 0 {
+        queue.retiring -= 1;
+        Rejoin::Retire
+    } else {
+        take_queued(&mut queue).map_or(Rejoin::Idle, Rejoin::Work)
+    };
+    match (&next, was_busy) {
+        (Rejoin::Work(_), false) => {
+            busy.fetch_add(1, Ordering::SeqCst);
+        }
+        (Rejoin::Idle | Rejoin::Retire, true) => {
+            busy.fetch_sub(1, Ordering::SeqCst);
+        }
+        _ => {}
+    }
+    if let Rejoin::Idle = next {
+        queue.release(tx.clone());
+        cvar.notify_one();
+    }
+    next
+}
+
+/// Log a checkout that started at `wait_start` if it waited past [`SLOW_CHECKOUT`].
+#[cfg(feature = "tracing")]
+fn trace_slow_checkout(wait_start: Instant, outcome: DispatchOutcome) {
+    let waited = wait_start.elapsed();
+    if waited >= SLOW_CHECKOUT {
+        tracing::warn!(
+            waited_ns = waited.as_nanos() as u64,
+            ?outcome,
+            "slow pool checkout"
+        );
+    }
+}
+
+/// Pops the oldest queued work item whose caller is still waiting, dropping
+/// any whose deadline has passed.
+fn take_queued(queue: &mut SlotQueue) -> Option<WorkItem> {
+    while let Some(item) = queue.pending.pop_front() {
+        let expired = item
+            .options
+            .deadline
+            .is_some_and(|deadline| Instant::now() >= deadline);
+        if !expired {
+            return Some(item);
+        }
+    }
+    None
+}
+
 /// Starts one pool slot: a dedicated OS thread that initializes a `PyInterp`
 /// and loops processing `WorkItem`s.
 ///
-/// Returns the `SyncSender<WorkItem>` that the pool uses to dispatch work to this slot.
+/// Returns the [`SlotSender`] that the pool uses to dispatch work to this slot.
 ///
-/// Called once per slot at pool initialization time.
+/// Called once per slot at pool initialization time and for each slot added by
+/// [`InterpreterPool::resize`]. The live-slot counter is incremented here and
+/// decremented when the slot thread exits. With `config.recycle_after` set, the
+/// slot rebuilds its interpreter after that many work items.
 fn start_slot_thread(
     slot_id: usize,
-    pool_available: Arc<(Mutex<VecDeque<std::sync::mpsc::SyncSender<WorkItem>>>, Condvar)>,
-) -> std::sync::mpsc::SyncSender<WorkItem> {
+    pool_available: SharedQueue,
+    counters: Arc<PoolCounters>,
+    config: Arc<SlotConfig>,
+) -> SlotSender {
     // Bounded channel capacity 1: the slot processes one item at a time.
-    // SyncSender<WorkItem> is Send; the channel is safe to share across threads.
-    let (tx, rx) = std::sync::mpsc::sync_channel::<WorkItem>(1);
+    // SyncSender<SlotMessage> is Send; the channel is safe to share across threads.
+    let (tx, rx) = std::sync::mpsc::sync_channel::<SlotMessage>(1);
     let tx_for_pool = tx.clone();
 
+    counters.live.fetch_add(1, Ordering::SeqCst);
+    let state = Arc::new(SlotState::default());
+    counters
+        .slots
+        .lock()
+        .expect("pool metrics poisoned")
+        .insert(slot_id, Arc::clone(&state));
+
     std::thread::Builder::new()
         .name(format!("pyexec-pool-slot-{slot_id}"))
+        .stack_size(VM_THREAD_STACK_SIZE)
         .spawn(move || {
-            // Initialize interpreter on the slot thread (never leaves this thread).
-            let default_set: HashSet<String> = DEFAULT_ALLOWED_MODULES
-                .iter()
-                .map(|s| s.to_string())
-                .collect();
-            let dummy_output = OutputBuffer::new(1_048_576);
-            let mut interp = build_interpreter(default_set, dummy_output);
+            // Initialize interpreter on the slot thread (never leaves this thread),
+            // run the pre-imports, then capture the baseline sys.modules set for
+            // state reset between calls. This happens before any user code runs,
+            // and again whenever the slot recycles.
+            let warm_up = || {
+                let dummy_output = OutputBuffer::new(1_048_576);
+                let interp =
+                    build_interpreter((*config.default_allowed).clone(), dummy_output, true, None);
+                preimport_modules(&interp, &config.preimport);
+                let baseline_modules = capture_baseline_modules(&interp);
+                (interp, baseline_modules)
+            };
+            let (mut interp, mut baseline_modules) = warm_up();
+            let mut since_recycle: u32 = 0;
 
-            // Capture the baseline sys.modules set for state reset between calls.
-            // This is done once after initialization and before any user code runs.
-            let baseline_modules = capture_baseline_modules(&interp);
+            // Signal to pool that this slot is ready (unless a shrink already
+            // claimed it while the interpreter was warming up).
+            let mut next = rejoin_or_retire(&pool_available, &tx, &counters.busy, false);
 
-            // Signal to pool that this slot is ready.
-            {
-                let (lock, cvar) = &*pool_available;
-                let mut queue = lock.lock().expect("pool slot queue poisoned");
-                queue.push_back(tx.clone());
-                cvar.notify_one();
-            }
-
-            // Process work items indefinitely.
+            // Process work items until retired.
             loop {
-                let item = match rx.recv() {
-                    Ok(item) => item,
-                    Err(_) => break, // Channel closed (pool dropped). Exit.
+                let item = match next {
+                    Rejoin::Work(item) => item,
+                    Rejoin::Retire => break,
+                    Rejoin::Idle => match rx.recv() {
+                        Ok(SlotMessage::Work(item)) => item,
+                        // Retired while idle, or channel closed (pool dropped). Exit.
+                        Ok(SlotMessage::Retire) | Err(_) => break,
+                        #[cfg(test)]
+                        Ok(SlotMessage::Die) => panic!("slot killed by test hook"),
+                    },
                 };
 
+                state.busy_since_ns.store(monotonic_ns(), Ordering::SeqCst);
+
                 // Override the allowlist for this call.
-                interp.set_allowed_set((*item.allowed_set).clone());
+                interp.set_allowed_set(Arc::clone(&item.allowed_set));
 
-                // Execute the code.
-                let result = run_code(&interp, &item.wrapped_source, item.output);
+                // Execute the code. A panic inside the VM must not take the slot
+                // thread down with it: report it as an Internal error and replace the
+                // interpreter, whose state can no longer be trusted.
+                let outcome = std::panic::catch_unwind(AssertUnwindSafe(|| {
+                    run_code(&interp, &item.wrapped_source, item.output, &item.options)
+                }));
 
-                // Reset sys.modules to baseline state (PRD M1 state reset contract).
-                reset_sys_modules(&interp, &baseline_modules);
+                state.executed.fetch_add(1, Ordering::Relaxed);
+
+                let mut result = match outcome {
+                    Ok(result) => {
+                        // Reset sys.modules to baseline state (PRD M1 state reset contract).
+                        reset_sys_modules(&interp, &baseline_modules);
+                        result
+                    }
+                    Err(payload) => {
+                        drop(interp);
+                        (interp, baseline_modules) = warm_up();
+                        since_recycle = 0;
+                        panic_result(payload.as_ref())
+                    }
+                };
+
+                result.slot_id = Some(slot_id);
 
                 // Send result back. If caller timed out (receiver dropped), this
                 // returns Err(SendError) — we discard it and continue the loop.
                 let _ = item.response.send(result);
 
-                // Return this slot's sender to the available queue.
+                // A slot written off as stuck has already been replaced and
+                // uncounted; it must not rejoin the queue.
+                if state.busy_since_ns.swap(SLOT_IDLE, Ordering::SeqCst) == SLOT_ABANDONED {
+                    return;
+                }
+
+                // Rebuild the interpreter once it has served its quota. The slot
+                // is not in the available queue, so no work can arrive meanwhile
+                // (queued work waits for it, or for another slot).
+                since_recycle += 1;
+                if config
+                    .recycle_after
+                    .is_some_and(|limit| since_recycle >= limit)
                 {
-                    let (lock, cvar) = &*pool_available;
-                    let mut queue = lock.lock().expect("pool slot queue poisoned");
-                    queue.push_back(tx.clone());
-                    cvar.notify_one();
+                    drop(interp);
+                    (interp, baseline_modules) = warm_up();
+                    since_recycle = 0;
+                    counters.recycles.fetch_add(1, Ordering::Relaxed);
                 }
+
+                // Take queued work, return this slot's sender to the available
+                // queue, or retire.
+                next = rejoin_or_retire(&pool_available, &tx, &counters.busy, true);
             }
+
+            counters.live.fetch_sub(1, Ordering::SeqCst);
         })
         .expect("Failed to spawn pool slot thread");
 
@@ -147,6 +533,26 @@ fn start_slot_thread(
 
 // ── sys.modules baseline capture and reset ──────────────────────────────────
 
+/// Imports each named module through the interpreter's original `__import__`.
+///
+/// Called at warm-up, before the import hook is first installed and before
+/// [`capture_baseline_modules`]. A module that fails to import is skipped; user
+/// code importing it later sees the usual error.
+fn preimport_modules(interp: &crate::vm::PyInterp, modules: &[String]) {
+    if modules.is_empty() {
+        return;
+    }
+    interp.with_vm(|vm| {
+        let import = match vm.builtins.get_attr("__import__", vm) {
+            Ok(f) => f,
+            Err(_) => return,
+        };
+        for name in modules {
+            let _ = import.call((vm.ctx.new_str(name.as_str()),), vm);
+        }
+    });
+}
+
 /// Captures the set of module names currently in sys.modules.
 ///
 /// Called once after `build_interpreter()` and before any user code runs.
@@ -227,19 +633,30 @@ fn reset_sys_modules(interp: &crate::vm::PyInterp, baseline: &HashSet<String>) {
 
 // ── InterpreterPool ──────────────────────────────────────────────────────────
 
-/// Fixed-size pool of pre-warmed RustPython interpreters.
+/// Backing storage for [`InterpreterPool::global`].
+static GLOBAL_POOL: OnceLock<InterpreterPool> = OnceLock::new();
+
+/// Pool of pre-warmed RustPython interpreters.
 ///
-/// Each slot is a dedicated OS thread. Work is dispatched via `SyncSender<WorkItem>`.
+/// Each slot is a dedicated OS thread. Work is dispatched via `SyncSender<SlotMessage>`.
 /// Results are returned via per-call `mpsc::sync_channel`.
 ///
 /// # Pool size
 ///
-/// Configured at construction time. Use [`InterpreterPool::global()`] for the
-/// process-global singleton which reads `PYEXEC_POOL_SIZE` env var (default 4).
+/// Configured at construction time and adjustable with [`InterpreterPool::resize`].
+/// Use [`InterpreterPool::global()`] for the process-global singleton which reads
+/// `PYEXEC_POOL_SIZE` env var (default 4), and [`InterpreterPool::builder`] for
+/// pools with pre-imports or a different default allowlist.
 pub struct InterpreterPool {
-    /// Queue of available slot senders.
-    available: Arc<(Mutex<VecDeque<std::sync::mpsc::SyncSender<WorkItem>>>, Condvar)>,
-    target_size: usize,
+    /// Queue of available slot senders and pending retirements.
+    available: SharedQueue,
+    target_size: AtomicUsize,
+    counters: Arc<PoolCounters>,
+    next_slot_id: AtomicUsize,
+    /// Configuration every slot, including later replacements, starts with.
+    slot_config: Arc<SlotConfig>,
+    /// Multiple of the largest seen timeout after which a busy slot is stuck.
+    stuck_timeout_multiple: AtomicU32,
 }
 
 impl InterpreterPool {
@@ -255,84 +672,389 @@ impl InterpreterPool {
     ///
     /// Panics if any slot thread fails to start.
     pub fn new(size: usize) -> Self {
-        let target_size = size.max(1);
+        Self::builder().size(size).build()
+    }
+
+    /// Like [`new`](Self::new), but each slot imports `warm_modules` while
+    /// warming up and keeps them loaded between calls (see the module docs).
+    ///
+    /// This trades a little startup time for not paying a module's first
+    /// import on every call. Modules that fail to import are skipped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any slot thread fails to start.
+    pub fn new_with_warmup(size: usize, warm_modules: &[&str]) -> Self {
+        Self::builder().size(size).preimport(warm_modules).build()
+    }
+
+    /// Returns a builder for a pool with non-default slot configuration.
+    pub fn builder() -> InterpreterPoolBuilder {
+        InterpreterPoolBuilder::default()
+    }
+
+    /// Like [`new`](Self::new), but each slot rebuilds its interpreter after
+    /// every `recycle_after` completed work items (see the module docs).
+    ///
+    /// `None` or `Some(0)` disables recycling.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any slot thread fails to start.
+    pub fn with_recycling(size: usize, recycle_after: Option<u32>) -> Self {
+        Self::builder()
+            .size(size)
+            .recycle_after(recycle_after)
+            .build()
+    }
+
+    /// Enable a bounded FIFO work queue of up to `depth` items (see the module
+    /// docs); a `depth` of 0 disables queueing, which is the default.
+    ///
+    /// Lowering the depth does not drop items already queued.
+    pub fn with_queue(self, depth: usize) -> Self {
+        {
+            let (lock, _) = &*self.available;
+            lock.lock().expect("pool queue poisoned").max_pending = depth;
+        }
+        self
+    }
+
+    /// Spawns the slots described by `builder` and waits until all are warm.
+    fn from_builder(builder: InterpreterPoolBuilder) -> Self {
+        let target_size = builder.size.max(1);
+        let slot_config = Arc::new(SlotConfig {
+            default_allowed: Arc::new(builder.default_allowed),
+            preimport: builder.preimport,
+            recycle_after: builder.recycle_after.filter(|&n| n > 0),
+        });
         let available = Arc::new((
-            Mutex::new(VecDeque::with_capacity(target_size)),
+            Mutex::new(SlotQueue {
+                idle: VecDeque::with_capacity(target_size),
+                retiring: 0,
+                pending: VecDeque::new(),
+                max_pending: builder.queue_depth,
+                waiters: VecDeque::new(),
+                next_ticket: 0,
+            }),
             Condvar::new(),
         ));
+        let counters = Arc::new(PoolCounters::default());
 
         for slot_id in 0..target_size {
-            start_slot_thread(slot_id, Arc::clone(&available));
+            start_slot_thread(
+                slot_id,
+                Arc::clone(&available),
+                Arc::clone(&counters),
+                Arc::clone(&slot_config),
+            );
         }
 
         // Wait until all slots have initialized and pushed themselves to available.
         {
             let (lock, cvar) = &*available;
             let mut queue = lock.lock().expect("pool queue poisoned");
-            while queue.len() < target_size {
+            while queue.idle.len() < target_size {
                 queue = cvar.wait(queue).expect("pool condvar poisoned");
             }
         }
 
-        InterpreterPool { available, target_size }
+        InterpreterPool {
+            available,
+            target_size: AtomicUsize::new(target_size),
+            counters,
+            next_slot_id: AtomicUsize::new(target_size),
+            slot_config,
+            stuck_timeout_multiple: AtomicU32::new(DEFAULT_STUCK_TIMEOUT_MULTIPLE),
+        }
     }
 
     /// Returns a reference to the process-global pool singleton.
     ///
-    /// Pool size is read from `PYEXEC_POOL_SIZE` env var at first call.
-    /// Default: 4.
+    /// The pool is built by [`InterpreterPoolBuilder::from_env`] at first call:
+    /// size from `PYEXEC_POOL_SIZE` (default 4), slot recycling from
+    /// `PYEXEC_SLOT_RECYCLE_AFTER` (default: none) and pre-imports from
+    /// `PYEXEC_PREIMPORT` and `PYEXEC_WARM_MODULES` (default: none).
     ///
     /// # Note
     ///
-    /// The `PYEXEC_POOL_SIZE` env var is read exactly once (at first call).
-    /// Tests that set this env var MUST run in a separate test binary
-    /// that has not yet called `global()`.
+    /// The env vars are read exactly once (at first call).
+    /// Tests that set these env vars MUST run in a separate test binary
+    /// that has not yet called `global()`. To configure the global pool in
+    /// code instead, see [`init_global`](Self::init_global); its size can
+    /// still be changed afterwards with [`resize`](Self::resize).
     pub fn global() -> &'static InterpreterPool {
-        static INSTANCE: OnceLock<InterpreterPool> = OnceLock::new();
-        INSTANCE.get_or_init(|| {
-            let size: usize = std::env::var("PYEXEC_POOL_SIZE")
-                .ok()
-                .and_then(|s| s.parse().ok())
-                .unwrap_or(4);
-            InterpreterPool::new(size)
-        })
+        GLOBAL_POOL.get_or_init(|| InterpreterPoolBuilder::from_env().build())
+    }
+
+    /// Install `pool` as the process-global pool returned by
+    /// [`global`](Self::global), in place of the env-configured default.
+    ///
+    /// Must be called before anything calls `global()` — including the first
+    /// pooled [`execute`](crate::execute). Fails with [`AlreadyInitialized`]
+    /// once the global exists, in which case `pool` is dropped.
+    pub fn init_global(pool: InterpreterPool) -> Result<(), AlreadyInitialized> {
+        GLOBAL_POOL.set(pool).map_err(|_| AlreadyInitialized)
     }
 
-    /// Dispatch a work item to an available slot thread.
+    /// Dispatch a work item to an available slot thread, or queue it.
     ///
-    /// Blocks until a slot is available or `checkout_timeout` elapses.
-    /// Returns `true` if dispatched, `false` if no slot was available within
-    /// the timeout (caller should fall back to a fresh interpreter).
+    /// Hands the item to an idle slot if there is one, otherwise queues it if
+    /// the work queue has room and no caller is already waiting, otherwise
+    /// waits its turn (first come, first served) until a slot is handed to it
+    /// or `checkout_timeout` elapses. Returns [`DispatchOutcome::Unavailable`]
+    /// in the last case (caller should fall back to a fresh interpreter), and
+    /// immediately if `checkout_timeout` is zero.
     ///
-    /// When `true` is returned, the caller must receive from `work.response`
-    /// (which was embedded in the WorkItem) to get the result.
+    /// On [`DispatchOutcome::Dispatched`] or [`DispatchOutcome::Queued`], the
+    /// caller must receive from `work.response` (which was embedded in the
+    /// WorkItem) to get the result.
     ///
-    /// When `false` is returned, the WorkItem was NOT sent to any slot thread
-    /// (the caller should drop it or use its components for a fallback path).
-    // executor.rs integration (sibling milestone) will use this method.
-    #[allow(dead_code)]
-    pub(crate) fn dispatch_work(&self, work: WorkItem, checkout_timeout: Duration) -> bool {
-        let (lock, cvar) = &*self.available;
-        let deadline = std::time::Instant::now() + checkout_timeout;
-
-        let slot_tx = loop {
+    /// On [`DispatchOutcome::Unavailable`], the WorkItem was NOT sent to any
+    /// slot thread (the caller should drop it or use its components for a
+    /// fallback path).
+    pub(crate) fn dispatch_work(
+        &self,
+        mut work: WorkItem,
+        checkout_timeout: Duration,
+    ) -> DispatchOutcome {
+        let wait_start = Instant::now();
+        let deadline = wait_start + checkout_timeout;
+
+        if let Some(budget) = work.options.deadline {
+            let budget_ns = budget.saturating_duration_since(wait_start).as_nanos() as u64;
+            self.counters
+                .max_timeout_ns
+                .fetch_max(budget_ns, Ordering::Relaxed);
+        }
+
+        loop {
+            let (slot_tx, item) = match self.check_out(work, deadline) {
+                Ok(checked_out) => checked_out,
+                Err(outcome) => {
+                    #[cfg(feature = "tracing")]
+                    trace_slow_checkout(wait_start, outcome);
+                    return outcome;
+                }
+            };
+            // Fails only if the slot thread has died; its sender is then
+            // discarded and the item goes to the next slot.
+            let Err(SendError(message)) = slot_tx.send(SlotMessage::Work(item)) else {
+                break;
+            };
+            let SlotMessage::Work(item) = message else {
+                unreachable!("dispatch_work only sends work items");
+            };
+            work = item;
+            self.replace_dead_slot();
+        }
+
+        #[cfg(feature = "tracing")]
+        trace_slow_checkout(wait_start, DispatchOutcome::Dispatched);
+        let waited_ns = wait_start.elapsed().as_nanos() as u64;
+        let counters = &self.counters;
+        counters.busy.fetch_add(1, Ordering::SeqCst);
+        counters.dispatches.fetch_add(1, Ordering::Relaxed);
+        counters
+            .total_wait_ns
+            .fetch_add(waited_ns, Ordering::Relaxed);
+        counters.max_wait_ns.fetch_max(waited_ns, Ordering::Relaxed);
+        DispatchOutcome::Dispatched
+    }
+
+    /// Check out a slot for `work`, waiting for one until `deadline` if needed.
+    ///
+    /// Returns the slot together with `work`, or the outcome for a work item
+    /// that was queued or could not be placed.
+    fn check_out(
+        &self,
+        work: WorkItem,
+        deadline: Instant,
+    ) -> Result<(SlotSender, WorkItem), DispatchOutcome> {
+        let (lock, _) = &*self.available;
+        let (handoff_tx, handoff_rx) = std::sync::mpsc::sync_channel::<SlotSender>(1);
+        let ticket = {
             let mut queue = lock.lock().expect("pool queue poisoned");
-            if let Some(tx) = queue.pop_front() {
-                break tx;
+            if let Some(tx) = queue.idle.pop_front() {
+                return Ok((tx, work));
+            }
+            // Queueing while callers wait for a slot would let this item overtake
+            // them (queued items are served first).
+            if queue.waiters.is_empty() && queue.pending.len() < queue.max_pending {
+                queue.pending.push_back(work);
+                drop(queue);
+                self.counters.dispatches.fetch_add(1, Ordering::Relaxed);
+                self.counters.queued.fetch_add(1, Ordering::Relaxed);
+                // Every slot may be stuck; make sure someone will serve the queue.
+                self.replace_stuck_slots();
+                return Err(DispatchOutcome::Queued);
             }
-            let remaining = deadline.saturating_duration_since(std::time::Instant::now());
-            if remaining.is_zero() {
-                return false; // Caller falls back to fresh interpreter.
+            if Instant::now() >= deadline {
+                return Err(DispatchOutcome::Unavailable); // Caller falls back to fresh interpreter.
             }
-            let result = cvar.wait_timeout(queue, remaining).expect("pool condvar poisoned");
-            drop(result.0); // Release lock; next iteration re-acquires.
+            let ticket = queue.next_ticket;
+            queue.next_ticket += 1;
+            queue.waiters.push_back((ticket, handoff_tx));
+            ticket
         };
 
-        // send() cannot fail: slot thread is alive and channel capacity is 1.
-        // If the slot is somehow busy (shouldn't happen — it was in available queue),
-        // this would block briefly. Channel capacity=1 handles this correctly.
-        let _ = slot_tx.send(work);
-        true
+        loop {
+            // No free slot: a stuck one may be the reason. Replacements hand
+            // themselves to the oldest waiter once warm, so keep looking.
+            self.replace_stuck_slots();
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            match handoff_rx.recv_timeout(remaining.min(STUCK_CHECK_INTERVAL)) {
+                Ok(tx) => return Ok((tx, work)),
+                Err(RecvTimeoutError::Timeout) if Instant::now() < deadline => {}
+                Err(_) => {
+                    // Slots hand over under the lock, so once it is held either
+                    // a slot has arrived or the ticket is still queued.
+                    let mut queue = lock.lock().expect("pool queue poisoned");
+                    if let Ok(tx) = handoff_rx.try_recv() {
+                        return Ok((tx, work));
+                    }
+                    queue.waiters.retain(|(waiting, _)| *waiting != ticket);
+                    return Err(DispatchOutcome::Unavailable); // Caller falls back to fresh interpreter.
+                }
+            }
+        }
+    }
+
+    /// Account for a slot thread found dead by `dispatch_work` and restore
+    /// capacity: the dead slot settles a pending retirement if there is one,
+    /// and is replaced by a new slot otherwise.
+    fn replace_dead_slot(&self) {
+        let counters = &self.counters;
+        counters.live.fetch_sub(1, Ordering::SeqCst);
+        counters.dead_replacements.fetch_add(1, Ordering::Relaxed);
+        {
+            let (lock, _) = &*self.available;
+            let mut queue = lock.lock().expect("pool queue poisoned");
+            if queue.retiring > 0 {
+                queue.retiring -= 1;
+                return;
+            }
+        }
+        let slot_id = self.next_slot_id.fetch_add(1, Ordering::SeqCst);
+        start_slot_thread(
+            slot_id,
+            Arc::clone(&self.available),
+            Arc::clone(counters),
+            Arc::clone(&self.slot_config),
+        );
+    }
+
+    /// Change the target number of slots.
+    ///
+    /// Growing first cancels any retirements still pending from an earlier
+    /// shrink, then spawns new slot threads for the remainder; it does not wait
+    /// for them to warm up. Shrinking retires idle slots immediately, and busy
+    /// slots as they finish their current work item. A `new_size` of 0 is
+    /// treated as 1.
+    ///
+    /// Safe to call concurrently with [`dispatch_work`](Self::dispatch_work) and
+    /// with other `resize` calls.
+    pub fn resize(&self, new_size: usize) {
+        let new_size = new_size.max(1);
+        let (lock, _) = &*self.available;
+
+        let mut to_retire = Vec::new();
+        let to_spawn = {
+            let mut queue = lock.lock().expect("pool queue poisoned");
+            let old_size = self.target_size.swap(new_size, Ordering::SeqCst);
+            if new_size >= old_size {
+                let grow_by = new_size - old_size;
+                let cancelled = grow_by.min(queue.retiring);
+                queue.retiring -= cancelled;
+                grow_by - cancelled
+            } else {
+                let mut surplus = old_size - new_size;
+                while surplus > 0 {
+                    match queue.idle.pop_back() {
+                        Some(tx) => to_retire.push(tx),
+                        None => break,
+                    }
+                    surplus -= 1;
+                }
+                queue.retiring += surplus;
+                0
+            }
+        };
+
+        // Idle slots are blocked in `recv()` with an empty channel, so this
+        // send never blocks.
+        for tx in to_retire {
+            let _ = tx.send(SlotMessage::Retire);
+        }
+        for _ in 0..to_spawn {
+            let slot_id = self.next_slot_id.fetch_add(1, Ordering::SeqCst);
+            start_slot_thread(
+                slot_id,
+                Arc::clone(&self.available),
+                Arc::clone(&self.counters),
+                Arc::clone(&self.slot_config),
+            );
+        }
+    }
+
+    /// Set how many times the largest seen execution timeout a slot may stay
+    /// busy before it is considered stuck and replaced (default 2). A
+    /// `multiple` of 0 is treated as 1.
+    ///
+    /// Work items dispatched without a deadline do not contribute a timeout;
+    /// until one with a deadline has been seen, no slot is considered stuck.
+    pub fn set_stuck_timeout_multiple(&self, multiple: u32) {
+        self.stuck_timeout_multiple
+            .store(multiple.max(1), Ordering::Relaxed);
+    }
+
+    /// Write off every slot busy for longer than the stuck threshold and spawn
+    /// a replacement for each.
+    fn replace_stuck_slots(&self) {
+        let max_timeout_ns = self.counters.max_timeout_ns.load(Ordering::Relaxed);
+        if max_timeout_ns == 0 {
+            return;
+        }
+        let multiple = u64::from(self.stuck_timeout_multiple.load(Ordering::Relaxed));
+        let threshold_ns = max_timeout_ns.saturating_mul(multiple);
+        let now = monotonic_ns();
+
+        let mut stuck = 0;
+        for state in self
+            .counters
+            .slots
+            .lock()
+            .expect("pool metrics poisoned")
+            .values()
+        {
+            let since = state.busy_since_ns.load(Ordering::SeqCst);
+            if since == SLOT_IDLE || since == SLOT_ABANDONED {
+                continue;
+            }
+            // Losing this race means the slot just finished and will rejoin.
+            if now.saturating_sub(since) > threshold_ns
+                && state
+                    .busy_since_ns
+                    .compare_exchange(since, SLOT_ABANDONED, Ordering::SeqCst, Ordering::SeqCst)
+                    .is_ok()
+            {
+                stuck += 1;
+            }
+        }
+
+        for _ in 0..stuck {
+            let counters = &self.counters;
+            counters.live.fetch_sub(1, Ordering::SeqCst);
+            counters.busy.fetch_sub(1, Ordering::SeqCst);
+            counters.stuck_replacements.fetch_add(1, Ordering::Relaxed);
+            let slot_id = self.next_slot_id.fetch_add(1, Ordering::SeqCst);
+            start_slot_thread(
+                slot_id,
+                Arc::clone(&self.available),
+                Arc::clone(counters),
+                Arc::clone(&self.slot_config),
+            );
+        }
     }
 
     /// Returns the number of idle (available) slots.
@@ -342,15 +1064,187 @@ impl InterpreterPool {
     pub fn idle_count(&self) -> usize {
         let (lock, _) = &*self.available;
         let queue = lock.lock().expect("pool queue poisoned");
-        queue.len()
+        queue.idle.len()
     }
 
-    /// Returns the configured pool size (total slots, idle + active).
+    /// Returns the target pool size (total slots, idle + active).
+    ///
+    /// After [`resize`](Self::resize) this is the new target immediately; see
+    /// [`active_slots`](Self::active_slots) for the number of live slot threads.
     pub fn size(&self) -> usize {
-        self.target_size
+        self.target_size.load(Ordering::SeqCst)
+    }
+
+    /// Returns the number of slot threads currently alive.
+    ///
+    /// Converges to [`size`](Self::size) after a resize: new slots count from
+    /// the moment they are spawned, retiring slots until their thread exits.
+    /// Slots written off as stuck stop counting as soon as they are replaced.
+    pub fn active_slots(&self) -> usize {
+        self.counters.live.load(Ordering::SeqCst)
+    }
+
+    /// Record that a caller fell back to a fresh interpreter because
+    /// [`dispatch_work`](Self::dispatch_work) found no free slot.
+    pub(crate) fn record_fallback(&self) {
+        self.counters.fallbacks.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Returns a snapshot of the pool's utilization counters.
+    pub fn metrics(&self) -> PoolMetrics {
+        let counters = &self.counters;
+        let slot_executions = counters
+            .slots
+            .lock()
+            .expect("pool metrics poisoned")
+            .values()
+            .map(|state| state.executed.load(Ordering::Relaxed))
+            .collect();
+        let queue_len = {
+            let (lock, _) = &*self.available;
+            lock.lock().expect("pool queue poisoned").pending.len()
+        };
+        PoolMetrics {
+            dispatches: counters.dispatches.load(Ordering::Relaxed),
+            fallbacks: counters.fallbacks.load(Ordering::Relaxed),
+            total_wait_ns: counters.total_wait_ns.load(Ordering::Relaxed),
+            max_wait_ns: counters.max_wait_ns.load(Ordering::Relaxed),
+            slot_executions,
+            busy_count: counters.busy.load(Ordering::SeqCst),
+            recycles: counters.recycles.load(Ordering::Relaxed),
+            stuck_replacements: counters.stuck_replacements.load(Ordering::Relaxed),
+            queued: counters.queued.load(Ordering::Relaxed),
+            queue_len,
+            dead_replacements: counters.dead_replacements.load(Ordering::Relaxed),
+        }
+    }
+}
+
+// ── InterpreterPoolBuilder ───────────────────────────────────────────────────
+
+/// Builder for an [`InterpreterPool`] with non-default slot configuration.
+///
+/// ```no_run
+/// use llm_pyexec::InterpreterPool;
+///
+/// let pool = InterpreterPool::builder()
+///     .size(2)
+///     .preimport(&["json", "math", "re"])
+///     .build();
+/// ```
+#[derive(Debug, Clone)]
+pub struct InterpreterPoolBuilder {
+    size: usize,
+    default_allowed: HashSet<String>,
+    preimport: Vec<String>,
+    recycle_after: Option<u32>,
+    queue_depth: usize,
+}
+
+impl Default for InterpreterPoolBuilder {
+    /// Four slots, [`DEFAULT_ALLOWED_MODULES`], no pre-imports, no recycling,
+    /// no work queue.
+    fn default() -> Self {
+        InterpreterPoolBuilder {
+            size: 4,
+            default_allowed: DEFAULT_ALLOWED_MODULES
+                .iter()
+                .map(|s| s.to_string())
+                .collect(),
+            preimport: Vec::new(),
+            recycle_after: None,
+            queue_depth: 0,
+        }
+    }
+}
+
+impl InterpreterPoolBuilder {
+    /// Reads `PYEXEC_POOL_SIZE`, `PYEXEC_SLOT_RECYCLE_AFTER`, and
+    /// `PYEXEC_PREIMPORT` and `PYEXEC_WARM_MODULES` (comma-separated module
+    /// names, combined). Unset or unparsable variables keep the [`Default`]
+    /// value.
+    pub fn from_env() -> Self {
+        let mut builder = Self::default();
+        if let Some(size) = std::env::var("PYEXEC_POOL_SIZE")
+            .ok()
+            .and_then(|s| s.parse().ok())
+        {
+            builder.size = size;
+        }
+        builder.recycle_after = std::env::var("PYEXEC_SLOT_RECYCLE_AFTER")
+            .ok()
+            .and_then(|s| s.parse().ok());
+        for var in ["PYEXEC_PREIMPORT", "PYEXEC_WARM_MODULES"] {
+            if let Ok(list) = std::env::var(var) {
+                builder.preimport.extend(parse_module_list(&list));
+            }
+        }
+        builder
+    }
+
+    /// Number of slots. A `size` of 0 is treated as 1.
+    pub fn size(mut self, size: usize) -> Self {
+        self.size = size;
+        self
+    }
+
+    /// The number of slots [`build`](Self::build) will create.
+    pub(crate) fn target_size(&self) -> usize {
+        self.size.max(1)
+    }
+
+    /// Allowlist each slot's interpreter is built with.
+    ///
+    /// Every work item still carries its own allowlist, which the slot swaps in
+    /// for that call without copying it.
+    pub fn default_allowed(mut self, modules: HashSet<String>) -> Self {
+        self.default_allowed = modules;
+        self
+    }
+
+    /// Modules each slot imports while warming up (see the module docs).
+    ///
+    /// Adds to any modules named by earlier calls. Modules that fail to import
+    /// are skipped.
+    pub fn preimport(mut self, modules: &[&str]) -> Self {
+        self.preimport
+            .extend(modules.iter().map(|name| name.to_string()));
+        self
+    }
+
+    /// Executions after which a slot rebuilds its interpreter (see the module
+    /// docs). `None` or `Some(0)` disables recycling.
+    pub fn recycle_after(mut self, recycle_after: Option<u32>) -> Self {
+        self.recycle_after = recycle_after;
+        self
+    }
+
+    /// Maximum number of work items queued while every slot is busy (see
+    /// [`InterpreterPool::with_queue`]). 0 disables queueing.
+    pub fn queue_depth(mut self, depth: usize) -> Self {
+        self.queue_depth = depth;
+        self
+    }
+
+    /// Creates and pre-warms the pool; see [`InterpreterPool::new`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if any slot thread fails to start.
+    pub fn build(self) -> InterpreterPool {
+        InterpreterPool::from_builder(self)
     }
 }
 
+/// Splits a comma-separated module list, ignoring whitespace and empty entries.
+fn parse_module_list(list: &str) -> Vec<String> {
+    list.split(',')
+        .map(str::trim)
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
 // PyInterp is intentionally NOT Send. If this ever compiles with Send, audit
 // the safety implications carefully (RustPython's Rc<> internals are not thread-safe).
 // static_assertions::assert_not_impl_any!(crate::vm::PyInterp: Send);
@@ -361,7 +1255,7 @@ impl InterpreterPool {
 mod tests {
     use super::*;
     use crate::modules::build_allowed_set;
-    use crate::types::ExecutionSettings;
+    use crate::types::{ExecutionError, ExecutionSettings};
     use std::sync::Arc;
     use std::time::Duration;
 
@@ -395,12 +1289,17 @@ mod tests {
             wrapped_source: "x = 1\n".to_string(),
             output,
             allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
             response: response_tx,
         };
 
         // Dispatch with a real (non-zero) timeout to grab the slot.
-        let dispatched = pool.dispatch_work(work, Duration::from_secs(5));
-        assert!(dispatched, "Expected first dispatch to succeed");
+        let outcome = pool.dispatch_work(work, Duration::from_secs(5));
+        assert_eq!(
+            outcome,
+            DispatchOutcome::Dispatched,
+            "Expected first dispatch to succeed"
+        );
 
         // Now the pool has 0 idle slots. A dispatch with zero timeout must fail immediately.
         let (response_tx2, _response_rx2) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
@@ -409,11 +1308,16 @@ mod tests {
             wrapped_source: "y = 2\n".to_string(),
             output: output2,
             allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
             response: response_tx2,
         };
 
-        let not_dispatched = pool.dispatch_work(work2, Duration::ZERO);
-        assert!(!not_dispatched, "Expected dispatch to fail with zero timeout and no slots");
+        let outcome = pool.dispatch_work(work2, Duration::ZERO);
+        assert_eq!(
+            outcome,
+            DispatchOutcome::Unavailable,
+            "Expected dispatch to fail with zero timeout and no slots"
+        );
     }
 
     // (3) Functional: dispatch one work item to a pool of 1, receive result via response channel,
@@ -429,11 +1333,16 @@ mod tests {
             wrapped_source: "__result__ = 1 + 1\n".to_string(),
             output,
             allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
             response: response_tx,
         };
 
-        let dispatched = pool.dispatch_work(work, Duration::from_secs(30));
-        assert!(dispatched, "Expected dispatch to succeed");
+        let outcome = pool.dispatch_work(work, Duration::from_secs(30));
+        assert_eq!(
+            outcome,
+            DispatchOutcome::Dispatched,
+            "Expected dispatch to succeed"
+        );
 
         let result = response_rx
             .recv_timeout(Duration::from_secs(30))
@@ -459,11 +1368,16 @@ mod tests {
             wrapped_source: "pass\n".to_string(),
             output,
             allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
             response: response_tx,
         };
 
-        let dispatched = pool.dispatch_work(work, Duration::from_secs(30));
-        assert!(dispatched, "Expected dispatch to succeed");
+        let outcome = pool.dispatch_work(work, Duration::from_secs(30));
+        assert_eq!(
+            outcome,
+            DispatchOutcome::Dispatched,
+            "Expected dispatch to succeed"
+        );
 
         // Wait for result — slot returns to pool after sending result.
         let _result = response_rx
@@ -481,6 +1395,528 @@ mod tests {
         );
     }
 
+    /// Polls `condition` for up to 30 seconds.
+    fn wait_until(mut condition: impl FnMut() -> bool) -> bool {
+        let deadline = std::time::Instant::now() + Duration::from_secs(30);
+        while std::time::Instant::now() < deadline {
+            if condition() {
+                return true;
+            }
+            std::thread::sleep(Duration::from_millis(20));
+        }
+        condition()
+    }
+
+    /// Dispatches `source` and waits for its result.
+    fn run_on(pool: &InterpreterPool, source: &str) -> VmRunResult {
+        let (tx, rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+        let work = WorkItem {
+            wrapped_source: source.to_string(),
+            output: OutputBuffer::new(1_048_576),
+            allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
+            response: tx,
+        };
+        assert_eq!(
+            pool.dispatch_work(work, Duration::from_secs(30)),
+            DispatchOutcome::Dispatched
+        );
+        rx.recv_timeout(Duration::from_secs(30))
+            .expect("result timeout")
+    }
+
+    // (8) Metrics: N dispatches on a 1-slot pool.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_metrics_count_dispatches_and_busy() {
+        let pool = InterpreterPool::new(1);
+        assert_eq!(
+            pool.metrics(),
+            PoolMetrics {
+                slot_executions: vec![0],
+                ..PoolMetrics::default()
+            }
+        );
+
+        const N: u64 = 5;
+        for i in 0..N {
+            let r = run_on(&pool, &format!("__result__ = {i}\n"));
+            assert!(r.error.is_none(), "unexpected error: {:?}", r.error);
+        }
+        assert!(
+            wait_until(|| pool.idle_count() == 1),
+            "slot did not return to the pool"
+        );
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.dispatches, N);
+        assert_eq!(metrics.busy_count, 0, "no slot is busy once idle");
+        assert_eq!(metrics.slot_executions, vec![N]);
+        assert_eq!(metrics.fallbacks, 0);
+        assert!(metrics.max_wait_ns <= metrics.total_wait_ns);
+    }
+
+    // (6) Resize: grow 1→3, run concurrent work, shrink back to 1.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_resize_grow_then_shrink() {
+        let pool = InterpreterPool::new(1);
+
+        pool.resize(3);
+        assert_eq!(pool.size(), 3, "size() reports the new target immediately");
+        assert_eq!(pool.active_slots(), 3);
+        assert!(
+            wait_until(|| pool.idle_count() == 3),
+            "idle_count did not reach 3: {}",
+            pool.idle_count()
+        );
+
+        std::thread::scope(|scope| {
+            for i in 0..6 {
+                let pool = &pool;
+                scope.spawn(move || {
+                    let r = run_on(pool, &format!("__result__ = {i} * 2\n"));
+                    assert!(r.error.is_none(), "worker {i} error: {:?}", r.error);
+                    assert_eq!(r.return_value, Some((i * 2).to_string()));
+                });
+            }
+        });
+
+        pool.resize(1);
+        assert_eq!(pool.size(), 1);
+        assert!(
+            wait_until(|| pool.active_slots() == 1 && pool.idle_count() == 1),
+            "pool did not converge to 1 slot: active={}, idle={}",
+            pool.active_slots(),
+            pool.idle_count()
+        );
+
+        let r = run_on(&pool, "__result__ = 'still works'\n");
+        assert!(r.error.is_none(), "unexpected error: {:?}", r.error);
+    }
+
+    // (7) Resize: a busy slot retires only after finishing its work item.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_shrink_retires_busy_slot_after_work() {
+        let pool = InterpreterPool::new(2);
+
+        let (tx, rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+        let work = WorkItem {
+            wrapped_source: "total = 0\nfor i in range(200000):\n    total += i\n".to_string(),
+            output: OutputBuffer::new(1_048_576),
+            allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
+            response: tx,
+        };
+        assert_eq!(
+            pool.dispatch_work(work, Duration::from_secs(30)),
+            DispatchOutcome::Dispatched
+        );
+
+        // The idle slot is retired first, so the busy one must survive to
+        // deliver its result before exiting.
+        pool.resize(1);
+        let result = rx
+            .recv_timeout(Duration::from_secs(30))
+            .expect("busy slot result");
+        assert!(
+            result.error.is_none(),
+            "unexpected error: {:?}",
+            result.error
+        );
+        assert!(
+            wait_until(|| pool.active_slots() == 1 && pool.idle_count() == 1),
+            "pool did not converge to 1 slot: active={}, idle={}",
+            pool.active_slots(),
+            pool.idle_count()
+        );
+
+        // Growing while nothing is pending spawns a fresh slot.
+        pool.resize(2);
+        assert!(wait_until(|| pool.idle_count() == 2));
+        assert_eq!(pool.active_slots(), 2);
+    }
+
+    // (9) Recycling: 10 executions on a 1-slot pool recycling every 3.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_recycle_after_keeps_serving_and_isolating() {
+        let pool = InterpreterPool::with_recycling(1, Some(3));
+
+        for i in 0..10 {
+            // Each call leaks a module into sys.modules and a global; neither
+            // may be visible to the next call, recycled or not.
+            let source = format!(
+                "import sys\n\
+                 seen = ('leaked_mod' in sys.modules, 'leaked_var' in globals())\n\
+                 sys.modules['leaked_mod'] = sys\n\
+                 leaked_var = {i}\n\
+                 __result__ = seen\n"
+            );
+            let r = run_on(&pool, &source);
+            assert!(r.error.is_none(), "call {i} error: {:?}", r.error);
+            assert_eq!(
+                r.return_value.as_deref(),
+                Some("(False, False)"),
+                "call {i}"
+            );
+        }
+
+        assert!(
+            wait_until(|| pool.idle_count() == 1),
+            "slot did not return to the pool"
+        );
+        let metrics = pool.metrics();
+        assert_eq!(metrics.recycles, 3, "recycled after calls 3, 6 and 9");
+        assert_eq!(metrics.slot_executions, vec![10]);
+        assert_eq!(metrics.fallbacks, 0);
+        assert_eq!(pool.active_slots(), 1);
+    }
+
+    // (10) Stuck slots: an abandoned infinite loop is written off and replaced.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_stuck_slot_replaced_after_abandoned_timeout() {
+        let pool = InterpreterPool::new(1);
+        pool.set_stuck_timeout_multiple(2);
+
+        let timeout = Duration::from_millis(100);
+        let (tx, rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+        let work = WorkItem {
+            wrapped_source: "while True:\n    pass\n".to_string(),
+            output: OutputBuffer::new(1_048_576),
+            allowed_set: make_allowed_set(),
+            options: RunOptions {
+                deadline: Some(Instant::now() + timeout),
+                ..RunOptions::default()
+            },
+            response: tx,
+        };
+        assert_eq!(
+            pool.dispatch_work(work, Duration::from_secs(30)),
+            DispatchOutcome::Dispatched
+        );
+        assert!(
+            rx.recv_timeout(timeout).is_err(),
+            "infinite loop must time out"
+        );
+        drop(rx);
+
+        // The only slot is stuck; dispatch must recover capacity rather than
+        // wait for it (or fall back) forever.
+        let started = Instant::now();
+        let r = run_on(&pool, "__result__ = 'recovered'\n");
+        assert!(r.error.is_none(), "unexpected error: {:?}", r.error);
+        assert_eq!(r.return_value.as_deref(), Some("'recovered'"));
+        assert!(started.elapsed() < Duration::from_secs(30));
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.stuck_replacements, 1);
+        assert_eq!(metrics.fallbacks, 0);
+        assert!(
+            wait_until(|| pool.idle_count() == pool.size()),
+            "idle_count did not recover to size()"
+        );
+        assert_eq!(pool.active_slots(), 1, "the stuck slot no longer counts");
+    }
+
+    // (11) Deep recursion: the slot survives and rejoins the queue.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_deep_recursion_does_not_lose_slot() {
+        let pool = InterpreterPool::new(1);
+
+        for _ in 0..3 {
+            let r = run_on(&pool, "def f(n):\n    return f(n + 1)\nf(0)\n");
+            assert!(
+                matches!(r.error, Some(ExecutionError::RecursionLimitExceeded { .. })),
+                "unexpected result: {:?}",
+                r.error
+            );
+            assert!(
+                wait_until(|| pool.idle_count() == 1),
+                "slot did not return to the pool"
+            );
+        }
+
+        let r = run_on(&pool, "__result__ = 'alive'\n");
+        assert_eq!(r.return_value.as_deref(), Some("'alive'"));
+        assert_eq!(pool.active_slots(), 1);
+    }
+
+    // (12) Pre-imports: a pre-imported module is already loaded on the pool path,
+    // so importing it is faster than on a fresh interpreter.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_preimported_module_faster_than_fresh_interpreter() {
+        let source = "import json\n__result__ = json.dumps({'a': [1, 2]})\n";
+        let pool = InterpreterPool::builder()
+            .size(1)
+            .preimport(&["json"])
+            .build();
+
+        // First call installs the import hook and output capture.
+        let r = run_on(&pool, source);
+        assert!(r.error.is_none(), "unexpected error: {:?}", r.error);
+        assert!(wait_until(|| pool.idle_count() == 1));
+
+        let started = Instant::now();
+        let r = run_on(&pool, source);
+        let pooled = started.elapsed();
+        assert_eq!(r.return_value.as_deref(), Some("'{\"a\": [1, 2]}'"));
+
+        // Time only the run on the fresh interpreter, not its construction.
+        let fresh_set = (*make_allowed_set()).clone();
+        let interp = build_interpreter(fresh_set, OutputBuffer::new(1_048_576), true, None);
+        let started = Instant::now();
+        let r = run_code(
+            &interp,
+            source,
+            OutputBuffer::new(1_048_576),
+            &RunOptions::default(),
+        );
+        let fresh = started.elapsed();
+        assert!(r.error.is_none(), "unexpected error: {:?}", r.error);
+
+        assert!(
+            pooled < fresh,
+            "pre-imported json took {pooled:?} on the pool, {fresh:?} fresh"
+        );
+    }
+
+    // (13) Pre-imports do not bypass a per-call allowlist that denies them.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_preimported_module_respects_per_call_allowlist() {
+        let pool = InterpreterPool::builder()
+            .size(1)
+            .preimport(&["json"])
+            .build();
+
+        let mut denied: HashSet<String> = (*make_allowed_set()).clone();
+        denied.remove("json");
+        for _ in 0..2 {
+            let (tx, rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+            let work = WorkItem {
+                wrapped_source: "import json\n".to_string(),
+                output: OutputBuffer::new(1_048_576),
+                allowed_set: Arc::new(denied.clone()),
+                options: RunOptions::default(),
+                response: tx,
+            };
+            assert_eq!(
+                pool.dispatch_work(work, Duration::from_secs(30)),
+                DispatchOutcome::Dispatched
+            );
+            let r = rx
+                .recv_timeout(Duration::from_secs(30))
+                .expect("result timeout");
+            assert_eq!(
+                r.error,
+                Some(ExecutionError::ModuleNotAllowed {
+                    module_name: "json".to_string()
+                })
+            );
+        }
+
+        // The default allowlist still permits it.
+        let r = run_on(&pool, "import json\n__result__ = json.loads('[3]')\n");
+        assert_eq!(r.return_value.as_deref(), Some("[3]"));
+    }
+
+    // (14) Work queue: with the only slot busy, items queue up to the depth,
+    // then dispatch fails; queued items all run, in order.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_queue_accepts_up_to_depth_then_unavailable() {
+        let pool = InterpreterPool::new(1).with_queue(2);
+        let submit = |source: &str, timeout: Duration| {
+            let (tx, rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+            let work = WorkItem {
+                wrapped_source: source.to_string(),
+                output: OutputBuffer::new(1_048_576),
+                allowed_set: make_allowed_set(),
+                options: RunOptions::default(),
+                response: tx,
+            };
+            (pool.dispatch_work(work, timeout), rx)
+        };
+
+        let (busy, busy_rx) = submit(
+            "total = 0\nfor i in range(300000):\n    total += i\n",
+            Duration::from_secs(30),
+        );
+        assert_eq!(busy, DispatchOutcome::Dispatched);
+        let (first, first_rx) = submit("__result__ = 'first'\n", Duration::ZERO);
+        let (second, second_rx) = submit("__result__ = 'second'\n", Duration::ZERO);
+        let (third, _third_rx) = submit("__result__ = 'third'\n", Duration::ZERO);
+        assert_eq!(first, DispatchOutcome::Queued);
+        assert_eq!(second, DispatchOutcome::Queued);
+        assert_eq!(third, DispatchOutcome::Unavailable);
+        assert_eq!(pool.metrics().queue_len, 2);
+
+        let recv = |rx: std::sync::mpsc::Receiver<VmRunResult>| {
+            rx.recv_timeout(Duration::from_secs(30))
+                .expect("result timeout")
+        };
+        assert!(recv(busy_rx).error.is_none());
+        assert_eq!(recv(first_rx).return_value.as_deref(), Some("'first'"));
+        assert_eq!(recv(second_rx).return_value.as_deref(), Some("'second'"));
+
+        assert!(
+            wait_until(|| pool.idle_count() == 1),
+            "slot did not return to the pool"
+        );
+        let metrics = pool.metrics();
+        assert_eq!((metrics.dispatches, metrics.queued), (3, 2));
+        assert_eq!(metrics.queue_len, 0);
+        assert_eq!(metrics.busy_count, 0);
+        assert_eq!(metrics.slot_executions, vec![3]);
+    }
+
+    /// Number of callers blocked in `dispatch_work` waiting for a slot.
+    fn waiter_count(pool: &InterpreterPool) -> usize {
+        let (lock, _) = &*pool.available;
+        lock.lock().expect("pool queue poisoned").waiters.len()
+    }
+
+    /// Take the only idle slot out of the queue, as if it were busy.
+    fn hold_slot(pool: &InterpreterPool) -> SlotSender {
+        let (lock, _) = &*pool.available;
+        let mut queue = lock.lock().expect("pool queue poisoned");
+        queue.idle.pop_front().expect("no idle slot to hold")
+    }
+
+    /// Give a slot taken by `hold_slot` back, as a finishing slot would.
+    fn release_slot(pool: &InterpreterPool, slot: SlotSender) {
+        let (lock, _) = &*pool.available;
+        lock.lock().expect("pool queue poisoned").release(slot);
+    }
+
+    // (15) Checkout order: callers waiting for the only slot get it in the
+    // order they started waiting.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_waiters_served_in_arrival_order() {
+        let pool = InterpreterPool::new(1);
+        let held = hold_slot(&pool);
+        let order = Mutex::new(Vec::new());
+
+        std::thread::scope(|scope| {
+            for id in 0..4 {
+                let (pool, order) = (&pool, &order);
+                scope.spawn(move || {
+                    let (tx, rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+                    let work = WorkItem {
+                        wrapped_source: format!("__result__ = {id}\n"),
+                        output: OutputBuffer::new(1_048_576),
+                        allowed_set: make_allowed_set(),
+                        options: RunOptions::default(),
+                        response: tx,
+                    };
+                    let outcome = pool.dispatch_work(work, Duration::from_secs(30));
+                    order.lock().expect("order poisoned").push(id);
+                    assert_eq!(outcome, DispatchOutcome::Dispatched);
+                    let result = rx
+                        .recv_timeout(Duration::from_secs(30))
+                        .expect("result timeout");
+                    assert_eq!(result.return_value, Some(id.to_string()));
+                });
+                // Start the next caller only once this one holds a ticket.
+                assert!(
+                    wait_until(|| waiter_count(pool) == id + 1),
+                    "caller {id} never started waiting"
+                );
+            }
+            release_slot(&pool, held);
+        });
+
+        assert_eq!(*order.lock().expect("order poisoned"), vec![0, 1, 2, 3]);
+        assert!(wait_until(|| pool.idle_count() == 1));
+        assert_eq!(waiter_count(&pool), 0);
+    }
+
+    // (16) Checkout timeout: a waiter that gives up withdraws its ticket, so
+    // the slot it was waiting for becomes idle instead of being handed to it.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_timed_out_waiter_withdraws_ticket() {
+        let pool = InterpreterPool::new(1);
+        let held = hold_slot(&pool);
+
+        let (tx, _rx) = std::sync::mpsc::sync_channel::<VmRunResult>(1);
+        let work = WorkItem {
+            wrapped_source: "__result__ = 1\n".to_string(),
+            output: OutputBuffer::new(1_048_576),
+            allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
+            response: tx,
+        };
+        let start = Instant::now();
+        let outcome = pool.dispatch_work(work, Duration::from_millis(100));
+        assert_eq!(outcome, DispatchOutcome::Unavailable);
+        assert!(start.elapsed() >= Duration::from_millis(100));
+        assert_eq!(waiter_count(&pool), 0);
+
+        release_slot(&pool, held);
+        assert_eq!(pool.idle_count(), 1);
+        assert_eq!(pool.metrics().dispatches, 0);
+    }
+
+    // (17) Dead slots: a slot thread that died with its sender still in the
+    // available queue is discarded and replaced, and the call still runs.
+    #[test]
+    #[ignore = "slow: VM init"]
+    fn test_dead_slot_replaced_on_dispatch() {
+        let pool = InterpreterPool::new(1);
+        let held = hold_slot(&pool);
+        let _ = held.send(SlotMessage::Die);
+        assert!(
+            wait_until(|| matches!(
+                held.try_send(SlotMessage::Die),
+                Err(TrySendError::Disconnected(_))
+            )),
+            "slot thread did not die"
+        );
+        release_slot(&pool, held);
+        assert_eq!(pool.active_slots(), 1, "the dead slot is not yet noticed");
+
+        let cache = crate::cache::BytecodeCache::new(8);
+        let settings = ExecutionSettings {
+            timeout_ns: 30_000_000_000,
+            ..ExecutionSettings::default()
+        };
+        let start = Instant::now();
+        let result = crate::executor::execute_with(&pool, &cache, "6 * 7", settings);
+        assert!(
+            start.elapsed() < Duration::from_secs(10),
+            "call waited for the dead slot"
+        );
+        assert!(
+            result.error.is_none(),
+            "unexpected error: {:?}",
+            result.error
+        );
+        assert_eq!(result.return_value.as_deref(), Some("42"));
+        assert!(result.used_pool, "call should run on the replacement slot");
+
+        let metrics = pool.metrics();
+        assert_eq!(metrics.dead_replacements, 1);
+        assert_eq!(metrics.dispatches, 1);
+        assert!(wait_until(|| pool.idle_count() == 1));
+        assert_eq!(pool.active_slots(), 1);
+    }
+
+    #[test]
+    fn test_parse_module_list() {
+        assert_eq!(
+            parse_module_list(" json, math ,,re,"),
+            ["json", "math", "re"]
+        );
+        assert!(parse_module_list("").is_empty());
+    }
+
     // (5) State isolation: variable assigned in call 1 must not be visible in call 2
     #[test]
     #[ignore = "slow: VM init"]
@@ -493,9 +1929,13 @@ mod tests {
             wrapped_source: "secret_var = 42\n".to_string(),
             output: OutputBuffer::new(1_048_576),
             allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
             response: tx1,
         };
-        assert!(pool.dispatch_work(work1, Duration::from_secs(30)));
+        assert_eq!(
+            pool.dispatch_work(work1, Duration::from_secs(30)),
+            DispatchOutcome::Dispatched
+        );
         let r1 = rx1.recv_timeout(Duration::from_secs(30)).expect("recv1 timeout");
         assert!(r1.error.is_none(), "Call 1 unexpected error: {:?}", r1.error);
 
@@ -508,9 +1948,13 @@ mod tests {
             wrapped_source: "__result__ = secret_var\n".to_string(),
             output: OutputBuffer::new(1_048_576),
             allowed_set: make_allowed_set(),
+            options: RunOptions::default(),
             response: tx2,
         };
-        assert!(pool.dispatch_work(work2, Duration::from_secs(30)));
+        assert_eq!(
+            pool.dispatch_work(work2, Duration::from_secs(30)),
+            DispatchOutcome::Dispatched
+        );
         let r2 = rx2.recv_timeout(Duration::from_secs(30)).expect("recv2 timeout");
 
         assert!(
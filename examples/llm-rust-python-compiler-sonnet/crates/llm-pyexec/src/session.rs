@@ -0,0 +1,158 @@
+//! Stateful single-interpreter sessions for REPL-style use.
+//!
+//! A [`Session`] owns one interpreter on a dedicated thread and keeps its
+//! `__main__` namespace and `sys.modules` between [`Session::eval`] calls, so
+//! `x = 1` followed by `x + 1` returns `2`. This is the opposite of
+//! [`execute`](crate::execute), which runs every call in a clean pooled
+//! interpreter.
+//!
+//! ## Not isolated
+//!
+//! Everything one `eval` does is visible to the next: variables, imported
+//! modules, monkey-patched builtins. Use a `Session` only for a single trusted
+//! conversation (a notebook, a tutoring exchange) and never share one between
+//! users.
+//!
+//! ## Single-threaded
+//!
+//! The interpreter lives on one thread and runs one snippet at a time.
+//! Concurrent `eval` calls on the same `Session` are serialized.
+//!
+//! ## Limits
+//!
+//! The allowlist, timeout and output limit from the session's
+//! [`ExecutionSettings`] are enforced on every call. A call that times out
+//! keeps running on the session thread until it finishes, so later calls
+//! queue behind it (and may time out themselves).
+
+use std::collections::HashSet;
+use std::sync::mpsc::{channel, sync_channel, Sender};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::cache::cache_key;
+use crate::executor::{fresh_result_name, into_execution_result, prepare_source};
+use crate::modules::build_allowed_set;
+use crate::output::OutputBuffer;
+use crate::pool::WorkItem;
+use crate::types::{ExecutionResult, ExecutionSettings};
+use crate::vm::{
+    build_interpreter, new_main_scope, run_code_in_scope, RunOptions, VmRunResult,
+    VM_THREAD_STACK_SIZE,
+};
+
+/// A persistent interpreter whose state carries over between calls.
+///
+/// Dropping the `Session` lets its thread exit once any in-flight call finishes.
+pub struct Session {
+    work: Sender<WorkItem>,
+    settings: ExecutionSettings,
+    allowed_set: Arc<HashSet<String>>,
+}
+
+impl Session {
+    /// Start a session with an empty namespace.
+    ///
+    /// Builds the interpreter on a new thread; the first `eval` waits for it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the session thread cannot be spawned.
+    pub fn new(settings: ExecutionSettings) -> Self {
+        let allowed_set = Arc::new(build_allowed_set(&settings));
+        let (work, rx) = channel::<WorkItem>();
+        let initial_set = (*allowed_set).clone();
+        let json_shim = settings.use_builtin_json_shim;
+        let stdlib_paths = settings.stdlib_paths.clone();
+        let module_name = settings.effective_module_name().to_owned();
+
+        std::thread::Builder::new()
+            .name("pyexec-session".to_string())
+            .stack_size(VM_THREAD_STACK_SIZE)
+            .spawn(move || {
+                let interp = build_interpreter(
+                    initial_set,
+                    OutputBuffer::new(1_048_576),
+                    json_shim,
+                    stdlib_paths.as_deref(),
+                );
+                let scope = new_main_scope(&interp, &module_name);
+                // Exits when the Session (the only sender) is dropped.
+                while let Ok(item) = rx.recv() {
+                    let result = run_code_in_scope(
+                        &interp,
+                        &item.wrapped_source,
+                        item.output,
+                        &item.options,
+                        &scope,
+                    );
+                    // The caller may have timed out and gone away.
+                    let _ = item.response.send(result);
+                }
+            })
+            .expect("Failed to spawn session thread");
+
+        Session {
+            work,
+            settings,
+            allowed_set,
+        }
+    }
+
+    /// Run `code` in the session's persistent namespace.
+    ///
+    /// The last bare expression is captured as `return_value`, as with
+    /// [`execute`](crate::execute).
+    pub fn eval(&self, code: &str) -> ExecutionResult {
+        let start = Instant::now();
+        let settings = &self.settings;
+        let output = OutputBuffer::new(settings.max_output_bytes);
+        let (response, response_rx) = sync_channel::<VmRunResult>(1);
+        let prepared = prepare_source(code, settings);
+        let result_name = fresh_result_name();
+        let debug_source = settings.debug.then(|| prepared.wrapped.clone());
+        let work = WorkItem {
+            wrapped_source: prepared.executable(&result_name),
+            output: output.clone(),
+            allowed_set: Arc::clone(&self.allowed_set),
+            options: RunOptions {
+                result_name: result_name.clone(),
+                report_none: prepared.report_none,
+                ..RunOptions::from_settings(settings, start)
+            },
+            response,
+        };
+
+        // The timeout covers queueing behind earlier calls as well as running.
+        let deadline = Duration::from_nanos(settings.effective_wall_timeout_ns());
+        let vm_result = match self.work.send(work) {
+            Ok(()) => response_rx
+                .recv_timeout(deadline.saturating_sub(start.elapsed()))
+                .ok(),
+            Err(_) => None,
+        };
+
+        let duration_ns = start.elapsed().as_nanos() as u64;
+        let mut result = ExecutionResult {
+            cache_key_hex: debug_source.as_deref().map(|s| cache_key(s).to_hex()),
+            wrapped_source: debug_source,
+            ..into_execution_result(
+                vm_result,
+                output,
+                settings.effective_wall_timeout_ns(),
+                settings.max_output_bytes,
+                settings.output_encoding_errors,
+                duration_ns,
+            )
+        };
+        if let Some(error) = result.error.as_mut() {
+            prepared.restore_error_column(error, &result_name);
+        }
+        result
+    }
+
+    /// The settings every call in this session runs with.
+    pub fn settings(&self) -> &ExecutionSettings {
+        &self.settings
+    }
+}
@@ -1,15 +1,100 @@
-use std::sync::mpsc;
-use std::thread;
+//! Wall-clock timeouts for work on a dedicated thread.
+//!
+//! A thread running Python cannot be killed, so a call that times out leaves
+//! its thread running until the snippet finishes on its own — forever, for an
+//! infinite loop. [`abandoned_threads`] counts such threads so operators can
+//! spot runaway snippets. The executor bounds them: a fresh interpreter's
+//! thread holds its [`ConcurrencyLimit`](crate::ConcurrencyLimit) permit until
+//! it exits, so once the limit is taken up by abandoned threads further calls
+//! fail with [`ExecutionError::Overloaded`](crate::ExecutionError::Overloaded)
+//! (or run on the pool) instead of spawning more.
+
+use std::sync::atomic::{AtomicU8, AtomicUsize, Ordering};
+use std::sync::{mpsc, Arc};
+use std::thread::{self, JoinHandle};
 use std::time::Duration;
 
+use crate::vm::VM_THREAD_STACK_SIZE;
+
+/// Worker threads that timed out and have not finished yet.
+static ABANDONED_THREADS: AtomicUsize = AtomicUsize::new(0);
+
+/// Worker states, in the order they can occur.
+const RUNNING: u8 = 0;
+const ABANDONED: u8 = 1;
+const FINISHED: u8 = 2;
+
+/// Number of threads started by [`run_with_timeout`] or
+/// [`run_with_timeout_outcome`] that outlived their timeout and are still
+/// running.
+///
+/// A value that keeps growing means snippets are looping past their timeout.
+pub fn abandoned_threads() -> usize {
+    ABANDONED_THREADS.load(Ordering::SeqCst)
+}
+
+/// How a call to [`run_with_timeout_outcome`] ended.
+#[derive(Debug)]
+pub enum TimeoutOutcome<T> {
+    /// `f` returned within the timeout.
+    Completed(T),
+    /// The timeout passed first. The thread keeps running and is counted by
+    /// [`abandoned_threads`] until it finishes.
+    TimedOut(AbandonedThread),
+    /// `f` panicked before returning.
+    Panicked,
+}
+
+impl<T> TimeoutOutcome<T> {
+    /// The value `f` returned, if it completed in time.
+    pub fn completed(self) -> Option<T> {
+        match self {
+            TimeoutOutcome::Completed(value) => Some(value),
+            TimeoutOutcome::TimedOut(_) | TimeoutOutcome::Panicked => None,
+        }
+    }
+}
+
+/// A worker thread left running after its timeout.
+///
+/// Dropping this detaches the thread; it is not stopped.
+#[derive(Debug)]
+pub struct AbandonedThread {
+    handle: JoinHandle<()>,
+}
+
+impl AbandonedThread {
+    /// Whether the thread has finished since it was abandoned.
+    pub fn is_finished(&self) -> bool {
+        self.handle.is_finished()
+    }
+}
+
+/// Moves a worker to [`FINISHED`] when dropped, even if `f` panics, and takes
+/// it off the abandoned count if the caller gave up on it.
+struct FinishGuard(Arc<AtomicU8>);
+
+impl Drop for FinishGuard {
+    fn drop(&mut self) {
+        if self.0.swap(FINISHED, Ordering::SeqCst) == ABANDONED {
+            ABANDONED_THREADS.fetch_sub(1, Ordering::SeqCst);
+        }
+    }
+}
+
 /// Run `f` in a new thread. Wait at most `timeout_ns` nanoseconds for it to finish.
 ///
+/// The thread gets a stack large enough to run an interpreter on.
+///
 /// # Returns
 /// - `Some(T)` if `f` completed within the timeout.
 /// - `None` if the timeout was exceeded. The spawned thread is abandoned (not joined).
-///   The thread will terminate on its own when it finishes its work or the process exits.
+///   The thread will terminate on its own when it finishes its work or the process exits;
+///   until then it is counted by [`abandoned_threads`].
 /// - `None` if the spawned thread panics (channel becomes Disconnected).
 ///
+/// Use [`run_with_timeout_outcome`] to tell these cases apart.
+///
 /// # Thread safety
 /// `f` must be `Send + 'static`. The return type `T` must be `Send + 'static`.
 ///
@@ -23,15 +108,29 @@ use std::time::Duration;
 /// all threads including the caller. Thread abandonment is the only portable,
 /// safe mechanism for interrupting a tight Python loop that never yields.
 pub fn run_with_timeout<F, T>(f: F, timeout_ns: u64) -> Option<T>
+where
+    F: FnOnce() -> T + Send + 'static,
+    T: Send + 'static,
+{
+    run_with_timeout_outcome(f, timeout_ns).completed()
+}
+
+/// Like [`run_with_timeout`], but reports whether the thread timed out or
+/// panicked, and hands back a timed-out thread as an [`AbandonedThread`].
+pub fn run_with_timeout_outcome<F, T>(f: F, timeout_ns: u64) -> TimeoutOutcome<T>
 where
     F: FnOnce() -> T + Send + 'static,
     T: Send + 'static,
 {
     let (tx, rx) = mpsc::channel::<T>();
+    let state = Arc::new(AtomicU8::new(RUNNING));
+    let guard = FinishGuard(Arc::clone(&state));
 
-    let _handle = thread::Builder::new()
+    let handle = thread::Builder::new()
         .name("pyexec-vm".to_string())
+        .stack_size(VM_THREAD_STACK_SIZE)
         .spawn(move || {
+            let _guard = guard;
             let result = f();
             // If send fails, the receiver was dropped (timed out). Ignore.
             let _ = tx.send(result);
@@ -40,11 +139,24 @@ where
 
     let timeout = Duration::from_nanos(timeout_ns);
     match rx.recv_timeout(timeout) {
-        Ok(result) => Some(result),
-        Err(mpsc::RecvTimeoutError::Timeout) => None,
+        Ok(result) => TimeoutOutcome::Completed(result),
+        Err(mpsc::RecvTimeoutError::Timeout) => {
+            let abandoned = state
+                .compare_exchange(RUNNING, ABANDONED, Ordering::SeqCst, Ordering::SeqCst)
+                .is_ok();
+            if abandoned {
+                ABANDONED_THREADS.fetch_add(1, Ordering::SeqCst);
+                return TimeoutOutcome::TimedOut(AbandonedThread { handle });
+            }
+            // The thread finished while the timeout was being handled.
+            match rx.try_recv() {
+                Ok(result) => TimeoutOutcome::Completed(result),
+                Err(_) => TimeoutOutcome::Panicked,
+            }
+        }
         Err(mpsc::RecvTimeoutError::Disconnected) => {
-            // Thread panicked without sending. Treat as timeout/error.
-            None
+            // Thread panicked without sending.
+            TimeoutOutcome::Panicked
         }
     }
 }
@@ -123,4 +235,34 @@ mod tests {
             result
         );
     }
+
+    /// The outcome distinguishes completion, timeout and panic, and a
+    /// timed-out thread can be watched until it finishes.
+    #[test]
+    fn test_outcome_variants() {
+        match run_with_timeout_outcome(|| 7u32, 1_000_000_000) {
+            TimeoutOutcome::Completed(value) => assert_eq!(value, 7),
+            other => panic!("expected Completed, got {other:?}"),
+        }
+
+        let outcome = run_with_timeout_outcome(
+            || -> u32 { panic!("intentional panic in spawned thread") },
+            1_000_000_000,
+        );
+        assert!(matches!(outcome, TimeoutOutcome::Panicked), "{outcome:?}");
+
+        let outcome = run_with_timeout_outcome(
+            || std::thread::sleep(Duration::from_millis(200)),
+            20_000_000,
+        );
+        let TimeoutOutcome::TimedOut(thread) = outcome else {
+            panic!("expected TimedOut, got {outcome:?}");
+        };
+        assert!(!thread.is_finished());
+        let deadline = Instant::now() + Duration::from_secs(5);
+        while !thread.is_finished() && Instant::now() < deadline {
+            std::thread::sleep(Duration::from_millis(10));
+        }
+        assert!(thread.is_finished());
+    }
 }
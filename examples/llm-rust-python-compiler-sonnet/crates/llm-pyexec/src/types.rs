@@ -2,17 +2,39 @@
 //!
 //! This module defines the core data structures used throughout the library:
 //! - [`ExecutionSettings`] — configuration for a single Python execution
+//! - [`ExecutionMode`] — pooled, pool-only, or isolated execution
+//! - [`DeterministicConfig`] — fixed clock and random seed
+//! - [`ResultCapture`] — how the last expression's value is captured
 //! - [`ExecutionResult`] — the result of a Python execution
 //! - [`ExecutionError`] — structured error variants
+//! - [`InspectQuery`] / [`InspectResult`] — post-execution state checks
+//! - [`AlreadyInitialized`] — a process-global was installed too late
 //! - [`DEFAULT_ALLOWED_MODULES`] — the default set of permitted stdlib modules
+//! - [`STRICT_DENIED_ATTRIBUTES`] — module attributes withheld under
+//!   [`ExecutionSettings::strict_attributes`]
+//! - [`UNGUARDABLE_ATTRIBUTES`] — module attributes that cannot be withheld
+
+use std::collections::HashMap;
+use std::fmt;
+use std::path::PathBuf;
+use std::time::Duration;
 
 use serde::{Deserialize, Serialize};
 
+use crate::latency::LatencySlo;
+use crate::modules::split_attribute_path;
+
 /// The default set of Python standard library modules permitted for import.
 ///
-/// Contains 11 modules commonly needed for data-processing and general scripting
+/// Contains 20 modules commonly needed for data-processing and general scripting
 /// while excluding network, filesystem, and subprocess modules that pose security
-/// or sandboxing concerns.
+/// or sandboxing concerns. `logging` writes to the captured stderr; `warnings`
+/// are collected in [`ExecutionResult::warnings`]; `io` is a frozen shim whose
+/// `open` only sees [`ExecutionSettings::files`].
+///
+/// `decimal`, `fractions` and `statistics` (which need `contextvars`), `typing`
+/// and `dataclasses` do not import under RustPython 0.3 and are left out; see
+/// [`ExecutionSettings::default_plus`] to allow further modules.
 pub const DEFAULT_ALLOWED_MODULES: &[&str] = &[
     "math",
     "re",
@@ -25,56 +47,882 @@ pub const DEFAULT_ALLOWED_MODULES: &[&str] = &[
     "random",
     "os.path",
     "sys",
+    "warnings",
+    "logging",
+    "heapq",
+    "bisect",
+    "textwrap",
+    "copy",
+    "enum",
+    "operator",
+    "io",
+];
+
+/// Module attributes withheld from user code when
+/// [`ExecutionSettings::strict_attributes`] is set, on top of
+/// [`ExecutionSettings::denied_attributes`].
+///
+/// Allowing `os.path` makes the `os` module itself importable; this list
+/// takes back the parts of `os` that run processes, change the environment or
+/// the filesystem, and the `sys` functions that would let user code replace
+/// the trace hook or raise the recursion limit.
+pub const STRICT_DENIED_ATTRIBUTES: &[&str] = &[
+    "os.system",
+    "os.popen",
+    "os.execl",
+    "os.execle",
+    "os.execlp",
+    "os.execv",
+    "os.execve",
+    "os.execvp",
+    "os.fork",
+    "os.kill",
+    "os.getenv",
+    "os.putenv",
+    "os.unsetenv",
+    "os.chdir",
+    "os.chmod",
+    "os.mkdir",
+    "os.makedirs",
+    "os.remove",
+    "os.unlink",
+    "os.rmdir",
+    "os.removedirs",
+    "os.rename",
+    "os.replace",
+    "sys.settrace",
+    "sys.setprofile",
+    "sys.setrecursionlimit",
 ];
 
+/// Module attributes [`ExecutionSettings::denied_attributes`] cannot list:
+/// the interpreter's import system and the sandbox's output capture use them
+/// while user code runs, so they cannot be replaced with a guard.
+/// [`ExecutionSettings::validate`] rejects them.
+pub const UNGUARDABLE_ATTRIBUTES: &[&str] = &[
+    "sys.modules",
+    "sys.path",
+    "sys.meta_path",
+    "sys.path_hooks",
+    "sys.path_importer_cache",
+    "sys.stdout",
+    "sys.stderr",
+    "builtins.__import__",
+];
+
+/// `__name__` of user code when [`ExecutionSettings::module_name`] is `None`.
+pub(crate) const MAIN_MODULE_NAME: &str = "__main__";
+
+/// Under [`TimeoutKind::Cpu`], the wall-clock limit is this multiple of
+/// [`ExecutionSettings::timeout_ns`].
+pub const CPU_MODE_WALL_TIMEOUT_MULTIPLE: u64 = 10;
+
+/// Python recursion limit used when [`ExecutionSettings::recursion_limit`] is
+/// `None`.
+///
+/// Lower than CPython's 1000 because each RustPython frame uses considerably
+/// more native stack than a CPython one.
+pub const DEFAULT_RECURSION_LIMIT: usize = 500;
+
 /// Configuration that governs how a single Python snippet is executed.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ExecutionSettings {
-    /// Maximum wall-clock time in nanoseconds before the execution is aborted.
-    /// Default: 5,000,000,000 ns (5 seconds).
+    /// Maximum time in nanoseconds before the execution is aborted, measured
+    /// as `timeout_kind` says. The wall-clock limit is one deadline for the
+    /// whole call, counted from when it starts: waiting for a pool slot and
+    /// compiling use up part of it. Default: 5,000,000,000 ns (5 seconds).
     pub timeout_ns: u64,
 
+    /// Whether `timeout_ns` limits wall-clock or CPU time. Under
+    /// [`TimeoutKind::Cpu`] the wall-clock limit becomes
+    /// [`CPU_MODE_WALL_TIMEOUT_MULTIPLE`] × `timeout_ns`, a backstop for code
+    /// that blocks without using CPU (see [`effective_wall_timeout_ns`](Self::effective_wall_timeout_ns)).
+    /// Default: [`TimeoutKind::Wall`].
+    #[serde(default)]
+    pub timeout_kind: TimeoutKind,
+
+    /// Maximum CPU time in nanoseconds the interpreter thread may spend running
+    /// the snippet, exceeding which fails with [`ExecutionError::Timeout`].
+    /// Unlike a wall-clock `timeout_ns`, time spent descheduled or sleeping
+    /// does not count. Both limits apply; whichever is hit first ends the call
+    /// (under [`TimeoutKind::Cpu`], the smaller CPU budget wins). On platforms
+    /// without a per-thread CPU clock (anything but Unix) this is measured in
+    /// wall-clock time from the start of execution. Default: `None`.
+    #[serde(default)]
+    pub cpu_timeout_ns: Option<u64>,
+
+    /// Maximum number of instructions the snippet may execute, exceeding which
+    /// fails with [`ExecutionError::InstructionLimitExceeded`]. Unlike the
+    /// time limits this does not depend on host speed, so a snippet passes or
+    /// fails the same way everywhere. Counted by a `sys.settrace` hook: every
+    /// trace event the interpreter reports (a call or return, and a line where
+    /// line events are reported) is one instruction. The count starts at zero
+    /// for every call and excludes inspection queries. Default: `None`, which
+    /// installs no hook at all.
+    #[serde(default)]
+    pub max_instructions: Option<u64>,
+
     /// Maximum number of bytes that may be written to stdout + stderr combined.
     /// Default: 1,048,576 bytes (1 MiB).
     pub max_output_bytes: usize,
 
+    /// Whether an uncaught exception's traceback is also written to stderr, as
+    /// CPython does, besides being reported in [`ExecutionError::RuntimeError`].
+    /// It counts toward `max_output_bytes` but is only ever cut short, never
+    /// turned into [`ExecutionError::OutputLimitExceeded`]; nothing is written
+    /// if the limit was already hit. `SystemExit`, blocked imports and the
+    /// crate's own limits (timeouts, instruction budget) write no traceback.
+    /// Default: `true`.
+    #[serde(default = "default_true")]
+    pub mirror_traceback_to_stderr: bool,
+
+    /// What to do when the snippet wrote bytes to stdout or stderr that are
+    /// not valid UTF-8 (possible only through `sys.stdout.buffer` /
+    /// `sys.stderr.buffer`). Default: [`EncodingErrors::Replace`].
+    #[serde(default)]
+    pub output_encoding_errors: EncodingErrors,
+
     /// List of Python module names that scripts are permitted to import.
-    /// Any `import` statement for a module not in this list raises
-    /// [`ExecutionError::ModuleNotAllowed`].
+    /// Any `import` statement for a module not in this list (or in
+    /// `extra_allowed_modules`) raises [`ExecutionError::ModuleNotAllowed`].
+    /// Default: [`DEFAULT_ALLOWED_MODULES`]; setting it replaces that base set.
     pub allowed_modules: Vec<String>,
+
+    /// Modules allowed in addition to `allowed_modules`, whatever that is set
+    /// to. Use this to extend [`DEFAULT_ALLOWED_MODULES`] without copying it,
+    /// so later additions to the default set still apply. Default: empty.
+    #[serde(default)]
+    pub extra_allowed_modules: Vec<String>,
+
+    /// Modules user code may not import even though they, or the package they
+    /// belong to, are allowed: with `"xml"` allowed and `"xml.etree"` denied,
+    /// `xml.dom` imports but `xml.etree.ElementTree` does not. Denying a
+    /// package denies all its submodules. Stdlib modules' own imports are not
+    /// affected. Default: empty.
+    #[serde(default)]
+    pub denied_modules: Vec<String>,
+
+    /// Let the allowlist decide about
+    /// [`FORBIDDEN_MODULES`](crate::modules::FORBIDDEN_MODULES) (`threading`,
+    /// `subprocess`, `socket`, ...) like any other module, for trusted
+    /// embedders. When `false`, importing one from user code fails with
+    /// [`ExecutionError::ModuleForbidden`] even if it is allowed.
+    /// Default: `false`.
+    #[serde(default)]
+    pub allow_forbidden: bool,
+
+    /// Module attributes withheld from user code, as dotted paths such as
+    /// `"os.system"` or `"sys.stdin"`. While a call runs, each listed
+    /// attribute of a loaded module is replaced with a guard that raises
+    /// `PermissionError` naming it when called or used in any other way; the
+    /// originals are put back when the call ends. An `os` entry also covers
+    /// the same attribute of `posix` / `nt`, which `os` re-exports. Entries in
+    /// [`UNGUARDABLE_ATTRIBUTES`] cannot be withheld and are skipped (see
+    /// [`validate`](Self::validate)). Default: empty.
+    #[serde(default)]
+    pub denied_attributes: Vec<String>,
+
+    /// Also withhold [`STRICT_DENIED_ATTRIBUTES`], as if they were listed in
+    /// `denied_attributes`. Default: `false`.
+    #[serde(default)]
+    pub strict_attributes: bool,
+
+    /// Whether to consult and populate the global [`BytecodeCache`](crate::BytecodeCache).
+    /// Set to `false` to bypass the cache entirely, e.g. when benchmarking cold compiles.
+    /// Default: `true`.
+    #[serde(default = "default_true")]
+    pub use_cache: bool,
+
+    /// Budget in nanoseconds for compiling the source, counted within `timeout_ns`.
+    /// If compilation alone takes longer, the call fails with
+    /// [`ExecutionError::CompileTimeout`] instead of running the code.
+    /// Default: `None`, meaning a quarter of `timeout_ns`
+    /// (see [`ExecutionSettings::effective_compile_timeout_ns`]).
+    #[serde(default)]
+    pub compile_timeout_ns: Option<u64>,
+
+    /// Queries evaluated against the snippet's final scope after it runs
+    /// (whether or not it raised), reported in [`ExecutionResult::inspections`].
+    /// Queries run under the same allowlist and overall timeout as the snippet,
+    /// and a failing query does not affect the others or the primary result.
+    /// Default: `None`.
+    #[serde(default)]
+    pub inspect: Option<Vec<InspectQuery>>,
+
+    /// Python modules supplied as source, keyed by (top-level) module name.
+    /// User code can import them like any other module; their names are added
+    /// to the allowlist automatically, and imports made from inside them are
+    /// checked against the allowlist just like user code. Each is executed at
+    /// most once per call, on first import. Default: empty.
+    #[serde(default)]
+    pub virtual_modules: HashMap<String, String>,
+
+    /// Read-only in-memory files served by `open()`, keyed by path. Snippets
+    /// never see the host filesystem: `open()` looks the path up here exactly
+    /// as given (a leading `./` is ignored), raises `FileNotFoundError` for
+    /// anything else, and raises `PermissionError` for modes that would write.
+    /// Text mode decodes with the `encoding` argument, UTF-8 by default.
+    /// Default: empty.
+    #[serde(default)]
+    pub files: HashMap<String, Vec<u8>>,
+
+    /// Directories appended to `sys.path` for this call, so that pure-Python
+    /// modules in them can be imported (subject to `allowed_modules`). Useful
+    /// when the stdlib lives somewhere `PYEXEC_STDLIB_PATH` and autodetection
+    /// do not cover. Default: empty.
+    #[serde(default)]
+    pub extra_sys_paths: Vec<PathBuf>,
+
+    /// Where to find the pure-Python standard library, replacing both
+    /// `PYEXEC_STDLIB_PATH` and the built-in list of host locations;
+    /// `Some(vec![])` uses no host paths at all. Pooled interpreters are built
+    /// with the default paths, so with `Some` the call runs on a fresh
+    /// interpreter as under [`ExecutionMode::Isolated`], whatever
+    /// `execution_mode` says. With the `embedded-stdlib` feature the frozen
+    /// stdlib is found first and these paths only supply modules it lacks.
+    /// Default: `None`.
+    #[serde(default)]
+    pub stdlib_paths: Option<Vec<PathBuf>>,
+
+    /// Directory reported as the current working directory by `os.getcwd()`,
+    /// and hence the base `os.path.abspath` resolves relative paths against.
+    /// Should be absolute. The process working directory is not changed and
+    /// the directory need not exist; this only makes path computations
+    /// deterministic and grants no filesystem access. Default: `None`, leaving
+    /// the host's working directory visible.
+    #[serde(default)]
+    pub cwd: Option<PathBuf>,
+
+    /// Pin the wall clock and seed `random` so that repeated runs of a
+    /// snippet print the same thing: `time.time()`, `datetime.datetime.now()`
+    /// and `datetime.date.today()` report [`DeterministicConfig::epoch_seconds`]
+    /// (local time is UTC), and the module-level `random` functions start from
+    /// [`DeterministicConfig::random_seed`]. Monotonic clocks and unseeded
+    /// `random.Random()` instances are unaffected. Both are put back after the
+    /// call. Default: `None`.
+    #[serde(default)]
+    pub deterministic: Option<DeterministicConfig>,
+
+    /// `__name__` of the namespace the snippet runs in, so that it can run
+    /// "as a module": under any name but `"__main__"`, an
+    /// `if __name__ == "__main__":` block is skipped. The name has no effect on
+    /// the allowlist: user code is recognised by its namespace, so even the
+    /// name of an allowed module does not exempt its imports. Default: `None`,
+    /// meaning `"__main__"`.
+    #[serde(default)]
+    pub module_name: Option<String>,
+
+    /// How long to wait for a free pool slot before falling back to a fresh
+    /// interpreter, in nanoseconds. `Some(0)` uses the pool only if a slot is
+    /// idle right now. The wait never outlasts the wall-clock `timeout_ns`; a
+    /// call still waiting when that runs out fails with
+    /// [`ExecutionError::Timeout`]. Default: `None`, meaning 30 seconds.
+    #[serde(default)]
+    pub pool_checkout_timeout_ns: Option<u64>,
+
+    /// Warm-path p95 target to check this call against. When set, it becomes
+    /// the SLO of the global [`latency_histogram`](crate::latency_histogram)
+    /// (keeping its current window if the target is unchanged), and
+    /// [`ExecutionDiagnostics::slo_breached`] reports whether the last
+    /// completed window exceeded it. Default: `None`.
+    #[serde(default)]
+    pub latency_slo: Option<LatencySlo>,
+
+    /// Python recursion limit (`sys.setrecursionlimit`) applied to every call.
+    /// Exceeding it fails with [`ExecutionError::RecursionLimitExceeded`]
+    /// instead of risking a native stack overflow.
+    /// Default: `None`, meaning [`DEFAULT_RECURSION_LIMIT`].
+    #[serde(default)]
+    pub recursion_limit: Option<usize>,
+
+    /// Whether the call may, must, or must not run on a pooled interpreter.
+    /// Default: [`ExecutionMode::PoolPreferred`].
+    #[serde(default)]
+    pub execution_mode: ExecutionMode,
+
+    /// How [`ExecutionResult::return_value`] is captured.
+    /// Default: [`ResultCapture::Wrap`].
+    #[serde(default)]
+    pub result_capture: ResultCapture,
+
+    /// Whether to capture the final expression at all. With `false` the
+    /// source runs exactly as written, never rewritten and compiled as a
+    /// module whatever `result_capture` says, and
+    /// [`ExecutionResult::return_value`] is always `None`; use `print()` to
+    /// report results. Default: `true`.
+    #[serde(default = "default_true")]
+    pub wrap_last_expr: bool,
+
+    /// Whether a final call expression reports its result even when that is
+    /// `None`: with `true`, a snippet ending in `print("x")` has a
+    /// [`ExecutionResult::return_value`] of `Some("None")`. With `false`, a
+    /// `None` result is dropped like any other. Only applies when the final
+    /// expression is wrapped (see `result_capture`). Default: `false`.
+    #[serde(default)]
+    pub capture_call_results: bool,
+
+    /// Whether `import json` resolves to the frozen shim llm-pyexec ships (see
+    /// [`frozen_modules`](crate::frozen_modules)) rather than the `json`
+    /// package found on `sys.path`. The shim works around RustPython's own
+    /// json package but does not honor every keyword (e.g. `indent`); turn it
+    /// off when the stdlib json on the path is known to work. Pooled
+    /// interpreters always carry the shim, so with `false` the call runs on a
+    /// fresh interpreter as under [`ExecutionMode::Isolated`], whatever
+    /// `execution_mode` says. Default: `true`.
+    #[serde(default = "default_true")]
+    pub use_builtin_json_shim: bool,
+
+    /// Report the source that was actually compiled and its cache key in
+    /// [`ExecutionResult::wrapped_source`] and [`ExecutionResult::cache_key_hex`].
+    /// Default: `false`.
+    #[serde(default)]
+    pub debug: bool,
+}
+
+/// Fixed clock and random seed for [`ExecutionSettings::deterministic`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DeterministicConfig {
+    /// The instant the clock is fixed at, in seconds since the Unix epoch.
+    pub epoch_seconds: i64,
+    /// Seed for the module-level `random` functions, as in `random.seed()`.
+    pub random_seed: u64,
+}
+
+/// What [`ExecutionSettings::timeout_ns`] measures, and which limit a
+/// [`ExecutionError::Timeout`] hit.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TimeoutKind {
+    /// Elapsed wall-clock time, including time the interpreter thread spent
+    /// descheduled or blocked.
+    #[default]
+    Wall,
+    /// CPU time consumed by the interpreter thread (see
+    /// [`ExecutionSettings::cpu_timeout_ns`] for how it is measured).
+    Cpu,
+}
+
+/// How output that is not valid UTF-8 is reported; see
+/// [`ExecutionSettings::output_encoding_errors`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum EncodingErrors {
+    /// Replace invalid sequences with `U+FFFD` and set
+    /// [`ExecutionResult::output_lossy`]; the run is otherwise unaffected.
+    #[default]
+    Replace,
+    /// Also fail the run with [`ExecutionError::InvalidOutputEncoding`],
+    /// unless it already failed with another error. `stdout` and `stderr`
+    /// still hold the replaced text.
+    Strict,
+}
+
+/// Where [`execute`](crate::execute) runs a snippet.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ExecutionMode {
+    /// Use a pooled interpreter, falling back to a fresh one if no slot frees
+    /// up within the checkout timeout.
+    #[default]
+    PoolPreferred,
+    /// Use a pooled interpreter only; if no slot frees up within the checkout
+    /// timeout, fail with [`ExecutionError::PoolUnavailable`].
+    PoolOnly,
+    /// Always build a fresh interpreter and never touch the pool, e.g. for
+    /// debugging or for code that intentionally poisons interpreter state.
+    Isolated,
+}
+
+/// How the value of a snippet's final expression becomes
+/// [`ExecutionResult::return_value`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ResultCapture {
+    /// Rewrite the source with [`maybe_wrap_last_expr`](crate::maybe_wrap_last_expr),
+    /// assigning a bare final expression (including a call) to `__result__`.
+    /// Columns reported for errors on that statement's first line are shifted
+    /// by the inserted `__result__ = `.
+    #[default]
+    Wrap,
+    /// Compile the unmodified source in interactive (`single`) mode, like a
+    /// REPL, and take the value of the final statement if it is an expression
+    /// (including a call). Error positions match the source exactly. Only the
+    /// final statement is captured; earlier bare expressions are evaluated but
+    /// not printed, and a final value of `None` yields no `return_value`.
+    Single,
+}
+
+fn default_true() -> bool {
+    true
 }
 
 impl Default for ExecutionSettings {
     fn default() -> Self {
         Self {
             timeout_ns: 5_000_000_000,
+            timeout_kind: TimeoutKind::Wall,
+            cpu_timeout_ns: None,
+            max_instructions: None,
             max_output_bytes: 1_048_576,
+            mirror_traceback_to_stderr: true,
+            output_encoding_errors: EncodingErrors::Replace,
             allowed_modules: DEFAULT_ALLOWED_MODULES
                 .iter()
                 .map(|s| s.to_string())
                 .collect(),
+            extra_allowed_modules: Vec::new(),
+            denied_modules: Vec::new(),
+            allow_forbidden: false,
+            denied_attributes: Vec::new(),
+            strict_attributes: false,
+            use_cache: true,
+            compile_timeout_ns: None,
+            inspect: None,
+            virtual_modules: HashMap::new(),
+            files: HashMap::new(),
+            extra_sys_paths: Vec::new(),
+            stdlib_paths: None,
+            cwd: None,
+            deterministic: None,
+            module_name: None,
+            pool_checkout_timeout_ns: None,
+            latency_slo: None,
+            recursion_limit: None,
+            execution_mode: ExecutionMode::PoolPreferred,
+            result_capture: ResultCapture::Wrap,
+            wrap_last_expr: true,
+            capture_call_results: false,
+            use_builtin_json_shim: true,
+            debug: false,
+        }
+    }
+}
+
+impl ExecutionSettings {
+    /// Default settings with `extra` modules allowed on top of
+    /// [`DEFAULT_ALLOWED_MODULES`], via `extra_allowed_modules`.
+    pub fn default_plus(extra: &[&str]) -> Self {
+        Self {
+            extra_allowed_modules: extra.iter().map(|s| s.to_string()).collect(),
+            ..Self::default()
+        }
+    }
+
+    /// The compile-phase budget in nanoseconds: `compile_timeout_ns` if set,
+    /// otherwise `timeout_ns / 4`. Never exceeds `timeout_ns`.
+    pub fn effective_compile_timeout_ns(&self) -> u64 {
+        self.compile_timeout_ns
+            .unwrap_or(self.timeout_ns / 4)
+            .min(self.timeout_ns)
+    }
+
+    /// The wall-clock limit in nanoseconds: `timeout_ns` under
+    /// [`TimeoutKind::Wall`], [`CPU_MODE_WALL_TIMEOUT_MULTIPLE`] × `timeout_ns`
+    /// under [`TimeoutKind::Cpu`].
+    pub fn effective_wall_timeout_ns(&self) -> u64 {
+        match self.timeout_kind {
+            TimeoutKind::Wall => self.timeout_ns,
+            TimeoutKind::Cpu => self
+                .timeout_ns
+                .saturating_mul(CPU_MODE_WALL_TIMEOUT_MULTIPLE),
+        }
+    }
+
+    /// The CPU-time budget in nanoseconds, if any: `cpu_timeout_ns` under
+    /// [`TimeoutKind::Wall`], the smaller of `timeout_ns` and `cpu_timeout_ns`
+    /// under [`TimeoutKind::Cpu`].
+    pub fn effective_cpu_timeout_ns(&self) -> Option<u64> {
+        match self.timeout_kind {
+            TimeoutKind::Wall => self.cpu_timeout_ns,
+            TimeoutKind::Cpu => Some(
+                self.cpu_timeout_ns
+                    .map_or(self.timeout_ns, |cpu| cpu.min(self.timeout_ns)),
+            ),
         }
     }
+
+    /// The `__name__` user code runs under: `module_name` if set, otherwise
+    /// `"__main__"`.
+    pub fn effective_module_name(&self) -> &str {
+        self.module_name.as_deref().unwrap_or(MAIN_MODULE_NAME)
+    }
+
+    /// The Python recursion limit: `recursion_limit` if set, otherwise
+    /// [`DEFAULT_RECURSION_LIMIT`].
+    pub fn effective_recursion_limit(&self) -> usize {
+        self.recursion_limit.unwrap_or(DEFAULT_RECURSION_LIMIT)
+    }
+
+    /// The execution mode actually used: [`ExecutionMode::Isolated`] when
+    /// `use_builtin_json_shim` is off (pool slots always have the shim) or
+    /// `stdlib_paths` is set (pool slots use the default paths), otherwise
+    /// `execution_mode`.
+    pub fn effective_execution_mode(&self) -> ExecutionMode {
+        if self.use_builtin_json_shim && self.stdlib_paths.is_none() {
+            self.execution_mode
+        } else {
+            ExecutionMode::Isolated
+        }
+    }
+
+    /// Check that the settings do not contradict themselves, e.g. a compile
+    /// budget larger than the whole timeout, which would otherwise be
+    /// silently clamped or ignored. [`execute`](crate::execute) does not call
+    /// this; callers that build settings from user input should, before
+    /// running.
+    ///
+    /// # Errors
+    ///
+    /// Returns the first [`SettingsError`] found.
+    pub fn validate(&self) -> Result<(), SettingsError> {
+        if self.timeout_ns == 0 {
+            return Err(SettingsError::ZeroTimeout);
+        }
+        if let Some(compile_timeout_ns) = self.compile_timeout_ns {
+            if compile_timeout_ns > self.timeout_ns {
+                return Err(SettingsError::CompileTimeoutExceedsTimeout {
+                    compile_timeout_ns,
+                    timeout_ns: self.timeout_ns,
+                });
+            }
+        }
+        if let Some(cpu_timeout_ns) = self.cpu_timeout_ns {
+            if cpu_timeout_ns > self.timeout_ns {
+                return Err(SettingsError::CpuTimeoutExceedsTimeout {
+                    cpu_timeout_ns,
+                    timeout_ns: self.timeout_ns,
+                });
+            }
+        }
+        if self.execution_mode == ExecutionMode::PoolOnly {
+            if !self.use_builtin_json_shim {
+                return Err(SettingsError::PoolOnlyRequiresFresh {
+                    field: "use_builtin_json_shim",
+                });
+            }
+            if self.stdlib_paths.is_some() {
+                return Err(SettingsError::PoolOnlyRequiresFresh {
+                    field: "stdlib_paths",
+                });
+            }
+        }
+        if self.result_capture == ResultCapture::Single && !self.wrap_last_expr {
+            return Err(SettingsError::SingleCaptureWithoutWrap);
+        }
+        if self.module_name.as_deref() == Some("") {
+            return Err(SettingsError::EmptyModuleName);
+        }
+        if let Some(module) = self.denied_modules.iter().find(|denied| {
+            self.allowed_modules.contains(denied) || self.extra_allowed_modules.contains(denied)
+        }) {
+            return Err(SettingsError::ModuleAllowedAndDenied {
+                module: module.clone(),
+            });
+        }
+        if let Some(entry) = self
+            .denied_attributes
+            .iter()
+            .find(|entry| split_attribute_path(entry).is_none())
+        {
+            return Err(SettingsError::InvalidDeniedAttribute {
+                entry: entry.clone(),
+            });
+        }
+        if let Some(entry) = self
+            .denied_attributes
+            .iter()
+            .find(|entry| UNGUARDABLE_ATTRIBUTES.contains(&entry.as_str()))
+        {
+            return Err(SettingsError::UnguardableAttribute {
+                entry: entry.clone(),
+            });
+        }
+        Ok(())
+    }
 }
 
 /// The outcome of executing a Python snippet.
-#[derive(Debug, Clone, Serialize, Deserialize)]
+///
+/// The [`Default`] value is a successful run with no output and no return
+/// value, convenient for building canned results in tests.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct ExecutionResult {
-    /// Everything written to `sys.stdout` during execution (UTF-8).
+    /// Everything written to `sys.stdout` during execution (UTF-8; see
+    /// [`output_lossy`](Self::output_lossy)).
+    ///
+    /// Writes are captured unbuffered as they happen, so when `error` is set
+    /// this is exactly what was written before the failure — by the
+    /// statements that ran up to the exception, `sys.exit()` or the moment a
+    /// limit stopped the snippet — and nothing after it.
     pub stdout: String,
 
-    /// Everything written to `sys.stderr` during execution (UTF-8).
+    /// Everything written to `sys.stderr` during execution (UTF-8; see
+    /// [`output_lossy`](Self::output_lossy)).
     pub stderr: String,
 
+    /// Warnings the snippet issued through the `warnings` module (including a
+    /// `SyntaxWarning`), one `"<file>:<line>: <Category>: <message>"` entry
+    /// each, in order. They are recorded here instead of being written to
+    /// `stderr`. A warning raised as an error by a filter is not listed.
+    #[serde(default)]
+    pub warnings: Vec<String>,
+
+    /// Modules the snippet tried to import and was refused, by resolved full
+    /// name, one entry per attempt in order. Attempts the snippet caught
+    /// itself are listed too, so this can be non-empty when `error` is `None`.
+    #[serde(default)]
+    pub denied_imports: Vec<String>,
+
     /// The `repr()` of the last expression evaluated, or `None` if the snippet
     /// ended with a statement (or produced no value).
     pub return_value: Option<String>,
 
+    /// The same value as `return_value`, converted to a [`PyValue`] so callers
+    /// can branch on its type without parsing `repr()` output.
+    #[serde(default)]
+    pub typed_value: Option<PyValue>,
+
     /// `None` on success; `Some(e)` if execution was terminated by an error.
     pub error: Option<ExecutionError>,
 
-    /// Elapsed wall-clock time of the execution in nanoseconds.
+    /// Exit status if the snippet ended with an uncaught `SystemExit`, as a
+    /// Python process would report it: `0` for `sys.exit()`, `sys.exit(0)` and
+    /// `sys.exit(None)`, the integer for an integer code, and `1` for any other
+    /// code, whose `str()` is appended to `stderr`. `error` is `None` for
+    /// status 0 and [`ExecutionError::NonZeroExit`] otherwise. `None` if the
+    /// snippet did not exit.
+    #[serde(default)]
+    pub exit_code: Option<i32>,
+
+    /// Elapsed wall-clock time of the whole call in nanoseconds, including
+    /// waiting for a pool slot. Same as [`Timing::total_ns`].
     pub duration_ns: u64,
+
+    /// Nanoseconds spent waiting to check out a pool slot; `0` off the pool
+    /// path. Equal to [`ExecutionDiagnostics::pool_wait_ns`].
+    #[serde(default)]
+    pub queue_duration_ns: u64,
+
+    /// Nanoseconds from handing the snippet to an interpreter (a pool slot or
+    /// a fresh one) until its result arrived. Work accepted into the pool's
+    /// queue waits for a slot within this span. The wall-clock timeout is not
+    /// measured over this span but over `duration_ns`: one deadline counted
+    /// from the start of the call (see [`ExecutionSettings::timeout_ns`]).
+    #[serde(default)]
+    pub exec_duration_ns: u64,
+
+    /// Bytes the snippet wrote to stdout + stderr combined, counted before the
+    /// output limit is applied (see [`OutputBuffer::total_written`](crate::OutputBuffer::total_written)).
+    /// Compare against [`ExecutionSettings::max_output_bytes`] to spot near-misses.
+    #[serde(default)]
+    pub output_bytes: usize,
+
+    /// `true` if the snippet wrote bytes that are not valid UTF-8 (via
+    /// `sys.stdout.buffer` / `sys.stderr.buffer`), in which case `stdout` /
+    /// `stderr` hold a lossy conversion with `\u{FFFD}` replacement characters.
+    #[serde(default)]
+    pub output_lossy: bool,
+
+    /// `true` if output was cut off at [`ExecutionSettings::max_output_bytes`].
+    /// `error` is then [`ExecutionError::OutputLimitExceeded`], unless the
+    /// call timed out. The cut never splits a character, so text output stays
+    /// valid UTF-8 without [`output_lossy`](Self::output_lossy) being set.
+    #[serde(default)]
+    pub output_truncated: bool,
+
+    /// The error the snippet itself ended with when `error` reports
+    /// [`ExecutionError::OutputLimitExceeded`] instead, e.g. a
+    /// `ZeroDivisionError` raised after the output overflowed. `None` if the
+    /// snippet finished cleanly or only failed on the overflowing write.
+    #[serde(default)]
+    pub secondary_error: Option<ExecutionError>,
+
+    /// One entry per [`ExecutionSettings::inspect`] query, in order. Empty when
+    /// no queries were given or the snippet never ran (syntax error, timeout).
+    #[serde(default)]
+    pub inspections: Vec<InspectResult>,
+
+    /// `true` if a pool slot ran the snippet; `false` if it ran on a freshly
+    /// built interpreter (pool fallback, [`ExecutionMode::Isolated`], a
+    /// [`Session`](crate::Session)) or never ran ([`ExecutionError::PoolUnavailable`]).
+    /// Frequent `false` under [`ExecutionMode::PoolPreferred`] means the pool
+    /// is undersized.
+    #[serde(default)]
+    pub used_pool: bool,
+
+    /// How the result was produced: execution path, pool slot, checkout wait,
+    /// and cache hit. Meant for diagnosing tail latencies.
+    #[serde(default)]
+    pub diagnostics: ExecutionDiagnostics,
+
+    /// `duration_ns` split into compiling, running and everything else.
+    /// Meant for tuning the pool size.
+    #[serde(default)]
+    pub timing: Timing,
+
+    /// The source handed to the interpreter, after
+    /// [`maybe_wrap_last_expr`](crate::maybe_wrap_last_expr) (see
+    /// [`ExecutionSettings::result_capture`]). Only set when
+    /// [`ExecutionSettings::debug`] is on.
+    ///
+    /// The result variable is shown as `__result__`, the name the cache key is
+    /// computed over; the interpreter actually sees a per-call name.
+    #[serde(default)]
+    pub wrapped_source: Option<String>,
+
+    /// The [`CacheKey`](crate::CacheKey) of `wrapped_source`, as 64 lowercase
+    /// hex characters. Only set when [`ExecutionSettings::debug`] is on.
+    #[serde(default)]
+    pub cache_key_hex: Option<String>,
+}
+
+/// A snippet's return value as structured data; see
+/// [`ExecutionResult::typed_value`].
+///
+/// Containers are converted element by element. Anything else, including an
+/// `int` outside the `i64` range, a subclass instance or a container nested
+/// too deeply or referring to itself, becomes [`PyValue::Other`] holding its
+/// `repr()`.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum PyValue {
+    /// `None`.
+    None,
+    /// `True` or `False`.
+    Bool(bool),
+    /// An `int` that fits in an `i64`.
+    Int(i64),
+    /// A `float`.
+    Float(f64),
+    /// A `str`.
+    Str(String),
+    /// A `list`.
+    List(Vec<PyValue>),
+    /// A `tuple`.
+    Tuple(Vec<PyValue>),
+    /// A `dict`, as key/value pairs in iteration order.
+    Dict(Vec<(PyValue, PyValue)>),
+    /// Any other value, as its `repr()`.
+    Other(String),
+}
+
+/// Which kind of interpreter ran a snippet; see [`ExecutionDiagnostics::path`].
+///
+/// Serialized as `"pool"` or `"fallback"`.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ExecutionPath {
+    /// A warm pool slot.
+    Pool,
+    /// A freshly built interpreter: the pool fallback, [`ExecutionMode::Isolated`],
+    /// or a [`Session`](crate::Session). Also reported when nothing ran
+    /// ([`ExecutionError::PoolUnavailable`]).
+    #[default]
+    Fallback,
+}
+
+/// Per-call diagnostics attached to [`ExecutionResult::diagnostics`].
+///
+/// Every field defaults when absent from JSON, so consumers of older results
+/// keep working.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExecutionDiagnostics {
+    /// Whether a pool slot or a fresh interpreter ran the snippet. Agrees with
+    /// [`ExecutionResult::used_pool`].
+    pub path: ExecutionPath,
+    /// Id of the pool slot that ran the snippet, or `None` off the pool path.
+    /// Ids are unique for the pool's lifetime; a replaced slot gets a new one.
+    pub pool_slot_id: Option<usize>,
+    /// Nanoseconds spent checking out a pool slot, including a checkout that
+    /// timed out before falling back. Work accepted into the pool's queue
+    /// ([`InterpreterPool::with_queue`](crate::InterpreterPool::with_queue)) waits
+    /// for a slot after checkout; that wait is not counted. `0` when the pool
+    /// was not used.
+    pub pool_wait_ns: u64,
+    /// Whether the bytecode cache already held this source. Always `false`
+    /// with [`ExecutionSettings::use_cache`] off.
+    pub cache_hit: bool,
+    /// Whether the last completed window of the global latency histogram
+    /// exceeded [`ExecutionSettings::latency_slo`]. Always `false` when no
+    /// SLO is set.
+    pub slo_breached: bool,
+}
+
+/// Where the time of a call went; see [`ExecutionResult::timing`].
+///
+/// `wait_ns` is what is left of `total_ns` after compiling and running, so
+/// the parts always add up to it.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Timing {
+    /// Nanoseconds the interpreter spent compiling the snippet.
+    pub compile_ns: u64,
+    /// Nanoseconds spent outside the interpreter's compile and run: checking
+    /// out a pool slot, waiting in the pool's queue, building a fresh
+    /// interpreter, and preparing the source and the result.
+    pub wait_ns: u64,
+    /// Nanoseconds spent running the compiled snippet. For a call that timed
+    /// out, the time from handing it to an interpreter until the timeout.
+    pub execute_ns: u64,
+    /// The whole call; equal to [`ExecutionResult::duration_ns`].
+    pub total_ns: u64,
+}
+
+impl Timing {
+    /// `total_ns` split into `compile_ns`, `execute_ns` and the remaining
+    /// `wait_ns`. The measured parts are capped so that they fit in the total.
+    pub(crate) fn from_parts(total_ns: u64, compile_ns: u64, execute_ns: u64) -> Self {
+        let execute_ns = execute_ns.min(total_ns);
+        let compile_ns = compile_ns.min(total_ns - execute_ns);
+        Timing {
+            compile_ns,
+            wait_ns: total_ns - compile_ns - execute_ns,
+            execute_ns,
+            total_ns,
+        }
+    }
+}
+
+/// A post-execution check against the snippet's final scope.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum InspectQuery {
+    /// Passes if the name is bound in the snippet's global scope or is a builtin.
+    NameExists(String),
+    /// Passes if the name's value is callable; an unbound name is an error.
+    IsCallable(String),
+    /// Passes if the expression `obj` evaluates to an object with attribute `attr`.
+    HasAttr {
+        /// Expression evaluated in the final scope (usually a name).
+        obj: String,
+        /// Attribute name to look up.
+        attr: String,
+    },
+    /// Evaluates an expression in the final scope; passes if it does not raise,
+    /// with its `repr()` in [`InspectResult::value_repr`].
+    EvalExpr(String),
+}
+
+/// Outcome of a single [`InspectQuery`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "status")]
+pub enum InspectOutcome {
+    /// The check held.
+    Passed,
+    /// The check ran and did not hold (e.g. the name is unbound).
+    Failed,
+    /// The check could not be evaluated.
+    Error {
+        /// Python exception line (e.g. `"NameError: name 'x' is not defined"`)
+        /// or a description of why the query was skipped.
+        message: String,
+    },
+}
+
+/// Result of a single [`InspectQuery`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InspectResult {
+    /// The query this result answers.
+    pub query: InspectQuery,
+    /// Whether the check held.
+    pub outcome: InspectOutcome,
+    /// `repr()` of the value, for `EvalExpr` queries that evaluated successfully.
+    pub value_repr: Option<String>,
 }
 
 /// Structured error variants produced when Python execution fails.
@@ -85,10 +933,20 @@ pub struct ExecutionResult {
 /// # Examples (JSON)
 /// ```json
 /// {"type":"SyntaxError","message":"invalid syntax","line":1,"col":5}
-/// {"type":"RuntimeError","message":"division by zero","traceback":"..."}
-/// {"type":"Timeout","limit_ns":5000000000}
+/// {"type":"RuntimeError","message":"division by zero","traceback":"...","line":2,"col":5,"frames":[...]}
+/// {"type":"Timeout","limit_ns":5000000000,"kind":"Wall"}
+/// {"type":"Cancelled"}
+/// {"type":"CompileTimeout","limit_ns":1250000000}
 /// {"type":"OutputLimitExceeded","limit_bytes":1048576}
-/// {"type":"ModuleNotAllowed","module_name":"socket"}
+/// {"type":"ModuleNotAllowed","module_name":"shutil"}
+/// {"type":"ModuleForbidden","module_name":"socket","reason":"network access is not permitted in the sandbox"}
+/// {"type":"StdlibUnavailable","module_name":"collections","searched_paths":[]}
+/// {"type":"InvalidOutputEncoding","stream":"stdout"}
+/// {"type":"NonZeroExit","code":3}
+/// {"type":"RecursionLimitExceeded","limit":500}
+/// {"type":"InstructionLimitExceeded","limit":10000000}
+/// {"type":"Overloaded","max_concurrency":8}
+/// {"type":"Internal","message":"interpreter panicked: ..."}
 /// ```
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -109,11 +967,78 @@ pub enum ExecutionError {
         message: String,
         /// Python-formatted traceback string, or empty if unavailable.
         traceback: String,
+        /// 1-based line of the innermost user-code frame in the traceback, or
+        /// `None` if no frame belongs to the submitted source.
+        #[serde(default)]
+        line: Option<u32>,
+        /// 1-based column of the failing instruction on `line`, or `None`.
+        ///
+        /// Like `line`, this refers to the source as executed: on a last line
+        /// rewritten to `__result__ = <expr>`, it is offset by that prefix.
+        #[serde(default)]
+        col: Option<u32>,
+        /// The traceback as structured frames, outermost call first, in the
+        /// same order as `traceback`.
+        #[serde(default)]
+        frames: Vec<TracebackFrame>,
+    },
+
+    /// The snippet exited with a nonzero status through an uncaught
+    /// `SystemExit`, e.g. `sys.exit(3)`. Exiting with status 0 is not an
+    /// error; see [`ExecutionResult::exit_code`].
+    NonZeroExit {
+        /// The exit status, as in [`ExecutionResult::exit_code`].
+        code: i32,
+    },
+
+    /// A `RecursionError` was raised: the call stack grew past the recursion
+    /// limit ([`ExecutionSettings::effective_recursion_limit`]).
+    RecursionLimitExceeded {
+        /// The recursion limit in effect.
+        limit: usize,
     },
 
-    /// Execution exceeded the configured [`ExecutionSettings::timeout_ns`].
+    /// The snippet executed more than [`ExecutionSettings::max_instructions`]
+    /// instructions.
+    InstructionLimitExceeded {
+        /// The instruction budget that was exceeded.
+        limit: u64,
+    },
+
+    /// [`ExecutionMode::PoolOnly`] was requested but no pool slot became free
+    /// within the checkout timeout; nothing was executed.
+    PoolUnavailable {
+        /// How long the call waited for a slot, in nanoseconds.
+        checkout_timeout_ns: u64,
+    },
+
+    /// The snippet needed a fresh interpreter, but the global
+    /// [`ConcurrencyLimit`](crate::ConcurrencyLimit) was already reached;
+    /// nothing was executed.
+    Overloaded {
+        /// The ceiling in effect (see `PYEXEC_MAX_CONCURRENCY`).
+        max_concurrency: usize,
+    },
+
+    /// Execution exceeded the configured [`ExecutionSettings::timeout_ns`] or
+    /// [`ExecutionSettings::cpu_timeout_ns`].
     Timeout {
-        /// The timeout limit that was exceeded, in nanoseconds.
+        /// The limit that was exceeded, in nanoseconds.
+        limit_ns: u64,
+        /// Whether the wall-clock limit or the CPU budget was exceeded.
+        #[serde(default)]
+        kind: TimeoutKind,
+    },
+
+    /// The call was stopped through its
+    /// [`ExecutionHandle`](crate::ExecutionHandle); the snippet may have
+    /// partly run.
+    Cancelled,
+
+    /// Compiling the source exceeded the compile budget
+    /// ([`ExecutionSettings::effective_compile_timeout_ns`]); nothing was executed.
+    CompileTimeout {
+        /// The compile budget that was exceeded, in nanoseconds.
         limit_ns: u64,
     },
 
@@ -129,8 +1054,240 @@ pub enum ExecutionError {
         /// The exact module name that was denied.
         module_name: String,
     },
+
+    /// The script attempted to import one of
+    /// [`FORBIDDEN_MODULES`](crate::modules::FORBIDDEN_MODULES), which no
+    /// allowlist permits unless [`ExecutionSettings::allow_forbidden`] is set.
+    ModuleForbidden {
+        /// The exact module name that was denied.
+        module_name: String,
+        /// Why the module is never allowed, e.g. `"spawning threads/processes
+        /// is not permitted in the sandbox"`.
+        reason: String,
+    },
+
+    /// An allowed module could not be found because the interpreter has no
+    /// pure-Python standard library: none was embedded (the `embedded-stdlib`
+    /// feature) and none was found on the searched paths. Reported instead of
+    /// the `ModuleNotFoundError` [`RuntimeError`](Self::RuntimeError); see
+    /// [`environment_report`](crate::environment_report) to check a host.
+    StdlibUnavailable {
+        /// The module that could not be found.
+        module_name: String,
+        /// The stdlib directories that were searched (see
+        /// [`ExecutionSettings::stdlib_paths`] and `PYEXEC_STDLIB_PATH`).
+        searched_paths: Vec<String>,
+    },
+
+    /// The snippet wrote output that is not valid UTF-8 while
+    /// [`ExecutionSettings::output_encoding_errors`] was
+    /// [`EncodingErrors::Strict`].
+    InvalidOutputEncoding {
+        /// `"stdout"` or `"stderr"`: the first stream found invalid.
+        stream: String,
+    },
+
+    /// The engine itself failed, e.g. the interpreter panicked; the snippet
+    /// may have partly run. Not caused by the snippet's own errors.
+    Internal {
+        /// Description of the failure.
+        message: String,
+    },
+}
+
+/// A short, lowercase message naming the variant's key fields, e.g.
+/// `module not allowed: socket` or `timed out after 5s`. Serialization is
+/// unaffected; use serde for the structured form.
+impl fmt::Display for ExecutionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ExecutionError::SyntaxError { message, line, col } => {
+                write!(f, "syntax error at line {line}, column {col}: {message}")
+            }
+            ExecutionError::RuntimeError {
+                message,
+                line: Some(line),
+                ..
+            } => write!(f, "runtime error at line {line}: {message}"),
+            ExecutionError::RuntimeError { message, .. } => {
+                write!(f, "runtime error: {message}")
+            }
+            ExecutionError::NonZeroExit { code } => write!(f, "exited with status {code}"),
+            ExecutionError::RecursionLimitExceeded { limit } => {
+                write!(f, "recursion limit of {limit} exceeded")
+            }
+            ExecutionError::InstructionLimitExceeded { limit } => {
+                write!(f, "instruction limit of {limit} exceeded")
+            }
+            ExecutionError::PoolUnavailable {
+                checkout_timeout_ns,
+            } => write!(
+                f,
+                "no pool slot free within {:?}",
+                Duration::from_nanos(*checkout_timeout_ns)
+            ),
+            ExecutionError::Overloaded { max_concurrency } => write!(
+                f,
+                "overloaded: {max_concurrency} fresh interpreters already running"
+            ),
+            ExecutionError::Timeout { limit_ns, kind } => {
+                let limit = Duration::from_nanos(*limit_ns);
+                match kind {
+                    TimeoutKind::Wall => write!(f, "timed out after {limit:?}"),
+                    TimeoutKind::Cpu => write!(f, "timed out after {limit:?} of CPU time"),
+                }
+            }
+            ExecutionError::Cancelled => f.write_str("cancelled"),
+            ExecutionError::CompileTimeout { limit_ns } => write!(
+                f,
+                "compilation timed out after {:?}",
+                Duration::from_nanos(*limit_ns)
+            ),
+            ExecutionError::OutputLimitExceeded { limit_bytes } => {
+                write!(f, "output limit of {limit_bytes} bytes exceeded")
+            }
+            ExecutionError::ModuleNotAllowed { module_name } => {
+                write!(f, "module not allowed: {module_name}")
+            }
+            ExecutionError::ModuleForbidden {
+                module_name,
+                reason,
+            } => write!(f, "module forbidden: {module_name} ({reason})"),
+            ExecutionError::StdlibUnavailable {
+                module_name,
+                searched_paths,
+            } => write!(
+                f,
+                "module {module_name} not found: no Python stdlib in {searched_paths:?}"
+            ),
+            ExecutionError::InvalidOutputEncoding { stream } => {
+                write!(f, "invalid UTF-8 in {stream}")
+            }
+            ExecutionError::Internal { message } => write!(f, "internal error: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ExecutionError {}
+
+/// One entry of a [`ExecutionError::RuntimeError`] traceback.
+///
+/// Frames from the submitted source have the filename
+/// [`USER_CODE_FILENAME`](TracebackFrame::USER_CODE_FILENAME); everything else
+/// is stdlib, frozen, or virtual-module code.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TracebackFrame {
+    /// Source file of the frame's code object, e.g. `"<string>"`.
+    pub filename: String,
+    /// 1-based line being executed in this frame when the exception passed
+    /// through it.
+    pub lineno: u32,
+    /// Name of the function, or `"<module>"` for top-level code.
+    pub function: String,
+}
+
+impl TracebackFrame {
+    /// Filename the submitted source is compiled under.
+    pub const USER_CODE_FILENAME: &'static str = "<string>";
+
+    /// Whether this frame belongs to the submitted source rather than a library.
+    pub fn is_user_code(&self) -> bool {
+        self.filename == Self::USER_CODE_FILENAME
+    }
+}
+
+/// Error returned by [`InterpreterPool::init_global`](crate::InterpreterPool::init_global)
+/// and [`BytecodeCache::init_global`](crate::BytecodeCache::init_global) when the
+/// global has already been initialized, either by an earlier `init_global` or
+/// lazily by the first call to `global()`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AlreadyInitialized;
+
+impl fmt::Display for AlreadyInitialized {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("global already initialized")
+    }
+}
+
+impl std::error::Error for AlreadyInitialized {}
+
+/// A contradiction found by [`ExecutionSettings::validate`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SettingsError {
+    /// `timeout_ns` is zero, so every call would time out immediately.
+    ZeroTimeout,
+    /// `compile_timeout_ns` is larger than `timeout_ns`, which it is clamped to.
+    CompileTimeoutExceedsTimeout {
+        compile_timeout_ns: u64,
+        timeout_ns: u64,
+    },
+    /// `cpu_timeout_ns` is larger than `timeout_ns`, so it can never be the
+    /// limit that is hit: the interpreter thread cannot use more CPU time than
+    /// wall-clock time, and under [`TimeoutKind::Cpu`] the smaller budget wins.
+    CpuTimeoutExceedsTimeout {
+        cpu_timeout_ns: u64,
+        timeout_ns: u64,
+    },
+    /// `execution_mode` is [`ExecutionMode::PoolOnly`], but `field` forces a
+    /// fresh interpreter (see [`ExecutionSettings::effective_execution_mode`]).
+    PoolOnlyRequiresFresh { field: &'static str },
+    /// `result_capture` is [`ResultCapture::Single`], but `wrap_last_expr` is
+    /// `false`, so no value would ever be captured.
+    SingleCaptureWithoutWrap,
+    /// `module_name` is `Some("")`.
+    EmptyModuleName,
+    /// `module` is listed in `denied_modules` and also in `allowed_modules`
+    /// or `extra_allowed_modules`.
+    ModuleAllowedAndDenied { module: String },
+    /// An entry of `denied_attributes` is not of the form `module.attribute`.
+    InvalidDeniedAttribute { entry: String },
+    /// An entry of `denied_attributes` is in [`UNGUARDABLE_ATTRIBUTES`].
+    UnguardableAttribute { entry: String },
+}
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SettingsError::ZeroTimeout => f.write_str("timeout_ns must be greater than zero"),
+            SettingsError::CompileTimeoutExceedsTimeout {
+                compile_timeout_ns,
+                timeout_ns,
+            } => write!(
+                f,
+                "compile_timeout_ns ({compile_timeout_ns}) exceeds timeout_ns ({timeout_ns})"
+            ),
+            SettingsError::CpuTimeoutExceedsTimeout {
+                cpu_timeout_ns,
+                timeout_ns,
+            } => write!(
+                f,
+                "cpu_timeout_ns ({cpu_timeout_ns}) exceeds timeout_ns ({timeout_ns}) and can never be reached"
+            ),
+            SettingsError::PoolOnlyRequiresFresh { field } => write!(
+                f,
+                "execution_mode is PoolOnly but {field} requires a fresh interpreter"
+            ),
+            SettingsError::SingleCaptureWithoutWrap => {
+                f.write_str("result_capture is Single but wrap_last_expr is false")
+            }
+            SettingsError::EmptyModuleName => f.write_str("module_name must not be empty"),
+            SettingsError::ModuleAllowedAndDenied { module } => {
+                write!(f, "module {module:?} is both allowed and denied")
+            }
+            SettingsError::InvalidDeniedAttribute { entry } => write!(
+                f,
+                "denied_attributes entry {entry:?} is not a dotted module.attribute path"
+            ),
+            SettingsError::UnguardableAttribute { entry } => write!(
+                f,
+                "denied_attributes entry {entry:?} is used by the sandbox itself and cannot be withheld"
+            ),
+        }
+    }
 }
 
+impl std::error::Error for SettingsError {}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -152,7 +1309,7 @@ mod tests {
     #[test]
     fn test_execution_settings_default_allowed_modules_count() {
         let settings = ExecutionSettings::default();
-        assert_eq!(settings.allowed_modules.len(), 11);
+        assert_eq!(settings.allowed_modules.len(), 20);
     }
 
     #[test]
@@ -167,15 +1324,171 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_execution_settings_default_use_cache() {
+        assert!(ExecutionSettings::default().use_cache);
+    }
+
+    #[test]
+    fn test_effective_compile_timeout_defaults_to_quarter_of_total() {
+        let settings = ExecutionSettings::default();
+        assert_eq!(settings.compile_timeout_ns, None);
+        assert_eq!(settings.effective_compile_timeout_ns(), 1_250_000_000);
+
+        let capped = ExecutionSettings {
+            timeout_ns: 100,
+            compile_timeout_ns: Some(1_000),
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(capped.effective_compile_timeout_ns(), 100);
+    }
+
+    #[test]
+    fn test_effective_timeouts_by_kind() {
+        let wall = ExecutionSettings {
+            timeout_ns: 1_000,
+            cpu_timeout_ns: Some(5_000),
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(wall.effective_wall_timeout_ns(), 1_000);
+        assert_eq!(wall.effective_cpu_timeout_ns(), Some(5_000));
+
+        let cpu = ExecutionSettings {
+            timeout_kind: TimeoutKind::Cpu,
+            ..wall
+        };
+        assert_eq!(
+            cpu.effective_wall_timeout_ns(),
+            1_000 * CPU_MODE_WALL_TIMEOUT_MULTIPLE
+        );
+        assert_eq!(cpu.effective_cpu_timeout_ns(), Some(1_000));
+    }
+
+    #[test]
+    fn test_execution_settings_missing_use_cache_defaults_true() {
+        let json = r#"{"timeout_ns":1,"max_output_bytes":2,"allowed_modules":[]}"#;
+        let settings: ExecutionSettings = serde_json::from_str(json).expect("deserialize");
+        assert!(settings.use_cache);
+    }
+
     // ── DEFAULT_ALLOWED_MODULES length assertion ──────────────────────────────
 
     #[test]
-    fn test_default_allowed_modules_length_is_11() {
-        assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 11);
+    fn test_default_allowed_modules_length_is_20() {
+        assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 20);
+    }
+
+    #[test]
+    fn test_default_plus_extends_default_modules() {
+        let settings = ExecutionSettings::default_plus(&["time", "decimal"]);
+        assert_eq!(
+            settings.allowed_modules,
+            ExecutionSettings::default().allowed_modules
+        );
+        assert_eq!(settings.extra_allowed_modules, ["time", "decimal"]);
+    }
+
+    #[test]
+    fn test_effective_module_name_defaults_to_main() {
+        assert_eq!(
+            ExecutionSettings::default().effective_module_name(),
+            "__main__"
+        );
+        let settings = ExecutionSettings {
+            module_name: Some("snippet".to_string()),
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(settings.effective_module_name(), "snippet");
+    }
+
+    // ── Inspection serde shapes ───────────────────────────────────────────────
+
+    #[test]
+    fn test_inspect_query_and_result_json_shape() {
+        let json = r#"{"timeout_ns":1,"max_output_bytes":2,"allowed_modules":[],
+            "inspect":[{"NameExists":"Stack"},{"HasAttr":{"obj":"s","attr":"push"}}]}"#;
+        let settings: ExecutionSettings = serde_json::from_str(json).expect("deserialize");
+        assert_eq!(
+            settings.inspect,
+            Some(vec![
+                InspectQuery::NameExists("Stack".to_string()),
+                InspectQuery::HasAttr {
+                    obj: "s".to_string(),
+                    attr: "push".to_string(),
+                },
+            ])
+        );
+
+        let result = InspectResult {
+            query: InspectQuery::EvalExpr("s.pop()".to_string()),
+            outcome: InspectOutcome::Error {
+                message: "IndexError: pop from empty list".to_string(),
+            },
+            value_repr: None,
+        };
+        let json = serde_json::to_string(&result).expect("serialize InspectResult");
+        assert!(json.contains(
+            r#""outcome":{"status":"Error","message":"IndexError: pop from empty list"}"#
+        ));
+        let deserialized: InspectResult = serde_json::from_str(&json).expect("deserialize");
+        assert_eq!(deserialized, result);
     }
 
     // ── ExecutionError serde round-trips ──────────────────────────────────────
 
+    #[test]
+    fn test_execution_error_recursion_limit_exceeded_round_trip() {
+        let error = ExecutionError::RecursionLimitExceeded { limit: 500 };
+        let json = serde_json::to_string(&error).expect("serialize RecursionLimitExceeded");
+        assert_eq!(json, r#"{"type":"RecursionLimitExceeded","limit":500}"#);
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize RecursionLimitExceeded");
+        assert_eq!(deserialized, error);
+    }
+
+    #[test]
+    fn test_execution_error_non_zero_exit_round_trip() {
+        let error = ExecutionError::NonZeroExit { code: 3 };
+        let json = serde_json::to_string(&error).expect("serialize NonZeroExit");
+        assert_eq!(json, r#"{"type":"NonZeroExit","code":3}"#);
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize NonZeroExit");
+        assert_eq!(deserialized, error);
+    }
+
+    #[test]
+    fn test_execution_error_instruction_limit_exceeded_round_trip() {
+        let error = ExecutionError::InstructionLimitExceeded { limit: 1_000_000 };
+        let json = serde_json::to_string(&error).expect("serialize InstructionLimitExceeded");
+        assert_eq!(
+            json,
+            r#"{"type":"InstructionLimitExceeded","limit":1000000}"#
+        );
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize InstructionLimitExceeded");
+        assert_eq!(deserialized, error);
+    }
+
+    #[test]
+    fn test_execution_error_overloaded_round_trip() {
+        let error = ExecutionError::Overloaded { max_concurrency: 8 };
+        let json = serde_json::to_string(&error).expect("serialize Overloaded");
+        assert_eq!(json, r#"{"type":"Overloaded","max_concurrency":8}"#);
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize Overloaded");
+        assert_eq!(deserialized, error);
+    }
+
+    #[test]
+    fn test_execution_error_cancelled_round_trip() {
+        let error = ExecutionError::Cancelled;
+        let json = serde_json::to_string(&error).expect("serialize Cancelled");
+        assert_eq!(json, r#"{"type":"Cancelled"}"#);
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize Cancelled");
+        assert_eq!(deserialized, error);
+    }
+
     #[test]
     fn test_execution_error_syntax_error_round_trip() {
         let error = ExecutionError::SyntaxError {
@@ -200,6 +1513,13 @@ mod tests {
         let error = ExecutionError::RuntimeError {
             message: "division by zero".to_string(),
             traceback: "Traceback (most recent call last):\n  ...".to_string(),
+            line: Some(2),
+            col: Some(5),
+            frames: vec![TracebackFrame {
+                filename: "<string>".to_string(),
+                lineno: 2,
+                function: "<module>".to_string(),
+            }],
         };
         let json = serde_json::to_string(&error).expect("serialize RuntimeError");
         assert!(
@@ -207,6 +1527,8 @@ mod tests {
             "JSON should contain type discriminator: {json}"
         );
         assert!(json.contains(r#""message":"division by zero""#));
+        assert!(json.contains(r#""line":2"#));
+        assert!(json.contains(r#""function":"<module>""#));
         let deserialized: ExecutionError = serde_json::from_str(&json).expect("deserialize RuntimeError");
         assert_eq!(deserialized, error);
     }
@@ -215,6 +1537,7 @@ mod tests {
     fn test_execution_error_timeout_round_trip() {
         let error = ExecutionError::Timeout {
             limit_ns: 5_000_000_000,
+            kind: TimeoutKind::Cpu,
         };
         let json = serde_json::to_string(&error).expect("serialize Timeout");
         assert!(
@@ -222,10 +1545,36 @@ mod tests {
             "JSON should contain type discriminator: {json}"
         );
         assert!(json.contains(r#""limit_ns":5000000000"#));
+        assert!(json.contains(r#""kind":"Cpu""#));
         let deserialized: ExecutionError = serde_json::from_str(&json).expect("deserialize Timeout");
         assert_eq!(deserialized, error);
     }
 
+    #[test]
+    fn test_execution_error_timeout_without_kind_is_wall() {
+        let json = r#"{"type":"Timeout","limit_ns":100}"#;
+        let error: ExecutionError = serde_json::from_str(json).expect("deserialize Timeout");
+        assert_eq!(
+            error,
+            ExecutionError::Timeout {
+                limit_ns: 100,
+                kind: TimeoutKind::Wall,
+            }
+        );
+    }
+
+    #[test]
+    fn test_execution_error_compile_timeout_round_trip() {
+        let error = ExecutionError::CompileTimeout {
+            limit_ns: 1_250_000_000,
+        };
+        let json = serde_json::to_string(&error).expect("serialize CompileTimeout");
+        assert_eq!(json, r#"{"type":"CompileTimeout","limit_ns":1250000000}"#);
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize CompileTimeout");
+        assert_eq!(deserialized, error);
+    }
+
     #[test]
     fn test_execution_error_output_limit_exceeded_round_trip() {
         let error = ExecutionError::OutputLimitExceeded { limit_bytes: 1_048_576 };
@@ -240,6 +1589,37 @@ mod tests {
         assert_eq!(deserialized, error);
     }
 
+    #[test]
+    fn test_execution_error_stdlib_unavailable_round_trip() {
+        let error = ExecutionError::StdlibUnavailable {
+            module_name: "collections".to_string(),
+            searched_paths: vec!["/usr/lib/python3".to_string()],
+        };
+        let json = serde_json::to_string(&error).expect("serialize StdlibUnavailable");
+        assert_eq!(
+            json,
+            r#"{"type":"StdlibUnavailable","module_name":"collections","searched_paths":["/usr/lib/python3"]}"#
+        );
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize StdlibUnavailable");
+        assert_eq!(deserialized, error);
+    }
+
+    #[test]
+    fn test_execution_error_invalid_output_encoding_round_trip() {
+        let error = ExecutionError::InvalidOutputEncoding {
+            stream: "stdout".to_string(),
+        };
+        let json = serde_json::to_string(&error).expect("serialize InvalidOutputEncoding");
+        assert_eq!(
+            json,
+            r#"{"type":"InvalidOutputEncoding","stream":"stdout"}"#
+        );
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize InvalidOutputEncoding");
+        assert_eq!(deserialized, error);
+    }
+
     #[test]
     fn test_execution_error_module_not_allowed_round_trip() {
         let error = ExecutionError::ModuleNotAllowed {
@@ -255,4 +1635,358 @@ mod tests {
             serde_json::from_str(&json).expect("deserialize ModuleNotAllowed");
         assert_eq!(deserialized, error);
     }
+
+    #[test]
+    fn test_execution_error_module_forbidden_round_trip() {
+        let error = ExecutionError::ModuleForbidden {
+            module_name: "threading".to_string(),
+            reason: "spawning threads/processes is not permitted in the sandbox".to_string(),
+        };
+        let json = serde_json::to_string(&error).expect("serialize ModuleForbidden");
+        assert_eq!(
+            json,
+            r#"{"type":"ModuleForbidden","module_name":"threading","reason":"spawning threads/processes is not permitted in the sandbox"}"#
+        );
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize ModuleForbidden");
+        assert_eq!(deserialized, error);
+        assert_eq!(
+            error.to_string(),
+            "module forbidden: threading (spawning threads/processes is not permitted in the sandbox)"
+        );
+    }
+
+    #[test]
+    fn test_execution_error_internal_round_trip() {
+        let error = ExecutionError::Internal {
+            message: "interpreter panicked: boom".to_string(),
+        };
+        let json = serde_json::to_string(&error).expect("serialize Internal");
+        assert!(
+            json.contains(r#""type":"Internal""#),
+            "JSON should contain type discriminator: {json}"
+        );
+        let deserialized: ExecutionError =
+            serde_json::from_str(&json).expect("deserialize Internal");
+        assert_eq!(deserialized, error);
+    }
+
+    // ── ExecutionError Display / Error ────────────────────────────────────────
+
+    #[test]
+    fn test_execution_error_display_names_key_fields() {
+        let cases = [
+            (
+                ExecutionError::SyntaxError {
+                    message: "invalid syntax".to_string(),
+                    line: 3,
+                    col: 7,
+                },
+                "syntax error at line 3, column 7: invalid syntax",
+            ),
+            (
+                ExecutionError::RuntimeError {
+                    message: "division by zero".to_string(),
+                    traceback: String::new(),
+                    line: Some(2),
+                    col: None,
+                    frames: Vec::new(),
+                },
+                "runtime error at line 2: division by zero",
+            ),
+            (
+                ExecutionError::RuntimeError {
+                    message: "boom".to_string(),
+                    traceback: String::new(),
+                    line: None,
+                    col: None,
+                    frames: Vec::new(),
+                },
+                "runtime error: boom",
+            ),
+            (
+                ExecutionError::NonZeroExit { code: 3 },
+                "exited with status 3",
+            ),
+            (
+                ExecutionError::RecursionLimitExceeded { limit: 500 },
+                "recursion limit of 500 exceeded",
+            ),
+            (
+                ExecutionError::InstructionLimitExceeded { limit: 1000 },
+                "instruction limit of 1000 exceeded",
+            ),
+            (
+                ExecutionError::PoolUnavailable {
+                    checkout_timeout_ns: 250_000_000,
+                },
+                "no pool slot free within 250ms",
+            ),
+            (
+                ExecutionError::Overloaded { max_concurrency: 8 },
+                "overloaded: 8 fresh interpreters already running",
+            ),
+            (
+                ExecutionError::Timeout {
+                    limit_ns: 5_000_000_000,
+                    kind: TimeoutKind::Wall,
+                },
+                "timed out after 5s",
+            ),
+            (
+                ExecutionError::Timeout {
+                    limit_ns: 1_500_000_000,
+                    kind: TimeoutKind::Cpu,
+                },
+                "timed out after 1.5s of CPU time",
+            ),
+            (ExecutionError::Cancelled, "cancelled"),
+            (
+                ExecutionError::CompileTimeout {
+                    limit_ns: 1_250_000_000,
+                },
+                "compilation timed out after 1.25s",
+            ),
+            (
+                ExecutionError::OutputLimitExceeded { limit_bytes: 1024 },
+                "output limit of 1024 bytes exceeded",
+            ),
+            (
+                ExecutionError::ModuleNotAllowed {
+                    module_name: "socket".to_string(),
+                },
+                "module not allowed: socket",
+            ),
+            (
+                ExecutionError::StdlibUnavailable {
+                    module_name: "datetime".to_string(),
+                    searched_paths: Vec::new(),
+                },
+                "module datetime not found: no Python stdlib in []",
+            ),
+            (
+                ExecutionError::InvalidOutputEncoding {
+                    stream: "stderr".to_string(),
+                },
+                "invalid UTF-8 in stderr",
+            ),
+            (
+                ExecutionError::Internal {
+                    message: "interpreter panicked: boom".to_string(),
+                },
+                "internal error: interpreter panicked: boom",
+            ),
+        ];
+        for (error, expected) in cases {
+            assert_eq!(error.to_string(), expected, "{error:?}");
+        }
+    }
+
+    #[test]
+    fn test_execution_error_boxes_as_std_error() {
+        fn run() -> Result<(), Box<dyn std::error::Error + Send + Sync>> {
+            Err(ExecutionError::ModuleNotAllowed {
+                module_name: "socket".to_string(),
+            })?;
+            Ok(())
+        }
+        let error = run().expect_err("should fail");
+        assert_eq!(error.to_string(), "module not allowed: socket");
+        assert!(error.source().is_none());
+        assert_eq!(
+            error.downcast_ref::<ExecutionError>(),
+            Some(&ExecutionError::ModuleNotAllowed {
+                module_name: "socket".to_string(),
+            })
+        );
+    }
+
+    // ── ExecutionSettings::validate ───────────────────────────────────────────
+
+    #[test]
+    fn test_validate_accepts_defaults() {
+        assert_eq!(ExecutionSettings::default().validate(), Ok(()));
+        let settings = ExecutionSettings {
+            compile_timeout_ns: Some(1_000),
+            cpu_timeout_ns: Some(5_000_000_000),
+            execution_mode: ExecutionMode::PoolOnly,
+            result_capture: ResultCapture::Single,
+            module_name: Some("snippet".to_string()),
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(settings.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_zero_timeout() {
+        let settings = ExecutionSettings {
+            timeout_ns: 0,
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(settings.validate(), Err(SettingsError::ZeroTimeout));
+    }
+
+    #[test]
+    fn test_validate_rejects_compile_timeout_over_timeout() {
+        let settings = ExecutionSettings {
+            timeout_ns: 1_000,
+            compile_timeout_ns: Some(2_000),
+            ..ExecutionSettings::default()
+        };
+        let error = settings.validate().unwrap_err();
+        assert_eq!(
+            error,
+            SettingsError::CompileTimeoutExceedsTimeout {
+                compile_timeout_ns: 2_000,
+                timeout_ns: 1_000,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "compile_timeout_ns (2000) exceeds timeout_ns (1000)"
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_cpu_timeout_over_timeout() {
+        for timeout_kind in [TimeoutKind::Wall, TimeoutKind::Cpu] {
+            let settings = ExecutionSettings {
+                timeout_ns: 1_000,
+                timeout_kind,
+                cpu_timeout_ns: Some(1_001),
+                ..ExecutionSettings::default()
+            };
+            assert_eq!(
+                settings.validate(),
+                Err(SettingsError::CpuTimeoutExceedsTimeout {
+                    cpu_timeout_ns: 1_001,
+                    timeout_ns: 1_000,
+                }),
+                "{timeout_kind:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn test_validate_rejects_pool_only_with_fresh_only_options() {
+        let without_shim = ExecutionSettings {
+            execution_mode: ExecutionMode::PoolOnly,
+            use_builtin_json_shim: false,
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(
+            without_shim.validate(),
+            Err(SettingsError::PoolOnlyRequiresFresh {
+                field: "use_builtin_json_shim"
+            })
+        );
+
+        let with_stdlib_paths = ExecutionSettings {
+            execution_mode: ExecutionMode::PoolOnly,
+            stdlib_paths: Some(Vec::new()),
+            ..ExecutionSettings::default()
+        };
+        let error = with_stdlib_paths.validate().unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "execution_mode is PoolOnly but stdlib_paths requires a fresh interpreter"
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_single_capture_without_wrap() {
+        let settings = ExecutionSettings {
+            result_capture: ResultCapture::Single,
+            wrap_last_expr: false,
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err(SettingsError::SingleCaptureWithoutWrap)
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_empty_module_name() {
+        let settings = ExecutionSettings {
+            module_name: Some(String::new()),
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(settings.validate(), Err(SettingsError::EmptyModuleName));
+    }
+
+    #[test]
+    fn test_validate_rejects_module_both_allowed_and_denied() {
+        let settings = ExecutionSettings {
+            denied_modules: vec!["xml.etree".to_string(), "json".to_string()],
+            ..ExecutionSettings::default()
+        };
+        let error = settings.validate().unwrap_err();
+        assert_eq!(
+            error,
+            SettingsError::ModuleAllowedAndDenied {
+                module: "json".to_string()
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "module \"json\" is both allowed and denied"
+        );
+
+        let extra = ExecutionSettings {
+            extra_allowed_modules: vec!["shutil".to_string()],
+            denied_modules: vec!["shutil".to_string()],
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(
+            extra.validate(),
+            Err(SettingsError::ModuleAllowedAndDenied {
+                module: "shutil".to_string()
+            })
+        );
+
+        // Denying a submodule of an allowed package is not a contradiction.
+        let submodule = ExecutionSettings {
+            allowed_modules: vec!["xml".to_string()],
+            denied_modules: vec!["xml.etree".to_string()],
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(submodule.validate(), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rejects_undotted_denied_attribute() {
+        let settings = ExecutionSettings {
+            denied_attributes: vec!["os.system".to_string(), "system".to_string()],
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(
+            settings.validate(),
+            Err(SettingsError::InvalidDeniedAttribute {
+                entry: "system".to_string()
+            })
+        );
+    }
+
+    #[test]
+    fn test_validate_rejects_unguardable_attribute() {
+        let settings = ExecutionSettings {
+            denied_attributes: vec!["sys.stdin".to_string(), "sys.modules".to_string()],
+            ..ExecutionSettings::default()
+        };
+        let error = settings.validate().unwrap_err();
+        assert_eq!(
+            error,
+            SettingsError::UnguardableAttribute {
+                entry: "sys.modules".to_string()
+            }
+        );
+        assert!(error.to_string().contains("cannot be withheld"), "{error}");
+
+        let strict = ExecutionSettings {
+            strict_attributes: true,
+            ..ExecutionSettings::default()
+        };
+        assert_eq!(strict.validate(), Ok(()));
+    }
 }
@@ -4,7 +4,9 @@
 //! - Creates a fresh interpreter per execution with stdlib, import hook, and output capture.
 //! - Compiles and executes Python source, returning a [`VmRunResult`].
 //! - Extracts structured errors (SyntaxError, RuntimeError, ModuleNotAllowed).
-//! - Extracts the `__result__` return value from the scope after execution.
+//! - Extracts the wrapped last expression's value (`__result__`, or the
+//!   per-call name in [`RunOptions::result_name`]) from the scope after
+//!   execution.
 //!
 //! ## Import Hook (Option C: `builtins.__import__` override)
 //!
@@ -19,32 +21,54 @@
 //! therefore install the import hook at the beginning of `run_code` (inside
 //! `interp.enter()`), which runs after full initialization. This is Option C.
 //!
+//! `importlib.import_module` and `importlib.__import__` reach the import
+//! machinery without calling `builtins.__import__`, so a `sys.meta_path`
+//! finder (Option B) applies the same check to every module not yet loaded.
+//! Both decide whether an import comes from user code by the namespace of
+//! the frame that started it, not by names user code can assign.
+//!
 //! ## Output Capture
 //!
-//! We replace `sys.stdout` and `sys.stderr` with minimal Python-level objects
+//! We replace `sys.stdout` and `sys.stderr` with file-like Python objects
 //! whose `write(s)` method delegates to [`OutputBuffer::write_stdout`] /
-//! [`OutputBuffer::write_stderr`]. The replacement also happens at the start of
-//! each `run_code` call (inside `enter()`).
+//! [`OutputBuffer::write_stderr`], plus a `buffer` attribute taking raw bytes.
+//! The replacement also happens at the start of each `run_code` call (inside
+//! `enter()`).
 //!
 //! ## Zero unsafe blocks (AC-23)
 //!
 //! This file contains no `unsafe` code. All RustPython integration uses the safe
 //! public Rust API.
 
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, AtomicU8, Ordering};
 use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use rustpython_vm::{
-    builtins::PyBaseExceptionRef,
+    builtins::{PyBaseExceptionRef, PyCode, PyDict, PyFloat, PyList, PyStr, PyTuple},
     compiler::Mode,
-    function::FuncArgs,
+    frame::FrameRef,
+    function::{ArgBytesLike, FuncArgs},
     scope::Scope,
-    AsObject, Interpreter, PyObjectRef, PyResult, VirtualMachine,
+    signal::{user_signal_channel, UserSignalSender},
+    AsObject, Interpreter, PyObjectRef, PyRef, PyResult, TryFromObject, VirtualMachine,
 };
 
-use crate::modules::check_module_allowed;
+use crate::cancel::{CancelGuard, ExecutionHandle};
+use crate::cpu_time::CpuWatchdog;
+use crate::frozen::{embedder_modules, BUILTIN_FROZEN};
+use crate::modules::{
+    build_denied_attributes, build_denied_set, check_module_allowed, check_module_allowed_verbose,
+    check_module_forbidden, DenialReason,
+};
 use crate::output::OutputBuffer;
-use crate::types::ExecutionError;
+use crate::types::{
+    DeterministicConfig, ExecutionError, ExecutionSettings, InspectOutcome, InspectQuery,
+    InspectResult, PyValue, ResultCapture, TimeoutKind, TracebackFrame, DEFAULT_RECURSION_LIMIT,
+    MAIN_MODULE_NAME,
+};
 
 // ── Public (crate-visible) types ─────────────────────────────────────────────
 
@@ -53,8 +77,162 @@ use crate::types::ExecutionError;
 pub(crate) struct VmRunResult {
     pub stdout: String,
     pub stderr: String,
+    /// Warnings recorded during the call (see [`install_warning_capture`]).
+    pub warnings: Vec<String>,
+    /// Imports refused to user code during the call, including caught ones.
+    pub denied_imports: Vec<String>,
+    /// Nanoseconds spent compiling the source.
+    pub compile_ns: u64,
+    /// Nanoseconds spent running the compiled code, not counting inspections.
+    pub execute_ns: u64,
     pub return_value: Option<String>,
+    pub typed_value: Option<PyValue>,
     pub error: Option<ExecutionError>,
+    pub exit_code: Option<i32>,
+    pub inspections: Vec<InspectResult>,
+    /// Id of the pool slot that ran the code; set by the slot, `None` elsewhere.
+    pub slot_id: Option<usize>,
+}
+
+/// Test hook: source containing this marker makes [`run_code`] panic, standing
+/// in for an internal RustPython panic.
+#[cfg(test)]
+pub(crate) const PANIC_HOOK: &str = "__pyexec_test_panic__";
+
+#[cfg(test)]
+thread_local! {
+    /// Test hook: how many calls [`run_code_in_scope`] has made on this thread.
+    pub(crate) static RUNS_ON_THIS_THREAD: std::cell::Cell<usize> =
+        const { std::cell::Cell::new(0) };
+}
+
+/// Native stack size for every thread that runs an interpreter.
+///
+/// RustPython frames are large; the default 2 MiB thread stack can overflow
+/// (aborting the whole process) below the Python recursion limit.
+pub(crate) const VM_THREAD_STACK_SIZE: usize = 32 * 1024 * 1024;
+
+/// Per-call options for [`run_code`] beyond the source and output buffer.
+#[derive(Debug, Clone)]
+pub(crate) struct RunOptions {
+    /// Budget for the compile phase alone; if compiling takes longer, the code
+    /// is not executed and [`ExecutionError::CompileTimeout`] is returned.
+    pub compile_timeout_ns: u64,
+    /// Post-execution queries against the final scope.
+    pub inspect: Vec<InspectQuery>,
+    /// When the caller stops waiting; `EvalExpr` queries are skipped after it.
+    pub deadline: Option<Instant>,
+    /// Source of modules importable for this call only, keyed by module name.
+    pub virtual_modules: Arc<HashMap<String, String>>,
+    /// Modules user code may not import for this call only, even if allowed.
+    pub denied_modules: Arc<HashSet<String>>,
+    /// Leave [`FORBIDDEN_MODULES`](crate::modules::FORBIDDEN_MODULES) to the
+    /// allowlist for this call only.
+    pub allow_forbidden: bool,
+    /// `(module, attribute)` pairs withheld from user code for this call only
+    /// (see [`AttributeGuards`]).
+    pub denied_attributes: Arc<Vec<(String, String)>>,
+    /// Contents served by `open()` for this call only, keyed by path.
+    pub files: Arc<HashMap<String, Vec<u8>>>,
+    /// Directories appended to `sys.path` for this call only.
+    pub extra_sys_paths: Vec<PathBuf>,
+    /// Directory `os.getcwd()` reports for this call only.
+    pub cwd: Option<PathBuf>,
+    /// Fixed clock and `random` seed for this call only.
+    pub deterministic: Option<DeterministicConfig>,
+    /// `__name__` of the scope [`run_code`] creates.
+    pub module_name: String,
+    /// Python recursion limit for this call.
+    pub recursion_limit: usize,
+    /// Whether the source was wrapped or is compiled in `Mode::Single`.
+    pub result_capture: ResultCapture,
+    /// CPU-time budget for executing the code (see [`crate::cpu_time`]).
+    pub cpu_timeout_ns: Option<u64>,
+    /// Instruction budget for executing the code (see [`InstructionBudget`]).
+    pub max_instructions: Option<u64>,
+    /// Stop the snippet itself once `deadline` passes and report a wall-clock
+    /// timeout of this many nanoseconds. Checked by the same trace hook as
+    /// `max_instructions`, for callers that cannot abandon the VM's thread.
+    pub cooperative_timeout_ns: Option<u64>,
+    /// Variable the wrapped last expression is assigned to. Removed from the
+    /// scope once its value has been read unless it is [`RESULT_NAME`].
+    pub result_name: String,
+    /// Report a `None` result as `"None"` instead of no return value.
+    pub report_none: bool,
+    /// Write the traceback of an uncaught exception to the captured stderr.
+    pub mirror_traceback_to_stderr: bool,
+    /// Lets another thread stop the call (see [`crate::cancel`]).
+    pub cancel: Option<ExecutionHandle>,
+}
+
+/// Name [`maybe_wrap_last_expr`](crate::executor::maybe_wrap_last_expr)
+/// assigns the last expression to. The executor replaces it with a per-call
+/// name before running, so user code cannot collide with it.
+pub(crate) const RESULT_NAME: &str = "__result__";
+
+impl Default for RunOptions {
+    /// No compile budget, no queries, no deadline, CPU or instruction budget,
+    /// default recursion limit, result read from [`RESULT_NAME`], tracebacks
+    /// mirrored to stderr, not cancellable.
+    fn default() -> Self {
+        Self {
+            compile_timeout_ns: u64::MAX,
+            inspect: Vec::new(),
+            deadline: None,
+            virtual_modules: Arc::default(),
+            denied_modules: Arc::default(),
+            allow_forbidden: false,
+            denied_attributes: Arc::default(),
+            files: Arc::default(),
+            extra_sys_paths: Vec::new(),
+            cwd: None,
+            deterministic: None,
+            module_name: MAIN_MODULE_NAME.to_string(),
+            recursion_limit: DEFAULT_RECURSION_LIMIT,
+            result_capture: ResultCapture::Wrap,
+            cpu_timeout_ns: None,
+            max_instructions: None,
+            cooperative_timeout_ns: None,
+            result_name: RESULT_NAME.to_string(),
+            report_none: false,
+            mirror_traceback_to_stderr: true,
+            cancel: None,
+        }
+    }
+}
+
+impl RunOptions {
+    /// Options for a call governed by `settings` that started at `start`.
+    pub(crate) fn from_settings(settings: &ExecutionSettings, start: Instant) -> Self {
+        Self {
+            compile_timeout_ns: settings.effective_compile_timeout_ns(),
+            inspect: settings.inspect.clone().unwrap_or_default(),
+            deadline: start.checked_add(Duration::from_nanos(settings.effective_wall_timeout_ns())),
+            virtual_modules: Arc::new(settings.virtual_modules.clone()),
+            denied_modules: Arc::new(build_denied_set(settings)),
+            allow_forbidden: settings.allow_forbidden,
+            denied_attributes: Arc::new(build_denied_attributes(settings)),
+            files: Arc::new(settings.files.clone()),
+            extra_sys_paths: settings.extra_sys_paths.clone(),
+            cwd: settings.cwd.clone(),
+            deterministic: settings.deterministic,
+            module_name: settings.effective_module_name().to_string(),
+            recursion_limit: settings.effective_recursion_limit(),
+            // Unwrapped source compiled as a module leaves no result to read.
+            result_capture: if settings.wrap_last_expr {
+                settings.result_capture
+            } else {
+                ResultCapture::Wrap
+            },
+            cpu_timeout_ns: settings.effective_cpu_timeout_ns(),
+            max_instructions: settings.max_instructions,
+            cooperative_timeout_ns: None,
+            result_name: RESULT_NAME.to_string(),
+            report_none: false,
+            mirror_traceback_to_stderr: settings.mirror_traceback_to_stderr,
+            cancel: None,
+        }
+    }
 }
 
 /// A configured interpreter bundled with its module allowlist.
@@ -65,6 +243,11 @@ pub(crate) struct VmRunResult {
 pub(crate) struct PyInterp {
     inner: Interpreter,
     allowed_set: Arc<HashSet<String>>,
+    /// Sends closures that the VM runs between instructions (CPU budget checks).
+    signals: UserSignalSender,
+    /// The stdlib paths that were searched, if the stdlib was not found in
+    /// any of them (see [`stdlib_found`]).
+    missing_stdlib: Option<Vec<String>>,
 }
 
 impl PyInterp {
@@ -72,11 +255,12 @@ impl PyInterp {
     ///
     /// Called by the pool slot thread before each `run_code()` call when the
     /// caller provides a custom allowlist that differs from the pool default.
+    /// The set is shared with the work item rather than copied.
     /// The new allowlist is reflected in the next `run_code()` call's import hook,
     /// because `install_import_hook()` re-reads `interp.allowed_set` each time.
     #[allow(dead_code)]
-    pub(crate) fn set_allowed_set(&mut self, allowed_set: HashSet<String>) {
-        self.allowed_set = Arc::new(allowed_set);
+    pub(crate) fn set_allowed_set(&mut self, allowed_set: Arc<HashSet<String>>) {
+        self.allowed_set = allowed_set;
     }
 
     /// Execute a closure with access to the VirtualMachine.
@@ -100,7 +284,30 @@ impl PyInterp {
 /// from a host Python installation when they are added to `Settings::path_list`.
 /// Native extension modules provided by `rustpython_stdlib::get_module_inits()`
 /// take precedence over any .so files on the same path.
-fn python_stdlib_paths() -> Vec<String> {
+///
+/// `overridden` ([`ExecutionSettings::stdlib_paths`]) is used as-is when given.
+/// Otherwise, if `PYEXEC_STDLIB_PATH` is set to a non-empty, `PATH`-style list
+/// (colon-separated on Unix, semicolon-separated on Windows), exactly those
+/// entries are used and autodetection is skipped. With the `embedded-stdlib`
+/// feature the stdlib is frozen into the binary and there is no autodetection.
+///
+/// [`ExecutionSettings::stdlib_paths`]: crate::ExecutionSettings::stdlib_paths
+pub(crate) fn python_stdlib_paths(overridden: Option<&[PathBuf]>) -> Vec<String> {
+    if let Some(paths) = overridden {
+        return paths
+            .iter()
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+    }
+    if let Some(paths) = std::env::var_os("PYEXEC_STDLIB_PATH").filter(|p| !p.is_empty()) {
+        return std::env::split_paths(&paths)
+            .map(|p| p.to_string_lossy().into_owned())
+            .collect();
+    }
+    if cfg!(feature = "embedded-stdlib") {
+        return Vec::new();
+    }
+
     // Common locations for Python 3.x stdlib on Linux.
     // We check multiple versions to be resilient across environments.
     let candidates = [
@@ -121,6 +328,25 @@ fn python_stdlib_paths() -> Vec<String> {
         .collect()
 }
 
+/// Stdlib package whose presence shows the pure-Python stdlib is available.
+const STDLIB_CANARY: &str = "collections";
+
+/// Whether the pure-Python stdlib is embedded in the binary or present under
+/// one of `paths`, judged by [`STDLIB_CANARY`].
+///
+/// Without it, pure-Python modules such as `collections`, `datetime` and `re`
+/// cannot be imported even when allowed; such failures are reported as
+/// [`ExecutionError::StdlibUnavailable`].
+pub(crate) fn stdlib_found(paths: &[String]) -> bool {
+    cfg!(feature = "embedded-stdlib")
+        || paths.iter().any(|p| {
+            Path::new(p)
+                .join(STDLIB_CANARY)
+                .join("__init__.py")
+                .is_file()
+        })
+}
+
 /// Create a new RustPython interpreter with stdlib configured.
 ///
 /// The import hook and output capture are installed at the beginning of each
@@ -130,12 +356,18 @@ fn python_stdlib_paths() -> Vec<String> {
 /// # Parameters
 /// - `allowed_set`: allowlisted module names, used to gate import calls
 /// - `output`: shared buffer (not used here; passed through to run_code)
+/// - `json_shim`: install the frozen `json` shim; when `false`, `import json`
+///   finds the stdlib package on `sys.path` instead
+/// - `stdlib_paths`: stdlib search paths replacing the defaults (see
+///   [`python_stdlib_paths`])
 ///
 /// # Returns
 /// A configured [`PyInterp`] ready for [`run_code`].
 pub(crate) fn build_interpreter(
     allowed_set: HashSet<String>,
     _output: OutputBuffer,
+    json_shim: bool,
+    stdlib_paths: Option<&[PathBuf]>,
 ) -> PyInterp {
     let mut settings = rustpython_vm::Settings::default();
 
@@ -144,182 +376,84 @@ pub(crate) fn build_interpreter(
     //
     // The `freeze-stdlib` feature in rustpython-vm 0.3 only freezes the VM's
     // own core modules (python_builtins, core_modules), not the full Python
-    // standard library. The full stdlib is available via the host Python
-    // installation at /usr/local/lib/python3.12 (fallback: /usr/lib/python3).
+    // standard library. Without the `embedded-stdlib` feature, the full stdlib
+    // comes from a host Python installation at /usr/local/lib/python3.12
+    // (fallback: /usr/lib/python3).
     //
     // We add these paths so RustPython can find and run pure-Python modules.
     // The native C-extension modules (e.g. _json, math, re) are provided by
     // rustpython_stdlib::get_module_inits() and shadow any CPython .so files.
-    for path in python_stdlib_paths() {
-        settings.path_list.push(path);
-    }
+    let stdlib_paths = python_stdlib_paths(stdlib_paths);
+    let missing_stdlib = (!stdlib_found(&stdlib_paths)).then(|| stdlib_paths.clone());
+    settings.path_list.extend(stdlib_paths);
 
+    let (signals, signal_rx) = user_signal_channel();
     let inner = Interpreter::with_init(settings, move |vm| {
+        // ── CPU budget checks ──────────────────────────────────────────────
+        // Closures sent on `signals` run on the VM thread between bytecode
+        // instructions (see `crate::cpu_time`).
+        vm.set_user_signal_channel(signal_rx);
+
         // ── Register stdlib modules ────────────────────────────────────────
         // This registers native (Rust-implemented) stdlib modules:
         // _json, math, _csv, unicodedata, zlib, etc.
         // These shadow any C extension .so files that might be on sys.path.
         vm.add_native_modules(rustpython_stdlib::get_module_inits());
 
-        // ── Register minimal frozen stdlib wrappers ────────────────────────
-        // Freeze a minimal Python-level json module compatible with RustPython.
-        // The native _json module is registered above; this Python wrapper
-        // provides the user-facing json.dumps/json.loads/etc. interface.
-        vm.add_frozen(rustpython_vm::py_freeze!(
-            source = r#"
-import _json
-
-class JSONDecodeError(ValueError):
-    def __init__(self, msg, doc, pos):
-        errmsg = '%s: line %d column %d (char %d)' % (
-            msg,
-            doc.count('\n', 0, pos) + 1,
-            pos - doc.rfind('\n', 0, pos),
-            pos,
-        )
-        ValueError.__init__(self, errmsg)
-        self.msg = msg
-        self.doc = doc
-        self.pos = pos
-        self.lineno = doc.count('\n', 0, pos) + 1
-        self.colno = pos - doc.rfind('\n', 0, pos)
-
-class JSONEncoder:
-    def __init__(self, skipkeys=False, ensure_ascii=True,
-                 check_circular=True, allow_nan=True, sort_keys=False,
-                 indent=None, separators=None, default=None):
-        self.skipkeys = skipkeys
-        self.ensure_ascii = ensure_ascii
-        self.check_circular = check_circular
-        self.allow_nan = allow_nan
-        self.sort_keys = sort_keys
-        self.indent = indent
-        if separators is not None:
-            self.item_separator, self.key_separator = separators
-        elif indent is not None:
-            self.item_separator = ','
-            self.key_separator = ': '
-        else:
-            self.item_separator = ', '
-            self.key_separator = ': '
-        self.default = default if default is not None else self._default
-
-    def _default(self, obj):
-        raise TypeError(f'Object of type {type(obj).__name__} is not JSON serializable')
-
-    def encode(self, o):
-        return _json.encode_basestring_ascii(str(o)) if False else _simple_encode(o, self)
-
-    def iterencode(self, o, _one_shot=False):
-        return iter([self.encode(o)])
-
-def _simple_encode(obj, encoder):
-    if obj is None:
-        return 'null'
-    elif obj is True:
-        return 'true'
-    elif obj is False:
-        return 'false'
-    elif isinstance(obj, int):
-        return str(obj)
-    elif isinstance(obj, float):
-        if obj != obj:
-            return 'NaN'
-        elif obj == float('inf'):
-            return 'Infinity'
-        elif obj == float('-inf'):
-            return '-Infinity'
-        return repr(obj)
-    elif isinstance(obj, str):
-        return _encode_str(obj)
-    elif isinstance(obj, (list, tuple)):
-        if not obj:
-            return '[]'
-        items = [_simple_encode(v, encoder) for v in obj]
-        return '[' + ', '.join(items) + ']'
-    elif isinstance(obj, dict):
-        if not obj:
-            return '{}'
-        keys = sorted(obj.keys()) if encoder.sort_keys else obj.keys()
-        items = [_encode_str(str(k)) + ': ' + _simple_encode(v, encoder) for k, v in ((k, obj[k]) for k in keys)]
-        return '{' + ', '.join(items) + '}'
-    else:
-        return encoder.default(obj)
-
-def _encode_str(s):
-    result = ['"']
-    for c in s:
-        if c == '"':
-            result.append('\\"')
-        elif c == '\\':
-            result.append('\\\\')
-        elif c == '\n':
-            result.append('\\n')
-        elif c == '\r':
-            result.append('\\r')
-        elif c == '\t':
-            result.append('\\t')
-        elif ord(c) < 0x20:
-            result.append('\\u{:04x}'.format(ord(c)))
-        else:
-            result.append(c)
-    result.append('"')
-    return ''.join(result)
-
-def dumps(obj, *, skipkeys=False, ensure_ascii=True, check_circular=True,
-          allow_nan=True, cls=None, indent=None, separators=None, default=None,
-          sort_keys=False, **kw):
-    encoder = (cls or JSONEncoder)(
-        skipkeys=skipkeys, ensure_ascii=ensure_ascii,
-        check_circular=check_circular, allow_nan=allow_nan,
-        indent=indent, separators=separators, default=default,
-        sort_keys=sort_keys, **kw
-    )
-    return encoder.encode(obj)
-
-def dump(obj, fp, **kwargs):
-    fp.write(dumps(obj, **kwargs))
-
-def loads(s, *, cls=None, object_hook=None, parse_float=None,
-          parse_int=None, parse_constant=None, object_pairs_hook=None, **kw):
-    if isinstance(s, (bytes, bytearray)):
-        s = s.decode('utf-8')
-    decoder = JSONDecoder(object_hook=object_hook, object_pairs_hook=object_pairs_hook,
-                          parse_float=parse_float, parse_int=parse_int, strict=True)
-    return decoder.decode(s)
-
-def load(fp, **kwargs):
-    return loads(fp.read(), **kwargs)
-
-class JSONDecoder:
-    def __init__(self, *, object_hook=None, parse_float=None, parse_int=None,
-                 parse_constant=None, strict=True, object_pairs_hook=None):
-        self.object_hook = object_hook
-        self.object_pairs_hook = object_pairs_hook
-        self.parse_float = parse_float or float
-        self.parse_int = parse_int or int
-        self.strict = strict
-        self.scan_once = _json.make_scanner(self)
-
-    def decode(self, s, _w=None):
-        obj, end = self.raw_decode(s, 0)
-        end = len(s.lstrip()) if not s else end
-        return obj
-
-    def raw_decode(self, s, idx=0):
-        try:
-            obj, end = self.scan_once(s, idx)
-        except StopIteration as err:
-            raise JSONDecodeError("Expecting value", s, err.value) from None
-        return obj, end
-"#,
-            module_name = "json"
-        ));
+        // ── Register the embedded pure-Python stdlib ───────────────────────
+        // RustPython's own Lib/ (collections, datetime, re, ...), frozen at
+        // build time so no host Python is needed. Frozen modules are found
+        // before anything on sys.path. Registered before the wrappers below
+        // so that the shims replace the stdlib json, random and io.
+        #[cfg(feature = "embedded-stdlib")]
+        vm.add_frozen(rustpython_pylib::FROZEN_STDLIB);
+
+        // ── Register frozen stdlib wrappers ────────────────────────────────
+        // Built-in shims (e.g. the Python-level json wrapper over the native
+        // _json module) are listed in the `crate::frozen` registry.
+        for module in BUILTIN_FROZEN {
+            if module.name == "json" && !json_shim {
+                continue;
+            }
+            (module.install)(vm);
+        }
     });
 
+    // Embedder-registered modules are compiled from source, which needs a fully
+    // initialized VM, so they are installed after `with_init`.
+    let embedder_modules = embedder_modules();
+    if !embedder_modules.is_empty() {
+        inner.enter(|vm| install_embedder_modules(vm, &embedder_modules));
+    }
+
     PyInterp {
         inner,
         allowed_set: Arc::new(allowed_set),
+        signals,
+        missing_stdlib,
+    }
+}
+
+/// Execute each embedder module's source and register it in `sys.modules`.
+///
+/// Sources were syntax-checked by [`crate::frozen::register_frozen_module`]; a
+/// module that raises while executing is skipped (and so fails to import).
+fn install_embedder_modules(vm: &VirtualMachine, modules: &[(String, String)]) {
+    let Ok(sys_modules) = vm.sys_module.get_attr("modules", vm) else {
+        return;
+    };
+    for (name, source) in modules {
+        let Ok(code) = vm.compile(source, Mode::Exec, format!("<frozen {name}>")) else {
+            continue;
+        };
+        let dict = vm.ctx.new_dict();
+        let _ = dict.set_item("__name__", vm.ctx.new_str(name.as_str()).into(), vm);
+        let scope = Scope::with_builtins(None, dict.clone(), vm);
+        if vm.run_code_obj(code, scope).is_err() {
+            continue;
+        }
+        let module = vm.new_module(name, dict, None);
+        let _ = sys_modules.set_item(name.as_str(), module.into(), vm);
     }
 }
 
@@ -332,170 +466,730 @@ class JSONDecoder:
 /// - `interp`: a configured interpreter (from [`build_interpreter`])
 /// - `code_str`: the Python source to compile and execute
 /// - `output`: shared buffer for capturing stdout/stderr and reading them back
+/// - `options`: compile budget, inspection queries and deadline (see [`RunOptions`])
 ///
 /// # Returns
 /// [`VmRunResult`] with captured output and any error.
-pub(crate) fn run_code(interp: &PyInterp, code_str: &str, output: OutputBuffer) -> VmRunResult {
+pub(crate) fn run_code(
+    interp: &PyInterp,
+    code_str: &str,
+    output: OutputBuffer,
+    options: &RunOptions,
+) -> VmRunResult {
+    let scope = new_main_scope(interp, &options.module_name);
+    run_code_in_scope(interp, code_str, output, options, &scope)
+}
+
+/// Create a fresh scope for user code with `__name__` set to `module_name`
+/// (normally `"__main__"`).
+///
+/// The import hook recognises user code by this scope's namespace, not by
+/// its name; see [`is_user_code_import`].
+pub(crate) fn new_main_scope(interp: &PyInterp, module_name: &str) -> Scope {
+    interp.inner.enter(|vm| {
+        let scope = vm.new_scope_with_builtins();
+        let _ = scope.globals.set_item(
+            "__name__",
+            vm.ctx.new_str(module_name).into(),
+            vm,
+        );
+        scope
+    })
+}
+
+/// Like [`run_code`], but executes in a caller-owned `scope` so that names
+/// defined by one call stay visible to the next (see [`crate::session`]).
+///
+/// A result variable left over from an earlier call is cleared first.
+pub(crate) fn run_code_in_scope(
+    interp: &PyInterp,
+    code_str: &str,
+    output: OutputBuffer,
+    options: &RunOptions,
+    scope: &Scope,
+) -> VmRunResult {
     let allowed_set = Arc::clone(&interp.allowed_set);
+    let compile_timeout_ns = options.compile_timeout_ns;
+
+    #[cfg(test)]
+    if code_str.contains(PANIC_HOOK) {
+        panic!("simulated interpreter panic");
+    }
+    #[cfg(test)]
+    RUNS_ON_THIS_THREAD.with(|runs| runs.set(runs.get() + 1));
 
     interp.inner.enter(|vm| {
         // ── Step 0: Install import hook and output capture ────────────────
         // These are idempotent: each call to run_code reinstalls them so each
         // execution starts with a clean hook state.
-        install_import_hook(vm, &allowed_set);
+        let denied_log = Arc::new(Mutex::new(Vec::new()));
+        let attribute_guards = AttributeGuards::new(&options.denied_attributes);
+        install_import_hook(
+            vm,
+            &allowed_set,
+            options,
+            &scope.globals,
+            Arc::clone(&denied_log),
+            Arc::clone(&attribute_guards),
+        );
+        install_allowlist_finder(
+            vm,
+            &allowed_set,
+            &options.denied_modules,
+            options.allow_forbidden,
+            &scope.globals,
+            Arc::clone(&denied_log),
+        );
         install_output_capture(vm, output.clone());
+        let warning_log = Arc::new(Mutex::new(Vec::new()));
+        install_warning_capture(vm, Arc::clone(&warning_log));
+        install_virtual_open(vm, &options.files);
+        set_recursion_limit(vm, options.recursion_limit);
 
         // ── Step 1: Compile ───────────────────────────────────────────────
-        // Catches SyntaxError before any execution.
-        let code = match vm.compile(code_str, Mode::Exec, "<string>".to_owned()) {
+        // Catches SyntaxError before any execution. Compilation cannot be
+        // interrupted, so the compile budget is checked once it returns; the
+        // caller's overall timeout still bounds the whole call.
+        let mode = match options.result_capture {
+            ResultCapture::Wrap => Mode::Exec,
+            ResultCapture::Single => Mode::Single,
+        };
+        let compile_start = Instant::now();
+        let compiled = vm.compile(code_str, mode, "<string>".to_owned());
+        let compile_ns = compile_start.elapsed().as_nanos() as u64;
+        #[cfg(feature = "tracing")]
+        tracing::debug!(compile_ns, ok = compiled.is_ok(), "compiled snippet");
+        if compile_ns > compile_timeout_ns {
+            let (stdout, stderr) = output.into_strings();
+            return VmRunResult {
+                stdout,
+                stderr,
+                warnings: take_log(&warning_log),
+                denied_imports: take_log(&denied_log),
+                compile_ns,
+                execute_ns: 0,
+                return_value: None,
+                typed_value: None,
+                error: Some(ExecutionError::CompileTimeout {
+                    limit_ns: compile_timeout_ns,
+                }),
+                exit_code: None,
+                inspections: Vec::new(),
+                slot_id: None,
+            };
+        }
+        let code = match compiled {
             Ok(c) => c,
             Err(e) => {
                 let (stdout, stderr) = output.into_strings();
                 return VmRunResult {
                     stdout,
                     stderr,
+                    warnings: take_log(&warning_log),
+                    denied_imports: take_log(&denied_log),
+                    compile_ns,
+                    execute_ns: 0,
                     return_value: None,
+                    typed_value: None,
                     error: Some(extract_syntax_error(e)),
+                    exit_code: None,
+                    inspections: Vec::new(),
+                    slot_id: None,
                 };
             }
         };
 
-        // ── Step 2: Execute in an isolated scope ──────────────────────────
-        // Set __name__ = "__main__" so the import hook can distinguish user
-        // code (which must pass the allowlist) from stdlib module internals.
-        let scope = vm.new_scope_with_builtins();
-        let _ = scope.globals.set_item(
-            "__name__",
-            vm.ctx.new_str("__main__").into(),
-            vm,
-        );
+        // ── Step 2: Execute in the given scope ────────────────────────────
+        let saved_sys_path = extend_sys_path(vm, &options.extra_sys_paths);
+        let saved_getcwd = options
+            .cwd
+            .as_deref()
+            .and_then(|cwd| override_getcwd(vm, cwd));
+        let saved_clock = options
+            .deterministic
+            .as_ref()
+            .and_then(|config| pin_clock_and_seed(vm, config));
+        let saved_displayhook = match mode {
+            Mode::Single => silence_displayhook(vm),
+            _ => None,
+        };
+        let _ = scope.globals.del_item(options.result_name.as_str(), vm);
+        let watchdog = options
+            .cpu_timeout_ns
+            .map(|budget_ns| CpuWatchdog::start(&interp.signals, budget_ns));
+        let trace_deadline = options.cooperative_timeout_ns.and(options.deadline);
+        let instructions = InstructionBudget::install(vm, options.max_instructions, trace_deadline);
+        let cancel = options
+            .cancel
+            .as_ref()
+            .map(|handle| handle.attach(&interp.signals));
+        // Guarded only while the snippet runs: the budget and cancel hooks
+        // above and below use `sys` themselves.
+        attribute_guards.apply(vm);
+        let execute_start = Instant::now();
         let exec_result = vm.run_code_obj(code, scope.clone());
-
-        let (stdout, stderr) = output.into_strings();
-
-        match exec_result {
-            Ok(_) => {
-                // ── Step 3: Extract return value ──────────────────────────
-                // If executor.rs wrapped the last expression as `__result__ = <expr>`,
-                // we can retrieve it from scope locals.
-                let return_value = extract_return_value(vm, &scope);
-                VmRunResult {
-                    stdout,
-                    stderr,
-                    return_value,
-                    error: None,
-                }
-            }
+        let execute_ns = execute_start.elapsed().as_nanos() as u64;
+        attribute_guards.restore(vm);
+        let cancelled = cancel.is_some_and(CancelGuard::finish);
+        let trace_limit = instructions.and_then(|budget| budget.finish(vm));
+        let cpu_exceeded = watchdog.is_some_and(CpuWatchdog::finish);
+        if let Some(saved) = saved_displayhook {
+            let _ = vm.sys_module.set_attr("displayhook", saved, vm);
+        }
+        // An uncaught `SystemExit` ends the snippet like a process exit; only
+        // a nonzero status is an error.
+        let exit_code = exec_result
+            .as_ref()
+            .err()
+            .and_then(|exc| system_exit_code(vm, exc, &output));
+
+        // Inspection queries see the final scope whether or not the code raised.
+        let (final_value, error) = match exec_result {
+            Ok(value) => (Some(value), None),
+            Err(_) if trace_limit == Some(TraceLimit::Instructions) => (
+                None,
+                options
+                    .max_instructions
+                    .map(|limit| ExecutionError::InstructionLimitExceeded { limit }),
+            ),
+            Err(_) if trace_limit == Some(TraceLimit::Deadline) => (
+                None,
+                options
+                    .cooperative_timeout_ns
+                    .map(|limit_ns| ExecutionError::Timeout {
+                        limit_ns,
+                        kind: TimeoutKind::Wall,
+                    }),
+            ),
+            Err(_) if cpu_exceeded => (
+                None,
+                options
+                    .cpu_timeout_ns
+                    .map(|limit_ns| ExecutionError::Timeout {
+                        limit_ns,
+                        kind: TimeoutKind::Cpu,
+                    }),
+            ),
+            Err(_) if cancelled => (None, Some(ExecutionError::Cancelled)),
+            Err(_) if exit_code.is_some() => (
+                None,
+                exit_code
+                    .filter(|code| *code != 0)
+                    .map(|code| ExecutionError::NonZeroExit { code }),
+            ),
             Err(exc) => {
-                // Check if it's our sentinel ModuleNotAllowed exception first.
-                if let Some(module_err) = extract_module_not_allowed(vm, &exc) {
-                    return VmRunResult {
-                        stdout,
-                        stderr,
-                        return_value: None,
-                        error: Some(module_err),
-                    };
-                }
-                // Otherwise it's a RuntimeError.
-                VmRunResult {
-                    stdout,
-                    stderr,
-                    return_value: None,
-                    error: Some(extract_runtime_error(vm, exc)),
-                }
+                // Check if it's our sentinel ModuleNotAllowed exception first,
+                // then for a module missing with the stdlib; otherwise it's a
+                // RuntimeError.
+                let error = extract_module_not_allowed(vm, &exc)
+                    .or_else(|| extract_stdlib_unavailable(vm, &exc, interp))
+                    .unwrap_or_else(|| {
+                        if options.mirror_traceback_to_stderr {
+                            mirror_traceback(vm, &exc, &output);
+                        }
+                        extract_runtime_error(vm, exc, options.recursion_limit)
+                    });
+                (None, Some(error))
             }
+        };
+        let (stdout, stderr) = output.into_strings();
+        let warnings = take_log(&warning_log);
+        let denied_imports = take_log(&denied_log);
+        // ── Step 3: Extract return value ──────────────────────────────────
+        // If executor.rs wrapped the last expression as `<result_name> = <expr>`,
+        // we can retrieve it from scope locals. Code compiled in single mode
+        // returns the value of a final expression statement instead.
+        let result_obj = match (options.result_capture, final_value) {
+            (_, None) => None,
+            (ResultCapture::Wrap, Some(_)) => extract_return_value(vm, scope, &options.result_name),
+            (ResultCapture::Single, Some(value)) => Some(value),
+        };
+        // A `None` result counts as no value unless asked for.
+        let result_obj = result_obj.filter(|obj| options.report_none || !vm.is_none(obj));
+        let return_value = result_obj.as_ref().and_then(|obj| try_repr(vm, obj));
+        let typed_value = result_obj.map(|obj| to_py_value(vm, &obj));
+        // A per-call name is an implementation detail: keep it out of
+        // inspections and of later calls in the same scope.
+        if options.result_name != RESULT_NAME {
+            let _ = scope.globals.del_item(options.result_name.as_str(), vm);
+        }
+        // ── Step 4: Answer inspection queries ─────────────────────────────
+        let inspections = options
+            .inspect
+            .iter()
+            .map(|query| run_inspection(vm, scope, query, options.deadline))
+            .collect();
+
+        if let Some(saved) = saved_sys_path {
+            let _ = vm.sys_module.set_attr("path", saved, vm);
+        }
+        if let Some(saved) = saved_getcwd {
+            saved.restore(vm);
+        }
+        if let Some(saved) = saved_clock {
+            saved.restore(vm);
+        }
+
+        VmRunResult {
+            stdout,
+            stderr,
+            warnings,
+            denied_imports,
+            compile_ns,
+            execute_ns,
+            return_value,
+            typed_value,
+            error,
+            exit_code,
+            inspections,
+            slot_id: None,
         }
     })
 }
 
 // ── Private helpers ───────────────────────────────────────────────────────────
 
-/// Returns `true` if the import is originating from user code (not from stdlib).
+/// Append `extra` to `sys.path`, returning a copy of the original list for the
+/// caller to restore afterwards (`None` if nothing was changed).
+fn extend_sys_path(vm: &VirtualMachine, extra: &[PathBuf]) -> Option<PyObjectRef> {
+    if extra.is_empty() {
+        return None;
+    }
+    let path = vm.sys_module.get_attr("path", vm).ok()?;
+    let saved = vm.call_method(&path, "copy", ()).ok()?;
+    for dir in extra {
+        let entry = vm.ctx.new_str(dir.to_string_lossy().into_owned());
+        let _ = vm.call_method(&path, "append", (entry,));
+    }
+    Some(saved)
+}
+
+/// The `os` module's own `getcwd`/`getcwdb`, saved by [`override_getcwd`].
+struct SavedGetcwd {
+    os: PyObjectRef,
+    getcwd: PyObjectRef,
+    getcwdb: PyObjectRef,
+}
+
+impl SavedGetcwd {
+    fn restore(self, vm: &VirtualMachine) {
+        let _ = self.os.set_attr("getcwd", self.getcwd, vm);
+        let _ = self.os.set_attr("getcwdb", self.getcwdb, vm);
+    }
+}
+
+/// Make `os.getcwd()` (and `os.getcwdb()`) return `cwd`, returning the
+/// originals for the caller to restore afterwards (`None` if `os` could not be
+/// loaded).
 ///
-/// Strategy: check `__name__` in the calling module's globals.
-/// - User code (compiled from a string) runs with `__name__ == "__main__"`.
-/// - Any real module (stdlib, frozen, etc.) has a non-"__main__" `__name__`.
-///
-/// Falls back to checking `__file__` when `__name__` is unavailable:
-/// user code has `__file__ == "<string>"` or no `__file__`, while stdlib modules
-/// have real filesystem paths.  Frozen modules may have `__file__ == None`, so
-/// we treat them as stdlib (non-user-code) when their `__name__ != "__main__"`.
-fn is_user_code_import(args: &FuncArgs, vm: &VirtualMachine) -> bool {
-    let globals = match args.args.get(1) {
-        Some(g) => g,
-        None => return true, // No globals — assume user code.
+/// `posixpath.abspath` and friends look `getcwd` up on the `os` module at call
+/// time, so replacing the attribute is enough; the process working directory
+/// is never touched. `os` is loaded through [`original_import`], so this does not make `os` importable by user code.
+fn override_getcwd(vm: &VirtualMachine, cwd: &Path) -> Option<SavedGetcwd> {
+    let import = original_import(vm);
+    let os = import.call((vm.ctx.new_str("os"),), vm).ok()?;
+    let saved = SavedGetcwd {
+        getcwd: os.get_attr("getcwd", vm).ok()?,
+        getcwdb: os.get_attr("getcwdb", vm).ok()?,
+        os,
     };
 
-    if vm.is_none(globals) {
-        return true; // None globals — assume user code.
+    let cwd = cwd.to_string_lossy().into_owned();
+    let cwd_bytes = cwd.clone().into_bytes();
+    let getcwd = vm.new_function(
+        "getcwd",
+        move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            Ok(vm.ctx.new_str(cwd.as_str()).into())
+        },
+    );
+    let getcwdb = vm.new_function(
+        "getcwdb",
+        move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            Ok(vm.ctx.new_bytes(cwd_bytes.clone()).into())
+        },
+    );
+    let _ = saved.os.set_attr("getcwd", getcwd, vm);
+    let _ = saved.os.set_attr("getcwdb", getcwdb, vm);
+    Some(saved)
+}
+
+/// What [`pin_clock_and_seed`] replaced: the `time` functions and the state
+/// of the shared `random` generator.
+struct SavedClock {
+    time: PyObjectRef,
+    functions: Vec<(&'static str, PyObjectRef)>,
+    random: Option<(PyObjectRef, PyObjectRef)>,
+}
+
+impl SavedClock {
+    fn restore(self, vm: &VirtualMachine) {
+        for (name, original) in self.functions {
+            let _ = self.time.set_attr(name, original, vm);
+        }
+        if let Some((random, state)) = self.random {
+            let _ = vm.call_method(&random, "setstate", (state,));
+        }
     }
+}
 
-    // Primary check: __name__ in globals.
-    // User code always runs as "__main__"; real modules have their own name.
-    if let Ok(name_val) = vm.call_method(globals, "get", (vm.ctx.new_str("__name__"),)) {
-        if !vm.is_none(&name_val) {
-            if let Ok(name_str) = name_val.str(vm) {
-                let name = name_str.as_str();
-                // If __name__ is explicitly set to a non-__main__ value, it's a
-                // module (stdlib, frozen, site-package) — not user code.
-                if !name.is_empty() && name != "__main__" {
-                    return false;
-                }
-                // "__main__" → user code; fall through to __file__ check only if
-                // __name__ is exactly "__main__".
-                if name == "__main__" {
-                    return true;
+/// Fix the wall clock at `config.epoch_seconds` and seed `random` with
+/// `config.random_seed`, returning the originals for the caller to restore
+/// afterwards (`None` if `time` could not be loaded).
+///
+/// `time.time`, `time.time_ns` and `time.localtime` are replaced on the `time`
+/// module itself. `datetime` reads the clock through that module at call time,
+/// so `datetime.datetime.now()` and `datetime.date.today()` follow; local time
+/// is reported as UTC so the output does not depend on the host's timezone.
+/// Like `os` in [`override_getcwd`], `time` and `random` are loaded through
+/// the original `__import__`, so neither becomes importable by user code.
+fn pin_clock_and_seed(vm: &VirtualMachine, config: &DeterministicConfig) -> Option<SavedClock> {
+    let import = original_import(vm);
+    let time = import.call((vm.ctx.new_str("time"),), vm).ok()?;
+    let mut functions = Vec::new();
+    for name in ["time", "time_ns", "localtime"] {
+        functions.push((name, time.get_attr(name, vm).ok()?));
+    }
+
+    let epoch = config.epoch_seconds;
+    let now = vm.new_function(
+        "time",
+        move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            Ok(vm.ctx.new_float(epoch as f64).into())
+        },
+    );
+    let now_ns = vm.new_function(
+        "time_ns",
+        move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            Ok(vm.ctx.new_int(i128::from(epoch) * 1_000_000_000).into())
+        },
+    );
+    let localtime = vm.new_function(
+        "localtime",
+        move |args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            let secs = args
+                .args
+                .first()
+                .filter(|secs| !vm.is_none(secs))
+                .cloned()
+                .unwrap_or_else(|| vm.ctx.new_int(epoch).into());
+            let time = vm
+                .sys_module
+                .get_attr("modules", vm)?
+                .get_item("time", vm)?;
+            time.get_attr("gmtime", vm)?.call((secs,), vm)
+        },
+    );
+    let _ = time.set_attr("time", now, vm);
+    let _ = time.set_attr("time_ns", now_ns, vm);
+    let _ = time.set_attr("localtime", localtime, vm);
+
+    let random = import
+        .call((vm.ctx.new_str("random"),), vm)
+        .ok()
+        .and_then(|random| {
+            let state = vm.call_method(&random, "getstate", ()).ok()?;
+            vm.call_method(&random, "seed", (vm.ctx.new_int(config.random_seed),))
+                .ok()?;
+            Some((random, state))
+        });
+    Some(SavedClock {
+        time,
+        functions,
+        random,
+    })
+}
+
+/// Withholds [`RunOptions::denied_attributes`] from user code for one call.
+///
+/// [`AttributeGuards::apply`] replaces each listed attribute of a module in
+/// `sys.modules` with a guard that raises `PermissionError`: a function for a
+/// callable (see [`attribute_guard`]), an object every use of which raises
+/// for data such as `sys.stdin` (see [`data_guard`]). It runs before the
+/// snippet and again from the import hook after every import, so modules
+/// loaded during the call are covered too.
+/// [`AttributeGuards::restore`] puts the originals back, so a pooled
+/// interpreter or a [`Session`](crate::Session) scope starts the next call
+/// unchanged, and nothing is guarded after that.
+struct AttributeGuards {
+    denied: Arc<Vec<(String, String)>>,
+    /// Each replaced attribute: its index in `denied`, the module, and the
+    /// original value. `None` once restored.
+    replaced: Mutex<Option<Vec<(usize, PyObjectRef, PyObjectRef)>>>,
+}
+
+impl AttributeGuards {
+    #[allow(clippy::arc_with_non_send_sync)]
+    fn new(denied: &Arc<Vec<(String, String)>>) -> Arc<Self> {
+        Arc::new(Self {
+            denied: Arc::clone(denied),
+            replaced: Mutex::new(Some(Vec::new())),
+        })
+    }
+
+    /// Guard every listed attribute of a loaded module not guarded yet.
+    fn apply(&self, vm: &VirtualMachine) {
+        if self.denied.is_empty() {
+            return;
+        }
+        let Ok(modules) = vm.sys_module.get_attr("modules", vm) else {
+            return;
+        };
+        let mut replaced = self
+            .replaced
+            .lock()
+            .expect("attribute guards mutex poisoned");
+        let Some(replaced) = replaced.as_mut() else {
+            return;
+        };
+        for (index, (module_name, attribute)) in self.denied.iter().enumerate() {
+            if replaced.iter().any(|(i, _, _)| *i == index) {
+                continue;
+            }
+            let Ok(module) = modules.get_item(module_name.as_str(), vm) else {
+                continue;
+            };
+            let Ok(original) = module.get_attr(attribute.as_str(), vm) else {
+                continue;
+            };
+            let path = format!("{module_name}.{attribute}");
+            let guard = if original.is_callable() {
+                attribute_guard(vm, path)
+            } else {
+                match data_guard(vm, &path) {
+                    Ok(guard) => guard,
+                    Err(_) => continue,
                 }
+            };
+            if module.set_attr(attribute.as_str(), guard, vm).is_ok() {
+                replaced.push((index, module, original));
             }
         }
     }
 
-    // Fallback: check __file__.
-    // User code is compiled from a string → __file__ is "<string>" (or absent).
-    // Frozen modules have __file__ == None → treat as stdlib (return false).
-    let file_val = vm
-        .call_method(globals, "get", (vm.ctx.new_str("__file__"),))
-        .ok();
-
-    match file_val {
-        None => true, // get() failed — assume user code.
-        Some(v) if vm.is_none(&v) => false, // None __file__ → frozen/stdlib module.
-        Some(v) => {
-            // Check if __file__ is "<string>" (user code) or a real path (stdlib).
-            v.str(vm)
-                .map(|s| {
-                    let file = s.as_str();
-                    // User code markers: compiled from source string
-                    file == "<string>" || file == "<stdin>" || file == "<module>" || file.is_empty()
-                })
-                .unwrap_or(true) // If str() fails, assume user code.
+    /// Put back every attribute [`AttributeGuards::apply`] replaced, and stop
+    /// guarding.
+    fn restore(&self, vm: &VirtualMachine) {
+        let replaced = self
+            .replaced
+            .lock()
+            .expect("attribute guards mutex poisoned")
+            .take();
+        for (index, module, original) in replaced.into_iter().flatten().rev() {
+            let _ = module.set_attr(self.denied[index].1.as_str(), original, vm);
         }
     }
 }
 
-/// Check if `module_name` is allowed, considering submodule imports.
+/// A function standing in for the denied attribute `path`, which raises
+/// `PermissionError` naming it whenever it is called.
+fn attribute_guard(vm: &VirtualMachine, path: String) -> PyObjectRef {
+    vm.new_function(
+        "__pyexec_denied_attribute__",
+        move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            Err(vm.new_exception_msg(
+                vm.ctx.exceptions.permission_error.to_owned(),
+                format!("{path} is not allowed"),
+            ))
+        },
+    )
+    .into()
+}
+
+/// Defines `__pyexec_denied_attribute__(path)`, which returns an object that
+/// raises `PermissionError` naming `path` on attribute access, calls,
+/// iteration, indexing, membership tests and conversion to `bool` or `str`.
+const DATA_GUARD_SOURCE: &str = "\
+def __pyexec_denied_attribute__(path):
+    def deny(*args, **kwargs):
+        raise PermissionError(path + ' is not allowed')
+    names = (
+        '__getattribute__', '__setattr__', '__delattr__', '__call__',
+        '__iter__', '__len__', '__getitem__', '__setitem__', '__delitem__',
+        '__contains__', '__bool__', '__repr__', '__str__', '__enter__',
+    )
+    return type('__pyexec_denied_attribute__', (), dict.fromkeys(names, deny))()
+";
+
+/// An object standing in for the denied data attribute `path`, which raises
+/// `PermissionError` naming it whenever it is used (see [`DATA_GUARD_SOURCE`]).
+fn data_guard(vm: &VirtualMachine, path: &str) -> PyResult<PyObjectRef> {
+    let code = vm
+        .compile(
+            DATA_GUARD_SOURCE,
+            Mode::Exec,
+            "<pyexec attribute guard>".to_owned(),
+        )
+        .map_err(|err| vm.new_syntax_error(&err, Some(DATA_GUARD_SOURCE)))?;
+    let scope = Scope::with_builtins(None, vm.ctx.new_dict(), vm);
+    vm.run_code_obj(code, scope.clone())?;
+    let factory = scope.globals.get_item("__pyexec_denied_attribute__", vm)?;
+    factory.call((vm.ctx.new_str(path),), vm)
+}
+
+/// Enforces [`RunOptions::max_instructions`] and
+/// [`RunOptions::cooperative_timeout_ns`] for one call through `sys.settrace`.
 ///
-/// If "json" is in the allowlist, then "json.decoder", "json.encoder", etc.
-/// are also allowed because they are internal submodules loaded automatically
-/// when importing the parent package.
+/// RustPython has no per-instruction hook, so every trace event counts as one
+/// instruction, and the deadline is only checked on trace events. Once either
+/// limit is hit each further event raises `KeyboardInterrupt` — a
+/// `BaseException`, as for the CPU budget, so `except Exception` in user code
+/// does not swallow it.
+struct InstructionBudget {
+    /// The limit that was hit, as a [`TraceLimit`] discriminant plus one.
+    exceeded: Arc<AtomicU8>,
+}
+
+/// Which limit an [`InstructionBudget`] stopped the snippet for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum TraceLimit {
+    Instructions = 1,
+    Deadline = 2,
+}
+
+impl InstructionBudget {
+    /// Start counting from zero; `None` if there is nothing to enforce or
+    /// `sys.settrace` is unavailable.
+    fn install(vm: &VirtualMachine, limit: Option<u64>, deadline: Option<Instant>) -> Option<Self> {
+        if limit.is_none() && deadline.is_none() {
+            return None;
+        }
+        let settrace = vm.sys_module.get_attr("settrace", vm).ok()?;
+        let executed = AtomicU64::new(0);
+        let exceeded = Arc::new(AtomicU8::new(0));
+        let flag = Arc::clone(&exceeded);
+        let hook = vm.new_function(
+            "__pyexec_instruction_budget__",
+            move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+                let (hit, message) = match limit {
+                    Some(limit) if executed.fetch_add(1, Ordering::Relaxed) >= limit => (
+                        TraceLimit::Instructions,
+                        format!("instruction limit of {limit} exceeded"),
+                    ),
+                    _ if deadline.is_some_and(|deadline| Instant::now() >= deadline) => {
+                        (TraceLimit::Deadline, "timed out".to_owned())
+                    }
+                    // Returning the hook keeps it as the frame's local trace
+                    // function, so line events are counted where they are reported.
+                    _ => return vm.sys_module.get_attr("gettrace", vm)?.call((), vm),
+                };
+                flag.store(hit as u8, Ordering::Relaxed);
+                Err(vm.new_exception_msg(vm.ctx.exceptions.keyboard_interrupt.to_owned(), message))
+            },
+        );
+        settrace.call((hook,), vm).ok()?;
+        Some(Self { exceeded })
+    }
+
+    /// Remove the hook. Returns the limit that was hit, if any.
+    fn finish(self, vm: &VirtualMachine) -> Option<TraceLimit> {
+        // Read first: removing the hook is itself a traced call.
+        let exceeded = self.exceeded.load(Ordering::Relaxed);
+        if let Ok(settrace) = vm.sys_module.get_attr("settrace", vm) {
+            let _ = settrace.call((vm.ctx.none(),), vm);
+        }
+        match exceeded {
+            1 => Some(TraceLimit::Instructions),
+            2 => Some(TraceLimit::Deadline),
+            _ => None,
+        }
+    }
+}
+
+/// Returns `true` if the import is originating from user code (not from stdlib).
 ///
-/// Also delegates to `check_module_allowed` for the "os" / "os.path" special case.
-fn is_module_allowed(module_name: &str, allowed_set: &HashSet<String>) -> bool {
-    // Direct match (handles "json", "math", "os.path", etc.)
-    if check_module_allowed(module_name, allowed_set).is_ok() {
+/// Decided from the frame that started the import (see [`import_initiator`]),
+/// never from the `globals` argument or from `__name__`/`__file__`, all of
+/// which user code controls. The import is trusted only if the frame's
+/// namespace is a real module's:
+/// - not `user_globals`, the namespace user code runs in;
+/// - not a virtual module (see [`VIRTUAL_MODULE_MARKER`]);
+/// - not code compiled from a string (`exec`, `eval`, `compile`), whatever
+///   namespace it runs in;
+/// - the `__dict__` of the module `sys.modules` holds under the namespace's
+///   own `__spec__.name` or `__name__` (see [`is_module_namespace`]).
+///
+/// Residual risk: code that is compiled with a real-looking file name
+/// (`code.replace(co_filename=...)`) and then run as a function over an
+/// allowed module's `__dict__` passes as that module. The allowlist alone
+/// does not defend against such a deliberately built code object.
+fn is_user_code_import(vm: &VirtualMachine, user_globals: &PyRef<PyDict>) -> bool {
+    let Some(frame) = import_initiator(vm) else {
+        return true; // No calling frame — assume user code.
+    };
+    let globals = &frame.globals;
+    if globals.is(user_globals) {
         return true;
     }
 
-    // Check if this is a submodule of an allowed parent package.
-    // e.g. "json.decoder" is allowed if "json" is allowed.
-    if let Some(dot_pos) = module_name.find('.') {
-        let parent = &module_name[..dot_pos];
-        if check_module_allowed(parent, allowed_set).is_ok() {
-            return true;
-        }
+    // Virtual modules are caller-supplied source, so their imports are checked
+    // like user code despite having their own __name__.
+    let marker = globals
+        .get_item_opt(VIRTUAL_MODULE_MARKER, vm)
+        .ok()
+        .flatten();
+    if marker.is_some_and(|m| m.is_true(vm).unwrap_or(false)) {
+        return true;
     }
 
-    false
+    let file = frame.code.source_path.as_str();
+    if matches!(file, "<string>" | "<stdin>" | "<module>" | "") || file.starts_with("<virtual ") {
+        return true;
+    }
+
+    module_namespace_name(vm, globals).is_none()
+}
+
+/// The innermost frame outside the import machinery (`importlib` and its
+/// frozen bootstrap), i.e. the code that asked for the import, whether
+/// through an `import` statement, `__import__` or `importlib.import_module`.
+fn import_initiator(vm: &VirtualMachine) -> Option<FrameRef> {
+    // Cloned so that no borrow is held while Python attributes are read.
+    let frames = vm.frames.borrow().clone();
+    frames.into_iter().rev().find(|frame| {
+        !module_namespace_name(vm, &frame.globals).is_some_and(|name| {
+            name == "importlib"
+                || name.starts_with("importlib.")
+                || name.starts_with("_frozen_importlib")
+        })
+    })
+}
+
+/// The name under which `globals` is the `__dict__` of a module in
+/// `sys.modules`, trying the namespace's own `__spec__.name`, then
+/// `__name__`.
+///
+/// `Some` for stdlib, frozen and embedder modules; `None` for a dict that
+/// only claims a module's name.
+fn module_namespace_name(vm: &VirtualMachine, globals: &PyRef<PyDict>) -> Option<String> {
+    let modules = vm.sys_module.get_attr("modules", vm).ok()?;
+    let item = |key: &str| {
+        globals
+            .get_item_opt(key, vm)
+            .ok()
+            .flatten()
+            .filter(|v| !vm.is_none(v))
+    };
+    let spec_name = item("__spec__").and_then(|spec| spec.get_attr("name", vm).ok());
+    [spec_name, item("__name__")]
+        .into_iter()
+        .flatten()
+        .filter_map(|name| name.str(vm).ok())
+        .find(|name| {
+            modules
+                .get_item(name.as_str(), vm)
+                .and_then(|module| module.get_attr("__dict__", vm))
+                .is_ok_and(|dict| dict.is(globals))
+        })
+        .map(|name| name.as_str().to_owned())
+}
+
+/// The interpreter's own `__import__`, which `builtins.__import__` wraps.
+///
+/// Kept by the VM rather than in `builtins`, so user code cannot reach it to
+/// sidestep [`install_import_hook`]. Modules loaded through it are not
+/// checked against the allowlist and so do not become importable by user code.
+fn original_import(vm: &VirtualMachine) -> PyObjectRef {
+    vm.import_func.clone()
 }
 
 /// Install `builtins.__import__` override that enforces the module allowlist.
@@ -503,39 +1197,36 @@ fn is_module_allowed(module_name: &str, allowed_set: &HashSet<String>) -> bool {
 /// **Approach**: Option C from architecture §17.
 /// We replace `builtins.__import__` with a Rust native function that:
 /// 1. Extracts the module name (first positional argument).
-/// 2. Checks it against `allowed_set` via `check_module_allowed`.
-/// 3. If denied, raises `ImportError("ModuleNotAllowed:<name>")`.
-/// 4. If allowed, delegates to the original `__import__` function.
-///
-/// This function is called inside `enter()` (after full initialization),
-/// so `builtins.__import__` is guaranteed to exist.
-fn install_import_hook(vm: &VirtualMachine, allowed_set: &Arc<HashSet<String>>) {
-    // On pool slot reuse, `builtins.__import__` may already be our hook from a
-    // previous call. We must always delegate to the REAL original Python __import__,
-    // not to a previously installed hook (which would use a stale allowed_set).
-    //
-    // Strategy: save the real original under `builtins.__pyexec_original_import__`
-    // on first install. On subsequent calls, use that saved original instead.
-    const SAVED_IMPORT_ATTR: &str = "__pyexec_original_import__";
-
-    let original_import = if let Ok(saved) = vm.builtins.get_attr(SAVED_IMPORT_ATTR, vm) {
-        // Already saved from a prior install — use it directly.
-        saved
-    } else {
-        // First install on this interpreter: save the real original.
-        let real_original = match vm.builtins.get_attr("__import__", vm) {
-            Ok(f) => f,
-            Err(_) => return, // Shouldn't happen but handle gracefully.
-        };
-        let _ = vm.builtins.set_attr(SAVED_IMPORT_ATTR, real_original.clone(), vm);
-        real_original
-    };
+/// 2. If the import comes from user code (see [`is_user_code_import`]),
+///    checks it with [`check_user_import`].
+/// 3. If denied, records the resolved name in `denied_log` and raises the
+///    error built by [`module_not_allowed`].
+/// 4. If allowed and the name is one of `options.virtual_modules` not yet in
+///    `sys.modules`, executes its source as a new module (see [`load_virtual_module`]).
+/// 5. Delegates to [`original_import`], then guards the denied attributes
+///    of any module the import loaded (see [`AttributeGuards`]).
+///
+/// On pool slot reuse `builtins.__import__` is still the previous call's
+/// hook; it is simply replaced, since the new hook never delegates to it.
+fn install_import_hook(
+    vm: &VirtualMachine,
+    allowed_set: &Arc<HashSet<String>>,
+    options: &RunOptions,
+    user_globals: &PyRef<PyDict>,
+    denied_log: Arc<Mutex<Vec<String>>>,
+    attribute_guards: Arc<AttributeGuards>,
+) {
+    let original_import = original_import(vm);
 
     // Wrap in Arc so the closure captures them safely.
     // PyObjectRef is not Send+Sync but the closure runs within the VM thread only.
     #[allow(clippy::arc_with_non_send_sync)]
     let original_import = Arc::new(original_import);
     let allowed_set = Arc::clone(allowed_set);
+    let denied_set = Arc::clone(&options.denied_modules);
+    let allow_forbidden = options.allow_forbidden;
+    let virtual_modules = Arc::clone(&options.virtual_modules);
+    let user_globals = user_globals.clone();
 
     let hook = vm.new_function(
         "__import__",
@@ -556,10 +1247,7 @@ fn install_import_hook(vm: &VirtualMachine, allowed_set: &Arc<HashSet<String>>)
             let level: i64 = args
                 .args
                 .get(4)
-                .and_then(|o| {
-                    use rustpython_vm::TryFromObject;
-                    i64::try_from_object(vm, o.clone()).ok()
-                })
+                .and_then(|o| i64::try_from_object(vm, o.clone()).ok())
                 .unwrap_or(0);
 
             // For relative imports (level > 0), extract the parent package from globals.
@@ -599,20 +1287,29 @@ fn install_import_hook(vm: &VirtualMachine, allowed_set: &Arc<HashSet<String>>)
                 module_name.clone()
             };
 
-            // Check if this import is coming from user code (<string>) or from
-            // an internal stdlib module. We only enforce the allowlist for imports
-            // originating from user code (where __file__ is "<string>" or None).
+            // Check if this import is coming from user code or from an internal
+            // stdlib module. We only enforce the allowlist for imports
+            // originating from user code.
             //
             // This allows stdlib modules to import their own dependencies freely
             // while still blocking user code from importing denied modules.
-            let importing_from_user_code = is_user_code_import(&args, vm);
+            let importing_from_user_code = is_user_code_import(vm, &user_globals);
 
             if importing_from_user_code {
                 // Check allowlist. We check both the full (resolved) module name AND its
                 // top-level package. For example, if "json" is allowed, then "json.decoder"
                 // and "decoder" (relative import within json) are also allowed.
-                let allowed = is_module_allowed(&full_module_name, &allowed_set);
-                if !allowed {
+                let allowed = check_user_import(
+                    &full_module_name,
+                    &allowed_set,
+                    &denied_set,
+                    allow_forbidden,
+                );
+                if let Err(reason) = allowed {
+                    denied_log
+                        .lock()
+                        .expect("call log mutex poisoned")
+                        .push(full_module_name.clone());
                     // Raise ImportError with sentinel prefix so extract_module_not_allowed
                     // can detect it. Use the user-visible name for the error message.
                     let deny_name = if full_module_name != module_name {
@@ -620,30 +1317,287 @@ fn install_import_hook(vm: &VirtualMachine, allowed_set: &Arc<HashSet<String>>)
                     } else {
                         module_name.clone()
                     };
-                    return Err(vm.new_import_error(
-                        format!("ModuleNotAllowed:{deny_name}"),
-                        vm.ctx.new_str(deny_name),
-                    ));
+                    return Err(module_not_allowed(vm, &deny_name, &reason));
                 }
             }
 
+            // Virtual modules are not on any path; make sure they are in
+            // sys.modules before the original __import__ looks for them.
+            if let Some(source) = virtual_modules.get(&full_module_name) {
+                load_virtual_module(vm, &full_module_name, source)?;
+            }
+
+            // The first `import warnings` of a call loads a fresh module whose
+            // `showwarning` still prints to stderr.
+            let routes_warnings = full_module_name == "warnings" && !is_loaded(vm, "warnings");
+
             // Allowed — delegate to original __import__.
-            original_import.call(args, vm)
+            let module = original_import.call(args, vm)?;
+            if routes_warnings {
+                let _ = route_warnings(vm, None);
+            }
+            attribute_guards.apply(vm);
+            Ok(module)
         },
     );
 
     let _ = vm.builtins.set_attr("__import__", hook, vm);
 }
 
+/// The check [`install_import_hook`] and [`install_allowlist_finder`] apply
+/// to imports by user code: [`check_module_forbidden`] unless
+/// `allow_forbidden` is set, then [`check_module_allowed_verbose`].
+fn check_user_import(
+    name: &str,
+    allowed_set: &HashSet<String>,
+    denied_set: &HashSet<String>,
+    allow_forbidden: bool,
+) -> Result<(), DenialReason> {
+    if !allow_forbidden {
+        check_module_forbidden(name)?;
+    }
+    check_module_allowed_verbose(name, allowed_set, denied_set)
+}
+
+/// `ImportError("ModuleNotAllowed:<name> (<reason>)")`, or
+/// `ImportError("ModuleForbidden:<name> (<reason>)")` for a forbidden module,
+/// which [`extract_module_not_allowed`] turns into
+/// [`ExecutionError::ModuleNotAllowed`] or [`ExecutionError::ModuleForbidden`].
+/// User code that catches it sees why the module was denied.
+fn module_not_allowed(
+    vm: &VirtualMachine,
+    name: &str,
+    reason: &DenialReason,
+) -> PyBaseExceptionRef {
+    let kind = match reason {
+        DenialReason::Forbidden { .. } => "ModuleForbidden",
+        _ => "ModuleNotAllowed",
+    };
+    vm.new_import_error(format!("{kind}:{name} ({reason})"), vm.ctx.new_str(name))
+}
+
+/// `__name__` of the `sys.meta_path` finder installed by
+/// [`install_allowlist_finder`].
+const ALLOWLIST_FINDER_NAME: &str = "_pyexec_allowlist_finder";
+
+/// Prepend a `sys.meta_path` finder that enforces the module allowlist for
+/// imports that bypass `builtins.__import__`, such as
+/// `importlib.import_module` (Option B from architecture §17).
+///
+/// The import machinery consults the finder for every module not yet in
+/// `sys.modules`. If the import was started by user code (see
+/// [`is_user_code_import`]) and the module is not allowed, the finder records
+/// it in `denied_log` and raises the same error as [`install_import_hook`]; otherwise it returns `None` and
+/// the regular finders run. The finder installed by a previous call is
+/// replaced.
+///
+/// Modules already in `sys.modules` never reach a finder; user code can read
+/// those through `sys.modules` anyway.
+fn install_allowlist_finder(
+    vm: &VirtualMachine,
+    allowed_set: &Arc<HashSet<String>>,
+    denied_set: &Arc<HashSet<String>>,
+    allow_forbidden: bool,
+    user_globals: &PyRef<PyDict>,
+    denied_log: Arc<Mutex<Vec<String>>>,
+) {
+    let Some(meta_path) = vm
+        .sys_module
+        .get_attr("meta_path", vm)
+        .ok()
+        .and_then(|list| list.downcast::<PyList>().ok())
+    else {
+        return;
+    };
+
+    let allowed_set = Arc::clone(allowed_set);
+    let denied_set = Arc::clone(denied_set);
+    let user_globals = user_globals.clone();
+    let find_spec = vm.new_function(
+        "find_spec",
+        move |args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            // find_spec(fullname, path, target=None)
+            if let Some(name) = args.args.first().and_then(|o| o.str(vm).ok()) {
+                let name = name.as_str();
+                let allowed = check_user_import(name, &allowed_set, &denied_set, allow_forbidden);
+                if let Err(reason) = allowed {
+                    if is_user_code_import(vm, &user_globals) {
+                        denied_log
+                            .lock()
+                            .expect("call log mutex poisoned")
+                            .push(name.to_owned());
+                        return Err(module_not_allowed(vm, name, &reason));
+                    }
+                }
+            }
+            Ok(vm.ctx.none())
+        },
+    );
+    let dict = vm.ctx.new_dict();
+    if dict.set_item("find_spec", find_spec.into(), vm).is_err() {
+        return;
+    }
+    let finder: PyObjectRef = vm.new_module(ALLOWLIST_FINDER_NAME, dict, None).into();
+
+    // Copied out first: reading `__name__` from a user-added finder could
+    // run Python code that touches the list.
+    let entries = meta_path.borrow_vec().to_vec();
+    let mut kept = vec![finder];
+    kept.extend(entries.into_iter().filter(|entry| {
+        !entry
+            .get_attr("__name__", vm)
+            .ok()
+            .and_then(|name| name.str(vm).ok())
+            .is_some_and(|name| name.as_str() == ALLOWLIST_FINDER_NAME)
+    }));
+    *meta_path.borrow_vec_mut() = kept;
+}
+
+/// Global set in every virtual module's namespace so that its own imports are
+/// checked against the allowlist (see [`is_user_code_import`]).
+const VIRTUAL_MODULE_MARKER: &str = "__pyexec_virtual__";
+
+/// Execute a virtual module's `source` and register it in `sys.modules` as
+/// `name`, unless it is already there.
+///
+/// As with a regular import, the module is registered before its body runs,
+/// so a circular import sees the partially initialized module (and fails with
+/// an ordinary `ImportError`/`AttributeError` if it needs names not yet
+/// defined). If the body raises, the module is removed again and the
+/// exception propagates to the importing code.
+fn load_virtual_module(vm: &VirtualMachine, name: &str, source: &str) -> PyResult<()> {
+    let sys_modules = vm.sys_module.get_attr("modules", vm)?;
+    if sys_modules.get_item(name, vm).is_ok() {
+        return Ok(());
+    }
+
+    let code = vm
+        .compile(source, Mode::Exec, format!("<virtual {name}>"))
+        .map_err(|err| vm.new_syntax_error(&err, Some(source)))?;
+    let dict = vm.ctx.new_dict();
+    dict.set_item("__name__", vm.ctx.new_str(name).into(), vm)?;
+    dict.set_item(
+        "__file__",
+        vm.ctx.new_str(format!("<virtual {name}>")).into(),
+        vm,
+    )?;
+    dict.set_item(VIRTUAL_MODULE_MARKER, vm.ctx.new_bool(true).into(), vm)?;
+    let module = vm.new_module(name, dict.clone(), None);
+    sys_modules.set_item(name, module.into(), vm)?;
+
+    let scope = Scope::with_builtins(None, dict, vm);
+    if let Err(exc) = vm.run_code_obj(code, scope) {
+        let _ = sys_modules.del_item(name, vm);
+        return Err(exc);
+    }
+    Ok(())
+}
+
+/// Replace `sys.displayhook` with a no-op, returning the original for the
+/// caller to restore afterwards.
+///
+/// Code compiled in `Mode::Single` passes every expression statement to
+/// `sys.displayhook`, which would otherwise print it to the captured stdout.
+fn silence_displayhook(vm: &VirtualMachine) -> Option<PyObjectRef> {
+    let original = vm.sys_module.get_attr("displayhook", vm).ok()?;
+    let silent = vm.new_function(
+        "displayhook",
+        |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> { Ok(vm.ctx.none()) },
+    );
+    vm.sys_module.set_attr("displayhook", silent, vm).ok()?;
+    Some(original)
+}
+
+/// Apply the per-call recursion limit via `sys.setrecursionlimit`.
+///
+/// Set on every call because user code (or an earlier call on the same pooled
+/// interpreter) may have changed it.
+fn set_recursion_limit(vm: &VirtualMachine, limit: usize) {
+    if let Ok(setter) = vm.sys_module.get_attr("setrecursionlimit", vm) {
+        let _ = setter.call((vm.ctx.new_int(limit),), vm);
+    }
+}
+
+/// Replace `builtins.open` with a read-only view of `files`.
+///
+/// Installed on every call, so snippets never reach the host filesystem
+/// through `open()`, with or without files (see [`ExecutionSettings::files`]).
+/// Binary mode returns an `io.BytesIO`; text mode decodes the bytes and
+/// returns an `io.StringIO` with `\r\n` and `\r` translated to `\n`, like
+/// the universal-newlines default of the real `open()`.
+fn install_virtual_open(vm: &VirtualMachine, files: &Arc<HashMap<String, Vec<u8>>>) {
+    let files = Arc::clone(files);
+    let open_fn = vm.new_function(
+        "open",
+        move |args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            // open(file, mode='r', buffering=-1, encoding=None, errors=None, ...)
+            let arg = |index: usize, name: &str| {
+                args.args
+                    .get(index)
+                    .or_else(|| args.kwargs.get(name))
+                    .filter(|value| !vm.is_none(value))
+                    .cloned()
+            };
+            let path = match arg(0, "file") {
+                Some(file) => file.str(vm)?.as_str().to_owned(),
+                None => {
+                    return Err(vm.new_type_error(
+                        "open() missing required argument 'file' (pos 1)".to_owned(),
+                    ))
+                }
+            };
+            let mode = match arg(1, "mode") {
+                Some(mode) => mode.str(vm)?.as_str().to_owned(),
+                None => "r".to_owned(),
+            };
+
+            if mode.contains(&['w', 'a', 'x', '+'][..]) {
+                return Err(vm.new_exception_msg(
+                    vm.ctx.exceptions.permission_error.to_owned(),
+                    format!("[Errno 30] Read-only file system: '{path}'"),
+                ));
+            }
+            let Some(data) = files.get(path.strip_prefix("./").unwrap_or(&path)) else {
+                return Err(vm.new_exception_msg(
+                    vm.ctx.exceptions.file_not_found_error.to_owned(),
+                    format!("[Errno 2] No such file or directory: '{path}'"),
+                ));
+            };
+
+            // `_io` is loaded through the original `__import__`, so serving
+            // files does not depend on `io` being allowlisted.
+            let io = original_import(vm).call((vm.ctx.new_str("_io"),), vm)?;
+            let bytes: PyObjectRef = vm.ctx.new_bytes(data.clone()).into();
+            if mode.contains('b') {
+                return vm.call_method(&io, "BytesIO", (bytes,));
+            }
+
+            let encoding = arg(3, "encoding").unwrap_or_else(|| vm.ctx.new_str("utf-8").into());
+            let errors = arg(4, "errors").unwrap_or_else(|| vm.ctx.new_str("strict").into());
+            let text = vm.call_method(&bytes, "decode", (encoding, errors))?;
+            let text = text
+                .str(vm)?
+                .as_str()
+                .replace("\r\n", "\n")
+                .replace('\r', "\n");
+            vm.call_method(&io, "StringIO", (vm.ctx.new_str(text),))
+        },
+    );
+    let _ = vm.builtins.set_attr("open", open_fn, vm);
+}
+
 /// Replace `sys.stdout` and `sys.stderr` with write-capturing objects.
 ///
-/// Creates two minimal Python-level objects (one for stdout, one for stderr).
-/// Each has:
-/// - `write(s)`: delegates to `OutputBuffer::write_stdout` / `write_stderr`
-/// - `flush()`: no-op
+/// Each is an instance of the `CapturedStream` class (see
+/// [`CAPTURED_STREAM_SOURCE`]) wrapping a Rust `write` that delegates to
+/// `OutputBuffer::write_stdout` / `write_stderr`, with a `buffer` attribute
+/// (another `CapturedStream`) for raw bytes, like `sys.stdout.buffer`.
 ///
 /// RustPython's `print()` calls `sys.stdout.write(s)` then `sys.stdout.write('\n')`,
-/// so this captures all print output.
+/// so this captures all print output. The `logging` last-resort handler looks
+/// up `sys.stderr` on every write; logging handlers that kept an earlier call's
+/// stream are re-pointed (see [`repoint_log_handlers`]). Warnings are recorded
+/// separately (see [`install_warning_capture`]).
 fn install_output_capture(vm: &VirtualMachine, output: OutputBuffer) {
     let stdout_buf = output.clone();
     let stderr_buf = output;
@@ -651,19 +1605,264 @@ fn install_output_capture(vm: &VirtualMachine, output: OutputBuffer) {
     let stdout_obj = build_writer_object(vm, stdout_buf, true);
     let stderr_obj = build_writer_object(vm, stderr_buf, false);
 
-    let _ = vm.sys_module.set_attr("stdout", stdout_obj, vm);
-    let _ = vm.sys_module.set_attr("stderr", stderr_obj, vm);
+    let _ = vm.sys_module.set_attr("stdout", stdout_obj.clone(), vm);
+    let _ = vm.sys_module.set_attr("stderr", stderr_obj.clone(), vm);
+    let _ = repoint_log_handlers(vm, stdout_obj, stderr_obj);
 }
 
-/// Build a minimal Python object with `write(s)` and `flush()` methods.
+/// Attribute of `builtins` holding [`LOG_HANDLERS_SOURCE`] compiled, saved
+/// the first time [`repoint_log_handlers`] needs it on an interpreter.
+const LOG_HANDLERS_CODE_ATTR: &str = "__pyexec_log_handlers_code__";
+
+/// Moves `logging` handlers still writing to an earlier call's captured
+/// stream onto this call's `stdout` / `stderr`.
 ///
-/// The object is a Python module (namespace) with callable attributes.
-/// When Python calls `obj.write(s)`, it calls the Rust closure which writes to
-/// the `OutputBuffer`.
+/// A `StreamHandler` keeps the stream it was created with, so the handler
+/// `logging.basicConfig()` adds in one call would otherwise keep writing to
+/// that call's output on every later call in a session or pooled interpreter.
+/// Handlers whose `stream` is a property, like `logging.lastResort`, are left
+/// alone.
+const LOG_HANDLERS_SOURCE: &str = "\
+loggers = [logging.root]
+loggers.extend(
+    logger
+    for logger in logging.Logger.manager.loggerDict.values()
+    if isinstance(logger, logging.Logger)
+)
+for logger in loggers:
+    for handler in logger.handlers:
+        stream = handler.__dict__.get('stream')
+        if (
+            type(stream).__name__ == 'CapturedStream'
+            and stream is not stdout
+            and stream is not stderr
+        ):
+            handler.stream = stdout if stream.name == '<stdout>' else stderr
+";
+
+/// Run [`LOG_HANDLERS_SOURCE`] if `logging` has been imported.
+///
+/// Only looks in `sys.modules`, so a call that never uses `logging` does not
+/// import it.
+fn repoint_log_handlers(
+    vm: &VirtualMachine,
+    stdout: PyObjectRef,
+    stderr: PyObjectRef,
+) -> PyResult<()> {
+    let modules = vm.sys_module.get_attr("modules", vm)?;
+    let Ok(logging) = modules.get_item("logging", vm) else {
+        return Ok(());
+    };
+    let code = cached_code(
+        vm,
+        LOG_HANDLERS_CODE_ATTR,
+        LOG_HANDLERS_SOURCE,
+        "<pyexec log handlers>",
+    )?;
+    let scope = vm.new_scope_with_builtins();
+    scope.globals.set_item("logging", logging, vm)?;
+    scope.globals.set_item("stdout", stdout, vm)?;
+    scope.globals.set_item("stderr", stderr, vm)?;
+    vm.run_code_obj(code, scope).map(drop)
+}
+
+/// Attribute of `builtins` holding the current call's replacement for
+/// `warnings.showwarning` (see [`install_warning_capture`]).
+const WARNING_RECORDER_ATTR: &str = "__pyexec_warning_recorder__";
+
+/// Record warnings shown during this call in `log` rather than writing them to
+/// the captured stderr.
+///
+/// `warnings` shows each warning through `warnings.showwarning`, so that is
+/// replaced with a recorder: right away if `warnings` is already loaded, and by
+/// the import hook once it is first imported. A `showwarning` the snippet set
+/// itself, or the one `catch_warnings(record=True)` installs, is left alone.
+/// RustPython prints warnings it issues while `warnings` is not loaded straight
+/// to stderr.
+fn install_warning_capture(vm: &VirtualMachine, log: Arc<Mutex<Vec<String>>>) {
+    let previous = vm.builtins.get_attr(WARNING_RECORDER_ATTR, vm).ok();
+    let recorder = vm.new_function(
+        "showwarning",
+        move |args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            let arg = |index: usize, name: &str| {
+                args.args
+                    .get(index)
+                    .or_else(|| args.kwargs.get(name))
+                    .cloned()
+                    .unwrap_or_else(|| vm.ctx.none())
+            };
+            // An explicit `file` is honoured, as by the original.
+            if !vm.is_none(&arg(4, "file")) {
+                let warnings = vm
+                    .sys_module
+                    .get_attr("modules", vm)?
+                    .get_item("warnings", vm)?;
+                warnings
+                    .get_attr("_showwarning_orig", vm)?
+                    .call(args.clone(), vm)?;
+                return Ok(vm.ctx.none());
+            }
+            let text = |obj: PyObjectRef| obj.str(vm).map(|s| s.as_str().to_owned());
+            let category = arg(1, "category");
+            let category = category.get_attr("__name__", vm).unwrap_or(category);
+            let entry = format!(
+                "{}:{}: {}: {}",
+                text(arg(2, "filename"))?,
+                text(arg(3, "lineno"))?,
+                text(category)?,
+                text(arg(0, "message"))?,
+            );
+            log.lock().expect("warning log mutex poisoned").push(entry);
+            Ok(vm.ctx.none())
+        },
+    );
+    let _ = vm.builtins.set_attr(WARNING_RECORDER_ATTR, recorder, vm);
+    let _ = route_warnings(vm, previous.as_ref());
+}
+
+/// Point `warnings.showwarning` at the current call's recorder if `warnings`
+/// is loaded and `showwarning` is still the module's own or `stale`, an
+/// earlier call's recorder.
+fn route_warnings(vm: &VirtualMachine, stale: Option<&PyObjectRef>) -> PyResult<()> {
+    let modules = vm.sys_module.get_attr("modules", vm)?;
+    let Ok(warnings) = modules.get_item("warnings", vm) else {
+        return Ok(());
+    };
+    let current = warnings.get_attr("showwarning", vm)?;
+    let original = warnings.get_attr("_showwarning_orig", vm)?;
+    if current.is(&original) || stale.is_some_and(|stale| current.is(stale)) {
+        let recorder = vm.builtins.get_attr(WARNING_RECORDER_ATTR, vm)?;
+        warnings.set_attr("showwarning", recorder, vm)?;
+    }
+    Ok(())
+}
+
+/// Whether `name` is in `sys.modules`.
+fn is_loaded(vm: &VirtualMachine, name: &str) -> bool {
+    vm.sys_module
+        .get_attr("modules", vm)
+        .and_then(|modules| modules.get_item(name, vm))
+        .is_ok()
+}
+
+/// Drain the entries recorded so far in a per-call log (warnings, denied
+/// imports).
+fn take_log(log: &Mutex<Vec<String>>) -> Vec<String> {
+    std::mem::take(&mut *log.lock().expect("call log mutex poisoned"))
+}
+
+/// `source` compiled, cached in `builtins` under `attr` after the first call
+/// on an interpreter.
+fn cached_code(
+    vm: &VirtualMachine,
+    attr: &'static str,
+    source: &str,
+    filename: &str,
+) -> PyResult<PyRef<PyCode>> {
+    let cached = vm
+        .builtins
+        .get_attr(attr, vm)
+        .ok()
+        .and_then(|code| code.downcast::<PyCode>().ok());
+    if let Some(code) = cached {
+        return Ok(code);
+    }
+    let code = vm
+        .compile(source, Mode::Exec, filename.to_owned())
+        .map_err(|err| vm.new_syntax_error(&err, Some(source)))?;
+    vm.builtins.set_attr(attr, code.clone(), vm)?;
+    Ok(code)
+}
+
+/// Attribute of `builtins` holding [`CAPTURED_STREAM_SOURCE`] compiled, saved
+/// the first time [`captured_stream_class`] runs on an interpreter.
+const STREAM_CODE_ATTR: &str = "__pyexec_captured_stream_code__";
+
+/// File-like class behind `sys.stdout`, `sys.stderr` and their `buffer`s.
+///
+/// `write` is a Rust function stored on the instance, so it is called without
+/// `self`. Nothing can be closed: `__exit__` leaves the stream usable, since
+/// `with sys.stdout:` must not silence the rest of the snippet.
+const CAPTURED_STREAM_SOURCE: &str = "\
+class CapturedStream:
+    closed = False
+
+    def __init__(self, write, name, buffer=None):
+        self._write = write
+        self.name = name
+        if buffer is not None:
+            self.buffer = buffer
+            self.encoding = 'utf-8'
+            self.errors = 'strict'
+
+    def write(self, data):
+        return self._write(data)
+
+    def writelines(self, lines):
+        for line in lines:
+            self._write(line)
+
+    def flush(self):
+        pass
+
+    def isatty(self):
+        return False
+
+    def readable(self):
+        return False
+
+    def writable(self):
+        return True
+
+    def seekable(self):
+        return False
+
+    def fileno(self):
+        raise UnsupportedOperation('fileno')
+
+    def __enter__(self):
+        return self
+
+    def __exit__(self, *exc_info):
+        return None
+";
+
+/// A fresh `CapturedStream` class from [`CAPTURED_STREAM_SOURCE`].
+///
+/// The source is compiled once per interpreter, but the class is rebuilt on
+/// every call so that a snippet patching it cannot affect the next call on a
+/// pooled interpreter. `fileno()` raises `io.UnsupportedOperation`, with `_io`
+/// loaded through the original `__import__` as in [`install_virtual_open`];
+/// `OSError` (its base class) stands in if `_io` cannot be loaded.
+fn captured_stream_class(vm: &VirtualMachine) -> PyResult<PyObjectRef> {
+    let code = cached_code(
+        vm,
+        STREAM_CODE_ATTR,
+        CAPTURED_STREAM_SOURCE,
+        "<pyexec streams>",
+    )?;
+    let unsupported_operation = original_import(vm)
+        .call((vm.ctx.new_str("_io"),), vm)
+        .and_then(|io| io.get_attr("UnsupportedOperation", vm))
+        .unwrap_or_else(|_| vm.ctx.exceptions.os_error.to_owned().into());
+
+    let scope = vm.new_scope_with_builtins();
+    scope
+        .globals
+        .set_item("UnsupportedOperation", unsupported_operation, vm)?;
+    vm.run_code_obj(code, scope.clone())?;
+    scope.globals.get_item("CapturedStream", vm)
+}
+
+/// Build the `CapturedStream` for stdout or stderr, with its `buffer`.
+///
+/// Both `write`s feed the same `OutputBuffer`: the text one writes `str(s)`
+/// as UTF-8, the `buffer` one writes any bytes-like object as is, so the
+/// captured output can hold bytes that are not valid UTF-8.
 fn build_writer_object(vm: &VirtualMachine, output: OutputBuffer, is_stdout: bool) -> PyObjectRef {
-    // Wrap the OutputBuffer in Arc<Mutex<>> so the closure can own it safely.
+    // Wrap the OutputBuffer in Arc<Mutex<>> so the closures can own it safely.
     let output = Arc::new(Mutex::new(output));
-    let output_clone = Arc::clone(&output);
+    let buffer_output = Arc::clone(&output);
 
     let write_fn = vm.new_function(
         "write",
@@ -676,45 +1875,81 @@ fn build_writer_object(vm: &VirtualMachine, output: OutputBuffer, is_stdout: boo
                 .unwrap_or_default();
 
             let buf = output.lock().expect("OutputBuffer mutex poisoned");
-            let write_result = if is_stdout {
-                buf.write_stdout(data.as_bytes())
-            } else {
-                buf.write_stderr(data.as_bytes())
-            };
-
-            match write_result {
-                Ok(()) => Ok(vm.ctx.new_int(data.len()).into()),
-                Err(ExecutionError::OutputLimitExceeded { limit_bytes }) => {
-                    // Raise an exception; Python code will see a RuntimeError.
-                    Err(vm.new_exception_msg(
-                        vm.ctx.exceptions.runtime_error.to_owned(),
-                        format!("Output limit exceeded: {limit_bytes} bytes"),
-                    ))
-                }
-                Err(_) => Err(vm.new_runtime_error("Write error".to_owned())),
-            }
+            write_captured(vm, &buf, is_stdout, data.as_bytes())
         },
     );
 
-    let flush_fn = vm.new_function(
-        "flush",
-        move |_args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
-            // Keep output_clone alive (ensures the buffer Arc stays valid).
-            let _buf = output_clone.lock().expect("OutputBuffer mutex poisoned");
-            Ok(vm.ctx.none())
+    let buffer_write_fn = vm.new_function(
+        "write",
+        move |args: FuncArgs, vm: &VirtualMachine| -> PyResult<PyObjectRef> {
+            let data = match args.args.first() {
+                Some(obj) => ArgBytesLike::try_from_object(vm, obj.clone())?,
+                None => return Err(vm.new_type_error("write() missing argument".to_owned())),
+            };
+
+            let buf = buffer_output.lock().expect("OutputBuffer mutex poisoned");
+            data.with_ref(|bytes| write_captured(vm, &buf, is_stdout, bytes))
         },
     );
 
-    // Use a Python module as a simple namespace — it supports get_attr/set_attr
-    // and is writable. This is the simplest approach that works with RustPython.
-    let ns = vm.new_module("<writer>", vm.ctx.new_dict(), None);
-    let _ = ns.set_attr("write", write_fn, vm);
-    let _ = ns.set_attr("flush", flush_fn, vm);
-    // Some Python code checks .closed; make it False.
-    let _ = ns.set_attr("closed", vm.ctx.new_bool(false), vm);
-    // Some code checks .encoding attribute.
-    let _ = ns.set_attr("encoding", vm.ctx.new_str("utf-8"), vm);
-    ns.into()
+    let name = if is_stdout { "<stdout>" } else { "<stderr>" };
+    let stream = || -> PyResult<PyObjectRef> {
+        let class = captured_stream_class(vm)?;
+        let buffer = class.call((buffer_write_fn, vm.ctx.new_str(name)), vm)?;
+        class.call((write_fn, vm.ctx.new_str(name), buffer), vm)
+    };
+    // `None` drops output rather than leaving the previous call's stream, and
+    // its buffer, in place on a reused interpreter.
+    stream().unwrap_or_else(|_| vm.ctx.none())
+}
+
+/// Append `data` to the captured stdout or stderr, returning the byte count
+/// as `write()` does, or raising once the output limit is hit. A write that
+/// crosses the limit still keeps the whole characters that fit (see
+/// [`OutputBuffer::write_stdout`]).
+fn write_captured(
+    vm: &VirtualMachine,
+    output: &OutputBuffer,
+    is_stdout: bool,
+    data: &[u8],
+) -> PyResult<PyObjectRef> {
+    let write_result = if is_stdout {
+        output.write_stdout(data)
+    } else {
+        output.write_stderr(data)
+    };
+
+    match write_result {
+        Ok(()) => Ok(vm.ctx.new_int(data.len()).into()),
+        Err(ExecutionError::OutputLimitExceeded { limit_bytes }) => {
+            // Raise an exception; Python code will see a RuntimeError.
+            Err(vm.new_exception_msg(
+                vm.ctx.exceptions.runtime_error.to_owned(),
+                output_limit_message(limit_bytes),
+            ))
+        }
+        Err(_) => Err(vm.new_runtime_error("Write error".to_owned())),
+    }
+}
+
+/// Message of the `RuntimeError` raised by a write past the output limit.
+pub(crate) fn output_limit_message(limit_bytes: usize) -> String {
+    format!("Output limit exceeded: {limit_bytes} bytes")
+}
+
+/// Compile `code_str` without executing it, returning any syntax error.
+///
+/// Does not need an interpreter, so it is cheap enough to call at startup for
+/// cache priming (see [`crate::executor::precompile`]).
+pub(crate) fn compile_check(code_str: &str) -> Result<(), ExecutionError> {
+    rustpython_vm::compiler::compile(
+        code_str,
+        Mode::Exec,
+        "<string>".to_owned(),
+        rustpython_vm::compiler::CompileOpts::default(),
+    )
+    .map(|_| ())
+    .map_err(extract_syntax_error)
 }
 
 /// Convert a RustPython compile error into [`ExecutionError::SyntaxError`].
@@ -727,26 +1962,119 @@ fn extract_syntax_error(err: rustpython_vm::compiler::CompileError) -> Execution
     }
 }
 
-/// Extract a [`ExecutionError::ModuleNotAllowed`] if the exception originated
-/// from our import hook sentinel. Returns `None` if it's a different exception.
+/// Extract a [`ExecutionError::ModuleNotAllowed`] or
+/// [`ExecutionError::ModuleForbidden`] if the exception originated from our
+/// import hook sentinel. Returns `None` if it's a different exception.
 fn extract_module_not_allowed(
     vm: &VirtualMachine,
     exc: &PyBaseExceptionRef,
 ) -> Option<ExecutionError> {
-    // The import hook raises ImportError("ModuleNotAllowed:<name>").
+    // The import hook raises ImportError("ModuleNotAllowed:<name> (<reason>)").
     // We detect this by converting the exception to string and checking the prefix.
     let msg = exc.as_object().str(vm).ok()?;
     let s = msg.as_str();
-    s.strip_prefix("ModuleNotAllowed:").map(|name| ExecutionError::ModuleNotAllowed {
-        module_name: name.to_string(),
+    if let Some(rest) = s.strip_prefix("ModuleForbidden:") {
+        let (name, reason) = rest.split_once(" (")?;
+        return Some(ExecutionError::ModuleForbidden {
+            module_name: name.to_string(),
+            reason: reason.strip_suffix(')').unwrap_or(reason).to_string(),
+        });
+    }
+    s.strip_prefix("ModuleNotAllowed:").map(|rest| {
+        let name = rest.split_once(" (").map_or(rest, |(name, _)| name);
+        ExecutionError::ModuleNotAllowed {
+            module_name: name.to_string(),
+        }
+    })
+}
+
+/// Extract an [`ExecutionError::StdlibUnavailable`] if the exception is a
+/// `ModuleNotFoundError` for an allowed module and `interp` was built without
+/// the stdlib. Returns `None` otherwise.
+fn extract_stdlib_unavailable(
+    vm: &VirtualMachine,
+    exc: &PyBaseExceptionRef,
+    interp: &PyInterp,
+) -> Option<ExecutionError> {
+    let searched_paths = interp.missing_stdlib.as_ref()?;
+    if !exc.fast_isinstance(vm.ctx.exceptions.module_not_found_error) {
+        return None;
+    }
+    let name = exc
+        .as_object()
+        .get_attr("name", vm)
+        .ok()
+        .filter(|name| !vm.is_none(name))?
+        .str(vm)
+        .ok()?;
+    let module_name = name.as_str();
+    let top_level = module_name.split('.').next().unwrap_or(module_name);
+    let allowed = check_module_allowed(module_name, &interp.allowed_set).is_ok()
+        || check_module_allowed(top_level, &interp.allowed_set).is_ok();
+    allowed.then(|| ExecutionError::StdlibUnavailable {
+        module_name: module_name.to_owned(),
+        searched_paths: searched_paths.clone(),
     })
 }
 
-/// Convert a RustPython runtime exception into [`ExecutionError::RuntimeError`].
+/// Exit status of an uncaught `SystemExit`, or `None` for any other
+/// exception.
+///
+/// Follows the interpreter's exit status rules: `None` is `0`, an integer is
+/// kept, and anything else (such as the message passed to `sys.exit("...")`)
+/// becomes `1`, with its `str()` written to stderr as CPython prints it. Like
+/// a mirrored traceback, the message is cut to fit the output limit rather
+/// than overflowing it.
+fn system_exit_code(
+    vm: &VirtualMachine,
+    exc: &PyBaseExceptionRef,
+    output: &OutputBuffer,
+) -> Option<i32> {
+    if !exc.fast_isinstance(vm.ctx.exceptions.system_exit) {
+        return None;
+    }
+    let Some(code) = exc
+        .as_object()
+        .get_attr("code", vm)
+        .ok()
+        .filter(|code| !vm.is_none(code))
+    else {
+        return Some(0);
+    };
+    if code.fast_isinstance(vm.ctx.types.int_type) {
+        return Some(i32::try_from_object(vm, code).unwrap_or(1));
+    }
+    if let Ok(message) = code.str(vm) {
+        output.write_stderr_prefix(format!("{}\n", message.as_str()).as_bytes());
+    }
+    Some(1)
+}
+
+/// Write `exc`'s formatted traceback to the captured stderr, as CPython does
+/// for an uncaught exception. It is cut to fit the output limit rather than
+/// overflowing it (see [`OutputBuffer::write_stderr_prefix`]).
+fn mirror_traceback(vm: &VirtualMachine, exc: &PyBaseExceptionRef, output: &OutputBuffer) {
+    let mut traceback = String::new();
+    let _ = vm.write_exception(&mut traceback, exc);
+    output.write_stderr_prefix(traceback.as_bytes());
+}
+
+/// Convert a RustPython runtime exception into [`ExecutionError::RuntimeError`],
+/// or [`ExecutionError::RecursionLimitExceeded`] for a `RecursionError`.
 ///
 /// Uses `vm.write_exception` to capture the full traceback. `String` implements
 /// `rustpython_vm::py_io::Write` via `write_fmt`, so no custom wrapper needed.
-fn extract_runtime_error(vm: &VirtualMachine, exc: PyBaseExceptionRef) -> ExecutionError {
+fn extract_runtime_error(
+    vm: &VirtualMachine,
+    exc: PyBaseExceptionRef,
+    recursion_limit: usize,
+) -> ExecutionError {
+    if exc.fast_isinstance(vm.ctx.exceptions.recursion_error) {
+        return ExecutionError::RecursionLimitExceeded {
+            limit: recursion_limit,
+        };
+    }
+
     // Get exception message via str().
     let message = exc
         .as_object()
@@ -758,31 +2086,275 @@ fn extract_runtime_error(vm: &VirtualMachine, exc: PyBaseExceptionRef) -> Execut
     let mut traceback = String::new();
     let _ = vm.write_exception(&mut traceback, &exc);
 
-    ExecutionError::RuntimeError { message, traceback }
+    let (line, col) = user_error_location(&exc).unzip();
+    ExecutionError::RuntimeError {
+        message,
+        traceback,
+        line,
+        col,
+        frames: traceback_frames(&exc),
+    }
+}
+
+/// Walk the exception's `__traceback__` into [`TracebackFrame`]s, outermost
+/// call first.
+fn traceback_frames(exc: &PyBaseExceptionRef) -> Vec<TracebackFrame> {
+    let Some(tb) = exc.traceback() else {
+        return Vec::new();
+    };
+    tb.iter()
+        .map(|tb| TracebackFrame {
+            filename: tb.frame.code.source_path.as_str().to_owned(),
+            lineno: tb.lineno.get(),
+            function: tb.frame.code.obj_name.as_str().to_owned(),
+        })
+        .collect()
+}
+
+/// 1-based `(line, col)` of the innermost traceback frame belonging to the
+/// submitted source, which is compiled as `<string>`.
+///
+/// Stdlib, frozen, and virtual-module frames are skipped, so an exception
+/// raised inside a library call points at the user's call site. Returns `None`
+/// when no traceback frame comes from user code.
+fn user_error_location(exc: &PyBaseExceptionRef) -> Option<(u32, u32)> {
+    let user_tb = exc
+        .traceback()?
+        .iter()
+        .filter(|tb| tb.frame.code.source_path.as_str() == TracebackFrame::USER_CODE_FILENAME)
+        .last()?;
+    let location = user_tb.frame.current_location();
+    Some((location.row.get(), location.column.get()))
+}
+
+/// Answer one inspection query against the post-execution scope.
+///
+/// Queries are independent: an exception raised while answering one becomes
+/// that query's [`InspectOutcome::Error`] and does not affect the others.
+fn run_inspection(
+    vm: &VirtualMachine,
+    scope: &Scope,
+    query: &InspectQuery,
+    deadline: Option<Instant>,
+) -> InspectResult {
+    let (outcome, value_repr) = match query {
+        InspectQuery::NameExists(name) => (passed_if(lookup_name(vm, scope, name).is_some()), None),
+        InspectQuery::IsCallable(name) => match lookup_name(vm, scope, name) {
+            Some(obj) => (passed_if(obj.is_callable()), None),
+            None => (inspect_error(format!("name '{name}' is not defined")), None),
+        },
+        InspectQuery::HasAttr { obj, attr } => match eval_in_scope(vm, scope, obj) {
+            Ok(target) => match vm.get_attribute_opt(target, attr.as_str()) {
+                Ok(found) => (passed_if(found.is_some()), None),
+                Err(exc) => (exception_outcome(vm, exc), None),
+            },
+            Err(outcome) => (outcome, None),
+        },
+        InspectQuery::EvalExpr(expr) => {
+            if deadline.is_some_and(|d| Instant::now() >= d) {
+                (inspect_error("time budget exhausted".to_owned()), None)
+            } else {
+                match eval_in_scope(vm, scope, expr) {
+                    Ok(value) => match value.repr(vm) {
+                        Ok(r) => (InspectOutcome::Passed, Some(r.as_str().to_owned())),
+                        Err(exc) => (exception_outcome(vm, exc), None),
+                    },
+                    Err(outcome) => (outcome, None),
+                }
+            }
+        }
+    };
+    InspectResult {
+        query: query.clone(),
+        outcome,
+        value_repr,
+    }
+}
+
+fn passed_if(ok: bool) -> InspectOutcome {
+    if ok {
+        InspectOutcome::Passed
+    } else {
+        InspectOutcome::Failed
+    }
+}
+
+fn inspect_error(message: String) -> InspectOutcome {
+    InspectOutcome::Error { message }
+}
+
+/// Look `name` up in the scope's globals, then in builtins.
+fn lookup_name(vm: &VirtualMachine, scope: &Scope, name: &str) -> Option<PyObjectRef> {
+    scope
+        .globals
+        .get_item_opt(name, vm)
+        .ok()
+        .flatten()
+        .or_else(|| vm.builtins.get_attr(name, vm).ok())
+}
+
+/// Compile `expr` in eval mode and evaluate it in `scope`.
+///
+/// Runs with the same import hook as the snippet itself, so the allowlist
+/// still applies.
+fn eval_in_scope(
+    vm: &VirtualMachine,
+    scope: &Scope,
+    expr: &str,
+) -> Result<PyObjectRef, InspectOutcome> {
+    let code = vm
+        .compile(expr, Mode::Eval, "<inspect>".to_owned())
+        .map_err(|e| inspect_error(format!("SyntaxError: {e}")))?;
+    vm.run_code_obj(code, scope.clone())
+        .map_err(|exc| exception_outcome(vm, exc))
+}
+
+/// Reduce an exception raised by a query to a one-line [`InspectOutcome::Error`].
+fn exception_outcome(vm: &VirtualMachine, exc: PyBaseExceptionRef) -> InspectOutcome {
+    match extract_module_not_allowed(vm, &exc) {
+        Some(ExecutionError::ModuleNotAllowed { module_name }) => {
+            return inspect_error(format!("ModuleNotAllowed: {module_name}"));
+        }
+        Some(ExecutionError::ModuleForbidden { module_name, .. }) => {
+            return inspect_error(format!("ModuleForbidden: {module_name}"));
+        }
+        _ => {}
+    }
+    let class_name = exc.class().name().to_string();
+    let message = exc
+        .as_object()
+        .str(vm)
+        .map(|s| s.as_str().to_owned())
+        .unwrap_or_default();
+    inspect_error(if message.is_empty() {
+        class_name
+    } else {
+        format!("{class_name}: {message}")
+    })
 }
 
 /// Try to extract the last expression value from the execution scope.
 ///
 /// Uses the `__result__` variable name convention: executor.rs wraps the last
-/// expression as `__result__ = <expr>` before compilation. This function looks
-/// for `__result__` in `scope.locals` and returns its `repr()` if found.
-fn extract_return_value(vm: &VirtualMachine, scope: &Scope) -> Option<String> {
+/// expression as `__result__ = <expr>` and renames the variable to
+/// `result_name` before compilation. This function looks for `result_name` in
+/// `scope.locals` and returns its value (`None` when unbound).
+fn extract_return_value(
+    vm: &VirtualMachine,
+    scope: &Scope,
+    result_name: &str,
+) -> Option<PyObjectRef> {
     // scope.locals is an ArgMapping which Deref's to PyObject via AsRef.
-    // We call .get("__result__") on it (Python dict protocol).
+    // We call .get(result_name) on it (Python dict protocol).
     let locals_obj: PyObjectRef = scope.locals.as_ref().to_owned();
 
-    let result_obj = vm
-        .call_method(&locals_obj, "get", (vm.ctx.new_str("__result__"),))
-        .ok()?;
+    vm.call_method(&locals_obj, "get", (vm.ctx.new_str(result_name),))
+        .ok()
+}
 
-    if vm.is_none(&result_obj) {
-        return None;
+/// Containers nested deeper than this are reported as [`PyValue::Other`].
+const PY_VALUE_MAX_DEPTH: usize = 64;
+
+/// Convert `obj` into a [`PyValue`].
+fn to_py_value(vm: &VirtualMachine, obj: &PyObjectRef) -> PyValue {
+    to_py_value_inner(vm, obj, &mut Vec::new())
+}
+
+/// [`to_py_value`], with `path` holding the ids of the containers currently
+/// being converted, so a self-referencing container ends in `Other`.
+fn to_py_value_inner(vm: &VirtualMachine, obj: &PyObjectRef, path: &mut Vec<usize>) -> PyValue {
+    let other = || PyValue::Other(repr_string(vm, obj));
+    if vm.is_none(obj) {
+        return PyValue::None;
+    }
+    // Only exact types are converted; subclasses such as IntEnum members keep
+    // their own repr.
+    let class = obj.class();
+    if class.is(vm.ctx.types.bool_type) {
+        return PyValue::Bool(obj.is(&vm.ctx.true_value));
+    }
+    if class.is(vm.ctx.types.int_type) {
+        return i64::try_from_object(vm, obj.clone()).map_or_else(|_| other(), PyValue::Int);
+    }
+    if class.is(vm.ctx.types.float_type) {
+        if let Some(float) = obj.downcast_ref::<PyFloat>() {
+            return PyValue::Float(float.to_f64());
+        }
+    }
+    if class.is(vm.ctx.types.str_type) {
+        if let Some(string) = obj.downcast_ref::<PyStr>() {
+            return PyValue::Str(string.as_str().to_owned());
+        }
     }
 
-    result_obj
-        .repr(vm)
-        .ok()
-        .map(|s| s.as_str().to_owned())
+    let id = obj.get_id();
+    if path.len() >= PY_VALUE_MAX_DEPTH || path.contains(&id) {
+        return other();
+    }
+    path.push(id);
+    let mut convert = |item: &PyObjectRef| to_py_value_inner(vm, item, path);
+    let value = if class.is(vm.ctx.types.list_type) {
+        let items = obj
+            .downcast_ref::<PyList>()
+            .map(|list| list.borrow_vec().to_vec());
+        items.map(|items| PyValue::List(items.iter().map(&mut convert).collect()))
+    } else if class.is(vm.ctx.types.tuple_type) {
+        let items = obj
+            .downcast_ref::<PyTuple>()
+            .map(|tuple| tuple.as_slice().to_vec());
+        items.map(|items| PyValue::Tuple(items.iter().map(&mut convert).collect()))
+    } else if class.is(vm.ctx.types.dict_type) {
+        let pairs: Option<Vec<(PyObjectRef, PyObjectRef)>> = obj
+            .downcast_ref::<PyDict>()
+            .map(|dict| dict.into_iter().collect());
+        pairs.map(|pairs| {
+            let pairs = pairs
+                .iter()
+                .map(|(key, value)| (convert(key), convert(value)));
+            PyValue::Dict(pairs.collect())
+        })
+    } else {
+        None
+    };
+    path.pop();
+    value.unwrap_or_else(other)
+}
+
+/// `repr(obj)`, or an empty string if its `repr` raises.
+fn repr_string(vm: &VirtualMachine, obj: &PyObjectRef) -> String {
+    obj.repr(vm)
+        .map_or_else(|_| String::new(), |s| s.as_str().to_owned())
+}
+
+/// `repr(obj)`, or `None` if its `repr` raises.
+fn try_repr(vm: &VirtualMachine, obj: &PyObjectRef) -> Option<String> {
+    obj.repr(vm).ok().map(|s| s.as_str().to_owned())
+}
+
+/// The result reported for a run that panicked inside the VM, with the panic
+/// payload as the [`ExecutionError::Internal`] message.
+pub(crate) fn panic_result(payload: &(dyn std::any::Any + Send)) -> VmRunResult {
+    let detail = payload
+        .downcast_ref::<&str>()
+        .map(|s| s.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown panic".to_string());
+    VmRunResult {
+        stdout: String::new(),
+        stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
+        compile_ns: 0,
+        execute_ns: 0,
+        return_value: None,
+        typed_value: None,
+        error: Some(ExecutionError::Internal {
+            message: format!("interpreter panicked: {detail}"),
+        }),
+        exit_code: None,
+        inspections: Vec::new(),
+        slot_id: None,
+    }
 }
 
 // ── Tests ─────────────────────────────────────────────────────────────────────
@@ -792,6 +2364,18 @@ mod tests {
     use super::*;
     use crate::types::DEFAULT_ALLOWED_MODULES;
 
+    #[test]
+    fn test_panic_result_reports_payload() {
+        let payload: Box<dyn std::any::Any + Send> = Box::new("boom");
+        let result = panic_result(payload.as_ref());
+        assert_eq!(
+            result.error,
+            Some(ExecutionError::Internal {
+                message: "interpreter panicked: boom".to_string(),
+            })
+        );
+    }
+
     fn make_allowed_set() -> HashSet<String> {
         DEFAULT_ALLOWED_MODULES
             .iter()
@@ -801,8 +2385,8 @@ mod tests {
 
     fn run(code: &str) -> VmRunResult {
         let output = OutputBuffer::new(1_048_576);
-        let interp = build_interpreter(make_allowed_set(), output.clone());
-        run_code(&interp, code, output)
+        let interp = build_interpreter(make_allowed_set(), output.clone(), true, None);
+        run_code(&interp, code, output, &RunOptions::default())
     }
 
     // (1) print statement verifies stdout capture
@@ -847,12 +2431,29 @@ mod tests {
     #[test]
     #[ignore = "slow: VM init per test"]
     fn test_denied_module() {
-        let result = run("import socket");
+        let result = run("import shutil");
         match result.error {
             Some(ExecutionError::ModuleNotAllowed { module_name }) => {
+                assert_eq!(module_name, "shutil");
+            }
+            other => panic!("Expected ModuleNotAllowed(shutil), got: {:?}", other),
+        }
+    }
+
+    // (4b) forbidden module returns ModuleForbidden with its reason
+    #[test]
+    #[ignore = "slow: VM init per test"]
+    fn test_forbidden_module() {
+        let result = run("import socket");
+        match result.error {
+            Some(ExecutionError::ModuleForbidden {
+                module_name,
+                reason,
+            }) => {
                 assert_eq!(module_name, "socket");
+                assert_eq!(reason, "network access is not permitted in the sandbox");
             }
-            other => panic!("Expected ModuleNotAllowed(socket), got: {:?}", other),
+            other => panic!("Expected ModuleForbidden(socket), got: {:?}", other),
         }
     }
 
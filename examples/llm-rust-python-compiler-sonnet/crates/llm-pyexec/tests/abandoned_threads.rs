@@ -0,0 +1,44 @@
+// crates/llm-pyexec/tests/abandoned_threads.rs
+// Tests: timeout::abandoned_threads under repeated fallback timeouts
+//
+// Changes the global concurrency limit, so it lives in its own test binary.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use llm_pyexec::timeout::abandoned_threads;
+use llm_pyexec::{execute, ConcurrencyLimit, ExecutionError, ExecutionMode, ExecutionSettings};
+
+const MAX: usize = 2;
+
+/// Fresh interpreters that outlive their timeout keep their concurrency
+/// permit, so repeated timeouts cannot pile up threads past the limit, and the
+/// count drains once the snippets finish.
+#[test]
+fn test_repeated_timeouts_do_not_grow_threads_past_limit() {
+    ConcurrencyLimit::global().set_max(MAX);
+    let settings = ExecutionSettings {
+        execution_mode: ExecutionMode::Isolated,
+        extra_allowed_modules: vec!["time".to_string()],
+        timeout_ns: 50_000_000,
+        ..ExecutionSettings::default()
+    };
+
+    let mut overloaded = 0;
+    for _ in 0..6 {
+        let result = execute("import time\ntime.sleep(1)", settings.clone());
+        match result.error {
+            Some(ExecutionError::Timeout { .. }) => {}
+            Some(ExecutionError::Overloaded { .. }) => overloaded += 1,
+            other => panic!("expected Timeout or Overloaded, got {other:?}"),
+        }
+        assert!(abandoned_threads() <= MAX, "{}", abandoned_threads());
+    }
+    assert!(overloaded > 0);
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while abandoned_threads() > 0 && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(abandoned_threads(), 0);
+}
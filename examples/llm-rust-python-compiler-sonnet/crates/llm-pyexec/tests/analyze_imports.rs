@@ -0,0 +1,63 @@
+// crates/llm-pyexec/tests/analyze_imports.rs
+// Tests: analyze_imports / ImportAnalysis
+
+use llm_pyexec::{analyze_imports, ExecutionError};
+
+fn modules(code: &str) -> Vec<String> {
+    analyze_imports(code).expect("parse").modules
+}
+
+#[test]
+fn test_import_and_from_import() {
+    assert_eq!(modules("import a\nfrom b.c import d"), ["a", "b.c"]);
+}
+
+#[test]
+fn test_literal_dunder_import_is_captured() {
+    let analysis = analyze_imports("m = __import__(\"e\")").expect("parse");
+    assert_eq!(analysis.modules, ["e"]);
+    assert!(!analysis.has_dynamic_imports);
+
+    assert_eq!(modules("__import__(name='f')"), ["f"]);
+}
+
+#[test]
+fn test_non_literal_dunder_import_is_flagged() {
+    let analysis = analyze_imports("name = 'o' + 's'\nm = __import__(name)").expect("parse");
+    assert!(analysis.modules.is_empty());
+    assert!(analysis.has_dynamic_imports);
+}
+
+/// Imports anywhere in the tree count, each module once, in source order.
+#[test]
+fn test_nested_duplicate_and_relative_imports() {
+    let code = "\
+import json, os.path as p
+def f():
+    import socket
+    from . import sibling
+if False:
+    from ..pkg.mod import x
+import json
+class C:
+    g = lambda: __import__('re')";
+    assert_eq!(
+        modules(code),
+        ["json", "os.path", "socket", ".", "..pkg.mod", "re"]
+    );
+}
+
+#[test]
+fn test_no_imports() {
+    let analysis = analyze_imports("x = 1\nprint(x)").expect("parse");
+    assert_eq!(analysis.modules, Vec::<String>::new());
+    assert!(!analysis.has_dynamic_imports);
+}
+
+#[test]
+fn test_syntax_error() {
+    match analyze_imports("import\n") {
+        Err(ExecutionError::SyntaxError { line, .. }) => assert_eq!(line, 1),
+        other => panic!("expected SyntaxError, got {other:?}"),
+    }
+}
@@ -0,0 +1,17 @@
+// crates/llm-pyexec/tests/cache_capacity_env.rs
+// Tests: PYEXEC_CACHE_CAPACITY is honored by BytecodeCache::global()
+//
+// This binary must contain only this test: the global cache reads the env var
+// exactly once, so it has to be set before anything touches `global()`.
+
+use llm_pyexec::BytecodeCache;
+
+#[test]
+fn test_global_cache_reads_capacity_env_var() {
+    std::env::set_var("PYEXEC_CACHE_CAPACITY", "3");
+    assert_eq!(BytecodeCache::global().capacity(), 3);
+
+    // Read-once semantics: later changes are ignored.
+    std::env::set_var("PYEXEC_CACHE_CAPACITY", "7");
+    assert_eq!(BytecodeCache::global().capacity(), 3);
+}
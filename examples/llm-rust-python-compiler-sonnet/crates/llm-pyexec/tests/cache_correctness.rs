@@ -1,10 +1,10 @@
 // crates/llm-pyexec/tests/cache_correctness.rs
-// Tests: AC-05, AC-16
+// Tests: AC-05, AC-16, cache stats, precompile, use_cache
 
 use std::sync::Mutex;
 
 use llm_pyexec::cache::BytecodeCache;
-use llm_pyexec::{execute, ExecutionSettings};
+use llm_pyexec::{execute, precompile, ExecutionSettings};
 
 /// Serialise all tests in this binary so that the shared global `BytecodeCache`
 /// singleton is not mutated concurrently by two tests at the same time.
@@ -80,3 +80,67 @@ fn test_cache_capacity_env_var() {
          (PYEXEC_BYTECODE_CACHE_SIZE env var may not have been set)"
     );
 }
+
+/// Two executions of the same snippet → exactly one cache miss, then one hit.
+///
+/// Run: cargo test --test cache_correctness -- test_cache_stats_miss_then_hit
+#[test]
+fn test_cache_stats_miss_then_hit() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    BytecodeCache::global().clear();
+    BytecodeCache::global().reset_stats();
+
+    let code = "sum(range(10))";
+    let first = execute(code, ExecutionSettings::default());
+    let second = execute(code, ExecutionSettings::default());
+    assert!(first.error.is_none(), "unexpected error: {:?}", first.error);
+    assert!(second.error.is_none(), "unexpected error: {:?}", second.error);
+
+    let stats = BytecodeCache::global().stats();
+    assert_eq!(stats.misses, 1, "first execution must miss: {stats:?}");
+    assert_eq!(stats.hits, 1, "second execution must hit: {stats:?}");
+}
+
+/// Precompiling a snippet makes the first real execution a cache hit.
+///
+/// Run: cargo test --test cache_correctness -- test_precompile_then_execute_is_hit
+#[test]
+fn test_precompile_then_execute_is_hit() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    BytecodeCache::global().clear();
+
+    let code = "[i * 3 for i in range(5)]";
+    let summary = precompile(&[code, "def broken(:"]);
+    assert_eq!(summary.compiled, 1);
+    assert_eq!(summary.rejected, 1);
+
+    BytecodeCache::global().reset_stats();
+    let result = execute(code, ExecutionSettings::default());
+    assert!(result.error.is_none(), "unexpected error: {:?}", result.error);
+
+    let stats = BytecodeCache::global().stats();
+    assert_eq!(stats.hits, 1, "precompiled source must hit: {stats:?}");
+    assert_eq!(stats.misses, 0, "precompiled source must not miss: {stats:?}");
+}
+
+/// `use_cache: false` bypasses the global cache entirely.
+///
+/// Run: cargo test --test cache_correctness -- test_use_cache_false_leaves_cache_untouched
+#[test]
+fn test_use_cache_false_leaves_cache_untouched() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    BytecodeCache::global().clear();
+    BytecodeCache::global().reset_stats();
+
+    let settings = ExecutionSettings {
+        use_cache: false,
+        ..ExecutionSettings::default()
+    };
+    let result = execute("40 + 2", settings);
+    assert!(result.error.is_none(), "unexpected error: {:?}", result.error);
+    assert_eq!(result.return_value, Some("42".to_string()));
+
+    assert_eq!(BytecodeCache::global().len(), 0, "cache must not be populated");
+    let stats = BytecodeCache::global().stats();
+    assert_eq!(stats.hits + stats.misses, 0, "cache must not be consulted: {stats:?}");
+}
@@ -0,0 +1,101 @@
+// crates/llm-pyexec/tests/cancellation.rs
+// Tests: execute_cancellable / ExecutionHandle / ExecutionError::Cancelled
+//
+// Checks the global pool's idle count, so it lives in its own test binary.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{
+    execute, execute_cancellable, ExecutionError, ExecutionMode, ExecutionSettings, InterpreterPool,
+};
+
+const BUSY_LOOP: &str = "total = 0\nwhile True:\n    total += 1";
+
+fn long_timeout() -> ExecutionSettings {
+    ExecutionSettings {
+        timeout_ns: 30_000_000_000,
+        ..ExecutionSettings::default()
+    }
+}
+
+/// Wait until every slot of the global pool is idle again.
+fn wait_for_idle_pool() {
+    let pool = InterpreterPool::global();
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while pool.idle_count() < pool.size() {
+        assert!(Instant::now() < deadline, "pool slot never became idle");
+        thread::sleep(Duration::from_millis(10));
+    }
+}
+
+/// Cancelling an infinite loop ends the call promptly with `Cancelled`, and
+/// the pool slot it ran on goes back to the pool.
+#[test]
+fn test_cancel_infinite_loop_returns_cancelled_and_frees_slot() {
+    let (handle, pending) = execute_cancellable(BUSY_LOOP, long_timeout());
+    thread::sleep(Duration::from_millis(200));
+    assert!(!pending.is_finished());
+
+    let cancelled_at = Instant::now();
+    handle.cancel();
+    let result = pending.join();
+    assert!(
+        cancelled_at.elapsed() < Duration::from_secs(5),
+        "cancel took {:?}",
+        cancelled_at.elapsed()
+    );
+    assert_eq!(result.error, Some(ExecutionError::Cancelled));
+    assert!(result.used_pool);
+    assert!(handle.is_cancelled());
+
+    wait_for_idle_pool();
+    let result = execute("1 + 1", ExecutionSettings::default());
+    assert_eq!(result.return_value.as_deref(), Some("2"));
+}
+
+/// The interrupt is raised again at every instruction, so user code cannot
+/// swallow it, not even with `except BaseException`.
+#[test]
+fn test_cancel_not_swallowed_by_except_base_exception() {
+    let code = "\
+while True:
+    try:
+        while True:
+            pass
+    except BaseException:
+        pass";
+    let (handle, pending) = execute_cancellable(code, long_timeout());
+    thread::sleep(Duration::from_millis(200));
+    handle.cancel();
+    assert_eq!(pending.join().error, Some(ExecutionError::Cancelled));
+}
+
+/// A call cancelled before it reaches an interpreter never runs.
+#[test]
+fn test_cancel_before_start_does_not_run() {
+    let settings = ExecutionSettings {
+        execution_mode: ExecutionMode::Isolated,
+        ..long_timeout()
+    };
+    let (handle, pending) = execute_cancellable(BUSY_LOOP, settings);
+    handle.cancel();
+    let result = pending.join();
+    assert_eq!(result.error, Some(ExecutionError::Cancelled));
+}
+
+/// Cancelling a call that already finished leaves its result alone.
+#[test]
+fn test_cancel_after_finish_is_ignored() {
+    let (handle, pending) = execute_cancellable("print('hi')\n6 * 7", ExecutionSettings::default());
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !pending.is_finished() {
+        assert!(Instant::now() < deadline, "call never finished");
+        thread::sleep(Duration::from_millis(10));
+    }
+    handle.cancel();
+    let result = pending.join();
+    assert_eq!(result.error, None);
+    assert_eq!(result.stdout, "hi\n");
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+}
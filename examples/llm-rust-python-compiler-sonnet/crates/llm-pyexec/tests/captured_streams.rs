@@ -0,0 +1,96 @@
+// crates/llm-pyexec/tests/captured_streams.rs
+// Tests: file-like API of the sys.stdout / sys.stderr replacements
+
+use llm_pyexec::{execute, ExecutionResult, ExecutionSettings};
+
+fn run(code: &str) -> ExecutionResult {
+    let result = execute(code, ExecutionSettings::default());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    result
+}
+
+#[test]
+fn test_writelines() {
+    let result = run(
+        "import sys\nsys.stdout.writelines(['a\\n', 'b\\n'])\nsys.stderr.writelines(iter(['c']))",
+    );
+    assert_eq!(result.stdout, "a\nb\n");
+    assert_eq!(result.stderr, "c");
+}
+
+#[test]
+fn test_print_to_stderr_with_flush() {
+    let result =
+        run("import sys\nprint('out', flush=True)\nprint('err', file=sys.stderr, flush=True)");
+    assert_eq!(result.stdout, "out\n");
+    assert_eq!(result.stderr, "err\n");
+}
+
+/// `with sys.stdout:` yields the stream and leaves it open afterwards.
+#[test]
+fn test_context_manager_keeps_stream_open() {
+    let code = "\
+import sys
+with sys.stdout as out:
+    out.write('inside\\n')
+with sys.stderr:
+    print('warn', file=sys.stderr)
+print('after', sys.stdout.closed)";
+    let result = run(code);
+    assert_eq!(result.stdout, "inside\nafter False\n");
+    assert_eq!(result.stderr, "warn\n");
+}
+
+#[test]
+fn test_capability_queries() {
+    let code = "\
+import sys
+for stream in (sys.stdout, sys.stderr, sys.stdout.buffer):
+    print(stream.isatty(), stream.readable(), stream.writable(), stream.seekable())
+print(sys.stdout.name, sys.stderr.name, sys.stdout.encoding)";
+    let result = run(code);
+    assert_eq!(
+        result.stdout,
+        "False False True False\n".repeat(3) + "<stdout> <stderr> utf-8\n"
+    );
+}
+
+#[test]
+fn test_fileno_raises_unsupported_operation() {
+    let code = "\
+import sys
+try:
+    sys.stdout.fileno()
+except OSError as e:
+    print(type(e).__name__)";
+    assert_eq!(run(code).stdout, "UnsupportedOperation\n");
+}
+
+/// Raw bytes go to the same buffer as text, in write order.
+#[test]
+fn test_buffer_write_lands_in_matching_stream() {
+    let code = "\
+import sys
+print('a', end='')
+sys.stdout.buffer.write(b'b')
+sys.stdout.buffer.writelines([b'c', bytearray(b'd')])
+sys.stdout.buffer.flush()
+sys.stderr.buffer.write('é'.encode())";
+    let result = run(code);
+    assert_eq!(result.stdout, "abcd");
+    assert_eq!(result.stderr, "é");
+    assert!(!result.output_lossy);
+}
+
+/// A snippet that patches the stream class does not affect the next call.
+#[test]
+fn test_patched_stream_class_does_not_leak() {
+    for _ in 0..8 {
+        run("import sys\ntype(sys.stdout).write = lambda self, s: 0");
+    }
+    assert_eq!(run("print('still here')").stdout, "still here\n");
+}
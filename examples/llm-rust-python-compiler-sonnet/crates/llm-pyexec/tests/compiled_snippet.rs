@@ -0,0 +1,173 @@
+// crates/llm-pyexec/tests/compiled_snippet.rs
+// Tests: two-phase CompiledSnippet::compile / execute
+
+use std::sync::Mutex;
+
+use llm_pyexec::{
+    compile, execute, execute_compiled, BytecodeCache, CompiledSnippet, ExecutionError,
+    ExecutionMode, ExecutionOverrides, ExecutionSettings, InterpreterPool,
+};
+
+/// Serialise tests in this binary so pool metrics are not disturbed by a
+/// concurrently running test.
+static TEST_LOCK: Mutex<()> = Mutex::new(());
+
+/// Compile-then-execute produces the same result as execute().
+#[test]
+fn test_compile_then_execute_matches_execute() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let code = "xs = [i * i for i in range(10)]\nprint(len(xs))\nsum(xs)";
+
+    let direct = execute(code, ExecutionSettings::default());
+    let snippet = CompiledSnippet::compile(code, ExecutionSettings::default())
+        .expect("valid source compiles");
+    let staged = snippet.execute(ExecutionOverrides::default());
+
+    assert!(
+        staged.error.is_none(),
+        "unexpected error: {:?}",
+        staged.error
+    );
+    assert_eq!(staged.stdout, direct.stdout);
+    assert_eq!(staged.stderr, direct.stderr);
+    assert_eq!(staged.return_value, direct.return_value);
+    assert_eq!(staged.return_value.as_deref(), Some("285"));
+}
+
+/// A syntax error is reported by compile() without dispatching to the pool.
+#[test]
+fn test_syntax_error_at_compile_time_without_pool() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let pool = InterpreterPool::global();
+    let before = pool.metrics();
+
+    let err = CompiledSnippet::compile("def f(:\n", ExecutionSettings::default())
+        .expect_err("syntax error must surface at compile time");
+    assert!(
+        matches!(err, ExecutionError::SyntaxError { .. }),
+        "expected SyntaxError, got {err:?}"
+    );
+
+    let after = pool.metrics();
+    assert_eq!(
+        after.dispatches, before.dispatches,
+        "compile must not dispatch"
+    );
+    assert_eq!(
+        after.fallbacks, before.fallbacks,
+        "compile must not fall back"
+    );
+}
+
+/// Runtime limits can be overridden at execute time.
+#[test]
+fn test_execute_overrides_runtime_limits() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let snippet = CompiledSnippet::compile("print('x' * 1000)", ExecutionSettings::default())
+        .expect("valid source compiles");
+
+    let limited = snippet.execute(ExecutionOverrides {
+        max_output_bytes: Some(10),
+        ..ExecutionOverrides::default()
+    });
+    assert_eq!(
+        limited.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 10 })
+    );
+
+    let unlimited = snippet.execute(ExecutionOverrides::default());
+    assert!(
+        unlimited.error.is_none(),
+        "unexpected error: {:?}",
+        unlimited.error
+    );
+}
+
+/// One CompiledSnippet executed from 4 threads at once.
+#[test]
+fn test_execute_one_snippet_from_four_threads() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let snippet = CompiledSnippet::compile(
+        "total = sum(range(100))\ntotal",
+        ExecutionSettings::default(),
+    )
+    .expect("valid source compiles");
+
+    let handles: Vec<_> = (0..4)
+        .map(|_| {
+            let snippet = snippet.clone();
+            std::thread::spawn(move || snippet.execute(ExecutionOverrides::default()))
+        })
+        .collect();
+
+    for handle in handles {
+        let result = handle.join().expect("thread panicked");
+        assert!(
+            result.error.is_none(),
+            "unexpected error: {:?}",
+            result.error
+        );
+        assert_eq!(result.return_value.as_deref(), Some("4950"));
+    }
+}
+
+/// compile() once, then execute_compiled() with a different full set of
+/// settings each time.
+#[test]
+fn test_execute_compiled_with_varying_settings() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let snippet = compile("print('hi')\n6 * 7").expect("valid source compiles");
+
+    let default = execute_compiled(&snippet, ExecutionSettings::default());
+    assert!(
+        default.error.is_none(),
+        "unexpected error: {:?}",
+        default.error
+    );
+    assert_eq!(default.stdout, "hi\n");
+    assert_eq!(default.return_value.as_deref(), Some("42"));
+
+    let limited = execute_compiled(
+        &snippet,
+        ExecutionSettings {
+            max_output_bytes: 1,
+            ..ExecutionSettings::default()
+        },
+    );
+    assert_eq!(
+        limited.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 1 })
+    );
+
+    let isolated = execute_compiled(
+        &snippet,
+        ExecutionSettings {
+            execution_mode: ExecutionMode::Isolated,
+            ..ExecutionSettings::default()
+        },
+    );
+    assert_eq!(isolated.return_value.as_deref(), Some("42"));
+    assert!(!isolated.used_pool);
+}
+
+/// Every run hits the entry inserted at compile time; none re-inserts it.
+#[test]
+fn test_execute_compiled_reuses_cache_entry() {
+    let _guard = TEST_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    let cache = BytecodeCache::global();
+    let snippet = compile("xs = list(range(5))\nsum(xs)").expect("valid source compiles");
+    assert_eq!(
+        cache.get(&snippet.cache_key()).as_deref(),
+        Some(snippet.source())
+    );
+
+    let before = cache.stats();
+    for _ in 0..5 {
+        let result = execute_compiled(&snippet, ExecutionSettings::default());
+        assert_eq!(result.return_value.as_deref(), Some("10"));
+        assert!(result.diagnostics.cache_hit);
+    }
+    let after = cache.stats();
+    assert_eq!(after.inserts, before.inserts, "runs must not re-insert");
+    assert!(after.hits >= before.hits + 5);
+}
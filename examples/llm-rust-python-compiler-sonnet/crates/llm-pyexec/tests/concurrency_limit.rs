@@ -0,0 +1,68 @@
+// crates/llm-pyexec/tests/concurrency_limit.rs
+// Tests: ConcurrencyLimit / ExecutionError::Overloaded
+//
+// Changes the global limit, so it lives in its own test binary.
+
+use std::sync::{Arc, Barrier};
+use std::thread;
+
+use llm_pyexec::{execute, ConcurrencyLimit, ExecutionError, ExecutionMode, ExecutionSettings};
+
+const MAX: usize = 2;
+const CALLERS: usize = 12;
+
+fn sleeping_settings() -> ExecutionSettings {
+    ExecutionSettings {
+        execution_mode: ExecutionMode::Isolated,
+        extra_allowed_modules: vec!["time".to_string()],
+        ..ExecutionSettings::default()
+    }
+}
+
+/// A burst far past the ceiling is refused with `Overloaded` instead of
+/// spawning an interpreter per caller, and the ceiling frees up afterwards.
+#[test]
+fn test_burst_past_ceiling_is_overloaded() {
+    let limit = ConcurrencyLimit::global();
+    limit.set_max(MAX);
+
+    let barrier = Arc::new(Barrier::new(CALLERS));
+    let handles: Vec<_> = (0..CALLERS)
+        .map(|_| {
+            let barrier = Arc::clone(&barrier);
+            thread::spawn(move || {
+                barrier.wait();
+                execute("import time\ntime.sleep(0.5)", sleeping_settings())
+            })
+        })
+        .collect();
+    let results: Vec<_> = handles
+        .into_iter()
+        .map(|handle| handle.join().expect("caller thread panicked"))
+        .collect();
+
+    let overloaded = results
+        .iter()
+        .filter(|result| {
+            result.error
+                == Some(ExecutionError::Overloaded {
+                    max_concurrency: MAX,
+                })
+        })
+        .count();
+    let ran = results
+        .iter()
+        .filter(|result| result.error.is_none())
+        .count();
+    assert!(overloaded > 0, "no call was refused: {results:?}");
+    assert_eq!(overloaded + ran, CALLERS, "{results:?}");
+    assert!(results
+        .iter()
+        .filter(|result| result.error.is_some())
+        .all(|result| !result.used_pool && result.exec_duration_ns == 0));
+
+    // Every permit was released once the interpreters finished.
+    assert_eq!(limit.active(), 0);
+    let result = execute("1 + 1", sleeping_settings());
+    assert_eq!(result.return_value.as_deref(), Some("2"));
+}
@@ -0,0 +1,130 @@
+// crates/llm-pyexec/tests/conformance.rs
+// Tests: sandbox conformance suite (feature `conformance`)
+//
+// Run: cargo test -p llm-pyexec --features conformance --test conformance
+
+#![cfg(feature = "conformance")]
+
+use llm_pyexec::conformance;
+use llm_pyexec::{ExecutionSettings, DEFAULT_ALLOWED_MODULES};
+
+/// Probes the default configuration does not resist yet, with the detail
+/// each currently reports. Each sandbox hardening change that closes one of
+/// these removes it from this list.
+const KNOWN_GAPS: &[(&str, &str)] = &[
+    ("environ_leak", "host environment readable via os.environ"),
+    (
+        "baseline_module_mutation",
+        "attribute set on sys by one call is visible to the next",
+    ),
+];
+
+fn is_known_gap(name: &str) -> bool {
+    KNOWN_GAPS.iter().any(|(gap, _)| *gap == name)
+}
+
+/// Probes whose attack target (`socket` / `importlib`) becomes reachable once
+/// the allowlist is weakened to include those modules and forbidden modules
+/// are left to it.
+const ALLOWLIST_PROBES: &[&str] = &["import_hook_restore", "importlib_bypass", "name_spoofing"];
+
+/// The default configuration passes every probe except the tracked gaps,
+/// which fail as recorded: closing a gap makes this fail until it is removed
+/// from [`KNOWN_GAPS`].
+#[test]
+fn test_default_configuration_passes_all_but_known_gaps() {
+    let report = conformance::run(&ExecutionSettings::default());
+    assert_eq!(
+        report.probes.len(),
+        9,
+        "unexpected probe count: {report:#?}"
+    );
+
+    for probe in &report.probes {
+        if let Some((_, detail)) = KNOWN_GAPS.iter().find(|(gap, _)| *gap == probe.name) {
+            assert!(
+                !probe.passed,
+                "known gap {} now passes ({}); remove it from KNOWN_GAPS",
+                probe.name, probe.detail
+            );
+            assert_eq!(probe.detail, *detail, "known gap {}", probe.name);
+            continue;
+        }
+        assert!(
+            probe.passed,
+            "probe {} failed: {}",
+            probe.name, probe.detail
+        );
+    }
+}
+
+/// Strict attributes are guarded wherever `os` gets them from.
+#[test]
+fn test_strict_attributes_pass_denied_attribute_probe() {
+    let strict = ExecutionSettings {
+        strict_attributes: true,
+        ..ExecutionSettings::default()
+    };
+    let report = conformance::run(&strict);
+    let probe = report
+        .get("denied_attribute_bypass")
+        .expect("denied_attribute_bypass probe");
+    assert!(probe.passed, "detail: {}", probe.detail);
+    assert!(
+        probe
+            .detail
+            .ends_with("guarded, including through sys.modules"),
+        "detail: {}",
+        probe.detail
+    );
+}
+
+/// Allowing the probe target modules fails exactly the allowlist probes (plus
+/// any tracked gap), with details naming what got through.
+#[test]
+fn test_weakened_allowlist_fails_expected_probes() {
+    let mut allowed_modules: Vec<String> = DEFAULT_ALLOWED_MODULES
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+    allowed_modules.push("socket".to_string());
+    allowed_modules.push("importlib".to_string());
+    let weakened = ExecutionSettings {
+        allowed_modules,
+        allow_forbidden: true,
+        ..ExecutionSettings::default()
+    };
+
+    let report = conformance::run(&weakened);
+
+    let mut failed: Vec<&str> = report
+        .failures()
+        .map(|p| p.name)
+        .filter(|name| !is_known_gap(name) || ALLOWLIST_PROBES.contains(name))
+        .collect();
+    failed.sort_unstable();
+    let mut expected = ALLOWLIST_PROBES.to_vec();
+    expected.sort_unstable();
+    assert_eq!(failed, expected, "report: {report:#?}");
+
+    let importlib = report
+        .get("importlib_bypass")
+        .expect("importlib_bypass probe");
+    assert!(
+        importlib
+            .detail
+            .contains("importlib.import_module('socket') was not denied"),
+        "detail: {}",
+        importlib.detail
+    );
+    let restore = report
+        .get("import_hook_restore")
+        .expect("import_hook_restore probe");
+    assert!(
+        restore
+            .detail
+            .starts_with("import of 'socket' not denied after __import__ replacement"),
+        "detail: {}",
+        restore.detail
+    );
+}
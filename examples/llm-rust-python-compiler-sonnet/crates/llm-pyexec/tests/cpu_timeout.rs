@@ -0,0 +1,103 @@
+// crates/llm-pyexec/tests/cpu_timeout.rs
+// Tests: ExecutionSettings::cpu_timeout_ns (CPU-time budget vs wall-clock timeout)
+
+use std::time::Duration;
+
+use llm_pyexec::{
+    execute_with, BytecodeCache, ExecutionError, ExecutionSettings, InterpreterPool, TimeoutKind,
+};
+
+const BUSY_LOOP: &str = "total = 0\nwhile True:\n    total += 1";
+
+fn cpu_budget(cpu_timeout_ns: u64) -> ExecutionSettings {
+    ExecutionSettings {
+        timeout_ns: 30_000_000_000,
+        cpu_timeout_ns: Some(cpu_timeout_ns),
+        allowed_modules: vec!["time".to_string()],
+        ..ExecutionSettings::default()
+    }
+}
+
+/// A CPU-bound loop trips the CPU budget long before the wall-clock timeout.
+#[test]
+fn test_busy_loop_trips_cpu_timeout() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let result = execute_with(&pool, &cache, BUSY_LOOP, cpu_budget(200_000_000));
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Timeout {
+            limit_ns: 200_000_000,
+            kind: TimeoutKind::Cpu,
+        })
+    );
+    assert!(
+        Duration::from_nanos(result.duration_ns) < Duration::from_secs(10),
+        "took {}ns",
+        result.duration_ns
+    );
+}
+
+/// The budget is raised as a `BaseException`, so `except Exception` in user
+/// code cannot swallow it.
+#[test]
+fn test_cpu_timeout_not_caught_by_except_exception() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "try:\n    while True:\n        pass\nexcept Exception:\n    pass\n'escaped'";
+    let result = execute_with(&pool, &cache, code, cpu_budget(100_000_000));
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Timeout {
+            limit_ns: 100_000_000,
+            kind: TimeoutKind::Cpu,
+        })
+    );
+    assert_eq!(result.return_value, None);
+}
+
+/// Sleeping uses no CPU, so it does not count against the budget.
+#[cfg(unix)]
+#[test]
+fn test_sleep_does_not_trip_cpu_timeout() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "import time\ntime.sleep(0.5)\n'rested'";
+    let result = execute_with(&pool, &cache, code, cpu_budget(100_000_000));
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'rested'"));
+    assert!(Duration::from_nanos(result.duration_ns) >= Duration::from_millis(500));
+}
+
+/// A slot whose previous call tripped the budget runs the next call normally,
+/// with or without a budget of its own.
+#[test]
+fn test_slot_reusable_after_cpu_timeout() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let tripped = execute_with(&pool, &cache, BUSY_LOOP, cpu_budget(100_000_000));
+    assert!(matches!(
+        tripped.error,
+        Some(ExecutionError::Timeout { .. })
+    ));
+
+    let code = "sum(range(100000))";
+    let unbudgeted = execute_with(&pool, &cache, code, ExecutionSettings::default());
+    assert!(
+        unbudgeted.error.is_none(),
+        "unexpected error: {:?}",
+        unbudgeted.error
+    );
+    let budgeted = execute_with(&pool, &cache, code, cpu_budget(2_000_000_000));
+    assert!(
+        budgeted.error.is_none(),
+        "unexpected error: {:?}",
+        budgeted.error
+    );
+    assert_eq!(budgeted.return_value.as_deref(), Some("4999950000"));
+    assert!(budgeted.used_pool);
+}
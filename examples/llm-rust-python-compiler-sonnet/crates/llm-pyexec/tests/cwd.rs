@@ -0,0 +1,70 @@
+// crates/llm-pyexec/tests/cwd.rs
+// Tests: ExecutionSettings::cwd for os.path computations
+
+#![cfg(unix)]
+
+use std::path::PathBuf;
+
+use llm_pyexec::{execute, execute_with, BytecodeCache, ExecutionSettings, InterpreterPool};
+
+fn with_cwd(cwd: &str) -> ExecutionSettings {
+    ExecutionSettings {
+        cwd: Some(PathBuf::from(cwd)),
+        ..ExecutionSettings::default()
+    }
+}
+
+#[test]
+fn test_abspath_resolves_against_configured_cwd() {
+    let result = execute(
+        "import os.path\np = os.path.abspath('x')\np",
+        with_cwd("/sandbox/work"),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'/sandbox/work/x'"));
+}
+
+#[test]
+fn test_relative_parent_resolves_against_configured_cwd() {
+    let result = execute(
+        "import os.path\np = os.path.abspath('../data/in.csv')\np",
+        with_cwd("/sandbox/work"),
+    );
+    assert_eq!(
+        result.return_value.as_deref(),
+        Some("'/sandbox/data/in.csv'")
+    );
+}
+
+/// `os` is reachable alongside `os.path`; its `getcwd` reports the same directory.
+#[test]
+fn test_getcwd_returns_configured_cwd() {
+    let result = execute(
+        "import os\ncwd = os.getcwd()\ncwd",
+        with_cwd("/sandbox/work"),
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'/sandbox/work'"));
+}
+
+/// A pool slot that ran with a cwd reports the host's again on the next call.
+#[test]
+fn test_cwd_is_restored_on_pool_slot() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "import os.path\np = os.path.abspath('x')\np";
+
+    let overridden = execute_with(&pool, &cache, code, with_cwd("/sandbox/work"));
+    assert!(overridden.used_pool);
+    assert_eq!(
+        overridden.return_value.as_deref(),
+        Some("'/sandbox/work/x'")
+    );
+
+    let host = execute_with(&pool, &cache, code, ExecutionSettings::default());
+    assert!(host.used_pool);
+    assert_ne!(host.return_value, overridden.return_value);
+}
@@ -0,0 +1,46 @@
+// crates/llm-pyexec/tests/debug_info.rs
+// Tests: ExecutionSettings::debug, ExecutionResult::wrapped_source / cache_key_hex
+
+use llm_pyexec::cache::cache_key;
+use llm_pyexec::{execute, ExecutionSettings, Session};
+
+fn debug() -> ExecutionSettings {
+    ExecutionSettings {
+        debug: true,
+        ..ExecutionSettings::default()
+    }
+}
+
+/// With `debug` on, the result shows the rewritten source and its cache key.
+#[test]
+fn test_debug_reports_wrapped_source_and_cache_key() {
+    let result = execute("1 + 1", debug());
+    assert_eq!(result.return_value.as_deref(), Some("2"));
+    assert_eq!(result.wrapped_source.as_deref(), Some("__result__ = 1 + 1"));
+
+    let key = result.cache_key_hex.expect("cache_key_hex");
+    assert_eq!(key.len(), 64);
+    assert!(key.chars().all(|c| matches!(c, '0'..='9' | 'a'..='f')));
+    assert_eq!(key, cache_key("__result__ = 1 + 1").to_hex());
+}
+
+/// Normal runs leave both fields unset.
+#[test]
+fn test_debug_off_by_default() {
+    let result = execute("1 + 1", ExecutionSettings::default());
+    assert_eq!(result.wrapped_source, None);
+    assert_eq!(result.cache_key_hex, None);
+}
+
+/// Sessions report the same debug information.
+#[test]
+fn test_session_debug_reports_wrapped_source() {
+    let session = Session::new(debug());
+    let result = session.eval("x = 3\nx * 2");
+    assert_eq!(result.return_value.as_deref(), Some("6"));
+    assert_eq!(
+        result.wrapped_source.as_deref(),
+        Some("x = 3\n__result__ = x * 2")
+    );
+    assert!(result.cache_key_hex.is_some());
+}
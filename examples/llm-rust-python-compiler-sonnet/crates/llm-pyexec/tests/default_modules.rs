@@ -0,0 +1,93 @@
+// crates/llm-pyexec/tests/default_modules.rs
+// Tests: modules added to DEFAULT_ALLOWED_MODULES; ExecutionSettings::default_plus
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, DEFAULT_ALLOWED_MODULES};
+
+fn value_of(code: &str) -> String {
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "{code}\nstderr: {}", result.stderr);
+    result.return_value.expect("return value")
+}
+
+#[test]
+fn test_heapq() {
+    let code = "\
+import heapq
+h = [5, 1, 4]
+heapq.heapify(h)
+heapq.heappush(h, 0)
+[heapq.heappop(h) for _ in range(4)]";
+    assert_eq!(value_of(code), "[0, 1, 4, 5]");
+}
+
+#[test]
+fn test_bisect() {
+    let code = "\
+import bisect
+xs = [1, 3, 5]
+bisect.insort(xs, 4)
+(bisect.bisect_left(xs, 4), xs)";
+    assert_eq!(value_of(code), "(2, [1, 3, 4, 5])");
+}
+
+#[test]
+fn test_textwrap() {
+    let code = "import textwrap\ntextwrap.wrap('the quick brown fox', width=10)";
+    assert_eq!(value_of(code), "['the quick', 'brown fox']");
+}
+
+#[test]
+fn test_copy() {
+    let code = "\
+import copy
+a = [[1], [2]]
+b = copy.deepcopy(a)
+b[0].append(9)
+(a, copy.copy(a) is a)";
+    assert_eq!(value_of(code), "([[1], [2]], False)");
+}
+
+#[test]
+fn test_enum() {
+    let code = "\
+import enum
+class Color(enum.Enum):
+    RED = 1
+    GREEN = 2
+(Color(2).name, [c.value for c in Color])";
+    assert_eq!(value_of(code), "('GREEN', [1, 2])");
+}
+
+#[test]
+fn test_operator() {
+    let code = "\
+import operator
+rows = [(2, 'b'), (1, 'a')]
+(sorted(rows, key=operator.itemgetter(0)), operator.add(2, 3))";
+    assert_eq!(value_of(code), "([(1, 'a'), (2, 'b')], 5)");
+}
+
+#[test]
+fn test_new_modules_are_in_default_list() {
+    for module in ["heapq", "bisect", "textwrap", "copy", "enum", "operator"] {
+        assert!(DEFAULT_ALLOWED_MODULES.contains(&module), "{module}");
+    }
+}
+
+/// `default_plus` keeps the default list and adds to it.
+#[test]
+fn test_default_plus_allows_extra_module() {
+    let denied = execute("import time", ExecutionSettings::default());
+    assert!(matches!(
+        denied.error,
+        Some(ExecutionError::ModuleNotAllowed { .. })
+    ));
+
+    let settings = ExecutionSettings::default_plus(&["time"]);
+    let result = execute(
+        "import time, heapq\nisinstance(time.time(), float)",
+        settings,
+    );
+    assert_eq!(result.error, None);
+    assert_eq!(result.return_value.as_deref(), Some("True"));
+}
@@ -0,0 +1,143 @@
+// crates/llm-pyexec/tests/denied_attributes.rs
+// Tests: ExecutionSettings::denied_attributes and strict_attributes
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+fn strict() -> ExecutionSettings {
+    ExecutionSettings {
+        strict_attributes: true,
+        ..ExecutionSettings::default()
+    }
+}
+
+fn runtime_error(error: &Option<ExecutionError>) -> (&str, &str) {
+    match error {
+        Some(ExecutionError::RuntimeError {
+            message, traceback, ..
+        }) => (message, traceback),
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_strict_keeps_os_path_and_guards_os_system() {
+    let allowed = execute("import os\nos.path.join('a', 'b')", strict());
+    assert_eq!(allowed.error, None, "stderr: {}", allowed.stderr);
+    assert_eq!(allowed.return_value.as_deref(), Some("'a/b'"));
+
+    let denied = execute("import os\nos.system('ls')", strict());
+    let (message, traceback) = runtime_error(&denied.error);
+    assert_eq!(message, "os.system is not allowed");
+    assert!(traceback.contains("PermissionError"), "{traceback}");
+}
+
+#[test]
+fn test_from_import_gets_the_guard() {
+    let code = "\
+from os import getenv
+try:
+    getenv('HOME')
+except PermissionError as e:
+    result = str(e)
+result";
+    let result = execute(code, strict());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(
+        result.return_value.as_deref(),
+        Some("'os.getenv is not allowed'")
+    );
+}
+
+#[test]
+fn test_custom_entry_guards_allowed_module_function() {
+    let settings = ExecutionSettings {
+        denied_attributes: vec!["json.dumps".to_string()],
+        ..ExecutionSettings::default()
+    };
+    let result = execute("import json\njson.loads('[1]')", settings.clone());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+
+    let result = execute("import json\njson.dumps([1])", settings);
+    assert_eq!(runtime_error(&result.error).0, "json.dumps is not allowed");
+}
+
+#[test]
+fn test_sys_settrace_is_guarded_under_strict() {
+    let result = execute("import sys\nsys.settrace(None)", strict());
+    assert_eq!(
+        runtime_error(&result.error).0,
+        "sys.settrace is not allowed"
+    );
+}
+
+/// Strict and permissive calls share the pooled interpreters: once a strict
+/// call ends, `os.system` is the real function again.
+#[test]
+fn test_pooled_calls_after_strict_see_originals() {
+    for _ in 0..8 {
+        let result = execute("import os\nos.system('true')", strict());
+        assert!(result.error.is_some());
+
+        let result = execute(
+            "import os\nos.system.__name__",
+            ExecutionSettings::default(),
+        );
+        assert_eq!(result.error, None, "stderr: {}", result.stderr);
+        assert_eq!(result.return_value.as_deref(), Some("'system'"));
+    }
+}
+
+/// A module bound in a session scope stays guarded in later calls.
+#[test]
+fn test_session_scope_stays_guarded() {
+    let session = Session::new(strict());
+    let first = session.eval("import os");
+    assert_eq!(first.error, None, "stderr: {}", first.stderr);
+
+    let second = session.eval("os.system('ls')");
+    assert_eq!(runtime_error(&second.error).0, "os.system is not allowed");
+}
+
+/// `os` re-exports the functions of `posix` / `nt`; reaching them through
+/// `sys.modules` does not get around the guard.
+#[test]
+fn test_strict_guards_os_backing_module() {
+    let result = execute(
+        "import os\nimport sys\nsys.modules[os.name].system('true')",
+        strict(),
+    );
+    let (message, _) = runtime_error(&result.error);
+    assert!(
+        message.ends_with(".system is not allowed") && !message.starts_with("os."),
+        "{message}"
+    );
+}
+
+/// Data attributes are withheld too, including through the backing module.
+#[test]
+fn test_data_attributes_are_guarded() {
+    let settings = ExecutionSettings {
+        denied_attributes: vec!["sys.stdin".to_string(), "os.environ".to_string()],
+        ..ExecutionSettings::default()
+    };
+    let result = execute("import sys\nsys.stdin.read()", settings.clone());
+    assert_eq!(runtime_error(&result.error).0, "sys.stdin is not allowed");
+
+    let result = execute("import os\nos.environ.get('PATH')", settings.clone());
+    assert_eq!(runtime_error(&result.error).0, "os.environ is not allowed");
+
+    let result = execute(
+        "import os\nimport sys\nlen(sys.modules[os.name].environ)",
+        settings,
+    );
+    let (message, _) = runtime_error(&result.error);
+    assert!(message.ends_with(".environ is not allowed"), "{message}");
+
+    // The originals are back for the next call.
+    let result = execute(
+        "import sys\ntype(sys.stdin).__name__ != '__pyexec_denied_attribute__'",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("True"));
+}
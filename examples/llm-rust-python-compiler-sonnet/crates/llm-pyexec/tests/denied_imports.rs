@@ -0,0 +1,79 @@
+// crates/llm-pyexec/tests/denied_imports.rs
+// Tests: ExecutionResult::denied_imports lists every refused import, caught or not
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+#[test]
+fn test_caught_denial_is_reported_on_success() {
+    let code = "try:\n import socket\nexcept ImportError:\n pass\nprint(\"ok\")";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "ok\n");
+    assert_eq!(result.denied_imports, ["socket"]);
+}
+
+#[test]
+fn test_every_attempt_is_listed_in_order() {
+    let code = "\
+for name in ['socket', 'subprocess', 'socket', 'json']:
+    try:
+        __import__(name)
+    except ImportError:
+        pass";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.denied_imports, ["socket", "subprocess", "socket"]);
+}
+
+#[test]
+fn test_uncaught_denial_is_listed_alongside_the_error() {
+    let result = execute("import urllib.request", ExecutionSettings::default());
+    assert!(matches!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed { .. })
+    ));
+    assert_eq!(result.denied_imports, ["urllib.request"]);
+}
+
+/// Stdlib modules' own imports are never refused, so a clean run reports
+/// nothing.
+#[test]
+fn test_allowed_imports_leave_the_list_empty() {
+    let result = execute(
+        "import json, datetime\njson.dumps([1])",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert!(
+        result.denied_imports.is_empty(),
+        "{:?}",
+        result.denied_imports
+    );
+}
+
+#[test]
+fn test_denials_through_importlib_are_listed() {
+    let code = "\
+import importlib
+try:
+    importlib.import_module('socket')
+except ImportError:
+    pass";
+    let result = execute(code, ExecutionSettings::default_plus(&["importlib"]));
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.denied_imports, ["socket"]);
+}
+
+#[test]
+fn test_session_calls_report_only_their_own_denials() {
+    let session = Session::new(ExecutionSettings::default());
+    let first = session.eval("try:\n import socket\nexcept ImportError:\n pass");
+    assert_eq!(first.denied_imports, ["socket"]);
+
+    let second = session.eval("1 + 1");
+    assert!(
+        second.denied_imports.is_empty(),
+        "{:?}",
+        second.denied_imports
+    );
+}
@@ -0,0 +1,73 @@
+// crates/llm-pyexec/tests/denied_modules.rs
+// Tests: ExecutionSettings::denied_modules and the reason given for a denial
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+fn xml_without_etree() -> ExecutionSettings {
+    ExecutionSettings {
+        extra_allowed_modules: vec!["xml".to_string()],
+        denied_modules: vec!["xml.etree".to_string()],
+        ..ExecutionSettings::default()
+    }
+}
+
+fn denied_name(error: &Option<ExecutionError>) -> &str {
+    match error {
+        Some(ExecutionError::ModuleNotAllowed { module_name }) => module_name,
+        other => panic!("expected ModuleNotAllowed, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_denied_submodule_of_allowed_package() {
+    let allowed = execute("import xml.dom\n'ok'", xml_without_etree());
+    assert_eq!(allowed.error, None, "stderr: {}", allowed.stderr);
+
+    let denied = execute("import xml.etree.ElementTree", xml_without_etree());
+    assert_eq!(denied_name(&denied.error), "xml.etree.ElementTree");
+}
+
+#[test]
+fn test_denied_entry_overrides_allowlist() {
+    let settings = ExecutionSettings {
+        denied_modules: vec!["json".to_string()],
+        ..ExecutionSettings::default()
+    };
+    let result = execute("import json", settings);
+    assert_eq!(denied_name(&result.error), "json");
+}
+
+/// Stdlib modules keep importing their own dependencies: `json` uses `re`.
+#[test]
+fn test_denied_module_still_usable_by_stdlib() {
+    let settings = ExecutionSettings {
+        denied_modules: vec!["re".to_string()],
+        ..ExecutionSettings::default()
+    };
+    let result = execute("import json\njson.dumps([1])", settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("'[1]'"));
+}
+
+/// User code that catches the ImportError sees why the module was denied.
+#[test]
+fn test_caught_denial_message_includes_reason() {
+    let code = "\
+def reason(name):
+    try:
+        __import__(name)
+    except ImportError as e:
+        return str(e)
+
+[reason('shutil'), reason('xml.etree'), reason('xml.etree.ElementTree')]";
+    let result = execute(code, xml_without_etree());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(
+        result.return_value.as_deref(),
+        Some(
+            "['ModuleNotAllowed:shutil (not in the allowlist)', \
+             'ModuleNotAllowed:xml.etree (explicitly denied)', \
+             \"ModuleNotAllowed:xml.etree.ElementTree (package 'xml.etree' is denied)\"]"
+        )
+    );
+}
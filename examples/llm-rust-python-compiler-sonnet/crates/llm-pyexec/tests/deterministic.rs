@@ -0,0 +1,87 @@
+// crates/llm-pyexec/tests/deterministic.rs
+// Tests: ExecutionSettings::deterministic (fixed clock and seeded random)
+
+use llm_pyexec::{
+    execute, execute_with, BytecodeCache, DeterministicConfig, ExecutionSettings, InterpreterPool,
+};
+
+/// 2023-11-14 22:13:20 UTC.
+const EPOCH: i64 = 1_700_000_000;
+
+fn deterministic() -> ExecutionSettings {
+    ExecutionSettings {
+        deterministic: Some(DeterministicConfig {
+            epoch_seconds: EPOCH,
+            random_seed: 42,
+        }),
+        ..ExecutionSettings::default()
+    }
+}
+
+const SNIPPET: &str = "\
+import datetime
+import random
+print(datetime.date.today())
+print(datetime.datetime.now())
+print(random.random())";
+
+#[test]
+fn test_same_config_gives_identical_stdout() {
+    let first = execute(SNIPPET, deterministic());
+    let second = execute(SNIPPET, deterministic());
+    assert_eq!(first.error, None, "stderr: {}", first.stderr);
+    assert_eq!(first.stdout, second.stdout);
+    assert_eq!(
+        first.stdout,
+        "2023-11-14\n2023-11-14 22:13:20\n0.6394267984578837\n"
+    );
+}
+
+#[test]
+fn test_time_module_reports_fixed_instant() {
+    let settings = ExecutionSettings {
+        extra_allowed_modules: vec!["time".to_string()],
+        ..deterministic()
+    };
+    let code = "import time\nprint(time.time(), time.time_ns(), time.localtime().tm_hour)";
+    let result = execute(code, settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "1700000000.0 1700000000000000000 22\n");
+}
+
+#[test]
+fn test_different_seed_changes_random_only() {
+    let mut settings = deterministic();
+    settings.deterministic = Some(DeterministicConfig {
+        epoch_seconds: EPOCH,
+        random_seed: 7,
+    });
+    let result = execute(SNIPPET, settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(
+        result.stdout,
+        "2023-11-14\n2023-11-14 22:13:20\n0.32383276483316237\n"
+    );
+}
+
+/// A pool slot that ran deterministically reads the real clock again on the
+/// next call.
+#[test]
+fn test_clock_is_restored_on_pool_slot() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "import datetime\ndatetime.datetime.now().year";
+
+    let pinned = execute_with(&pool, &cache, code, deterministic());
+    assert!(pinned.used_pool);
+    assert_eq!(pinned.return_value.as_deref(), Some("2023"));
+
+    let real = execute_with(&pool, &cache, code, ExecutionSettings::default());
+    assert!(real.used_pool);
+    let year: i32 = real
+        .return_value
+        .as_deref()
+        .and_then(|year| year.parse().ok())
+        .expect("a year");
+    assert!(year > 2023, "clock still pinned: {year}");
+}
@@ -0,0 +1,91 @@
+// crates/llm-pyexec/tests/duration_split.rs
+// Tests: ExecutionResult::queue_duration_ns / exec_duration_ns
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{
+    execute_with, BytecodeCache, ExecutionError, ExecutionMode, ExecutionSettings, InspectQuery,
+    InterpreterPool, TimeoutKind,
+};
+
+#[test]
+fn test_durations_add_up_on_idle_pool() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let result = execute_with(&pool, &cache, "6 * 7", ExecutionSettings::default());
+    assert!(result.used_pool);
+    assert!(result.exec_duration_ns > 0);
+    assert!(result.queue_duration_ns + result.exec_duration_ns <= result.duration_ns);
+    assert_eq!(result.queue_duration_ns, result.diagnostics.pool_wait_ns);
+}
+
+#[test]
+fn test_isolated_run_has_no_queue_time() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let settings = ExecutionSettings {
+        execution_mode: ExecutionMode::Isolated,
+        ..ExecutionSettings::default()
+    };
+
+    let result = execute_with(&pool, &cache, "6 * 7", settings);
+    assert_eq!(result.queue_duration_ns, 0);
+    assert!(result.exec_duration_ns > 0);
+}
+
+/// A call that spends its whole timeout waiting for the only slot times out
+/// without running: the timeout covers checkout as well as execution.
+#[test]
+fn test_slow_checkout_counts_against_timeout() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    std::thread::scope(|scope| {
+        let busy = scope.spawn(|| {
+            execute_with(
+                &pool,
+                &cache,
+                "total = 0\nfor i in range(3000000):\n    total += i\ntotal",
+                ExecutionSettings::default(),
+            )
+        });
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while pool.idle_count() > 0 {
+            assert!(Instant::now() < deadline, "slot never became busy");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let timeout = Duration::from_millis(100);
+        let result = execute_with(
+            &pool,
+            &cache,
+            "x = 6 * 7\nx",
+            ExecutionSettings {
+                timeout_ns: timeout.as_nanos() as u64,
+                inspect: Some(vec![InspectQuery::EvalExpr("x + 1".to_string())]),
+                ..ExecutionSettings::default()
+            },
+        );
+        let busy = busy.join().expect("busy call panicked");
+        assert!(busy.error.is_none(), "busy call failed: {:?}", busy.error);
+
+        assert_eq!(
+            result.error,
+            Some(ExecutionError::Timeout {
+                limit_ns: timeout.as_nanos() as u64,
+                kind: TimeoutKind::Wall,
+            })
+        );
+        assert!(
+            result.queue_duration_ns >= timeout.as_nanos() as u64,
+            "checkout should have used up the timeout, waited {}ns",
+            result.queue_duration_ns
+        );
+        assert!(!result.used_pool);
+        assert_eq!(result.exec_duration_ns, 0);
+        assert!(result.inspections.is_empty());
+        assert!(result.duration_ns >= result.queue_duration_ns);
+    });
+}
@@ -0,0 +1,78 @@
+// crates/llm-pyexec/tests/embedded_stdlib.rs
+// Tests: the `embedded-stdlib` feature — the AC-13 module matrix with no host
+// stdlib paths (run with `cargo test --features embedded-stdlib`)
+#![cfg(feature = "embedded-stdlib")]
+
+use llm_pyexec::{execute, ExecutionMode, ExecutionSettings};
+
+/// No host stdlib: `stdlib_paths: Some(vec![])` overrides both
+/// `PYEXEC_STDLIB_PATH` and autodetection.
+fn no_host_paths() -> ExecutionSettings {
+    ExecutionSettings {
+        stdlib_paths: Some(Vec::new()),
+        ..ExecutionSettings::default()
+    }
+}
+
+#[test]
+fn test_ac13_modules_import_without_host_stdlib() {
+    let must_pass: &[(&str, &str)] = &[
+        ("math", "import math; x = math.sqrt(4)"),
+        ("re", "import re; m = re.match(r'\\d+', '123')"),
+        ("json", "import json; s = json.dumps({'key': 'value'})"),
+        ("datetime", "import datetime; d = datetime.date.today()"),
+        (
+            "collections",
+            "import collections; c = collections.Counter('aabbcc')",
+        ),
+        (
+            "itertools",
+            "import itertools; x = list(itertools.islice(itertools.count(), 3))",
+        ),
+        (
+            "functools",
+            "import functools; f = functools.reduce(lambda a, b: a + b, [1, 2, 3])",
+        ),
+        ("string", "import string; letters = string.ascii_letters"),
+        ("sys", "import sys; v = sys.version"),
+        ("random", "import random; x = random.random()"),
+    ];
+
+    for (module_name, code) in must_pass {
+        let result = execute(code, no_host_paths());
+        assert_eq!(
+            result.error, None,
+            "importing '{module_name}' failed: {}",
+            result.stderr
+        );
+    }
+}
+
+#[test]
+fn test_sys_path_has_no_host_stdlib() {
+    let result = execute(
+        "import sys\n[p for p in sys.path if 'python3' in p]",
+        no_host_paths(),
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("[]"));
+}
+
+/// Modules are loaded from the frozen stdlib, not from a file on disk.
+#[test]
+fn test_collections_is_frozen() {
+    let result = execute(
+        "import collections\ncollections.__spec__.origin",
+        no_host_paths(),
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("'frozen'"));
+}
+
+#[test]
+fn test_stdlib_paths_forces_isolated() {
+    assert_eq!(
+        no_host_paths().effective_execution_mode(),
+        ExecutionMode::Isolated
+    );
+}
@@ -0,0 +1,66 @@
+// crates/llm-pyexec/tests/execute_batch.rs
+// Tests: execute_batch
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute_batch, ExecutionError, ExecutionSettings};
+
+fn short_timeout() -> ExecutionSettings {
+    ExecutionSettings {
+        timeout_ns: 500_000_000,
+        ..ExecutionSettings::default()
+    }
+}
+
+/// A snippet that times out among fast ones gets its own `Timeout`; every
+/// other result is present and in input order.
+#[test]
+fn test_batch_results_in_input_order_despite_timeout() {
+    let mut codes = vec!["while True:\n    pass"];
+    let fast: Vec<String> = (0..12).map(|i| format!("{i} * 10")).collect();
+    codes.extend(fast.iter().map(String::as_str));
+    codes.insert(5, "sum(range(300_000))");
+
+    let started = Instant::now();
+    let results = execute_batch(&codes, short_timeout());
+    assert!(
+        started.elapsed() < Duration::from_secs(20),
+        "batch stalled for {:?}",
+        started.elapsed()
+    );
+
+    assert_eq!(results.len(), codes.len());
+    assert!(
+        matches!(results[0].error, Some(ExecutionError::Timeout { .. })),
+        "got {:?}",
+        results[0].error
+    );
+    assert_eq!(results[5].return_value.as_deref(), Some("44999850000"));
+    let fast_values: Vec<_> = results
+        .iter()
+        .enumerate()
+        .filter(|(index, _)| *index != 0 && *index != 5)
+        .map(|(_, result)| result.return_value.clone())
+        .collect();
+    let expected: Vec<_> = (0..12).map(|i| Some((i * 10).to_string())).collect();
+    assert_eq!(fast_values, expected);
+}
+
+#[test]
+fn test_batch_of_errors_keeps_each_error_in_place() {
+    let results = execute_batch(&["1 / 0", "'ok'", "def f(:"], ExecutionSettings::default());
+    assert!(matches!(
+        results[0].error,
+        Some(ExecutionError::RuntimeError { .. })
+    ));
+    assert_eq!(results[1].return_value.as_deref(), Some("'ok'"));
+    assert!(matches!(
+        results[2].error,
+        Some(ExecutionError::SyntaxError { .. })
+    ));
+}
+
+#[test]
+fn test_empty_batch() {
+    assert!(execute_batch(&[], ExecutionSettings::default()).is_empty());
+}
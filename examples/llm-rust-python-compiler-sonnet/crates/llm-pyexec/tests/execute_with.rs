@@ -0,0 +1,53 @@
+// crates/llm-pyexec/tests/execute_with.rs
+// Tests: execute_with() against a local pool and cache
+//
+// No test in this binary may call execute(): the global cache must stay empty.
+
+use llm_pyexec::{execute_with, BytecodeCache, ExecutionMode, ExecutionSettings, InterpreterPool};
+
+/// Calls run on the supplied pool and cache; the global cache is untouched.
+#[test]
+fn test_execute_with_uses_local_pool_and_cache() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    for _ in 0..2 {
+        let result = execute_with(&pool, &cache, "6 * 7", ExecutionSettings::default());
+        assert!(
+            result.error.is_none(),
+            "unexpected error: {:?}",
+            result.error
+        );
+        assert_eq!(result.return_value.as_deref(), Some("42"));
+    }
+
+    assert_eq!(pool.metrics().dispatches, 2);
+    assert_eq!(cache.len(), 1);
+    let stats = cache.stats();
+    assert_eq!((stats.hits, stats.misses), (1, 1));
+
+    assert_eq!(BytecodeCache::global().len(), 0);
+}
+
+/// Isolated calls bypass the supplied pool but still use the supplied cache.
+#[test]
+fn test_execute_with_isolated_skips_pool() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let result = execute_with(
+        &pool,
+        &cache,
+        "'isolated'",
+        ExecutionSettings {
+            execution_mode: ExecutionMode::Isolated,
+            ..ExecutionSettings::default()
+        },
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'isolated'"));
+
+    let metrics = pool.metrics();
+    assert_eq!((metrics.dispatches, metrics.fallbacks), (0, 0));
+    assert_eq!(cache.len(), 1);
+    assert_eq!(BytecodeCache::global().len(), 0);
+}
@@ -0,0 +1,111 @@
+// crates/llm-pyexec/tests/execution_diagnostics.rs
+// Tests: ExecutionResult::diagnostics (path, slot id, checkout wait, cache hit)
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{
+    execute_with, BytecodeCache, ExecutionDiagnostics, ExecutionMode, ExecutionPath,
+    ExecutionResult, ExecutionSettings, InterpreterPool,
+};
+
+#[test]
+fn test_second_run_of_snippet_is_cache_hit() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let first = execute_with(&pool, &cache, "x = 2\nx * 21", ExecutionSettings::default());
+    assert_eq!(first.return_value.as_deref(), Some("42"));
+    assert!(!first.diagnostics.cache_hit);
+
+    let second = execute_with(&pool, &cache, "x = 2\nx * 21", ExecutionSettings::default());
+    assert!(second.diagnostics.cache_hit);
+}
+
+#[test]
+fn test_pool_run_reports_slot() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let result = execute_with(&pool, &cache, "1", ExecutionSettings::default());
+    assert!(result.used_pool);
+    assert_eq!(result.diagnostics.path, ExecutionPath::Pool);
+    assert_eq!(result.diagnostics.pool_slot_id, Some(0));
+}
+
+#[test]
+fn test_isolated_run_reports_fallback_without_wait() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let settings = ExecutionSettings {
+        execution_mode: ExecutionMode::Isolated,
+        ..ExecutionSettings::default()
+    };
+
+    let result = execute_with(&pool, &cache, "1", settings);
+    assert_eq!(result.diagnostics.path, ExecutionPath::Fallback);
+    assert_eq!(result.diagnostics.pool_slot_id, None);
+    assert_eq!(result.diagnostics.pool_wait_ns, 0);
+}
+
+/// With the only slot busy, a concurrent call falls back and says so.
+#[test]
+fn test_saturated_pool_reports_fallback_path() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    std::thread::scope(|scope| {
+        let busy = scope.spawn(|| {
+            execute_with(
+                &pool,
+                &cache,
+                "total = 0\nfor i in range(3000000):\n    total += i\ntotal",
+                ExecutionSettings::default(),
+            )
+        });
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while pool.idle_count() > 0 {
+            assert!(Instant::now() < deadline, "slot never became busy");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let checkout_timeout = Duration::from_millis(20);
+        let fallback = execute_with(
+            &pool,
+            &cache,
+            "6 * 7",
+            ExecutionSettings {
+                pool_checkout_timeout_ns: Some(checkout_timeout.as_nanos() as u64),
+                ..ExecutionSettings::default()
+            },
+        );
+        assert_eq!(fallback.return_value.as_deref(), Some("42"));
+        assert_eq!(fallback.diagnostics.path, ExecutionPath::Fallback);
+        assert_eq!(fallback.diagnostics.pool_slot_id, None);
+        assert!(
+            fallback.diagnostics.pool_wait_ns >= checkout_timeout.as_nanos() as u64,
+            "checkout wait: {}ns",
+            fallback.diagnostics.pool_wait_ns
+        );
+
+        let busy = busy.join().expect("busy call panicked");
+        assert_eq!(busy.diagnostics.path, ExecutionPath::Pool);
+    });
+}
+
+/// Serialized under `diagnostics`, with the path as a lowercase string; results
+/// without the key deserialize to the default.
+#[test]
+fn test_diagnostics_serde() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let result = execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    let mut json = serde_json::to_value(&result).expect("serialize");
+    assert_eq!(json["diagnostics"]["path"], "pool");
+    assert_eq!(json["diagnostics"]["pool_slot_id"], 0);
+
+    json.as_object_mut().expect("object").remove("diagnostics");
+    let old: ExecutionResult = serde_json::from_value(json).expect("deserialize");
+    assert_eq!(old.diagnostics, ExecutionDiagnostics::default());
+}
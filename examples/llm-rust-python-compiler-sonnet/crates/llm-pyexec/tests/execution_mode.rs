@@ -0,0 +1,90 @@
+// crates/llm-pyexec/tests/execution_mode.rs
+// Tests: ExecutionSettings::execution_mode (PoolPreferred / PoolOnly / Isolated)
+//
+// This binary must contain only this test: it relies on PYEXEC_POOL_SIZE being
+// read exactly once, at the first call to InterpreterPool::global().
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute, ExecutionError, ExecutionMode, ExecutionSettings, InterpreterPool};
+
+fn with_mode(mode: ExecutionMode) -> ExecutionSettings {
+    ExecutionSettings {
+        execution_mode: mode,
+        ..ExecutionSettings::default()
+    }
+}
+
+#[test]
+fn test_execution_modes() {
+    // ── Isolated: works before the global pool exists, and does not create it ──
+    std::env::set_var("PYEXEC_POOL_SIZE", "2");
+    let result = execute("6 * 7", with_mode(ExecutionMode::Isolated));
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+
+    // Had the isolated call initialized the pool, it would have 2 slots.
+    std::env::set_var("PYEXEC_POOL_SIZE", "1");
+    let pool = InterpreterPool::global();
+    assert_eq!(pool.size(), 1, "Isolated must not initialize the pool");
+
+    // ── PoolOnly: served by the idle slot ─────────────────────────────────────
+    let dispatches_before = pool.metrics().dispatches;
+    let result = execute("'pooled'", with_mode(ExecutionMode::PoolOnly));
+    assert_eq!(result.return_value.as_deref(), Some("'pooled'"));
+    assert_eq!(pool.metrics().dispatches, dispatches_before + 1);
+
+    // Occupy the only slot for far longer than the checks below take (the
+    // caller gives up after the default timeout; the slot keeps running).
+    let busy = std::thread::spawn(|| {
+        execute(
+            "total = 0\nfor i in range(30000000):\n    total += i",
+            ExecutionSettings::default(),
+        )
+    });
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while pool.idle_count() > 0 {
+        assert!(Instant::now() < deadline, "slot never became busy");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    // ── PoolOnly: no slot, no fallback ────────────────────────────────────────
+    let fallbacks_before = pool.metrics().fallbacks;
+    let result = execute(
+        "1",
+        ExecutionSettings {
+            pool_checkout_timeout_ns: Some(0),
+            ..with_mode(ExecutionMode::PoolOnly)
+        },
+    );
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::PoolUnavailable {
+            checkout_timeout_ns: 0
+        })
+    );
+    assert_eq!(result.return_value, None);
+    assert_eq!(pool.metrics().fallbacks, fallbacks_before);
+
+    // ── PoolPreferred: falls back to a fresh interpreter ──────────────────────
+    let result = execute(
+        "'fallback'",
+        ExecutionSettings {
+            pool_checkout_timeout_ns: Some(0),
+            ..with_mode(ExecutionMode::PoolPreferred)
+        },
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'fallback'"));
+    assert_eq!(pool.metrics().fallbacks, fallbacks_before + 1);
+
+    // ── Isolated: bypasses the busy pool without counting a fallback ──────────
+    let result = execute("'isolated'", with_mode(ExecutionMode::Isolated));
+    assert_eq!(result.return_value.as_deref(), Some("'isolated'"));
+    assert_eq!(pool.metrics().fallbacks, fallbacks_before + 1);
+
+    let _ = busy.join();
+}
@@ -18,7 +18,7 @@
 //! Priority 3: Shared file — lib.rs was modified by the merge to expose the
 //! executor module; tests verify all re-exports are accessible.
 
-use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, PyValue};
 
 // ── AC-05: Arithmetic sum of squares ──────────────────────────────────────────
 
@@ -119,7 +119,7 @@ fn test_timeout_enforced() {
     let result = execute("while True: pass", settings);
 
     match &result.error {
-        Some(ExecutionError::Timeout { limit_ns }) => {
+        Some(ExecutionError::Timeout { limit_ns, .. }) => {
             assert_eq!(
                 *limit_ns, 100_000_000,
                 "test_timeout_enforced: limit_ns must match configured timeout"
@@ -202,7 +202,8 @@ fn test_stdout_captured() {
 // ── AC-12: Output limit exceeded ──────────────────────────────────────────────
 
 /// AC-12: execute("print('x' * 10000)", ExecutionSettings { max_output_bytes: 100, .. })
-/// returns error == Some(ExecutionError::OutputLimitExceeded).
+/// returns error == Some(ExecutionError::OutputLimitExceeded), with the first
+/// 100 bytes in stdout.
 #[test]
 fn test_output_limit_exceeded() {
     let settings = ExecutionSettings {
@@ -225,19 +226,15 @@ fn test_output_limit_exceeded() {
             other
         ),
     }
+    // The part of the write that fits is kept.
+    assert_eq!(result.stdout, "x".repeat(100));
+    assert!(result.output_truncated);
 }
 
 // ── AC-13: stdlib modules importable ─────────────────────────────────────────
 
 /// AC-13: Each of math, re, json, datetime, collections, itertools, functools,
 /// string, random, sys imports and basic usage succeeds with error == None.
-///
-/// Note: The `random` module in RustPython 0.3 may produce a RuntimeError due
-/// to missing native extensions (_sha2/_hashlib.openssl_md_meth_names) in the
-/// embedded VM environment. This is a known RustPython compatibility limitation
-/// in this environment, not a defect in the merged executor code. The test
-/// verifies that `random` is at minimum not blocked by the module allowlist
-/// (i.e., does not produce ModuleNotAllowed), which is the key contract.
 #[test]
 fn test_stdlib_all_modules_importable() {
     // Core stdlib modules that must import cleanly with no error
@@ -257,6 +254,7 @@ fn test_stdlib_all_modules_importable() {
         ),
         ("string", "import string; letters = string.ascii_letters"),
         ("sys", "import sys; v = sys.version"),
+        ("random", "import random; x = random.random()"),
     ];
 
     for (module_name, code) in must_pass {
@@ -268,20 +266,6 @@ fn test_stdlib_all_modules_importable() {
             result.error
         );
     }
-
-    // `random` is in the DEFAULT_ALLOWED_MODULES list, so it must NOT produce
-    // ModuleNotAllowed. It may produce a RuntimeError due to RustPython 0.3
-    // native extension gaps, but that is an environment limitation.
-    let random_result = execute("import random", ExecutionSettings::default());
-    assert!(
-        !matches!(
-            random_result.error,
-            Some(ExecutionError::ModuleNotAllowed { .. })
-        ),
-        "test_stdlib_all_modules_importable: 'random' must not produce ModuleNotAllowed \
-        (it is in DEFAULT_ALLOWED_MODULES), got: {:?}",
-        random_result.error
-    );
 }
 
 // ── AC-14: Concurrent execution ───────────────────────────────────────────────
@@ -419,6 +403,89 @@ fn test_executor_maybe_wrap_last_expr_captured_as_return_value() {
     );
 }
 
+/// Final calls, multi-line expressions and `;`-separated statements are
+/// captured too: the last statement is found by parsing, not by line shape.
+#[test]
+fn test_executor_captures_final_call_and_multiline_expression() {
+    let cases = [
+        ("xs = [3, 1, 2]\nsorted(xs)", "[1, 2, 3]"),
+        ("total = 0\n(total +\n    1 +\n    2)", "3"),
+        ("x = 2; x * 21  # answer", "42"),
+        ("'a=b'", "'a=b'"),
+    ];
+    for (code, expected) in cases {
+        let result = execute(code, ExecutionSettings::default());
+        assert!(
+            result.error.is_none(),
+            "{code:?}: unexpected error: {:?}",
+            result.error
+        );
+        assert_eq!(result.return_value.as_deref(), Some(expected), "{code:?}");
+    }
+
+    // A final call that evaluates to None still reports no return value.
+    let result = execute("print('hi')", ExecutionSettings::default());
+    assert_eq!(result.stdout, "hi\n");
+    assert_eq!(result.return_value, None);
+}
+
+/// Final calls are captured without opting in, including chained method
+/// calls; `print` yields `None` and so no return value.
+#[test]
+fn test_executor_captures_final_call_results_by_default() {
+    let cases = [
+        ("sum(range(10))", "45"),
+        ("words = ['a', 'b']\n\" \".join(words).upper()", "'A B'"),
+    ];
+    for (code, expected) in cases {
+        let result = execute(code, ExecutionSettings::default());
+        assert_eq!(result.return_value.as_deref(), Some(expected), "{code:?}");
+    }
+
+    let result = execute("print(\"x\")", ExecutionSettings::default());
+    assert_eq!(result.stdout, "x\n");
+    assert_eq!(result.return_value, None);
+}
+
+/// With `capture_call_results`, a final call reports its result even when it
+/// is `None`, and the snippet is cached apart from a run without the setting.
+#[test]
+fn test_executor_capture_call_results_reports_none() {
+    let capture = ExecutionSettings {
+        capture_call_results: true,
+        debug: true,
+        ..ExecutionSettings::default()
+    };
+    let cases = [
+        ("sum(range(10))", "45"),
+        ("print(\"x\")", "None"),
+        ("words = ['a', 'b']\n\" \".join(words).upper()", "'A B'"),
+    ];
+    for (code, expected) in cases {
+        let result = execute(code, capture.clone());
+        assert!(result.error.is_none(), "{code:?}: {:?}", result.error);
+        assert_eq!(result.return_value.as_deref(), Some(expected), "{code:?}");
+    }
+
+    let captured = execute("print(\"x\")", capture.clone());
+    assert_eq!(captured.stdout, "x\n");
+    assert_eq!(captured.typed_value, Some(PyValue::None));
+    let default = execute(
+        "print(\"x\")",
+        ExecutionSettings {
+            debug: true,
+            ..ExecutionSettings::default()
+        },
+    );
+    assert_eq!(default.return_value, None);
+    assert_eq!(default.wrapped_source, captured.wrapped_source);
+    assert_ne!(default.cache_key_hex, captured.cache_key_hex);
+
+    // Only calls: a final `None` that is not a call still reports nothing.
+    let result = execute("x = None\nx", capture);
+    assert_eq!(result.return_value, None);
+}
+
 /// Verifies the executor returns the correct duration_ns after a timeout.
 ///
 /// The timeout path in executor.rs recovers partial output via into_strings()
@@ -537,3 +604,93 @@ fn test_executor_result_always_has_all_fields() {
         "JSON must have duration_ns field"
     );
 }
+
+// ── Compile-phase timeout ─────────────────────────────────────────────────────
+
+/// A huge generated literal spends its budget in the compiler and is reported
+/// as CompileTimeout (not Timeout); a normal snippet stays under the default
+/// compile budget.
+#[test]
+fn test_compile_timeout_distinguished_from_execution_timeout() {
+    let elements: Vec<String> = (0..200_000).map(|i| i.to_string()).collect();
+    let huge = format!("data = [{}]\nprint(len(data))", elements.join(", "));
+    let settings = ExecutionSettings {
+        compile_timeout_ns: Some(1_000_000), // 1ms
+        use_cache: false,
+        ..ExecutionSettings::default()
+    };
+
+    let result = execute(&huge, settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::CompileTimeout { limit_ns: 1_000_000 }),
+        "expected CompileTimeout, got {:?}",
+        result.error
+    );
+    assert_eq!(result.stdout, "", "code must not run after a compile timeout");
+
+    let normal = execute("print(sum(range(10)))", ExecutionSettings::default());
+    assert!(
+        normal.error.is_none(),
+        "normal snippet must stay under the default compile budget: {:?}",
+        normal.error
+    );
+    assert_eq!(normal.stdout, "45\n");
+}
+
+// ── output_bytes reporting ────────────────────────────────────────────────────
+
+/// output_bytes equals the printed byte count when under the limit.
+#[test]
+fn test_output_bytes_matches_printed_bytes() {
+    let result = execute(
+        "import sys\nprint('hello')\nsys.stderr.write('oops')",
+        ExecutionSettings::default(),
+    );
+    assert!(result.error.is_none(), "unexpected error: {:?}", result.error);
+    assert_eq!(result.output_bytes, "hello\n".len() + "oops".len());
+    assert_eq!(
+        result.output_bytes,
+        result.stdout.len() + result.stderr.len()
+    );
+}
+
+/// output_bytes reaches (or passes) the limit on an overflow run.
+#[test]
+fn test_output_bytes_at_least_limit_on_overflow() {
+    let settings = ExecutionSettings {
+        max_output_bytes: 100,
+        ..ExecutionSettings::default()
+    };
+    let result = execute("print('x' * 10000)", settings);
+    assert!(
+        matches!(
+            result.error,
+            Some(ExecutionError::OutputLimitExceeded { limit_bytes: 100 })
+        ),
+        "expected OutputLimitExceeded, got {:?}",
+        result.error
+    );
+    assert!(
+        result.output_bytes >= 100,
+        "output_bytes {} should be at least the 100-byte limit",
+        result.output_bytes
+    );
+}
+
+/// A print cut at the limit never leaves half a character in stdout.
+#[test]
+fn test_output_limit_cut_keeps_whole_characters() {
+    let settings = ExecutionSettings {
+        max_output_bytes: 10,
+        ..ExecutionSettings::default()
+    };
+    // Each "€" is 3 bytes, so the 10-byte limit falls inside the fourth one.
+    let result = execute("print('€' * 10)", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 10 })
+    );
+    assert_eq!(result.stdout, "€€€");
+    assert!(!result.output_lossy);
+}
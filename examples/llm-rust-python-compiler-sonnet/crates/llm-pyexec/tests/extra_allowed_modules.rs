@@ -0,0 +1,106 @@
+// crates/llm-pyexec/tests/extra_allowed_modules.rs
+// Tests: ExecutionSettings::extra_allowed_modules extends the allowlist
+
+use std::collections::HashSet;
+
+use llm_pyexec::modules::build_allowed_set;
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+/// Extra modules are importable alongside the defaults; anything else is
+/// still denied.
+#[test]
+fn test_extra_modules_extend_default_allowlist() {
+    let settings = ExecutionSettings {
+        extra_allowed_modules: vec!["time".to_string()],
+        ..ExecutionSettings::default()
+    };
+
+    let result = execute(
+        "import json\nimport time\njson.dumps(time.time() > 0)",
+        settings.clone(),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'true'"));
+
+    let result = execute("import shutil", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "shutil".to_string()
+        })
+    );
+}
+
+/// With an explicit `allowed_modules`, the defaults no longer apply but the
+/// extras still do.
+#[test]
+fn test_extra_modules_extend_explicit_allowlist() {
+    let settings = ExecutionSettings {
+        allowed_modules: vec!["math".to_string()],
+        extra_allowed_modules: vec!["time".to_string()],
+        ..ExecutionSettings::default()
+    };
+
+    let result = execute(
+        "import math\nimport time\nmath.floor(1.5)",
+        settings.clone(),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("1"));
+
+    let result = execute("import json", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "json".to_string()
+        })
+    );
+}
+
+/// Defaults plus `csv`: both a default module and the extra one import.
+#[test]
+fn test_defaults_plus_csv_allows_json_and_csv() {
+    let settings = ExecutionSettings {
+        extra_allowed_modules: vec!["csv".to_string()],
+        ..ExecutionSettings::default()
+    };
+    let result = execute(
+        "import csv\nimport json\nrows = list(csv.reader(['a,b']))\njson.dumps(rows)",
+        settings,
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some(r#"'[["a", "b"]]'"#));
+}
+
+/// An explicit `allowed_modules` plus extras allows exactly their union.
+#[test]
+fn test_explicit_math_plus_csv_allows_exactly_those() {
+    let settings = ExecutionSettings {
+        allowed_modules: vec!["math".to_string()],
+        extra_allowed_modules: vec!["csv".to_string()],
+        ..ExecutionSettings::default()
+    };
+    let allowed = build_allowed_set(&settings);
+    assert_eq!(
+        allowed,
+        HashSet::from(["math".to_string(), "csv".to_string()])
+    );
+
+    let result = execute("import math\nimport csv\nmath.floor(2.5)", settings.clone());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("2"));
+
+    let result = execute("import json", settings);
+    assert!(matches!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed { .. })
+    ));
+}
@@ -0,0 +1,81 @@
+// crates/llm-pyexec/tests/extra_sys_paths.rs
+// Tests: ExecutionSettings::extra_sys_paths
+
+use std::path::PathBuf;
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+/// Creates a fresh directory containing `dummy_pure.py`.
+fn module_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("llm-pyexec-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create module dir");
+    std::fs::write(
+        dir.join("dummy_pure.py"),
+        "def greet(name):\n    return 'hello ' + name\n",
+    )
+    .expect("write module");
+    dir
+}
+
+/// A module in an extra path is importable once allowlisted.
+#[test]
+fn test_extra_sys_path_module_importable_when_allowed() {
+    let dir = module_dir("importable");
+    let mut settings = ExecutionSettings {
+        extra_sys_paths: vec![dir.clone()],
+        ..ExecutionSettings::default()
+    };
+    settings.allowed_modules.push("dummy_pure".to_string());
+
+    let result = execute("import dummy_pure\ndummy_pure.greet('path')", settings);
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'hello path'"));
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// Adding a path does not bypass the allowlist.
+#[test]
+fn test_extra_sys_path_module_still_allowlisted() {
+    let dir = module_dir("denied");
+    let settings = ExecutionSettings {
+        extra_sys_paths: vec![dir.clone()],
+        ..ExecutionSettings::default()
+    };
+
+    let result = execute("import dummy_pure", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "dummy_pure".to_string()
+        })
+    );
+
+    let _ = std::fs::remove_dir_all(dir);
+}
+
+/// The path is only added for the call that asked for it.
+#[test]
+fn test_extra_sys_path_not_left_on_sys_path() {
+    let dir = module_dir("scoped");
+    let entry = dir.to_string_lossy().into_owned();
+    let settings = ExecutionSettings {
+        extra_sys_paths: vec![dir.clone()],
+        ..ExecutionSettings::default()
+    };
+
+    let code = format!("import sys\n{entry:?} in sys.path");
+    let during = execute(&code, settings);
+    assert_eq!(during.return_value.as_deref(), Some("True"));
+
+    for _ in 0..8 {
+        let after = execute(&code, ExecutionSettings::default());
+        assert_eq!(after.return_value.as_deref(), Some("False"));
+    }
+
+    let _ = std::fs::remove_dir_all(dir);
+}
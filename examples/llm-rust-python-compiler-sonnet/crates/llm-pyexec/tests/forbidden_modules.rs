@@ -0,0 +1,102 @@
+// crates/llm-pyexec/tests/forbidden_modules.rs
+// Tests: FORBIDDEN_MODULES fail with ModuleForbidden unless allow_forbidden is set
+
+use llm_pyexec::modules::FORBIDDEN_MODULES;
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+fn forbidden(result_error: &Option<ExecutionError>) -> (&str, &str) {
+    match result_error {
+        Some(ExecutionError::ModuleForbidden {
+            module_name,
+            reason,
+        }) => (module_name, reason),
+        other => panic!("expected ModuleForbidden, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_every_forbidden_module_reports_its_reason() {
+    for (name, reason) in FORBIDDEN_MODULES {
+        let result = execute(&format!("import {name}"), ExecutionSettings::default());
+        assert_eq!(forbidden(&result.error), (*name, *reason));
+    }
+}
+
+#[test]
+fn test_threading_reason_mentions_spawning() {
+    let result = execute("import threading", ExecutionSettings::default());
+    assert_eq!(
+        forbidden(&result.error).1,
+        "spawning threads/processes is not permitted in the sandbox"
+    );
+}
+
+#[test]
+fn test_permissive_allowlist_does_not_allow_forbidden_modules() {
+    let settings = ExecutionSettings::default_plus(&["threading", "multiprocessing"]);
+    let result = execute("import threading", settings.clone());
+    assert_eq!(forbidden(&result.error).0, "threading");
+
+    let result = execute("from multiprocessing import pool", settings);
+    assert_eq!(forbidden(&result.error).0, "multiprocessing");
+}
+
+#[test]
+fn test_allow_forbidden_leaves_them_to_the_allowlist() {
+    let settings = ExecutionSettings {
+        allow_forbidden: true,
+        ..ExecutionSettings::default_plus(&["threading"])
+    };
+    let result = execute("import threading\nthreading.__name__", settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("'threading'"));
+
+    let settings = ExecutionSettings {
+        allow_forbidden: true,
+        ..ExecutionSettings::default()
+    };
+    let result = execute("import threading", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "threading".to_string()
+        })
+    );
+}
+
+/// `logging` imports `threading` itself; only user code is refused.
+#[test]
+fn test_stdlib_modules_may_import_forbidden_modules() {
+    let result = execute(
+        "import logging\nlogging.getLogger('x').name",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("'x'"));
+}
+
+#[test]
+fn test_caught_error_and_json_carry_the_reason() {
+    let code = "\
+try:
+    import _thread
+except ImportError as e:
+    message = str(e)
+message";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(
+        result.return_value.as_deref(),
+        Some("'ModuleForbidden:_thread (spawning threads/processes is not permitted in the sandbox)'")
+    );
+    assert_eq!(result.denied_imports, ["_thread"]);
+
+    let result = execute("import ctypes", ExecutionSettings::default());
+    let json = serde_json::to_value(&result).expect("serialize");
+    assert_eq!(json["error"]["type"], "ModuleForbidden");
+    assert_eq!(json["error"]["module_name"], "ctypes");
+    assert_eq!(
+        json["error"]["reason"],
+        "calling native code is not permitted in the sandbox"
+    );
+}
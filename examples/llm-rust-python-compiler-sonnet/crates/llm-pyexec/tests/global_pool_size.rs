@@ -0,0 +1,52 @@
+// crates/llm-pyexec/tests/global_pool_size.rs
+// Tests: a test binary can run execute() against a global pool of a chosen size
+//
+// This binary must contain only this test: the global pool can be installed
+// only before anything has called `global()`.
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute, ExecutionSettings, InterpreterPool};
+
+fn wait_until_idle(pool: &InterpreterPool, idle: usize) {
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while pool.idle_count() != idle {
+        assert!(
+            Instant::now() < deadline,
+            "expected {idle} idle slots, have {}",
+            pool.idle_count()
+        );
+        std::thread::sleep(Duration::from_millis(1));
+    }
+}
+
+#[test]
+fn test_execute_runs_on_installed_global_pool_and_follows_resize() {
+    InterpreterPool::init_global(InterpreterPool::new(2)).expect("pool not yet initialized");
+    let pool = InterpreterPool::global();
+    assert_eq!(pool.size(), 2);
+
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..4)
+            .map(|i| {
+                scope.spawn(move || execute(&format!("{i} * 2"), ExecutionSettings::default()))
+            })
+            .collect();
+        handles.into_iter().map(|h| h.join().unwrap()).collect()
+    });
+    for (i, result) in results.iter().enumerate() {
+        assert_eq!(result.error, None, "stderr: {}", result.stderr);
+        assert_eq!(result.return_value, Some((i * 2).to_string()));
+    }
+    assert_eq!(pool.metrics().dispatches, 4);
+    assert_eq!(pool.metrics().fallbacks, 0);
+    wait_until_idle(pool, 2);
+
+    // The same binary can go on to test another size.
+    pool.resize(1);
+    wait_until_idle(pool, 1);
+    let result = execute("'one slot'", ExecutionSettings::default());
+    assert_eq!(result.return_value.as_deref(), Some("'one slot'"));
+    assert_eq!(pool.size(), 1);
+    assert_eq!(pool.metrics().dispatches, 5);
+}
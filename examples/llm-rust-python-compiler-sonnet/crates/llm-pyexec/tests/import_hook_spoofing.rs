@@ -0,0 +1,103 @@
+// crates/llm-pyexec/tests/import_hook_spoofing.rs
+// Tests: user code cannot pass itself off as a module to skip the allowlist
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+fn assert_denied(code: &str, module: &str) {
+    let result = execute(code, ExecutionSettings::default());
+    assert!(
+        matches!(
+            result.error,
+            Some(ExecutionError::ModuleForbidden { ref module_name, .. }) if module_name == module
+        ),
+        "{code:?}: got {:?}, stdout {:?}",
+        result.error,
+        result.stdout
+    );
+}
+
+#[test]
+fn test_reassigned_name_is_still_user_code() {
+    assert_denied("__name__ = 'not_main'\nimport socket", "socket");
+    assert_denied("__name__ = 'json'\nimport socket", "socket");
+}
+
+#[test]
+fn test_forged_import_globals_are_ignored() {
+    assert_denied("__import__('socket', {'__name__': 'json'})", "socket");
+    assert_denied(
+        "__import__('socket', {'__name__': 'json', '__file__': '/usr/lib/json.py'})",
+        "socket",
+    );
+}
+
+#[test]
+fn test_deleted_name_and_file_are_still_user_code() {
+    assert_denied("del __name__\nimport socket", "socket");
+    assert_denied("__file__ = None\nimport socket", "socket");
+}
+
+/// A fresh namespace made up to look like an allowed module is not one.
+#[test]
+fn test_exec_in_forged_namespace_is_checked() {
+    assert_denied("exec('import socket', {'__name__': 'json'})", "socket");
+    assert_denied(
+        "import json\nexec('import socket', {'__name__': 'json', '__spec__': json.__spec__})",
+        "socket",
+    );
+}
+
+/// Running in the real namespace of an allowed module does not help code
+/// compiled from a string.
+#[test]
+fn test_exec_in_allowed_module_namespace_is_checked() {
+    assert_denied(
+        "import json\nexec('import socket', json.__dict__)",
+        "socket",
+    );
+}
+
+#[test]
+fn test_functions_defined_by_user_code_are_checked() {
+    assert_denied("def f():\n    import socket\nf()", "socket");
+    assert_denied("(lambda: __import__('socket'))()", "socket");
+}
+
+#[test]
+fn test_builtins_expose_only_the_filtered_import() {
+    let code = "\
+b = __builtins__ if isinstance(__builtins__, dict) else __builtins__.__dict__
+sorted(name for name in b if 'import' in name)";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("['__import__']"));
+}
+
+/// Stdlib modules still import their own dependencies, including lazily
+/// from inside functions (`strptime` loads `_strptime` on first use).
+#[test]
+fn test_stdlib_internal_imports_still_work() {
+    let code = "\
+import datetime
+datetime.datetime.strptime('2024-03-01', '%Y-%m-%d').month";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("3"));
+}
+
+#[test]
+fn test_spoofing_is_denied_in_a_session() {
+    let session = Session::new(ExecutionSettings::default());
+    let forged = session.eval("__name__ = 'json'");
+    assert_eq!(forged.error, None, "stderr: {}", forged.stderr);
+
+    let result = session.eval("import socket");
+    assert!(
+        matches!(
+            result.error,
+            Some(ExecutionError::ModuleForbidden { ref module_name, .. }) if module_name == "socket"
+        ),
+        "got {:?}",
+        result.error
+    );
+}
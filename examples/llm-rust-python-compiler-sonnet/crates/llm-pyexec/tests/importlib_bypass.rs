@@ -0,0 +1,101 @@
+// crates/llm-pyexec/tests/importlib_bypass.rs
+// Tests: the allowlist holds for imports that do not go through an import statement
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+/// Default settings plus `importlib`, so that the attacks below get past
+/// `import importlib` and reach the module they are after.
+fn with_importlib() -> ExecutionSettings {
+    ExecutionSettings {
+        extra_allowed_modules: vec!["importlib".to_string()],
+        ..ExecutionSettings::default()
+    }
+}
+
+fn assert_socket_denied(code: &str) {
+    let result = execute(code, with_importlib());
+    assert!(
+        matches!(
+            result.error,
+            Some(ExecutionError::ModuleForbidden { ref module_name, .. }) if module_name == "socket"
+        ),
+        "{code:?}: got {:?}, stdout {:?}",
+        result.error,
+        result.stdout
+    );
+}
+
+#[test]
+fn test_importlib_import_module_is_denied() {
+    assert_socket_denied("import importlib\nimportlib.import_module('socket')");
+}
+
+#[test]
+fn test_importlib_dunder_import_is_denied() {
+    assert_socket_denied("import importlib\nimportlib.__import__('socket')");
+}
+
+#[test]
+fn test_builtin_dunder_import_is_denied() {
+    assert_socket_denied("__import__('socket')");
+}
+
+#[test]
+fn test_nested_exec_import_is_denied() {
+    assert_socket_denied("exec('import socket')");
+    assert_socket_denied("exec(\"exec('import socket')\")");
+}
+
+/// The parent package is loaded, and denied, first.
+#[test]
+fn test_importlib_submodule_of_denied_package_is_denied() {
+    let result = execute(
+        "import importlib\nimportlib.import_module('xml.dom')",
+        with_importlib(),
+    );
+    assert!(
+        matches!(
+            result.error,
+            Some(ExecutionError::ModuleNotAllowed { ref module_name }) if module_name == "xml"
+        ),
+        "got {:?}",
+        result.error
+    );
+}
+
+#[test]
+fn test_importlib_loads_allowed_modules() {
+    let code = "\
+import importlib
+importlib.import_module('textwrap').dedent('  x').strip()";
+    let result = execute(code, with_importlib());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("'x'"));
+}
+
+/// Allowed modules loaded through `importlib` still import their own
+/// dependencies, which are not on the allowlist.
+#[test]
+fn test_modules_loaded_by_importlib_import_their_dependencies() {
+    let code = "\
+import importlib
+datetime = importlib.import_module('datetime')
+datetime.datetime.strptime('2024-03-01', '%Y-%m-%d').day";
+    let result = execute(code, with_importlib());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("1"));
+}
+
+/// Each call installs one finder; repeated calls on pooled interpreters do
+/// not stack them up.
+#[test]
+fn test_finder_is_installed_once() {
+    let code = "\
+import sys
+sum(1 for f in sys.meta_path if getattr(f, '__name__', None) == '_pyexec_allowlist_finder')";
+    for _ in 0..3 {
+        let result = execute(code, ExecutionSettings::default());
+        assert_eq!(result.error, None, "stderr: {}", result.stderr);
+        assert_eq!(result.return_value.as_deref(), Some("1"));
+    }
+}
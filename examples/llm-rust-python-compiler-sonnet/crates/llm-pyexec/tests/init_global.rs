@@ -0,0 +1,54 @@
+// crates/llm-pyexec/tests/init_global.rs
+// Tests: InterpreterPool::init_global / BytecodeCache::init_global install the
+// globals used by execute()
+//
+// This binary must contain only this test: the globals can be installed only
+// before anything has called `global()`.
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute, AlreadyInitialized, BytecodeCache, ExecutionSettings, InterpreterPool};
+
+#[test]
+fn test_init_global_installs_pool_and_cache() {
+    // Would otherwise size the lazily created pool.
+    std::env::set_var("PYEXEC_POOL_SIZE", "3");
+
+    InterpreterPool::init_global(InterpreterPool::new(1)).expect("pool not yet initialized");
+    BytecodeCache::init_global(BytecodeCache::new(5)).expect("cache not yet initialized");
+
+    let result = execute("6 * 7", ExecutionSettings::default());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+
+    let pool = InterpreterPool::global();
+    assert_eq!(pool.size(), 1);
+    assert_eq!(
+        pool.metrics().dispatches,
+        1,
+        "execute() used the installed pool"
+    );
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while pool.idle_count() != 1 {
+        assert!(Instant::now() < deadline, "slot never returned to the pool");
+        std::thread::sleep(Duration::from_millis(1));
+    }
+
+    let cache = BytecodeCache::global();
+    assert_eq!(cache.capacity(), 5);
+    assert_eq!(cache.len(), 1, "execute() used the installed cache");
+
+    // A second install is rejected.
+    assert_eq!(
+        InterpreterPool::init_global(InterpreterPool::new(1)).unwrap_err(),
+        AlreadyInitialized
+    );
+    assert_eq!(
+        BytecodeCache::init_global(BytecodeCache::new(1)).unwrap_err(),
+        AlreadyInitialized
+    );
+}
@@ -0,0 +1,27 @@
+// crates/llm-pyexec/tests/init_global_after_use.rs
+// Tests: init_global fails once global() has lazily initialized the globals
+//
+// This binary must contain only this test: it depends on the globals being
+// created by execute(), not by another test's init_global call.
+
+use llm_pyexec::{execute, AlreadyInitialized, BytecodeCache, ExecutionSettings, InterpreterPool};
+
+#[test]
+fn test_init_global_after_first_use_fails() {
+    let result = execute("1 + 1", ExecutionSettings::default());
+    assert_eq!(result.return_value.as_deref(), Some("2"));
+
+    let size_before = InterpreterPool::global().size();
+    assert_eq!(
+        InterpreterPool::init_global(InterpreterPool::new(size_before + 1)),
+        Err(AlreadyInitialized)
+    );
+    assert_eq!(InterpreterPool::global().size(), size_before);
+
+    let capacity_before = BytecodeCache::global().capacity();
+    assert_eq!(
+        BytecodeCache::init_global(BytecodeCache::new(capacity_before + 1)),
+        Err(AlreadyInitialized)
+    );
+    assert_eq!(BytecodeCache::global().capacity(), capacity_before);
+}
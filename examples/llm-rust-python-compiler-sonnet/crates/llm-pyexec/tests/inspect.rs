@@ -0,0 +1,138 @@
+// crates/llm-pyexec/tests/inspect.rs
+// Tests: post-execution inspect queries
+
+use llm_pyexec::{execute, ExecutionSettings, InspectOutcome, InspectQuery};
+
+const STACK_SNIPPET: &str = "\
+class Stack:
+    def __init__(self):
+        self.items = []
+    def push(self, x):
+        self.items.append(x)
+    def pop(self):
+        return self.items.pop()
+
+s = Stack()
+s.push(1)
+s.push(2)
+";
+
+fn inspect_settings(queries: Vec<InspectQuery>) -> ExecutionSettings {
+    ExecutionSettings {
+        inspect: Some(queries),
+        ..ExecutionSettings::default()
+    }
+}
+
+/// Class definitions, methods and final object state can all be checked.
+#[test]
+fn test_inspect_object_oriented_snippet() {
+    let result = execute(
+        STACK_SNIPPET,
+        inspect_settings(vec![
+            InspectQuery::NameExists("Stack".to_string()),
+            InspectQuery::IsCallable("Stack".to_string()),
+            InspectQuery::HasAttr {
+                obj: "s".to_string(),
+                attr: "push".to_string(),
+            },
+            InspectQuery::HasAttr {
+                obj: "s".to_string(),
+                attr: "peek".to_string(),
+            },
+            InspectQuery::EvalExpr("s.items".to_string()),
+            InspectQuery::NameExists("Queue".to_string()),
+        ]),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+
+    let outcomes: Vec<_> = result.inspections.iter().map(|r| &r.outcome).collect();
+    assert_eq!(
+        outcomes,
+        [
+            &InspectOutcome::Passed,
+            &InspectOutcome::Passed,
+            &InspectOutcome::Passed,
+            &InspectOutcome::Failed,
+            &InspectOutcome::Passed,
+            &InspectOutcome::Failed,
+        ]
+    );
+    assert_eq!(result.inspections[4].value_repr.as_deref(), Some("[1, 2]"));
+}
+
+/// A query that raises is reported as an error without aborting later queries
+/// or changing the snippet's own result.
+#[test]
+fn test_failing_query_does_not_abort_others() {
+    let result = execute(
+        "x = 1\nx",
+        inspect_settings(vec![
+            InspectQuery::EvalExpr("1 / 0".to_string()),
+            InspectQuery::IsCallable("undefined_name".to_string()),
+            InspectQuery::EvalExpr("x + 1".to_string()),
+        ]),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("1"));
+
+    match &result.inspections[0].outcome {
+        InspectOutcome::Error { message } => {
+            assert!(message.starts_with("ZeroDivisionError"), "got: {message}")
+        }
+        other => panic!("expected Error, got {other:?}"),
+    }
+    assert!(matches!(
+        result.inspections[1].outcome,
+        InspectOutcome::Error { .. }
+    ));
+    assert_eq!(result.inspections[2].outcome, InspectOutcome::Passed);
+    assert_eq!(result.inspections[2].value_repr.as_deref(), Some("2"));
+}
+
+/// Queries still run when the snippet itself raised, seeing the partial state.
+#[test]
+fn test_inspect_runs_after_runtime_error() {
+    let result = execute(
+        "done = []\ndone.append(1)\nraise ValueError('boom')",
+        inspect_settings(vec![InspectQuery::EvalExpr("done".to_string())]),
+    );
+    assert!(result.error.is_some());
+    assert_eq!(result.inspections.len(), 1);
+    assert_eq!(result.inspections[0].value_repr.as_deref(), Some("[1]"));
+}
+
+/// EvalExpr is subject to the same import allowlist as the snippet.
+#[test]
+fn test_eval_expr_respects_allowlist() {
+    let result = execute(
+        "pass",
+        inspect_settings(vec![InspectQuery::EvalExpr(
+            "__import__('socket')".to_string(),
+        )]),
+    );
+    match &result.inspections[0].outcome {
+        InspectOutcome::Error { message } => {
+            assert!(message.contains("socket"), "got: {message}")
+        }
+        other => panic!("expected Error, got {other:?}"),
+    }
+}
+
+/// Snippets that never ran produce no inspections.
+#[test]
+fn test_no_inspections_after_syntax_error() {
+    let result = execute(
+        "def f(:",
+        inspect_settings(vec![InspectQuery::NameExists("f".to_string())]),
+    );
+    assert!(result.inspections.is_empty());
+}
@@ -0,0 +1,100 @@
+// crates/llm-pyexec/tests/instruction_limit.rs
+// Tests: ExecutionSettings::max_instructions / ExecutionError::InstructionLimitExceeded
+
+use std::time::Duration;
+
+use llm_pyexec::{execute_with, BytecodeCache, ExecutionError, ExecutionSettings, InterpreterPool};
+
+const BUDGET: u64 = 1_000_000;
+
+/// A billion iterations, each one a Python-level call.
+const HUGE_LOOP: &str = "def step(n):\n    return n + 1\n\n\
+                         total = 0\nfor i in range(10**9):\n    total = step(total)\ntotal";
+
+fn budgeted() -> ExecutionSettings {
+    ExecutionSettings {
+        timeout_ns: 120_000_000_000,
+        max_instructions: Some(BUDGET),
+        ..ExecutionSettings::default()
+    }
+}
+
+#[test]
+fn test_huge_loop_fails_fast_with_instruction_limit() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let settings = budgeted();
+    let timeout = Duration::from_nanos(settings.timeout_ns);
+
+    let result = execute_with(&pool, &cache, HUGE_LOOP, settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::InstructionLimitExceeded { limit: BUDGET })
+    );
+    assert!(
+        Duration::from_nanos(result.duration_ns) < timeout / 4,
+        "took {}ns",
+        result.duration_ns
+    );
+}
+
+#[test]
+fn test_trivial_snippet_fits_budget() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let result = execute_with(&pool, &cache, "x = sum(range(10))\nx * 2", budgeted());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("90"));
+}
+
+/// `except Exception` cannot swallow the limit.
+#[test]
+fn test_limit_not_caught_by_except_exception() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "def step(n):\n    return n + 1\n\n\
+                try:\n    n = 0\n    while True:\n        n = step(n)\n\
+                except Exception:\n    pass\n'swallowed'";
+
+    let result = execute_with(&pool, &cache, code, budgeted());
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::InstructionLimitExceeded { limit: BUDGET })
+    );
+    assert_eq!(result.return_value, None);
+}
+
+/// The count starts over for each call, and no hook is left behind on the
+/// pool slot for calls without a budget.
+#[test]
+fn test_budget_is_per_call() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let exhausted = execute_with(&pool, &cache, HUGE_LOOP, budgeted());
+    assert!(exhausted.used_pool);
+    assert!(matches!(
+        exhausted.error,
+        Some(ExecutionError::InstructionLimitExceeded { .. })
+    ));
+
+    let next = execute_with(&pool, &cache, "x = sum(range(10))\nx * 2", budgeted());
+    assert!(next.used_pool);
+    assert!(next.error.is_none(), "unexpected error: {:?}", next.error);
+
+    let unbudgeted = execute_with(
+        &pool,
+        &cache,
+        "import sys\nhook = sys.gettrace()\nhook is None",
+        ExecutionSettings {
+            extra_allowed_modules: vec!["sys".to_string()],
+            ..ExecutionSettings::default()
+        },
+    );
+    assert_eq!(unbudgeted.return_value.as_deref(), Some("True"));
+}
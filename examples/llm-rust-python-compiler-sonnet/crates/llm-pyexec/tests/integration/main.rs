@@ -77,10 +77,10 @@ fn test_allowed_module_json() {
 fn test_denied_module_socket() {
     let r = execute("import socket", Default::default());
     match &r.error {
-        Some(ExecutionError::ModuleNotAllowed { module_name }) => {
+        Some(ExecutionError::ModuleForbidden { module_name, .. }) => {
             assert_eq!(module_name, "socket");
         }
-        other => panic!("Expected ModuleNotAllowed(socket), got: {:?}", other),
+        other => panic!("Expected ModuleForbidden(socket), got: {:?}", other),
     }
 }
 
@@ -127,17 +127,11 @@ fn test_stdlib_all_modules_importable() {
             "Module '{}' failed: {:?}", module, r.error
         );
     }
-    // `random` is in the DEFAULT_ALLOWED_MODULES list, so it must NOT produce
-    // ModuleNotAllowed. It may produce a RuntimeError due to RustPython 0.3
-    // native extension gaps — that is an environment limitation, not a defect.
+    // `random` is in the DEFAULT_ALLOWED_MODULES list and ships as a frozen
+    // module, so it must import and run cleanly.
     let r = execute("import random; random.seed(42); random.randint(0,10)", Default::default());
-    assert!(
-        !matches!(r.error, Some(ExecutionError::ModuleNotAllowed { .. })),
-        "Module 'random' must not be blocked by allowlist; got: {:?}", r.error
-    );
-    if r.error.is_some() {
-        eprintln!("WARNING: random module produced an error (RustPython limitation): {:?}", r.error);
-    }
+    assert!(r.error.is_none(), "Module 'random' failed: {:?}", r.error);
+    assert_eq!(r.return_value.as_deref(), Some("10"));
 }
 
 // ─── AC-14 ───────────────────────────────────────────────────────────────────
@@ -313,8 +307,8 @@ fn test_multiline_code() {
 fn test_denied_module_subprocess() {
     let r = execute("import subprocess", Default::default());
     assert!(
-        matches!(r.error, Some(ExecutionError::ModuleNotAllowed { .. })),
-        "Expected ModuleNotAllowed for subprocess, got: {:?}", r.error
+        matches!(r.error, Some(ExecutionError::ModuleForbidden { .. })),
+        "Expected ModuleForbidden for subprocess, got: {:?}", r.error
     );
 }
 
@@ -431,22 +425,9 @@ fn test_random_seeded_output() {
         "import random; random.seed(12345); print(random.randint(0, 100))",
         Default::default(),
     );
-    // RustPython 0.3 may fail on random due to missing native extensions
-    // (_sha2/_hashlib.openssl_md_meth_names). Accept that outcome gracefully.
-    if r.error.is_some() {
-        eprintln!("WARNING: random module produced an error (known RustPython limitation): {:?}", r.error);
-        assert!(
-            !matches!(r.error, Some(ExecutionError::ModuleNotAllowed { .. })),
-            "random must not be blocked by allowlist; got: {:?}", r.error
-        );
-        return;
-    }
-    // If no error, verify the output is a number in range [0, 100].
-    let trimmed = r.stdout.trim();
-    if let Ok(val) = trimmed.parse::<i32>() {
-        assert!((0..=100).contains(&val), "Expected value in [0,100], got: {val}");
-    }
-    // If trimmed is empty or non-numeric, that's also acceptable (no error raised).
+    assert!(r.error.is_none(), "random failed: {:?}", r.error);
+    // Same value CPython prints for this seed.
+    assert_eq!(r.stdout, "53\n");
 }
 
 #[test]
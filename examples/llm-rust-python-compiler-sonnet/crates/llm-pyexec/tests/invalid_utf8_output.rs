@@ -0,0 +1,49 @@
+// crates/llm-pyexec/tests/invalid_utf8_output.rs
+// Tests: raw bytes written to sys.stdout.buffer, ExecutionResult::output_lossy,
+// and OutputBuffer::into_bytes
+
+use llm_pyexec::{execute, ExecutionSettings, OutputBuffer};
+
+#[test]
+fn test_invalid_utf8_on_stdout_buffer_is_reported_lossy() {
+    let result = execute(
+        "import sys\nsys.stdout.buffer.write(b'ok \\xff\\n')",
+        ExecutionSettings::default(),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert!(result.output_lossy);
+    assert_eq!(result.stdout, "ok \u{FFFD}\n");
+    assert_eq!(result.output_bytes, 4);
+}
+
+#[test]
+fn test_text_and_valid_bytes_are_not_lossy() {
+    let result = execute(
+        "import sys\nprint('héllo')\nsys.stderr.buffer.write('wörld'.encode())",
+        ExecutionSettings::default(),
+    );
+    assert!(!result.output_lossy);
+    assert_eq!(result.stdout, "héllo\n");
+    assert_eq!(result.stderr, "wörld");
+}
+
+/// The buffer keeps what was written; only `into_strings` replaces bytes.
+#[test]
+fn test_into_bytes_preserves_invalid_utf8() {
+    let buf = OutputBuffer::new(64);
+    buf.write_stdout(b"ok \xff\xfe").unwrap();
+    buf.write_stderr(b"\x80").unwrap();
+    assert!(buf.has_invalid_utf8());
+
+    let (stdout, stderr) = buf.clone().into_bytes();
+    assert_eq!(stdout, b"ok \xff\xfe");
+    assert_eq!(stderr, b"\x80");
+
+    let (stdout, stderr) = buf.into_strings();
+    assert_eq!(stdout, "ok \u{FFFD}\u{FFFD}");
+    assert_eq!(stderr, "\u{FFFD}");
+}
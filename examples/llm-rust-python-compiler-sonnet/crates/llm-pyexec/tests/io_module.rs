@@ -0,0 +1,97 @@
+// crates/llm-pyexec/tests/io_module.rs
+// Tests: `io` in DEFAULT_ALLOWED_MODULES and the frozen io shim (src/frozen/io.py)
+
+use std::collections::HashMap;
+
+use llm_pyexec::{execute, ExecutionSettings, DEFAULT_ALLOWED_MODULES};
+
+fn run(code: &str, settings: ExecutionSettings) -> String {
+    let result = execute(code, settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    result.stdout
+}
+
+#[test]
+fn test_io_is_allowed_by_default() {
+    assert!(DEFAULT_ALLOWED_MODULES.contains(&"io"));
+}
+
+#[test]
+fn test_string_io_write_getvalue() {
+    let code = "\
+import io
+buf = io.StringIO()
+buf.write('hello ')
+print('world', file=buf)
+print(repr(buf.getvalue()))
+buf.seek(0)
+print(buf.readline().strip())";
+    assert_eq!(
+        run(code, ExecutionSettings::default()),
+        "'hello world\\n'\nhello world\n"
+    );
+}
+
+#[test]
+fn test_bytes_io_round_trip() {
+    let code = "\
+import io
+buf = io.BytesIO()
+buf.write(b'\\x00ab')
+buf.write('é'.encode())
+print(buf.getvalue())
+buf.seek(1)
+print(buf.read(2), buf.tell())
+print(io.BytesIO(b'xyz').read())";
+    assert_eq!(
+        run(code, ExecutionSettings::default()),
+        "b'\\x00ab\\xc3\\xa9'\nb'ab' 3\nb'xyz'\n"
+    );
+}
+
+#[test]
+fn test_io_abcs_recognise_native_classes() {
+    let code = "\
+import io
+print(isinstance(io.StringIO(), io.TextIOBase), isinstance(io.BytesIO(), io.BufferedIOBase))
+wrapper = io.TextIOWrapper(io.BytesIO('ünï'.encode()), encoding='utf-8')
+print(wrapper.read())";
+    assert_eq!(run(code, ExecutionSettings::default()), "True True\nünï\n");
+}
+
+#[test]
+fn test_csv_writer_into_string_io() {
+    let settings = ExecutionSettings::default_plus(&["csv"]);
+    let code = "\
+import csv
+import io
+buf = io.StringIO()
+writer = csv.writer(buf)
+writer.writerow(['name', 'n'])
+writer.writerow(['a,b', 1])
+print(repr(buf.getvalue()))";
+    assert_eq!(run(code, settings), "'name,n\\r\\n\"a,b\",1\\r\\n'\n");
+}
+
+/// `io.open` is the sandbox's `open()`: it serves `ExecutionSettings::files`
+/// and nothing else.
+#[test]
+fn test_io_open_only_sees_virtual_files() {
+    let settings = ExecutionSettings {
+        files: HashMap::from([("data.txt".to_string(), b"virtual".to_vec())]),
+        ..ExecutionSettings::default()
+    };
+    let code = "\
+import io
+import os.path
+print(io.open('data.txt').read())
+for attempt in (lambda: io.open(os.path.__file__), lambda: io.FileIO(os.path.__file__)):
+    try:
+        attempt()
+    except OSError as e:
+        print(type(e).__name__)";
+    assert_eq!(
+        run(code, settings),
+        "virtual\nFileNotFoundError\nPermissionError\n"
+    );
+}
@@ -0,0 +1,125 @@
+// crates/llm-pyexec/tests/json_shim.rs
+// Tests: ExecutionSettings::use_builtin_json_shim, shim encoder depth and
+// cycle checks
+
+use llm_pyexec::{
+    execute, execute_with, BytecodeCache, ExecutionError, ExecutionMode, ExecutionResult,
+    ExecutionSettings, InterpreterPool,
+};
+
+const DUMPS_INDENTED: &str = "import json\njson.dumps({'a': 1}, indent=2)";
+
+/// With the shim disabled, `json` comes from the stdlib on `sys.path`, which
+/// honors `indent`. Skipped when that json cannot be imported or ignores it.
+#[test]
+fn test_disabled_shim_uses_stdlib_json() {
+    let settings = ExecutionSettings {
+        use_builtin_json_shim: false,
+        ..ExecutionSettings::default()
+    };
+    let result = execute(DUMPS_INDENTED, settings);
+    assert!(!result.used_pool, "shim-less calls must not use the pool");
+    let value = match (&result.error, result.return_value.as_deref()) {
+        (None, Some(value)) if value.contains("\\n") => value.to_string(),
+        _ => {
+            eprintln!("skipping: stdlib json unavailable or ignores indent ({result:?})");
+            return;
+        }
+    };
+    assert_eq!(value, r#"'{\n  "a": 1\n}'"#);
+}
+
+/// The shim is the default, and calls that keep it may still use the pool.
+#[test]
+fn test_shim_enabled_by_default() {
+    let settings = ExecutionSettings::default();
+    assert!(settings.use_builtin_json_shim);
+    assert_eq!(
+        settings.effective_execution_mode(),
+        ExecutionMode::PoolPreferred
+    );
+
+    let result = execute("import json\njson.dumps({'a': 1})", settings);
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some(r#"'{"a": 1}'"#));
+}
+
+/// Disabling the shim forces a fresh interpreter even under `PoolOnly`.
+#[test]
+fn test_disabled_shim_forces_isolated_mode() {
+    let settings = ExecutionSettings {
+        use_builtin_json_shim: false,
+        execution_mode: ExecutionMode::PoolOnly,
+        ..ExecutionSettings::default()
+    };
+    assert_eq!(settings.effective_execution_mode(), ExecutionMode::Isolated);
+}
+
+/// Settings serialized before the field existed keep the shim.
+#[test]
+fn test_use_builtin_json_shim_defaults_to_true() {
+    let mut json = serde_json::to_value(ExecutionSettings::default()).expect("serialize");
+    json.as_object_mut()
+        .expect("object")
+        .remove("use_builtin_json_shim");
+    let settings: ExecutionSettings = serde_json::from_value(json).expect("deserialize");
+    assert!(settings.use_builtin_json_shim);
+}
+
+/// Run each snippet in turn on a single-slot pool.
+fn run_on_one_slot(codes: &[&str]) -> Vec<ExecutionResult> {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    codes
+        .iter()
+        .map(|code| execute_with(&pool, &cache, code, ExecutionSettings::default()))
+        .collect()
+}
+
+/// Encoding a 2000-deep list fails with a recursion error, and the slot goes
+/// on serving calls.
+#[test]
+fn test_deeply_nested_dumps_raises() {
+    let results = run_on_one_slot(&[
+        "import json\nx = []\nfor _ in range(2000):\n    x = [x]\njson.dumps(x)",
+        "import json\njson.dumps([[1]])",
+    ]);
+    assert!(
+        matches!(
+            results[0].error,
+            Some(ExecutionError::RecursionLimitExceeded { .. })
+        ),
+        "{:?}",
+        results[0].error
+    );
+    assert_eq!(results[1].return_value.as_deref(), Some("'[[1]]'"));
+    assert!(results.iter().all(|result| result.used_pool));
+    assert_eq!(
+        results[0].diagnostics.pool_slot_id,
+        results[1].diagnostics.pool_slot_id
+    );
+}
+
+/// A self-referential dict is reported as a circular reference.
+#[test]
+fn test_circular_dumps_raises() {
+    let results = run_on_one_slot(&[
+        "import json\nd = {}\nd['self'] = d\njson.dumps(d)",
+        "import json\nshared = [1]\njson.dumps([shared, shared])",
+    ]);
+    match &results[0].error {
+        Some(ExecutionError::RuntimeError { message, .. }) => {
+            assert_eq!(message, "Circular reference detected");
+        }
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+    assert_eq!(results[1].return_value.as_deref(), Some("'[[1], [1]]'"));
+    assert_eq!(
+        results[0].diagnostics.pool_slot_id,
+        results[1].diagnostics.pool_slot_id
+    );
+}
@@ -0,0 +1,42 @@
+// crates/llm-pyexec/tests/latency_slo.rs
+// Tests: ExecutionSettings::latency_slo and ExecutionDiagnostics::slo_breached
+
+use llm_pyexec::{
+    execute_with, latency_histogram, BytecodeCache, ExecutionPath, ExecutionSettings,
+    InterpreterPool, LatencySlo,
+};
+
+/// A pool-served call under an unreachable target breaches it once a window
+/// completes; a generous target and no target report no breach.
+#[test]
+fn test_latency_slo_reported_in_diagnostics() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    let strict = LatencySlo {
+        p95_ns: 1,
+        window: 1,
+    };
+    let settings = ExecutionSettings {
+        latency_slo: Some(strict),
+        ..ExecutionSettings::default()
+    };
+    let result = execute_with(&pool, &cache, "1 + 1", settings);
+    assert_eq!(result.diagnostics.path, ExecutionPath::Pool);
+    assert!(result.diagnostics.slo_breached);
+    assert_eq!(latency_histogram().slo(), Some(strict));
+
+    let generous = ExecutionSettings {
+        latency_slo: Some(LatencySlo {
+            p95_ns: u64::MAX / 2,
+            window: 1,
+        }),
+        ..ExecutionSettings::default()
+    };
+    let result = execute_with(&pool, &cache, "1 + 1", generous);
+    assert!(!result.diagnostics.slo_breached);
+
+    let result = execute_with(&pool, &cache, "1 + 1", ExecutionSettings::default());
+    assert!(!result.diagnostics.slo_breached);
+}
@@ -0,0 +1,62 @@
+// crates/llm-pyexec/tests/mock_executor.rs
+// Tests: mock::MockExecutor (feature `test-util`)
+//
+// Run: cargo test -p llm-pyexec --features test-util --test mock_executor
+
+#![cfg(feature = "test-util")]
+
+use llm_pyexec::mock::MockExecutor;
+use llm_pyexec::{ExecutionError, ExecutionResult, ExecutionSettings, PyExecutor};
+
+fn returning(value: &str) -> ExecutionResult {
+    ExecutionResult {
+        return_value: Some(value.to_string()),
+        ..ExecutionResult::default()
+    }
+}
+
+/// The first registered substring that matches wins.
+#[test]
+fn test_mock_returns_first_matching_result() {
+    let mock = MockExecutor::new()
+        .on("json", returning("'json'"))
+        .on("import", returning("'import'"));
+
+    let result = mock.execute("import json", ExecutionSettings::default());
+    assert_eq!(result.return_value.as_deref(), Some("'json'"));
+
+    let result = mock.execute("import math", ExecutionSettings::default());
+    assert_eq!(result.return_value.as_deref(), Some("'import'"));
+}
+
+/// Unmatched sources get the fallback, or a RuntimeError without one.
+#[test]
+fn test_mock_unmatched_source() {
+    let strict = MockExecutor::new().on("x", returning("1"));
+    match strict
+        .execute("print(1)", ExecutionSettings::default())
+        .error
+    {
+        Some(ExecutionError::RuntimeError { message, .. }) => {
+            assert!(message.contains("print(1)"), "got: {message}")
+        }
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+
+    let lenient = strict.otherwise(returning("None"));
+    let result = lenient.execute("print(1)", ExecutionSettings::default());
+    assert_eq!(result.return_value.as_deref(), Some("None"));
+}
+
+/// The mock can stand in behind a boxed trait object and records its calls.
+#[test]
+fn test_mock_boxed_records_calls() {
+    let executor: Box<dyn PyExecutor> = Box::new(MockExecutor::new().otherwise(returning("0")));
+    executor.execute("a = 1", ExecutionSettings::default());
+    executor.execute("a", ExecutionSettings::default());
+
+    let mock = MockExecutor::new();
+    assert!(mock.calls().is_empty());
+    mock.execute("b", ExecutionSettings::default());
+    assert_eq!(mock.calls(), ["b"]);
+}
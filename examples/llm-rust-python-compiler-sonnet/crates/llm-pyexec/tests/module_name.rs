@@ -0,0 +1,61 @@
+// crates/llm-pyexec/tests/module_name.rs
+// Tests: ExecutionSettings::module_name (the `__name__` seen by user code)
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+const MAIN_GUARD: &str = "\
+if __name__ == '__main__':
+    print('x')";
+
+fn named(name: &str) -> ExecutionSettings {
+    ExecutionSettings {
+        module_name: Some(name.to_string()),
+        ..ExecutionSettings::default()
+    }
+}
+
+#[test]
+fn test_main_guard_runs_by_default() {
+    let result = execute(MAIN_GUARD, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "x\n");
+}
+
+#[test]
+fn test_main_guard_skipped_under_custom_name() {
+    let result = execute(MAIN_GUARD, named("snippet"));
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "");
+}
+
+#[test]
+fn test_custom_name_is_visible_to_user_code() {
+    let result = execute("__name__", named("snippet"));
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("'snippet'"));
+}
+
+/// Renaming the scope does not let user code slip past the allowlist.
+#[test]
+fn test_custom_name_still_enforces_allowlist() {
+    let result = execute("import socket", named("snippet"));
+    assert!(
+        matches!(
+            result.error,
+            Some(ExecutionError::ModuleNotAllowed { ref module_name }) if module_name == "socket"
+        ),
+        "got {:?}",
+        result.error
+    );
+
+    let allowed = execute("import json\njson.dumps([1])", named("snippet"));
+    assert_eq!(allowed.error, None, "stderr: {}", allowed.stderr);
+    assert_eq!(allowed.return_value.as_deref(), Some("'[1]'"));
+}
+
+#[test]
+fn test_session_uses_custom_name() {
+    let session = Session::new(named("repl"));
+    let result = session.eval("__name__");
+    assert_eq!(result.return_value.as_deref(), Some("'repl'"));
+}
@@ -0,0 +1,89 @@
+// crates/llm-pyexec/tests/output_encoding.rs
+// Tests: ExecutionSettings::output_encoding_errors (Replace vs Strict)
+
+use llm_pyexec::{execute, EncodingErrors, ExecutionError, ExecutionSettings};
+
+fn settings(errors: EncodingErrors) -> ExecutionSettings {
+    ExecutionSettings {
+        output_encoding_errors: errors,
+        ..ExecutionSettings::default()
+    }
+}
+
+#[test]
+fn test_replace_is_the_default() {
+    assert_eq!(
+        ExecutionSettings::default().output_encoding_errors,
+        EncodingErrors::Replace
+    );
+}
+
+/// Replace keeps the lossy behaviour: the run succeeds with `U+FFFD` in place
+/// of the invalid byte.
+#[test]
+fn test_replace_substitutes_invalid_bytes() {
+    let result = execute(
+        "import sys\nsys.stdout.buffer.write(b'ok \\xff')\nNone",
+        settings(EncodingErrors::Replace),
+    );
+    assert_eq!(result.error, None);
+    assert_eq!(result.stdout, "ok \u{FFFD}");
+    assert!(result.output_lossy);
+}
+
+#[test]
+fn test_strict_reports_invalid_stdout() {
+    let result = execute(
+        "import sys\nsys.stdout.buffer.write(b'\\xff')\nNone",
+        settings(EncodingErrors::Strict),
+    );
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::InvalidOutputEncoding {
+            stream: "stdout".to_string()
+        })
+    );
+    assert!(result.output_lossy);
+}
+
+#[test]
+fn test_strict_reports_invalid_stderr() {
+    let result = execute(
+        "import sys\nprint('fine')\nsys.stderr.buffer.write(b'\\xfe')\nNone",
+        settings(EncodingErrors::Strict),
+    );
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::InvalidOutputEncoding {
+            stream: "stderr".to_string()
+        })
+    );
+    assert_eq!(result.stdout, "fine\n");
+}
+
+/// Valid UTF-8, including non-ASCII text, passes under Strict.
+#[test]
+fn test_strict_accepts_valid_output() {
+    let result = execute(
+        "import sys\nsys.stdout.buffer.write('héllo'.encode())\nprint(' ok')",
+        settings(EncodingErrors::Strict),
+    );
+    assert_eq!(result.error, None);
+    assert_eq!(result.stdout, "héllo ok\n");
+    assert!(!result.output_lossy);
+}
+
+/// A snippet that already failed keeps its own error.
+#[test]
+fn test_strict_keeps_earlier_error() {
+    let result = execute(
+        "import sys\nsys.stdout.buffer.write(b'\\xff')\n1 / 0",
+        settings(EncodingErrors::Strict),
+    );
+    assert!(
+        matches!(result.error, Some(ExecutionError::RuntimeError { .. })),
+        "got {:?}",
+        result.error
+    );
+    assert!(result.output_lossy);
+}
@@ -0,0 +1,64 @@
+// crates/llm-pyexec/tests/output_limit_secondary_error.rs
+// Tests: ExecutionResult::output_truncated / secondary_error
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+fn small_limit() -> ExecutionSettings {
+    ExecutionSettings {
+        max_output_bytes: 64,
+        ..ExecutionSettings::default()
+    }
+}
+
+/// The error raised after the output overflowed survives next to
+/// `OutputLimitExceeded`, which stays the primary error.
+#[test]
+fn test_error_after_print_storm_is_kept() {
+    let code = "\
+for i in range(100):
+    try:
+        print('line', i)
+    except RuntimeError:
+        pass
+1 / 0";
+    let result = execute(code, small_limit());
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 64 })
+    );
+    assert!(result.output_truncated);
+    match result.secondary_error {
+        Some(ExecutionError::RuntimeError { traceback, .. }) => {
+            assert!(traceback.contains("ZeroDivisionError"), "{traceback}");
+        }
+        other => panic!("expected an underlying RuntimeError, got {other:?}"),
+    }
+
+    let json = serde_json::to_value(&result).expect("serialize");
+    assert_eq!(json["error"]["type"], "OutputLimitExceeded");
+    assert_eq!(json["secondary_error"]["type"], "RuntimeError");
+}
+
+/// Failing on the overflowing write itself leaves no secondary error.
+#[test]
+fn test_overflow_alone_has_no_secondary_error() {
+    let result = execute("print('x' * 1000)", small_limit());
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 64 })
+    );
+    assert!(result.output_truncated);
+    assert_eq!(result.secondary_error, None);
+}
+
+#[test]
+fn test_errors_within_the_limit_are_unchanged() {
+    let result = execute("print('ok')\n1 / 0", small_limit());
+    assert!(!result.output_truncated);
+    assert_eq!(result.secondary_error, None);
+    assert!(
+        matches!(result.error, Some(ExecutionError::RuntimeError { .. })),
+        "{:?}",
+        result.error
+    );
+}
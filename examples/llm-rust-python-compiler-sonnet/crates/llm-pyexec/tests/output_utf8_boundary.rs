@@ -0,0 +1,86 @@
+// crates/llm-pyexec/tests/output_utf8_boundary.rs
+// Tests: ExecutionResult::stdout / stderr stay valid UTF-8 when
+// ExecutionSettings::max_output_bytes falls inside a multi-byte character
+
+use llm_pyexec::{execute, ExecutionError, ExecutionResult, ExecutionSettings};
+
+/// CJK (3-byte) and emoji (4-byte) text, with ASCII to shift the offsets.
+const TEXTS: &[&str] = &["中文字符", "🎉🎉🎉", "a中🎉b文", "é€🎉中"];
+
+fn run(code: &str, limit: usize) -> ExecutionResult {
+    let settings = ExecutionSettings {
+        max_output_bytes: limit,
+        ..ExecutionSettings::default()
+    };
+    execute(code, settings)
+}
+
+/// `captured` is the longest whole-character prefix of `written` that fits in
+/// `limit` bytes, and the result survives a JSON round trip unchanged.
+fn assert_cut_at_boundary(result: &ExecutionResult, captured: &str, written: &str, limit: usize) {
+    let expected_len = written
+        .char_indices()
+        .map(|(i, c)| i + c.len_utf8())
+        .take_while(|&end| end <= limit)
+        .last()
+        .unwrap_or(0);
+    assert_eq!(captured, &written[..expected_len], "limit {limit}");
+    assert!(!result.output_lossy, "limit {limit}");
+
+    let json = serde_json::to_string(result).expect("serialize");
+    let back: ExecutionResult = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(back.stdout, result.stdout);
+    assert_eq!(back.stderr, result.stderr);
+}
+
+#[test]
+fn test_stdout_cut_at_every_offset() {
+    for text in TEXTS {
+        for limit in 0..text.len() {
+            let result = run(&format!("print({text:?}, end='')"), limit);
+            assert_eq!(
+                result.error,
+                Some(ExecutionError::OutputLimitExceeded { limit_bytes: limit })
+            );
+            assert!(result.output_truncated);
+            assert_cut_at_boundary(&result, &result.stdout, text, limit);
+        }
+    }
+}
+
+#[test]
+fn test_stderr_cut_at_every_offset() {
+    for text in TEXTS {
+        for limit in 0..text.len() {
+            let code = format!("import sys\nsys.stderr.write({text:?})");
+            let result = run(&code, limit);
+            assert!(result.output_truncated, "limit {limit}");
+            assert_cut_at_boundary(&result, &result.stderr, text, limit);
+        }
+    }
+}
+
+/// Text that fits exactly is kept whole; one byte less drops the last
+/// character rather than part of it.
+#[test]
+fn test_limit_exactly_at_character_end() {
+    let text = "数据🎉";
+    let result = run(&format!("print({text:?}, end='')"), text.len());
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.stdout, text);
+
+    let result = run(&format!("print({text:?}, end='')"), text.len() - 1);
+    assert_eq!(result.stdout, "数据");
+}
+
+/// Output spread over several writes and both streams is cut the same way.
+#[test]
+fn test_cut_across_writes_and_streams() {
+    let code = "import sys\nprint('日本')\nsys.stderr.write('🎉')\nprint('語')";
+    // "日本\n" is 7 bytes; the emoji needs 4 more.
+    let result = run(code, 9);
+    assert_eq!(result.stdout, "日本\n");
+    assert_eq!(result.stderr, "");
+    assert!(result.output_truncated);
+    assert!(!result.output_lossy);
+}
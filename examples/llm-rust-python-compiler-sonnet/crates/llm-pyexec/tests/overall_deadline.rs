@@ -0,0 +1,157 @@
+// crates/llm-pyexec/tests/overall_deadline.rs
+// Tests: ExecutionSettings::timeout_ns as one deadline over checkout and execution
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{
+    execute_with, BytecodeCache, ExecutionError, ExecutionMode, ExecutionSettings, InterpreterPool,
+    TimeoutKind,
+};
+
+const BUSY_LOOP: &str = "total = 0\nfor i in range(3000000):\n    total += i\ntotal";
+
+fn timeout_error(timeout: Duration) -> Option<ExecutionError> {
+    Some(ExecutionError::Timeout {
+        limit_ns: timeout.as_nanos() as u64,
+        kind: TimeoutKind::Wall,
+    })
+}
+
+/// Run `check` while the only slot of a fresh pool is busy with [`BUSY_LOOP`].
+fn with_saturated_pool(check: impl FnOnce(&InterpreterPool, &BytecodeCache)) {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    std::thread::scope(|scope| {
+        let busy =
+            scope.spawn(|| execute_with(&pool, &cache, BUSY_LOOP, ExecutionSettings::default()));
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while pool.idle_count() > 0 {
+            assert!(Instant::now() < deadline, "slot never became busy");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        check(&pool, &cache);
+        busy.join().expect("busy call panicked");
+    });
+}
+
+/// With the default 30-second checkout timeout, a saturated pool makes the
+/// call time out after roughly `timeout_ns`, not after the checkout timeout.
+#[test]
+fn test_saturated_pool_times_out_within_budget() {
+    with_saturated_pool(|pool, cache| {
+        for mode in [ExecutionMode::PoolPreferred, ExecutionMode::PoolOnly] {
+            let timeout = Duration::from_millis(50);
+            let settings = ExecutionSettings {
+                timeout_ns: timeout.as_nanos() as u64,
+                execution_mode: mode,
+                ..ExecutionSettings::default()
+            };
+            let started = Instant::now();
+            let result = execute_with(pool, cache, "1 + 1", settings);
+            let elapsed = started.elapsed();
+
+            assert_eq!(result.error, timeout_error(timeout), "{mode:?}");
+            assert!(!result.used_pool);
+            assert!(elapsed >= timeout, "{mode:?} returned after {elapsed:?}");
+            assert!(
+                elapsed < Duration::from_secs(2),
+                "{mode:?} returned after {elapsed:?}"
+            );
+        }
+    });
+}
+
+/// A fallback after a short checkout only gets what is left of the budget.
+#[test]
+fn test_fallback_runs_on_remaining_budget() {
+    with_saturated_pool(|pool, cache| {
+        let checkout = Duration::from_millis(200);
+        let timeout = Duration::from_millis(500);
+        let settings = ExecutionSettings {
+            timeout_ns: timeout.as_nanos() as u64,
+            pool_checkout_timeout_ns: Some(checkout.as_nanos() as u64),
+            extra_allowed_modules: vec!["time".to_string()],
+            ..ExecutionSettings::default()
+        };
+        let started = Instant::now();
+        let result = execute_with(pool, cache, "import time\ntime.sleep(2)", settings);
+        let elapsed = started.elapsed();
+
+        assert_eq!(result.error, timeout_error(timeout));
+        assert!(!result.used_pool);
+        assert!(result.queue_duration_ns >= checkout.as_nanos() as u64);
+        // Checkout plus a full timeout would take 700ms.
+        assert!(
+            elapsed < timeout + checkout / 2,
+            "returned after {elapsed:?}"
+        );
+    });
+}
+
+/// A PoolOnly call whose checkout timeout runs out first is still
+/// `PoolUnavailable`.
+#[test]
+fn test_checkout_timeout_within_budget_is_pool_unavailable() {
+    with_saturated_pool(|pool, cache| {
+        let settings = ExecutionSettings {
+            execution_mode: ExecutionMode::PoolOnly,
+            pool_checkout_timeout_ns: Some(10_000_000),
+            ..ExecutionSettings::default()
+        };
+        let result = execute_with(pool, cache, "1 + 1", settings);
+        assert_eq!(
+            result.error,
+            Some(ExecutionError::PoolUnavailable {
+                checkout_timeout_ns: 10_000_000
+            })
+        );
+    });
+}
+
+/// A call that waited for its slot does not get a fresh budget once it runs:
+/// the slot is interrupted and idle again about `timeout_ns` after the call.
+#[test]
+fn test_slow_checkout_does_not_extend_deadline() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    let hold = Duration::from_millis(500);
+    let timeout = Duration::from_secs(1);
+    std::thread::scope(|scope| {
+        let sleeper = scope.spawn(|| {
+            let settings = ExecutionSettings {
+                extra_allowed_modules: vec!["time".to_string()],
+                ..ExecutionSettings::default()
+            };
+            let code = format!("import time\ntime.sleep({})", hold.as_secs_f64());
+            execute_with(&pool, &cache, &code, settings)
+        });
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while pool.idle_count() > 0 {
+            assert!(Instant::now() < deadline, "slot never became busy");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let settings = ExecutionSettings {
+            timeout_ns: timeout.as_nanos() as u64,
+            ..ExecutionSettings::default()
+        };
+        let started = Instant::now();
+        let result = execute_with(&pool, &cache, "while True:\n    pass", settings);
+        assert_eq!(result.error, timeout_error(timeout));
+
+        while pool.idle_count() == 0 {
+            // With the deadline counted from checkout this would take 1.5s.
+            assert!(
+                started.elapsed() < timeout + hold / 2,
+                "slot still busy after {:?}",
+                started.elapsed()
+            );
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        sleeper.join().expect("sleeping call panicked");
+    });
+}
@@ -0,0 +1,77 @@
+// crates/llm-pyexec/tests/partial_output.rs
+// Tests: stdout holds exactly what was written before a failure, alongside the error
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+fn runtime_error_message(error: &Option<ExecutionError>) -> &str {
+    match error {
+        Some(ExecutionError::RuntimeError { message, .. }) => message,
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_print_then_raise_populates_stdout_and_error() {
+    let result = execute("print('a'); 1/0", ExecutionSettings::default());
+    assert_eq!(result.stdout, "a\n");
+    assert_eq!(runtime_error_message(&result.error), "division by zero");
+}
+
+/// Output from statements after the failing one never appears.
+#[test]
+fn test_stdout_stops_at_the_failing_statement() {
+    let code = "\
+for i in range(5):
+    print(i)
+    if i == 2:
+        raise ValueError('stop at 2')
+print('unreachable')";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.stdout, "0\n1\n2\n");
+    assert_eq!(runtime_error_message(&result.error), "stop at 2");
+}
+
+/// A write without a trailing newline is not held back in a buffer.
+#[test]
+fn test_unterminated_write_before_error_is_kept() {
+    let code = "import sys\nsys.stdout.write('partial')\nraise KeyError('k')";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.stdout, "partial");
+    assert!(matches!(
+        result.error,
+        Some(ExecutionError::RuntimeError { .. })
+    ));
+}
+
+/// Output written inside a function that raises, from a caller that catches
+/// nothing, is kept in order with output from before the call.
+#[test]
+fn test_output_from_nested_calls_before_error() {
+    let code = "\
+def inner():
+    print('inner')
+    return [][0]
+
+print('outer')
+inner()";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.stdout, "outer\ninner\n");
+    assert_eq!(
+        runtime_error_message(&result.error),
+        "list index out of range"
+    );
+}
+
+#[test]
+fn test_session_call_reports_its_own_partial_output() {
+    let session = Session::new(ExecutionSettings::default());
+    let first = session.eval("print('one')");
+    assert_eq!(first.stdout, "one\n");
+
+    let failed = session.eval("print('two'); undefined_name");
+    assert_eq!(failed.stdout, "two\n");
+    assert!(matches!(
+        failed.error,
+        Some(ExecutionError::RuntimeError { .. })
+    ));
+}
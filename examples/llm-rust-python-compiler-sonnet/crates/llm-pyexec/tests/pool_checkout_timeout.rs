@@ -0,0 +1,55 @@
+// crates/llm-pyexec/tests/pool_checkout_timeout.rs
+// Tests: ExecutionSettings::pool_checkout_timeout_ns
+//
+// This binary must contain only this test: it sizes the global pool through
+// PYEXEC_POOL_SIZE, which is read exactly once.
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute, ExecutionSettings, InterpreterPool};
+
+#[test]
+fn test_zero_checkout_timeout_falls_back_when_pool_busy() {
+    std::env::set_var("PYEXEC_POOL_SIZE", "1");
+    let pool = InterpreterPool::global();
+    assert_eq!(pool.size(), 1);
+
+    // Occupy the only slot.
+    let busy = std::thread::spawn(|| {
+        execute(
+            "while True:\n    pass",
+            ExecutionSettings {
+                timeout_ns: 3_000_000_000,
+                ..ExecutionSettings::default()
+            },
+        )
+    });
+    let deadline = Instant::now() + Duration::from_secs(30);
+    while pool.idle_count() > 0 {
+        assert!(Instant::now() < deadline, "slot never became busy");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    let fallbacks_before = pool.metrics().fallbacks;
+    let result = execute(
+        "6 * 7",
+        ExecutionSettings {
+            pool_checkout_timeout_ns: Some(0),
+            ..ExecutionSettings::default()
+        },
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+    assert_eq!(pool.metrics().fallbacks, fallbacks_before + 1);
+    assert!(
+        result.duration_ns < Duration::from_secs(30).as_nanos() as u64,
+        "fallback waited for the default checkout timeout: {} ns",
+        result.duration_ns
+    );
+
+    let _ = busy.join();
+}
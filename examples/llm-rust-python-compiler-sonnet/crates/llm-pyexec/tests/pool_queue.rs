@@ -0,0 +1,45 @@
+// crates/llm-pyexec/tests/pool_queue.rs
+// Tests: InterpreterPool::with_queue under a burst of concurrent calls
+
+use llm_pyexec::{execute_with, BytecodeCache, ExecutionSettings, InterpreterPool};
+
+/// A 1-slot pool with a queue of 4 serves 5 concurrent calls without falling
+/// back, even though no caller is willing to wait for a slot.
+#[test]
+fn test_queue_absorbs_burst_without_fallback() {
+    let pool = InterpreterPool::new(1).with_queue(4);
+    let cache = BytecodeCache::new(8);
+    let settings = ExecutionSettings {
+        timeout_ns: 60_000_000_000,
+        pool_checkout_timeout_ns: Some(0),
+        ..ExecutionSettings::default()
+    };
+
+    let results: Vec<_> = std::thread::scope(|scope| {
+        let handles: Vec<_> = (0..5)
+            .map(|i| {
+                let (pool, cache, settings) = (&pool, &cache, settings.clone());
+                scope.spawn(move || {
+                    let code = format!("total = 0\nfor i in range(100000):\n    total += i\n{i}");
+                    execute_with(pool, cache, &code, settings)
+                })
+            })
+            .collect();
+        handles
+            .into_iter()
+            .map(|h| h.join().expect("execute panicked"))
+            .collect()
+    });
+
+    for (i, result) in results.iter().enumerate() {
+        assert!(result.error.is_none(), "call {i} error: {:?}", result.error);
+        assert_eq!(result.return_value, Some(i.to_string()));
+        assert!(result.used_pool, "call {i} fell back");
+    }
+
+    let metrics = pool.metrics();
+    assert_eq!(metrics.fallbacks, 0);
+    assert_eq!(metrics.dispatches, 5);
+    assert!(metrics.queued >= 1, "no call was queued: {metrics:?}");
+    assert_eq!(metrics.queue_len, 0);
+}
@@ -0,0 +1,54 @@
+// crates/llm-pyexec/tests/pool_warmup.rs
+// Tests: InterpreterPool::new_with_warmup
+
+use llm_pyexec::{execute_with, BytecodeCache, ExecutionSettings, InterpreterPool};
+
+/// Reports whether `module` was already loaded before the snippet imported it.
+fn preloaded(pool: &InterpreterPool, module: &str) -> String {
+    let code = format!("import sys\nloaded = '{module}' in sys.modules\nimport {module}\nloaded");
+    let result = execute_with(
+        pool,
+        &BytecodeCache::new(8),
+        &code,
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.error, None);
+    assert!(result.used_pool);
+    result.return_value.expect("return value")
+}
+
+/// Warm modules are loaded before the first call and survive the per-call
+/// `sys.modules` reset, so no call pays their first import.
+#[test]
+fn test_warm_modules_stay_loaded_across_calls() {
+    let pool = InterpreterPool::new_with_warmup(1, &["json", "datetime"]);
+    for _ in 0..3 {
+        assert_eq!(preloaded(&pool, "json"), "True");
+        assert_eq!(preloaded(&pool, "datetime"), "True");
+    }
+
+    let result = execute_with(
+        &pool,
+        &BytecodeCache::new(8),
+        "import json\njson.dumps({})",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.return_value.as_deref(), Some("'{}'"));
+}
+
+/// Without warm-up, a module a call imports is dropped again afterwards.
+#[test]
+fn test_cold_pool_reimports_each_call() {
+    let pool = InterpreterPool::new(1);
+    for _ in 0..2 {
+        assert_eq!(preloaded(&pool, "datetime"), "False");
+    }
+}
+
+/// A module that cannot be imported is skipped rather than failing the pool.
+#[test]
+fn test_unknown_warm_module_is_skipped() {
+    let pool = InterpreterPool::new_with_warmup(1, &["no_such_module_xyz", "json"]);
+    assert_eq!(pool.idle_count(), 1);
+    assert_eq!(preloaded(&pool, "json"), "True");
+}
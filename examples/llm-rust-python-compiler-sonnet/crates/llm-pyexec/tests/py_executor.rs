@@ -0,0 +1,40 @@
+// crates/llm-pyexec/tests/py_executor.rs
+// Tests: PyExecutor / DefaultExecutor
+
+use std::sync::Arc;
+
+use llm_pyexec::{BytecodeCache, DefaultExecutor, ExecutionSettings, InterpreterPool, PyExecutor};
+
+/// A boxed DefaultExecutor runs real code on the resources it was given.
+#[test]
+fn test_default_executor_with_resources_boxed() {
+    let pool = Arc::new(InterpreterPool::new(1));
+    let cache = Arc::new(BytecodeCache::new(8));
+    let executor: Box<dyn PyExecutor> = Box::new(DefaultExecutor::with_resources(
+        Arc::clone(&pool),
+        Arc::clone(&cache),
+    ));
+
+    let result = executor.execute("6 * 7", ExecutionSettings::default());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+    assert!(result.used_pool);
+    assert_eq!(pool.metrics().dispatches, 1);
+    assert_eq!(cache.len(), 1);
+}
+
+/// The global-backed executor behaves like execute().
+#[test]
+fn test_default_executor_matches_execute() {
+    let executor = DefaultExecutor::new();
+    let result = executor.execute("print('hi')", ExecutionSettings::default());
+    assert_eq!(result.stdout, "hi\n");
+    assert_eq!(
+        result.stdout,
+        llm_pyexec::execute("print('hi')", ExecutionSettings::default()).stdout
+    );
+}
@@ -0,0 +1,70 @@
+// crates/llm-pyexec/tests/random_module.rs
+// Tests: the frozen `random` module (src/frozen/random.py)
+//
+// Expected values are what CPython prints for the same seed.
+
+use llm_pyexec::{execute, ExecutionSettings};
+
+fn run(code: &str) -> String {
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    result.stdout
+}
+
+#[test]
+fn test_seeded_sequence_matches_cpython() {
+    let code = "\
+import random
+random.seed(7)
+print(random.random())
+print(random.randrange(0, 100, 5))
+print(random.choice(['a', 'b', 'c', 'd']))
+items = list(range(10))
+random.shuffle(items)
+print(items)
+print(random.sample(range(100), 5))
+print(random.uniform(1, 2))
+print(random.getrandbits(64))";
+    assert_eq!(
+        run(code),
+        "0.32383276483316237\n20\nd\n[3, 6, 5, 7, 9, 4, 2, 8, 1, 0]\n\
+         [55, 53, 8, 30, 11]\n1.5510472537913857\n15252729517591092662\n"
+    );
+}
+
+#[test]
+fn test_reseeding_repeats_the_sequence() {
+    let code = "\
+import random
+random.seed('label')
+first = [random.randint(1, 6) for _ in range(20)]
+random.seed('label')
+print(first == [random.randint(1, 6) for _ in range(20)])";
+    assert_eq!(run(code), "True\n");
+}
+
+#[test]
+fn test_random_instances_are_independent() {
+    let code = "\
+import random
+a = random.Random(3)
+b = random.Random(3)
+a.random()
+state = b.getstate()
+x = b.random()
+b.setstate(state)
+print(b.random() == x, a.random() == x)";
+    assert_eq!(run(code), "True False\n");
+}
+
+#[test]
+fn test_empty_inputs_raise() {
+    let code = "\
+import random
+for call in (lambda: random.choice([]), lambda: random.randrange(0)):
+    try:
+        call()
+    except (IndexError, ValueError) as e:
+        print(type(e).__name__)";
+    assert_eq!(run(code), "IndexError\nValueError\n");
+}
@@ -0,0 +1,51 @@
+// crates/llm-pyexec/tests/recursion_limit.rs
+// Tests: ExecutionSettings::recursion_limit / ExecutionError::RecursionLimitExceeded
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, DEFAULT_RECURSION_LIMIT};
+
+/// Unbounded recursion is reported with its own variant, not as a RuntimeError.
+#[test]
+fn test_infinite_recursion_is_recursion_limit_exceeded() {
+    let result = execute(
+        "def f():\n    return f()\nf()",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::RecursionLimitExceeded {
+            limit: DEFAULT_RECURSION_LIMIT
+        })
+    );
+}
+
+/// Legitimate deep recursion succeeds when the limit allows it.
+#[test]
+fn test_depth_500_recursion_within_limit_1000_succeeds() {
+    let settings = ExecutionSettings {
+        recursion_limit: Some(1000),
+        ..ExecutionSettings::default()
+    };
+    let code = "def depth(n):\n    return 0 if n == 0 else 1 + depth(n - 1)\ndepth(500)";
+    let result = execute(code, settings);
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("500"));
+}
+
+/// The reported limit is the one configured for the call.
+#[test]
+fn test_custom_limit_reported() {
+    let settings = ExecutionSettings {
+        recursion_limit: Some(100),
+        ..ExecutionSettings::default()
+    };
+    let code = "def depth(n):\n    return 0 if n == 0 else 1 + depth(n - 1)\ndepth(200)";
+    let result = execute(code, settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::RecursionLimitExceeded { limit: 100 })
+    );
+}
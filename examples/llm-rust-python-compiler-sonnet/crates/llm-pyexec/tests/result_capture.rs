@@ -0,0 +1,78 @@
+// crates/llm-pyexec/tests/result_capture.rs
+// Tests: ExecutionSettings::result_capture (Wrap / Single)
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, ResultCapture};
+
+fn single() -> ExecutionSettings {
+    ExecutionSettings {
+        result_capture: ResultCapture::Single,
+        ..ExecutionSettings::default()
+    }
+}
+
+fn syntax_error_position(code: &str, settings: ExecutionSettings) -> (u32, u32) {
+    match execute(code, settings).error {
+        Some(ExecutionError::SyntaxError { line, col, .. }) => (line, col),
+        other => panic!("expected SyntaxError, got {other:?}"),
+    }
+}
+
+/// A bare final expression, including a call, becomes the return value.
+#[test]
+fn test_single_captures_final_expression() {
+    let result = execute("x = 6\nx * 7", single());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+
+    let result = execute("len([1, 2, 3])", single());
+    assert_eq!(result.return_value.as_deref(), Some("3"));
+}
+
+/// Only the final statement is captured, and nothing is echoed to stdout.
+#[test]
+fn test_single_ignores_earlier_expressions() {
+    let result = execute("'first'\nprint('out')\n'last'", single());
+    assert_eq!(result.return_value.as_deref(), Some("'last'"));
+    assert_eq!(result.stdout, "out\n");
+
+    let result = execute("'only'\nx = 1", single());
+    assert_eq!(result.return_value, None);
+    assert_eq!(result.stdout, "");
+}
+
+/// A syntax error on the last line is reported where it is in the source.
+#[test]
+fn test_single_syntax_error_position_unshifted() {
+    // Not the last line, so never rewritten: the reference position.
+    let expected = syntax_error_position("x = 1\nx +* 2\npass", ExecutionSettings::default());
+    assert_eq!(expected.0, 2);
+
+    assert_eq!(syntax_error_position("x = 1\nx +* 2", single()), expected);
+}
+
+/// Runtime tracebacks name the line the error is on.
+#[test]
+fn test_single_runtime_error_line() {
+    let result = execute("a = 1\nb = 0\na / b", single());
+    match result.error {
+        Some(ExecutionError::RuntimeError { traceback, .. }) => {
+            assert!(traceback.contains("line 3"), "got: {traceback}")
+        }
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+/// Settings serialized before the field existed default to Wrap.
+#[test]
+fn test_result_capture_defaults_to_wrap() {
+    let mut json = serde_json::to_value(ExecutionSettings::default()).expect("serialize");
+    json.as_object_mut()
+        .expect("object")
+        .remove("result_capture");
+    let settings: ExecutionSettings = serde_json::from_value(json).expect("deserialize");
+    assert_eq!(settings.result_capture, ResultCapture::Wrap);
+}
@@ -0,0 +1,64 @@
+// crates/llm-pyexec/tests/result_sentinel.rs
+// Tests: per-call result variable (user code assigning `__result__`)
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+fn run(code: &str) -> Option<String> {
+    let result = execute(code, ExecutionSettings::default());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    result.return_value
+}
+
+/// A user's own `__result__` is not mistaken for the last expression's value.
+#[test]
+fn test_user_result_variable_is_not_the_return_value() {
+    assert_eq!(run("__result__ = \"user\"\nx = 1"), None);
+    assert_eq!(run("__result__ = \"user\"\nprint('done')"), None);
+}
+
+/// A bare final expression is still captured, including one that reads the
+/// user's `__result__`.
+#[test]
+fn test_bare_final_expression_still_returned() {
+    assert_eq!(run("1 + 2").as_deref(), Some("3"));
+    assert_eq!(run("__result__ = 5\n__result__ + 1").as_deref(), Some("6"));
+    assert_eq!(
+        run("__result__ = \"user\"\n__result__").as_deref(),
+        Some("'user'")
+    );
+}
+
+/// Sessions neither leak the per-call name into the namespace nor pick up a
+/// `__result__` the user left behind.
+#[test]
+fn test_session_result_variable() {
+    let session = Session::new(ExecutionSettings::default());
+    assert_eq!(session.eval("__result__ = 'user'").return_value, None);
+    assert_eq!(session.eval("x = 1").return_value, None);
+    assert_eq!(session.eval("x + 1").return_value.as_deref(), Some("2"));
+
+    let names = session.eval("sorted(k for k in globals() if k.startswith('__pyexec'))");
+    assert_eq!(names.return_value.as_deref(), Some("[]"));
+    assert_eq!(
+        session.eval("__result__").return_value.as_deref(),
+        Some("'user'")
+    );
+}
+
+/// Columns on a wrapped last line are reported against `__result__ = <expr>`,
+/// whatever name the run actually used.
+#[test]
+fn test_error_column_matches_canonical_wrapped_source() {
+    let location = |code: &str| match execute(code, ExecutionSettings::default()).error {
+        Some(ExecutionError::RuntimeError { line, col, .. }) => (line, col),
+        other => panic!("expected RuntimeError, got {other:?}"),
+    };
+    assert_eq!(
+        location("x = 0\n1 / x"),
+        location("x = 0\n__result__ = 1 / x")
+    );
+}
@@ -0,0 +1,42 @@
+// crates/llm-pyexec/tests/runtime_error_location.rs
+// Tests: ExecutionError::RuntimeError line/col from the innermost user frame
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+fn runtime_error_location(code: &str) -> (Option<u32>, Option<u32>) {
+    match execute(code, ExecutionSettings::default()).error {
+        Some(ExecutionError::RuntimeError { line, col, .. }) => (line, col),
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_error_on_second_line_reports_line_2() {
+    let (line, col) = runtime_error_location("x = 1\ny = 1/0");
+    assert_eq!(line, Some(2));
+    assert!(col.is_some_and(|col| col >= 1), "col: {col:?}");
+}
+
+/// An exception raised inside a user function points into the function body,
+/// not at the line that called it.
+#[test]
+fn test_error_in_user_function_reports_innermost_user_line() {
+    let code = "def f(d):\n    return d['missing']\n\nx = 1\nf({})";
+    let (line, _) = runtime_error_location(code);
+    assert_eq!(line, Some(2));
+}
+
+/// An exception raised inside an allowed stdlib call never points into the
+/// library's own source.
+#[test]
+fn test_error_in_stdlib_call_points_at_user_call_site() {
+    let code = "import json\n\nvalue = json.loads('{not json')";
+    let (line, col) = runtime_error_location(code);
+    assert!(
+        line.is_none() || line == Some(3),
+        "expected no location or the call on line 3, got {line:?}"
+    );
+    if line.is_none() {
+        assert_eq!(col, None);
+    }
+}
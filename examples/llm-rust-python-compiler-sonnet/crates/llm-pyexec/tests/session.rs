@@ -0,0 +1,75 @@
+// crates/llm-pyexec/tests/session.rs
+// Tests: stateful Session eval
+
+use llm_pyexec::{ExecutionError, ExecutionSettings, Session};
+
+/// Names and imports defined by one eval are visible to the next.
+#[test]
+fn test_session_state_persists_across_evals() {
+    let session = Session::new(ExecutionSettings::default());
+
+    let first = session.eval("x = 1");
+    assert!(first.error.is_none(), "unexpected error: {:?}", first.error);
+    assert_eq!(first.return_value, None);
+
+    let second = session.eval("x + 1");
+    assert!(
+        second.error.is_none(),
+        "unexpected error: {:?}",
+        second.error
+    );
+    assert_eq!(second.return_value.as_deref(), Some("2"));
+
+    session.eval("import math\ndef area(r):\n    return math.pi * r * r");
+    let third = session.eval("round(area(1), 2)");
+    assert_eq!(third.return_value.as_deref(), Some("3.14"));
+
+    // A statement-only eval does not report a stale return value.
+    let fourth = session.eval("y = x");
+    assert_eq!(fourth.return_value, None);
+}
+
+/// A new Session does not see another session's names.
+#[test]
+fn test_fresh_session_starts_empty() {
+    let first = Session::new(ExecutionSettings::default());
+    first.eval("secret = 42");
+
+    let fresh = Session::new(ExecutionSettings::default());
+    let result = fresh.eval("secret");
+    match &result.error {
+        Some(ExecutionError::RuntimeError { message, .. }) => {
+            assert!(message.contains("secret"), "unexpected message: {message}");
+        }
+        other => panic!("expected NameError, got {other:?}"),
+    }
+}
+
+/// The allowlist and output limit still apply to every call.
+#[test]
+fn test_session_enforces_limits_per_call() {
+    let settings = ExecutionSettings {
+        max_output_bytes: 50,
+        ..ExecutionSettings::default()
+    };
+    let session = Session::new(settings);
+
+    let denied = session.eval("import socket");
+    assert_eq!(
+        denied.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "socket".to_string()
+        })
+    );
+
+    let flood = session.eval("print('x' * 1000)");
+    assert_eq!(
+        flood.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 50 })
+    );
+
+    // Each call gets a fresh output budget.
+    let ok = session.eval("print('fine')");
+    assert!(ok.error.is_none(), "unexpected error: {:?}", ok.error);
+    assert_eq!(ok.stdout, "fine\n");
+}
@@ -0,0 +1,61 @@
+// crates/llm-pyexec/tests/stdlib_paths.rs
+// Tests: ExecutionSettings::stdlib_paths
+
+use std::path::PathBuf;
+
+use llm_pyexec::{execute, ExecutionMode, ExecutionSettings};
+
+/// Creates a fresh directory standing in for a stdlib installation.
+fn stdlib_dir(name: &str) -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("llm-pyexec-stdlib-{}-{name}", std::process::id()));
+    std::fs::create_dir_all(&dir).expect("create stdlib dir");
+    std::fs::write(dir.join("fake_stdlib_mod.py"), "ANSWER = 42\n").expect("write module");
+    dir
+}
+
+#[test]
+fn test_stdlib_paths_defaults_to_none() {
+    let json = serde_json::to_value(ExecutionSettings::default()).unwrap();
+    let mut object = json.as_object().unwrap().clone();
+    object.remove("stdlib_paths");
+    let settings: ExecutionSettings = serde_json::from_value(object.into()).unwrap();
+    assert_eq!(settings.stdlib_paths, None);
+    assert_eq!(
+        settings.effective_execution_mode(),
+        ExecutionMode::PoolPreferred
+    );
+}
+
+/// Pool slots are built with the default paths, so an override runs fresh.
+#[test]
+fn test_stdlib_paths_forces_isolated() {
+    let settings = ExecutionSettings {
+        stdlib_paths: Some(Vec::new()),
+        ..ExecutionSettings::default()
+    };
+    assert_eq!(settings.effective_execution_mode(), ExecutionMode::Isolated);
+
+    let result = execute("1", settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert!(!result.used_pool);
+}
+
+#[test]
+fn test_stdlib_paths_replace_host_paths() {
+    let dir = stdlib_dir("replace");
+    let mut settings = ExecutionSettings {
+        stdlib_paths: Some(vec![dir.clone()]),
+        ..ExecutionSettings::default()
+    };
+    settings.allowed_modules.push("fake_stdlib_mod".to_string());
+
+    let code = format!(
+        "import sys\nimport fake_stdlib_mod\n\
+         print(fake_stdlib_mod.ANSWER)\n\
+         print({:?} in sys.path, [p for p in sys.path if 'lib/python3' in p])",
+        dir.to_string_lossy()
+    );
+    let result = execute(&code, settings);
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "42\nTrue []\n");
+}
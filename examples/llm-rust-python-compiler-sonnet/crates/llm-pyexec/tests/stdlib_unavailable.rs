@@ -0,0 +1,105 @@
+// crates/llm-pyexec/tests/stdlib_unavailable.rs
+// Tests: ExecutionError::StdlibUnavailable and environment_report(), simulating
+// a host without Python through an empty `stdlib_paths` override
+#![cfg(not(feature = "embedded-stdlib"))]
+
+use std::path::PathBuf;
+
+use llm_pyexec::{environment_report_for, execute, ExecutionError, ExecutionSettings};
+
+fn no_stdlib() -> ExecutionSettings {
+    ExecutionSettings {
+        stdlib_paths: Some(Vec::new()),
+        ..ExecutionSettings::default()
+    }
+}
+
+/// A directory that passes the stdlib check but holds nothing else.
+fn fake_stdlib_dir() -> PathBuf {
+    let dir = std::env::temp_dir().join(format!("llm-pyexec-fake-stdlib-{}", std::process::id()));
+    std::fs::create_dir_all(dir.join("collections")).expect("create fake stdlib");
+    std::fs::write(dir.join("collections").join("__init__.py"), "").expect("write canary");
+    dir
+}
+
+#[test]
+fn test_allowed_stdlib_module_reports_stdlib_unavailable() {
+    let result = execute("import collections", no_stdlib());
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::StdlibUnavailable {
+            module_name: "collections".to_string(),
+            searched_paths: Vec::new(),
+        })
+    );
+}
+
+#[test]
+fn test_native_modules_still_import_without_stdlib() {
+    let result = execute("import math\nmath.sqrt(16)", no_stdlib());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("4.0"));
+}
+
+#[test]
+fn test_denied_module_is_still_module_not_allowed() {
+    let result = execute("import socket", no_stdlib());
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "socket".to_string(),
+        })
+    );
+}
+
+/// With the stdlib present, a missing module is an ordinary
+/// `ModuleNotFoundError`.
+#[test]
+fn test_missing_module_with_stdlib_is_runtime_error() {
+    let dir = fake_stdlib_dir();
+    let settings = ExecutionSettings {
+        stdlib_paths: Some(vec![dir]),
+        ..ExecutionSettings::default()
+    };
+    let result = execute("import datetime", settings);
+    match result.error {
+        Some(ExecutionError::RuntimeError { message, .. }) => {
+            assert!(message.contains("datetime"), "message: {message}")
+        }
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_environment_report_without_stdlib() {
+    let report = environment_report_for(&no_stdlib());
+    assert!(!report.stdlib_found);
+    assert!(report.stdlib_paths.is_empty());
+    assert!(!report.all_importable());
+
+    let status = |name: &str| {
+        report
+            .modules
+            .iter()
+            .find(|module| module.name == name)
+            .unwrap_or_else(|| panic!("{name} not in report"))
+    };
+    assert!(status("math").importable());
+    assert!(matches!(
+        status("collections").error,
+        Some(ExecutionError::StdlibUnavailable { .. })
+    ));
+}
+
+#[test]
+fn test_environment_report_covers_extra_modules_in_order() {
+    let settings = ExecutionSettings {
+        allowed_modules: vec!["math".to_string()],
+        extra_allowed_modules: vec!["sys".to_string()],
+        ..no_stdlib()
+    };
+    let report = environment_report_for(&settings);
+    let names: Vec<&str> = report.modules.iter().map(|m| m.name.as_str()).collect();
+    assert_eq!(names, ["math", "sys"]);
+    assert!(report.all_importable());
+}
@@ -0,0 +1,90 @@
+// crates/llm-pyexec/tests/system_exit.rs
+// Tests: uncaught SystemExit sets ExecutionResult::exit_code; nonzero maps to
+// ExecutionError::NonZeroExit
+
+use llm_pyexec::{execute, ExecutionError, ExecutionResult, ExecutionSettings};
+
+fn run(code: &str) -> ExecutionResult {
+    execute(code, ExecutionSettings::default())
+}
+
+#[test]
+fn test_sys_exit_without_code_is_success() {
+    for code in [
+        "import sys\nsys.exit()",
+        "raise SystemExit",
+        "import sys\nsys.exit(None)",
+    ] {
+        let result = run(code);
+        assert_eq!(result.exit_code, Some(0), "{code}");
+        assert_eq!(result.error, None, "{code}");
+        assert_eq!(result.stderr, "", "{code}");
+    }
+}
+
+#[test]
+fn test_sys_exit_zero_is_success() {
+    let result = run("import sys\nsys.exit(0)");
+    assert_eq!(result.exit_code, Some(0));
+    assert_eq!(result.error, None);
+}
+
+#[test]
+fn test_sys_exit_with_code() {
+    let result = run("import sys\nsys.exit(3)");
+    assert_eq!(result.exit_code, Some(3));
+    assert_eq!(result.error, Some(ExecutionError::NonZeroExit { code: 3 }));
+    assert_eq!(result.stderr, "");
+
+    let result = run("raise SystemExit(3)");
+    assert_eq!(result.error, Some(ExecutionError::NonZeroExit { code: 3 }));
+}
+
+/// A message is printed to stderr and the status is 1, as in CPython.
+#[test]
+fn test_sys_exit_with_message_is_status_one() {
+    let result = run("import sys\nprint('out')\nsys.exit('bad input')");
+    assert_eq!(result.exit_code, Some(1));
+    assert_eq!(result.error, Some(ExecutionError::NonZeroExit { code: 1 }));
+    assert_eq!(result.stdout, "out\n");
+    assert_eq!(result.stderr, "bad input\n");
+}
+
+/// Output written before the exit is kept.
+#[test]
+fn test_output_before_exit_is_kept() {
+    let result = run("print('partial')\nraise SystemExit(0)");
+    assert_eq!(result.stdout, "partial\n");
+    assert_eq!(result.exit_code, Some(0));
+    assert_eq!(result.error, None);
+}
+
+/// A snippet that does not exit reports no exit code.
+#[test]
+fn test_no_exit_code_without_system_exit() {
+    assert_eq!(run("1 + 1").exit_code, None);
+    assert_eq!(run("1 / 0").exit_code, None);
+}
+
+/// Like any exception, a caught `SystemExit` does not end the snippet.
+#[test]
+fn test_caught_system_exit_is_not_an_error() {
+    let result =
+        run("try:\n    raise SystemExit(2)\nexcept SystemExit as e:\n    code = e.code\ncode");
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.exit_code, None);
+    assert_eq!(result.return_value.as_deref(), Some("2"));
+}
+
+#[test]
+fn test_exit_code_json_round_trip() {
+    let result = run("import sys\nsys.exit(4)");
+    let json = serde_json::to_value(&result).expect("serialize");
+    assert_eq!(json["exit_code"], 4);
+    assert_eq!(json["error"]["type"], "NonZeroExit");
+    assert_eq!(json["error"]["code"], 4);
+}
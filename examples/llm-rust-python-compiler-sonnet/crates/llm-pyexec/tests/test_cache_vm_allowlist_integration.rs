@@ -376,8 +376,8 @@ fn test_lib_reexports_cache_and_execute_coexist() {
         "ExecutionSettings::default().max_output_bytes must still be 1 MiB after M2 merge"
     );
     assert_eq!(
-        settings.allowed_modules.len(), 11,
-        "ExecutionSettings::default().allowed_modules must still have 11 entries after M2 merge"
+        settings.allowed_modules.len(), 20,
+        "ExecutionSettings::default().allowed_modules must still have 20 entries after M2 merge"
     );
 
     // ExecutionError variants must be available (shared types.rs not broken by M1/M2)
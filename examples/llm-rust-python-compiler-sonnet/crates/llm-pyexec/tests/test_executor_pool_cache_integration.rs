@@ -578,7 +578,7 @@ fn test_execute_timeout_via_pool_returns_correct_error() {
 
     // Must be a Timeout error with matching limit_ns
     match &result.error {
-        Some(ExecutionError::Timeout { limit_ns }) => {
+        Some(ExecutionError::Timeout { limit_ns, .. }) => {
             assert_eq!(
                 *limit_ns, timeout_ns,
                 "Timeout error limit_ns must match settings.timeout_ns; \
@@ -630,8 +630,8 @@ fn test_all_m1_executor_exports_coexist_in_lib() {
         "OutputBuffer from lib.rs re-export must work after M1 merge");
 
     // DEFAULT_ALLOWED_MODULES is re-exported from types.rs (used by pool + executor)
-    assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 11,
-        "DEFAULT_ALLOWED_MODULES must remain 11 entries after M1-executor-integration merge");
+    assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 20,
+        "DEFAULT_ALLOWED_MODULES must remain 20 entries after M1-executor-integration merge");
 
     // InterpreterPool is re-exported from pool.rs
     // We verify the type is importable and has expected API without creating a global pool
@@ -654,6 +654,7 @@ fn test_execute_function_signature_compatibility() {
         timeout_ns: 1_000_000_000,
         max_output_bytes: 1_048_576,
         allowed_modules: vec!["math".to_string()],
+        ..ExecutionSettings::default()
     };
 }
 
@@ -748,6 +749,7 @@ fn test_execute_custom_allowlist_restricts_imports_via_pool_set_allowed_set() {
         allowed_modules: vec!["math".to_string()],
         timeout_ns: 5_000_000_000,
         max_output_bytes: 1_048_576,
+        ..ExecutionSettings::default()
     };
 
     // json should be denied even though it's in DEFAULT_ALLOWED_MODULES
@@ -132,6 +132,7 @@ fn test_execution_settings_drives_both_output_and_modules() {
         max_output_bytes: 10,
         allowed_modules: vec!["math".to_string(), "json".to_string()],
         timeout_ns: 5_000_000_000,
+        ..ExecutionSettings::default()
     };
 
     // Use settings.max_output_bytes with OutputBuffer
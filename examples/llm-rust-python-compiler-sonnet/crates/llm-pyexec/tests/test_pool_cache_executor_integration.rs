@@ -152,8 +152,6 @@ fn test_lib_reexports_pool_and_cache_coexist() {
 #[test]
 fn test_pool_workitem_source_produces_consistent_cache_key() {
     // Use a bare arithmetic expression — maybe_wrap_last_expr wraps these.
-    // Note: expressions ending with ')' (like function calls) are NOT wrapped
-    // because the heuristic treats them as call statements (not bare expressions).
     let raw_source = "1 + 1";
     let wrapped1 = maybe_wrap_last_expr(raw_source);
     let wrapped2 = maybe_wrap_last_expr(raw_source);
@@ -177,21 +175,21 @@ fn test_pool_workitem_source_produces_consistent_cache_key() {
         "cache_key of wrapped source must be deterministic"
     );
 
-    // Verify that call-like expressions (ending with ')') are NOT wrapped —
-    // this is the is_call_statement heuristic. sum(...) looks like a call.
+    // Calls are expressions too: a final call is wrapped like any other.
     let call_like = "sum(i*i for i in range(1000))";
-    let not_wrapped = maybe_wrap_last_expr(call_like);
+    let call_wrapped = maybe_wrap_last_expr(call_like);
     assert_eq!(
-        not_wrapped, call_like,
-        "expressions ending with ')' are NOT wrapped by maybe_wrap_last_expr (call heuristic)"
+        call_wrapped,
+        format!("__result__ = {call_like}"),
+        "a final call expression is wrapped by maybe_wrap_last_expr"
     );
 
-    // But the cache key of the unwrapped call-like source is still deterministic
-    let key_call1 = cache_key(&not_wrapped);
-    let key_call2 = cache_key(call_like);
+    // And the cache key of the wrapped call source is still deterministic
+    let key_call1 = cache_key(&call_wrapped);
+    let key_call2 = cache_key(&maybe_wrap_last_expr(call_like));
     assert_eq!(
         key_call1, key_call2,
-        "cache_key must be deterministic for call-like (non-wrapped) sources"
+        "cache_key must be deterministic for wrapped call sources"
     );
 }
 
@@ -533,10 +531,12 @@ fn test_default_allowed_modules_is_stable_baseline_for_pool_reset() {
     // Any module in DEFAULT_ALLOWED_MODULES that gets auto-imported during
     // initialization would be in the baseline and NOT removed by reset_sys_modules().
 
-    // Verify the 11 canonical modules are all present
+    // Verify the 20 canonical modules are all present
     const EXPECTED_MODULES: &[&str] = &[
         "math", "re", "json", "datetime", "collections",
         "itertools", "functools", "string", "random", "os.path", "sys",
+        "warnings", "logging", "heapq", "bisect", "textwrap", "copy", "enum",
+        "operator", "io",
     ];
 
     assert_eq!(
@@ -575,7 +575,7 @@ fn test_execute_and_cache_global_coexist_after_pool_merge() {
     let _ = before_len; // suppress unused warning
 
     // Verify DEFAULT_ALLOWED_MODULES is accessible (used by both execute and pool)
-    assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 11);
+    assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 20);
 }
 
 // ── Priority 1: pool.rs ↔ output.rs OutputBuffer interface compatibility ─────
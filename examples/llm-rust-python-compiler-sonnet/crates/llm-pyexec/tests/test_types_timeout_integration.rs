@@ -13,7 +13,8 @@
 
 use llm_pyexec::timeout::run_with_timeout;
 use llm_pyexec::{
-    ExecutionError, ExecutionResult, ExecutionSettings, DEFAULT_ALLOWED_MODULES,
+    ExecutionDiagnostics, ExecutionError, ExecutionResult, ExecutionSettings, TimeoutKind, Timing,
+    DEFAULT_ALLOWED_MODULES,
 };
 use std::time::{Duration, Instant};
 
@@ -84,20 +85,45 @@ fn test_all_four_type_reexports_accessible() {
     let _result: ExecutionResult = ExecutionResult {
         stdout: String::new(),
         stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
         return_value: None,
+        typed_value: None,
         error: None,
+        exit_code: None,
         duration_ns: 0,
+        queue_duration_ns: 0,
+        exec_duration_ns: 0,
+        output_bytes: 0,
+        output_lossy: false,
+        output_truncated: false,
+        secondary_error: None,
+        inspections: Vec::new(),
+        used_pool: false,
+        diagnostics: ExecutionDiagnostics::default(),
+        timing: Timing::default(),
+        wrapped_source: None,
+        cache_key_hex: None,
     };
 
     // ExecutionError — all 5 variants must be constructible
     let _e1 = ExecutionError::SyntaxError { message: "msg".to_string(), line: 1, col: 1 };
-    let _e2 = ExecutionError::RuntimeError { message: "msg".to_string(), traceback: String::new() };
-    let _e3 = ExecutionError::Timeout { limit_ns: 100 };
+    let _e2 = ExecutionError::RuntimeError {
+        message: "msg".to_string(),
+        traceback: String::new(),
+        line: None,
+        col: None,
+        frames: Vec::new(),
+    };
+    let _e3 = ExecutionError::Timeout {
+        limit_ns: 100,
+        kind: TimeoutKind::Wall,
+    };
     let _e4 = ExecutionError::OutputLimitExceeded { limit_bytes: 1024 };
     let _e5 = ExecutionError::ModuleNotAllowed { module_name: "socket".to_string() };
 
     // DEFAULT_ALLOWED_MODULES
-    assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 11);
+    assert_eq!(DEFAULT_ALLOWED_MODULES.len(), 20);
 }
 
 // ── Priority 2: Cross-Feature Interactions ───────────────────────────────────
@@ -172,11 +198,12 @@ fn test_timeout_error_limit_ns_matches_execution_settings_timeout_ns() {
     // Construct the error that would be returned, using the settings value
     let error = ExecutionError::Timeout {
         limit_ns: settings.timeout_ns,
+        kind: TimeoutKind::Wall,
     };
 
     // Verify the error correctly reflects the settings
     match error {
-        ExecutionError::Timeout { limit_ns } => {
+        ExecutionError::Timeout { limit_ns, .. } => {
             assert_eq!(
                 limit_ns, timeout_ns,
                 "ExecutionError::Timeout::limit_ns must match ExecutionSettings::timeout_ns"
@@ -211,11 +238,28 @@ fn test_execution_result_with_timeout_error() {
     let exec_result = ExecutionResult {
         stdout: String::new(),
         stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
         return_value: None,
+        typed_value: None,
         error: Some(ExecutionError::Timeout {
             limit_ns: settings.timeout_ns,
+            kind: TimeoutKind::Wall,
         }),
+        exit_code: None,
         duration_ns,
+        queue_duration_ns: 0,
+        exec_duration_ns: duration_ns,
+        output_bytes: 0,
+        output_lossy: false,
+        output_truncated: false,
+        secondary_error: None,
+        inspections: Vec::new(),
+        used_pool: false,
+        diagnostics: ExecutionDiagnostics::default(),
+        timing: Timing::default(),
+        wrapped_source: None,
+        cache_key_hex: None,
     };
 
     // Verify it serializes to correct JSON with the internal tag
@@ -290,9 +334,21 @@ fn test_execution_error_internal_serde_tagging_all_variants() {
         ),
         (
             "RuntimeError",
-            ExecutionError::RuntimeError { message: "err".to_string(), traceback: String::new() },
+            ExecutionError::RuntimeError {
+                message: "err".to_string(),
+                traceback: String::new(),
+                line: Some(1),
+                col: None,
+                frames: Vec::new(),
+            },
+        ),
+        (
+            "Timeout",
+            ExecutionError::Timeout {
+                limit_ns: 1_000,
+                kind: TimeoutKind::Wall,
+            },
         ),
-        ("Timeout", ExecutionError::Timeout { limit_ns: 1_000 }),
         ("OutputLimitExceeded", ExecutionError::OutputLimitExceeded { limit_bytes: 256 }),
         (
             "ModuleNotAllowed",
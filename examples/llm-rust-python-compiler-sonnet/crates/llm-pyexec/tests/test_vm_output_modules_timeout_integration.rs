@@ -15,7 +15,10 @@
 use llm_pyexec::modules::build_allowed_set;
 use llm_pyexec::output::OutputBuffer;
 use llm_pyexec::timeout::run_with_timeout;
-use llm_pyexec::{ExecutionError, ExecutionResult, ExecutionSettings, DEFAULT_ALLOWED_MODULES};
+use llm_pyexec::{
+    ExecutionDiagnostics, ExecutionError, ExecutionResult, ExecutionSettings, TimeoutKind, Timing,
+    DEFAULT_ALLOWED_MODULES,
+};
 
 // ── Priority 1: VM-OutputBuffer interaction boundaries ───────────────────────
 
@@ -198,8 +201,8 @@ fn test_vm_modules_allowed_set_contract() {
 /// Verify the ExecutionError::ModuleNotAllowed variant has the correct structure
 /// that vm.rs's extract_module_not_allowed expects to detect.
 ///
-/// vm.rs raises ImportError("ModuleNotAllowed:<name>") and then extracts it
-/// via strip_prefix("ModuleNotAllowed:"). The check_module_allowed function
+/// vm.rs raises ImportError("ModuleNotAllowed:<name> (<reason>)") and then
+/// extracts the name after strip_prefix("ModuleNotAllowed:"). The check_module_allowed function
 /// returns ExecutionError::ModuleNotAllowed { module_name }.
 /// Both must use the exact same module name.
 #[test]
@@ -317,9 +320,25 @@ fn test_vm_timeout_wrapping_pattern_with_result_struct() {
             ExecutionResult {
                 stdout,
                 stderr,
+                warnings: Vec::new(),
+                denied_imports: Vec::new(),
                 return_value: Some("42".to_string()),
+                typed_value: None,
                 error: None,
+                exit_code: None,
                 duration_ns: 1_000_000,
+                queue_duration_ns: 0,
+                exec_duration_ns: 1_000_000,
+                output_bytes: 0,
+                output_lossy: false,
+                output_truncated: false,
+                secondary_error: None,
+                inspections: Vec::new(),
+                used_pool: false,
+                diagnostics: ExecutionDiagnostics::default(),
+                timing: Timing::default(),
+                wrapped_source: None,
+                cache_key_hex: None,
             }
         },
         settings.timeout_ns,
@@ -364,18 +383,35 @@ fn test_vm_timeout_constructs_correct_execution_result() {
         ExecutionResult {
             stdout: String::new(),
             stderr: String::new(),
+            warnings: Vec::new(),
+            denied_imports: Vec::new(),
             return_value: None,
+            typed_value: None,
             error: Some(ExecutionError::Timeout {
                 limit_ns: settings.timeout_ns,
+                kind: TimeoutKind::Wall,
             }),
+            exit_code: None,
             duration_ns,
+            queue_duration_ns: 0,
+            exec_duration_ns: duration_ns,
+            output_bytes: 0,
+            output_lossy: false,
+            output_truncated: false,
+            secondary_error: None,
+            inspections: Vec::new(),
+            used_pool: false,
+            diagnostics: ExecutionDiagnostics::default(),
+            timing: Timing::default(),
+            wrapped_source: None,
+            cache_key_hex: None,
         }
     };
 
     assert!(
         matches!(
             exec_result.error,
-            Some(ExecutionError::Timeout { limit_ns }) if limit_ns == settings.timeout_ns
+            Some(ExecutionError::Timeout { limit_ns, .. }) if limit_ns == settings.timeout_ns
         ),
         "Timed-out execution must produce ExecutionError::Timeout with correct limit_ns: {:?}",
         exec_result.error
@@ -476,18 +512,51 @@ fn test_full_pipeline_settings_to_result_no_vm() {
         Some((stdout, stderr, error)) => ExecutionResult {
             stdout,
             stderr,
+            warnings: Vec::new(),
+            denied_imports: Vec::new(),
             return_value: None,
+            typed_value: None,
             error,
+            exit_code: None,
             duration_ns,
+            queue_duration_ns: 0,
+            exec_duration_ns: duration_ns,
+            output_bytes: 0,
+            output_lossy: false,
+            output_truncated: false,
+            secondary_error: None,
+            inspections: Vec::new(),
+            used_pool: false,
+            diagnostics: ExecutionDiagnostics::default(),
+            timing: Timing::default(),
+            wrapped_source: None,
+            cache_key_hex: None,
         },
         None => ExecutionResult {
             stdout: String::new(),
             stderr: String::new(),
+            warnings: Vec::new(),
+            denied_imports: Vec::new(),
             return_value: None,
+            typed_value: None,
             error: Some(ExecutionError::Timeout {
                 limit_ns: settings.timeout_ns,
+                kind: TimeoutKind::Wall,
             }),
+            exit_code: None,
             duration_ns,
+            queue_duration_ns: 0,
+            exec_duration_ns: duration_ns,
+            output_bytes: 0,
+            output_lossy: false,
+            output_truncated: false,
+            secondary_error: None,
+            inspections: Vec::new(),
+            used_pool: false,
+            diagnostics: ExecutionDiagnostics::default(),
+            timing: Timing::default(),
+            wrapped_source: None,
+            cache_key_hex: None,
         },
     };
 
@@ -535,9 +604,25 @@ fn test_full_pipeline_denied_module_error_propagation() {
     let exec_result = ExecutionResult {
         stdout: String::new(),
         stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
         return_value: None,
+        typed_value: None,
         error: Some(import_err),
+        exit_code: None,
         duration_ns: 100_000,
+        queue_duration_ns: 0,
+        exec_duration_ns: 100_000,
+        output_bytes: 0,
+        output_lossy: false,
+        output_truncated: false,
+        secondary_error: None,
+        inspections: Vec::new(),
+        used_pool: false,
+        diagnostics: ExecutionDiagnostics::default(),
+        timing: Timing::default(),
+        wrapped_source: None,
+        cache_key_hex: None,
     };
 
     // Verify the result
@@ -598,9 +683,25 @@ fn test_full_pipeline_output_limit_exceeded_error_propagation() {
     let exec_result = ExecutionResult {
         stdout: String::new(),
         stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
         return_value: None,
+        typed_value: None,
         error: Some(output_err),
+        exit_code: None,
         duration_ns: 50_000,
+        queue_duration_ns: 0,
+        exec_duration_ns: 50_000,
+        output_bytes: 0,
+        output_lossy: false,
+        output_truncated: false,
+        secondary_error: None,
+        inspections: Vec::new(),
+        used_pool: false,
+        diagnostics: ExecutionDiagnostics::default(),
+        timing: Timing::default(),
+        wrapped_source: None,
+        cache_key_hex: None,
     };
 
     // Verify
@@ -634,9 +735,25 @@ fn test_execution_result_json_schema_has_all_required_fields() {
     let success = ExecutionResult {
         stdout: "hello\n".to_string(),
         stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
         return_value: None,
+        typed_value: None,
         error: None,
+        exit_code: None,
         duration_ns: 12345,
+        queue_duration_ns: 0,
+        exec_duration_ns: 12345,
+        output_bytes: 0,
+        output_lossy: false,
+        output_truncated: false,
+        secondary_error: None,
+        inspections: Vec::new(),
+        used_pool: false,
+        diagnostics: ExecutionDiagnostics::default(),
+        timing: Timing::default(),
+        wrapped_source: None,
+        cache_key_hex: None,
     };
 
     let json = serde_json::to_string(&success).expect("serialize success");
@@ -662,13 +779,29 @@ fn test_execution_result_json_schema_has_all_required_fields() {
     let syntax_err = ExecutionResult {
         stdout: String::new(),
         stderr: String::new(),
+        warnings: Vec::new(),
+        denied_imports: Vec::new(),
         return_value: None,
+        typed_value: None,
         error: Some(ExecutionError::SyntaxError {
             message: "invalid syntax".to_string(),
             line: 1,
             col: 5,
         }),
+        exit_code: None,
         duration_ns: 1000,
+        queue_duration_ns: 0,
+        exec_duration_ns: 1000,
+        output_bytes: 0,
+        output_lossy: false,
+        output_truncated: false,
+        secondary_error: None,
+        inspections: Vec::new(),
+        used_pool: false,
+        diagnostics: ExecutionDiagnostics::default(),
+        timing: Timing::default(),
+        wrapped_source: None,
+        cache_key_hex: None,
     };
 
     let err_json = serde_json::to_string(&syntax_err).expect("serialize error");
@@ -754,9 +887,13 @@ fn test_all_vm_error_variants_serialize_correctly() {
         ExecutionError::RuntimeError {
             message: "division by zero".to_string(),
             traceback: "Traceback...\n".to_string(),
+            line: Some(1),
+            col: Some(1),
+            frames: Vec::new(),
         },
         ExecutionError::Timeout {
             limit_ns: 5_000_000_000,
+            kind: TimeoutKind::Wall,
         },
         ExecutionError::OutputLimitExceeded {
             limit_bytes: 1_048_576,
@@ -770,9 +907,25 @@ fn test_all_vm_error_variants_serialize_correctly() {
         let result = ExecutionResult {
             stdout: String::new(),
             stderr: String::new(),
+            warnings: Vec::new(),
+            denied_imports: Vec::new(),
             return_value: None,
+            typed_value: None,
             error: Some(variant.clone()),
+            exit_code: None,
             duration_ns: 0,
+            queue_duration_ns: 0,
+            exec_duration_ns: 0,
+            output_bytes: 0,
+            output_lossy: false,
+            output_truncated: false,
+            secondary_error: None,
+            inspections: Vec::new(),
+            used_pool: false,
+            diagnostics: ExecutionDiagnostics::default(),
+            timing: Timing::default(),
+            wrapped_source: None,
+            cache_key_hex: None,
         };
 
         let json = serde_json::to_string(&result).expect("ExecutionResult must serialize");
@@ -802,16 +955,19 @@ fn test_all_vm_error_variants_serialize_correctly() {
 #[test]
 fn test_default_allowed_modules_prd_compliance() {
     // PRD specifies: math, re, json, datetime, collections, itertools, functools,
-    // string, random, os.path, sys — 11 modules
+    // string, random, os.path, sys, warnings, logging, heapq, bisect,
+    // textwrap, copy, enum, operator, io — 20 modules
     let expected: &[&str] = &[
         "math", "re", "json", "datetime", "collections",
         "itertools", "functools", "string", "random", "os.path", "sys",
+        "warnings", "logging", "heapq", "bisect", "textwrap", "copy", "enum",
+        "operator", "io",
     ];
 
     assert_eq!(
         DEFAULT_ALLOWED_MODULES.len(),
-        11,
-        "DEFAULT_ALLOWED_MODULES must have exactly 11 entries (AC-13)"
+        20,
+        "DEFAULT_ALLOWED_MODULES must have exactly 20 entries (AC-13)"
     );
 
     for module in expected {
@@ -0,0 +1,80 @@
+// crates/llm-pyexec/tests/timeout_kind.rs
+// Tests: ExecutionSettings::timeout_kind (wall-clock vs CPU-time timeout_ns)
+
+use llm_pyexec::{
+    execute_with, BytecodeCache, ExecutionError, ExecutionSettings, InterpreterPool, TimeoutKind,
+    CPU_MODE_WALL_TIMEOUT_MULTIPLE,
+};
+
+const TIMEOUT_NS: u64 = 200_000_000;
+
+fn settings(timeout_kind: TimeoutKind) -> ExecutionSettings {
+    ExecutionSettings {
+        timeout_ns: TIMEOUT_NS,
+        timeout_kind,
+        allowed_modules: vec!["time".to_string()],
+        ..ExecutionSettings::default()
+    }
+}
+
+/// Sleeping past `timeout_ns` trips a wall-clock timeout but uses no CPU, so
+/// the same snippet finishes under a CPU-time timeout.
+#[cfg(unix)]
+#[test]
+fn test_sleep_times_out_only_under_wall() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "import time\ntime.sleep(0.5)\n'done'";
+
+    let wall = execute_with(&pool, &cache, code, settings(TimeoutKind::Wall));
+    assert_eq!(
+        wall.error,
+        Some(ExecutionError::Timeout {
+            limit_ns: TIMEOUT_NS,
+            kind: TimeoutKind::Wall,
+        })
+    );
+
+    let cpu = execute_with(&pool, &cache, code, settings(TimeoutKind::Cpu));
+    assert!(cpu.error.is_none(), "unexpected error: {:?}", cpu.error);
+    assert_eq!(cpu.return_value.as_deref(), Some("'done'"));
+}
+
+#[test]
+fn test_tight_loop_times_out_under_cpu() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "total = 0\nwhile True:\n    total += 1";
+
+    let result = execute_with(&pool, &cache, code, settings(TimeoutKind::Cpu));
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Timeout {
+            limit_ns: TIMEOUT_NS,
+            kind: TimeoutKind::Cpu,
+        })
+    );
+    assert!(
+        result.exec_duration_ns < TIMEOUT_NS * CPU_MODE_WALL_TIMEOUT_MULTIPLE,
+        "stopped by the wall backstop after {}ns",
+        result.exec_duration_ns
+    );
+}
+
+/// Code that blocks forever is still stopped by the wall-clock backstop.
+#[cfg(unix)]
+#[test]
+fn test_cpu_mode_keeps_wall_backstop() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let code = "import time\nwhile True:\n    time.sleep(0.05)";
+
+    let result = execute_with(&pool, &cache, code, settings(TimeoutKind::Cpu));
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::Timeout {
+            limit_ns: TIMEOUT_NS * CPU_MODE_WALL_TIMEOUT_MULTIPLE,
+            kind: TimeoutKind::Wall,
+        })
+    );
+}
@@ -0,0 +1,51 @@
+// crates/llm-pyexec/tests/timing.rs
+// Tests: ExecutionResult::timing splits the call into compile, wait and execute
+
+use llm_pyexec::{execute, ExecutionError, ExecutionResult, ExecutionSettings, Session};
+
+fn assert_parts_add_up(result: &ExecutionResult) {
+    let t = result.timing;
+    assert_eq!(t.total_ns, result.duration_ns);
+    assert_eq!(t.compile_ns + t.wait_ns + t.execute_ns, t.total_ns, "{t:?}");
+}
+
+#[test]
+fn test_busy_loop_is_counted_as_execution() {
+    let result = execute(
+        "total = 0\nfor i in range(20000):\n    total += i\ntotal",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_parts_add_up(&result);
+    assert!(result.timing.compile_ns > 0, "{:?}", result.timing);
+    assert!(result.timing.execute_ns > 0, "{:?}", result.timing);
+}
+
+#[test]
+fn test_syntax_error_has_no_execution_time() {
+    let result = execute("def broken(:\n    pass", ExecutionSettings::default());
+    assert!(matches!(
+        result.error,
+        Some(ExecutionError::SyntaxError { .. })
+    ));
+    assert_parts_add_up(&result);
+    assert_eq!(result.timing.execute_ns, 0);
+}
+
+#[test]
+fn test_session_results_carry_timing() {
+    let session = Session::new(ExecutionSettings::default());
+    for code in ["x = 21", "x * 2"] {
+        let result = session.eval(code);
+        assert_eq!(result.error, None, "stderr: {}", result.stderr);
+        assert_parts_add_up(&result);
+    }
+}
+
+#[test]
+fn test_timing_round_trips_through_json() {
+    let result = execute("1 + 1", ExecutionSettings::default());
+    let json = serde_json::to_string(&result).expect("serialize");
+    let back: ExecutionResult = serde_json::from_str(&json).expect("deserialize");
+    assert_eq!(back.timing, result.timing);
+}
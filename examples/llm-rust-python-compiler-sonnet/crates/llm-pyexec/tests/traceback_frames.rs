@@ -0,0 +1,54 @@
+// crates/llm-pyexec/tests/traceback_frames.rs
+// Tests: ExecutionError::RuntimeError structured traceback frames
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, TracebackFrame};
+
+fn runtime_error_frames(code: &str) -> Vec<TracebackFrame> {
+    match execute(code, ExecutionSettings::default()).error {
+        Some(ExecutionError::RuntimeError {
+            frames, traceback, ..
+        }) => {
+            assert!(!traceback.is_empty(), "formatted traceback is kept");
+            frames
+        }
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_nested_function_error_lists_frames_outermost_first() {
+    let code = "def outer():\n    return inner()\n\ndef inner():\n    return 1 / 0\n\nouter()";
+    let frames = runtime_error_frames(code);
+    let user: Vec<(&str, u32)> = frames
+        .iter()
+        .filter(|f| f.is_user_code())
+        .map(|f| (f.function.as_str(), f.lineno))
+        .collect();
+    assert_eq!(user, [("<module>", 7), ("outer", 2), ("inner", 5)]);
+}
+
+#[test]
+fn test_top_level_error_has_single_module_frame() {
+    let frames = runtime_error_frames("x = 1\ny = x / 0");
+    assert_eq!(
+        frames,
+        [TracebackFrame {
+            filename: TracebackFrame::USER_CODE_FILENAME.to_owned(),
+            lineno: 2,
+            function: "<module>".to_owned(),
+        }]
+    );
+}
+
+/// Frames from library code are kept but are not marked as user code.
+#[test]
+fn test_stdlib_frames_are_not_user_code() {
+    let frames = runtime_error_frames("import json\n\njson.loads('{not json')");
+    assert!(
+        frames.first().is_some_and(TracebackFrame::is_user_code),
+        "outermost frame is the user's call: {frames:?}"
+    );
+    for frame in frames.iter().filter(|f| !f.is_user_code()) {
+        assert_ne!(frame.filename, TracebackFrame::USER_CODE_FILENAME);
+    }
+}
@@ -0,0 +1,105 @@
+// crates/llm-pyexec/tests/traceback_stderr.rs
+// Tests: ExecutionSettings::mirror_traceback_to_stderr
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings, Session};
+
+fn no_mirror() -> ExecutionSettings {
+    ExecutionSettings {
+        mirror_traceback_to_stderr: false,
+        ..ExecutionSettings::default()
+    }
+}
+
+fn traceback_of(error: Option<ExecutionError>) -> String {
+    match error {
+        Some(ExecutionError::RuntimeError { traceback, .. }) => traceback,
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+/// By default stderr ends with the same traceback the error carries.
+#[test]
+fn test_traceback_written_after_output_by_default() {
+    let code = "import sys\nprint('out')\nsys.stderr.write('warn\\n')\n1 / 0";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.stdout, "out\n");
+    let traceback = traceback_of(result.error);
+    assert!(traceback.contains("ZeroDivisionError"), "{traceback}");
+    assert_eq!(result.stderr, format!("warn\n{traceback}"));
+    assert!(!result.output_truncated);
+}
+
+#[test]
+fn test_mirroring_off_leaves_stderr_alone() {
+    let result = execute("print('out')\n1 / 0", no_mirror());
+    assert_eq!(result.stdout, "out\n");
+    assert_eq!(result.stderr, "");
+    assert!(traceback_of(result.error).contains("ZeroDivisionError"));
+}
+
+/// Caught exceptions, `SystemExit` and blocked imports print nothing.
+#[test]
+fn test_nothing_written_without_uncaught_exception() {
+    let default = ExecutionSettings::default;
+    assert_eq!(
+        execute(
+            "try:\n    1 / 0\nexcept ZeroDivisionError:\n    pass",
+            default()
+        )
+        .stderr,
+        ""
+    );
+
+    let result = execute("import sys\nsys.exit(3)", default());
+    assert_eq!(result.error, Some(ExecutionError::NonZeroExit { code: 3 }));
+    assert_eq!(result.stderr, "");
+
+    let result = execute("import socket", default());
+    assert!(
+        matches!(result.error, Some(ExecutionError::ModuleNotAllowed { .. })),
+        "{:?}",
+        result.error
+    );
+    assert_eq!(result.stderr, "");
+}
+
+/// A traceback that does not fit is cut short but does not count as an
+/// output overflow.
+#[test]
+fn test_long_traceback_is_cut_without_overflow() {
+    let settings = ExecutionSettings {
+        max_output_bytes: 20,
+        ..ExecutionSettings::default()
+    };
+    let result = execute("print('out')\nraise ValueError('x' * 100)", settings);
+    assert_eq!(result.stdout, "out\n");
+    assert_eq!(result.stderr, "Traceback (most ");
+    assert!(!result.output_truncated);
+    assert!(traceback_of(result.error).contains("ValueError"));
+}
+
+/// Once a write has overflowed, the traceback of the resulting error is not
+/// written at all.
+#[test]
+fn test_no_traceback_after_overflow() {
+    let settings = ExecutionSettings {
+        max_output_bytes: 10,
+        ..ExecutionSettings::default()
+    };
+    let result = execute("print('x' * 100)", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::OutputLimitExceeded { limit_bytes: 10 })
+    );
+    assert_eq!(result.stdout, "x".repeat(10));
+    assert_eq!(result.stderr, "");
+}
+
+#[test]
+fn test_session_mirrors_traceback() {
+    let session = Session::new(ExecutionSettings::default());
+    let result = session.eval("undefined_name");
+    let traceback = traceback_of(result.error);
+    assert!(traceback.contains("NameError"), "{traceback}");
+    assert_eq!(result.stderr, traceback);
+}
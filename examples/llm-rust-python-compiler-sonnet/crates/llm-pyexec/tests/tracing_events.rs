@@ -0,0 +1,141 @@
+// crates/llm-pyexec/tests/tracing_events.rs
+// Tests: execute() lifecycle events behind the `tracing` feature
+
+#![cfg(feature = "tracing")]
+
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute_with, BytecodeCache, ExecutionSettings, InterpreterPool};
+use tracing::field::{Field, Visit};
+use tracing::{span, Event, Metadata, Subscriber};
+
+/// Field name → formatted value, for one event.
+type Fields = HashMap<String, String>;
+
+/// Subscriber that records the fields of every event on this thread.
+#[derive(Clone, Default)]
+struct Capture(Arc<Mutex<Vec<Fields>>>);
+
+impl Capture {
+    fn events(&self) -> Vec<Fields> {
+        self.0.lock().unwrap().clone()
+    }
+
+    /// Record events emitted on this thread while `f` runs.
+    fn run<T>(&self, f: impl FnOnce() -> T) -> T {
+        tracing::subscriber::with_default(self.clone(), f)
+    }
+}
+
+struct FieldVisitor<'a>(&'a mut Fields);
+
+impl Visit for FieldVisitor<'_> {
+    fn record_str(&mut self, field: &Field, value: &str) {
+        self.0.insert(field.name().to_string(), value.to_string());
+    }
+
+    fn record_debug(&mut self, field: &Field, value: &dyn fmt::Debug) {
+        self.0
+            .insert(field.name().to_string(), format!("{value:?}"));
+    }
+}
+
+impl Subscriber for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn new_span(&self, _: &span::Attributes<'_>) -> span::Id {
+        span::Id::from_u64(1)
+    }
+
+    fn record(&self, _: &span::Id, _: &span::Record<'_>) {}
+
+    fn record_follows_from(&self, _: &span::Id, _: &span::Id) {}
+
+    fn event(&self, event: &Event<'_>) {
+        let mut fields = Fields::new();
+        event.record(&mut FieldVisitor(&mut fields));
+        self.0.lock().unwrap().push(fields);
+    }
+
+    fn enter(&self, _: &span::Id) {}
+
+    fn exit(&self, _: &span::Id) {}
+}
+
+fn field<'a>(event: &'a Fields, name: &str) -> Option<&'a str> {
+    event.get(name).map(String::as_str)
+}
+
+#[test]
+fn test_saturated_pool_emits_fallback_event() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    std::thread::scope(|scope| {
+        let busy = scope.spawn(|| {
+            execute_with(
+                &pool,
+                &cache,
+                "total = 0\nfor i in range(3000000):\n    total += i\ntotal",
+                ExecutionSettings::default(),
+            )
+        });
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while pool.idle_count() > 0 {
+            assert!(Instant::now() < deadline, "slot never became busy");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let capture = Capture::default();
+        let result = capture.run(|| {
+            execute_with(
+                &pool,
+                &cache,
+                "6 * 7",
+                ExecutionSettings {
+                    pool_checkout_timeout_ns: Some(0),
+                    ..ExecutionSettings::default()
+                },
+            )
+        });
+        assert!(!result.used_pool);
+
+        let events = capture.events();
+        assert!(
+            events
+                .iter()
+                .any(|event| field(event, "path") == Some("fallback")),
+            "no fallback event in {events:?}"
+        );
+        busy.join().expect("busy call panicked");
+    });
+}
+
+#[test]
+fn test_finished_event_reports_error_kind() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let capture = Capture::default();
+
+    capture.run(|| execute_with(&pool, &cache, "1 / 0", ExecutionSettings::default()));
+
+    let events = capture.events();
+    assert!(
+        events
+            .iter()
+            .any(|event| field(event, "path") == Some("pool")),
+        "no dispatch event in {events:?}"
+    );
+    let finished = events
+        .iter()
+        .find(|event| field(event, "message") == Some("execution finished"))
+        .unwrap_or_else(|| panic!("no finished event in {events:?}"));
+    assert_eq!(field(finished, "error"), Some("RuntimeError"));
+    assert!(finished.contains_key("exec_ns"));
+}
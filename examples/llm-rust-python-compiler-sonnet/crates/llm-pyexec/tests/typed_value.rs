@@ -0,0 +1,101 @@
+// crates/llm-pyexec/tests/typed_value.rs
+// Tests: ExecutionResult::typed_value / PyValue
+
+use llm_pyexec::{execute, ExecutionSettings, PyValue};
+
+fn typed(code: &str) -> Option<PyValue> {
+    let result = execute(code, ExecutionSettings::default());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    result.typed_value
+}
+
+fn str_value(s: &str) -> PyValue {
+    PyValue::Str(s.to_string())
+}
+
+#[test]
+fn test_scalars() {
+    assert_eq!(typed("40 + 2"), Some(PyValue::Int(42)));
+    assert_eq!(typed("-7"), Some(PyValue::Int(-7)));
+    assert_eq!(typed("1.5 * 2"), Some(PyValue::Float(3.0)));
+    assert_eq!(typed("'a' + 'b'"), Some(str_value("ab")));
+    assert_eq!(typed("1 < 2"), Some(PyValue::Bool(true)));
+    assert_eq!(typed("not True"), Some(PyValue::Bool(false)));
+}
+
+/// A top-level `None` is no value at all, matching `return_value`.
+#[test]
+fn test_none_result_has_no_typed_value() {
+    assert_eq!(typed("None"), None);
+    assert_eq!(typed("x = 1"), None);
+}
+
+#[test]
+fn test_list_and_tuple_are_distinct() {
+    assert_eq!(
+        typed("[1, None, 'x']"),
+        Some(PyValue::List(vec![
+            PyValue::Int(1),
+            PyValue::None,
+            str_value("x")
+        ]))
+    );
+    assert_eq!(
+        typed("(1, 2.5)"),
+        Some(PyValue::Tuple(vec![PyValue::Int(1), PyValue::Float(2.5)]))
+    );
+}
+
+#[test]
+fn test_nested_dict_keeps_insertion_order() {
+    let value = typed("{'b': [1, (2,)], 'a': {'k': True}, 3: None}");
+    assert_eq!(
+        value,
+        Some(PyValue::Dict(vec![
+            (
+                str_value("b"),
+                PyValue::List(vec![PyValue::Int(1), PyValue::Tuple(vec![PyValue::Int(2)])])
+            ),
+            (
+                str_value("a"),
+                PyValue::Dict(vec![(str_value("k"), PyValue::Bool(true))])
+            ),
+            (PyValue::Int(3), PyValue::None),
+        ]))
+    );
+}
+
+/// Values without a structured form fall back to their `repr`.
+#[test]
+fn test_other_fallback() {
+    let code = "\
+class Point:
+    def __repr__(self):
+        return 'Point(1, 2)'
+Point()";
+    assert_eq!(typed(code), Some(PyValue::Other("Point(1, 2)".to_string())));
+    assert_eq!(
+        typed("2 ** 64"),
+        Some(PyValue::Other("18446744073709551616".to_string()))
+    );
+    assert_eq!(
+        typed("[{1, 2}]"),
+        Some(PyValue::List(vec![PyValue::Other("{1, 2}".to_string())]))
+    );
+}
+
+/// A container holding itself is converted up to the cycle.
+#[test]
+fn test_self_referencing_list() {
+    assert_eq!(
+        typed("x = [1]\nx.append(x)\nx"),
+        Some(PyValue::List(vec![
+            PyValue::Int(1),
+            PyValue::Other("[1, [...]]".to_string())
+        ]))
+    );
+}
@@ -0,0 +1,63 @@
+// crates/llm-pyexec/tests/used_pool.rs
+// Tests: ExecutionResult::used_pool
+
+use std::time::{Duration, Instant};
+
+use llm_pyexec::{execute_with, BytecodeCache, ExecutionSettings, InterpreterPool};
+
+/// With the only slot busy, a concurrent call falls back and says so.
+#[test]
+fn test_saturated_pool_reports_fallback() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+
+    let warm = execute_with(&pool, &cache, "1", ExecutionSettings::default());
+    assert!(warm.used_pool, "an idle slot should serve the call");
+
+    std::thread::scope(|scope| {
+        let busy = scope.spawn(|| {
+            execute_with(
+                &pool,
+                &cache,
+                "total = 0\nfor i in range(3000000):\n    total += i\ntotal",
+                ExecutionSettings::default(),
+            )
+        });
+        let deadline = Instant::now() + Duration::from_secs(30);
+        while pool.idle_count() > 0 {
+            assert!(Instant::now() < deadline, "slot never became busy");
+            std::thread::sleep(Duration::from_millis(1));
+        }
+
+        let fallback = execute_with(
+            &pool,
+            &cache,
+            "6 * 7",
+            ExecutionSettings {
+                pool_checkout_timeout_ns: Some(0),
+                ..ExecutionSettings::default()
+            },
+        );
+        assert_eq!(fallback.return_value.as_deref(), Some("42"));
+        assert!(!fallback.used_pool);
+        assert_eq!(pool.metrics().fallbacks, 1);
+
+        let busy = busy.join().expect("busy call panicked");
+        assert!(busy.used_pool);
+    });
+}
+
+/// The flag round-trips through JSON and defaults to `false` when absent.
+#[test]
+fn test_used_pool_serde() {
+    let pool = InterpreterPool::new(1);
+    let cache = BytecodeCache::new(8);
+    let result = execute_with(&pool, &cache, "1", ExecutionSettings::default());
+
+    let mut json = serde_json::to_value(&result).expect("serialize");
+    assert_eq!(json["used_pool"], serde_json::Value::Bool(true));
+
+    json.as_object_mut().expect("object").remove("used_pool");
+    let old: llm_pyexec::ExecutionResult = serde_json::from_value(json).expect("deserialize");
+    assert!(!old.used_pool);
+}
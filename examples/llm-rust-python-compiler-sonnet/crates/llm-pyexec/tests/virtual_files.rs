@@ -0,0 +1,116 @@
+// crates/llm-pyexec/tests/virtual_files.rs
+// Tests: ExecutionSettings::files read-only in-memory filesystem behind open()
+
+use std::collections::HashMap;
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+const CSV: &str = "name,qty\r\napple,3\r\npear,5\r\n";
+
+fn with_files(files: &[(&str, &[u8])]) -> ExecutionSettings {
+    ExecutionSettings {
+        files: files
+            .iter()
+            .map(|(path, data)| (path.to_string(), data.to_vec()))
+            .collect::<HashMap<_, _>>(),
+        ..ExecutionSettings::default()
+    }
+}
+
+fn runtime_error(error: Option<ExecutionError>) -> (String, String) {
+    match error {
+        Some(ExecutionError::RuntimeError {
+            message, traceback, ..
+        }) => (message, traceback),
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_read_provided_file() {
+    let result = execute(
+        "with open('data.csv') as f:\n    text = f.read()\ntext",
+        with_files(&[("data.csv", CSV.as_bytes())]),
+    );
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(
+        result.return_value.as_deref(),
+        Some("'name,qty\\napple,3\\npear,5\\n'")
+    );
+}
+
+#[test]
+fn test_iterate_lines() {
+    let code = "total = 0\nfor line in open('./data.csv'):\n    name, qty = line.strip().split(',')\n    if qty.isdigit():\n        total += int(qty)\ntotal";
+    let result = execute(code, with_files(&[("data.csv", CSV.as_bytes())]));
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("8"));
+}
+
+#[test]
+fn test_binary_mode_returns_bytes() {
+    let result = execute(
+        "data = open('blob.bin', 'rb').read()\ndata",
+        with_files(&[("blob.bin", b"\x00\xff")]),
+    );
+    assert_eq!(result.return_value.as_deref(), Some("b'\\x00\\xff'"));
+}
+
+#[test]
+fn test_missing_file_is_clean_error() {
+    let result = execute(
+        "open('missing.csv')",
+        with_files(&[("data.csv", CSV.as_bytes())]),
+    );
+    let (message, traceback) = runtime_error(result.error);
+    assert!(message.contains("missing.csv"), "message: {message}");
+    assert!(
+        traceback.contains("FileNotFoundError"),
+        "traceback: {traceback}"
+    );
+}
+
+/// The error is a real `FileNotFoundError`, so snippets can handle it.
+#[test]
+fn test_missing_file_can_be_caught() {
+    let result = execute(
+        "try:\n    open('missing.csv')\n    found = True\nexcept FileNotFoundError:\n    found = False\nfound",
+        ExecutionSettings::default(),
+    );
+    assert_eq!(result.return_value.as_deref(), Some("False"));
+}
+
+#[test]
+fn test_writes_are_rejected() {
+    for mode in ["w", "a", "x", "r+", "wb"] {
+        let code = format!("open('data.csv', '{mode}')");
+        let result = execute(&code, with_files(&[("data.csv", CSV.as_bytes())]));
+        let (_, traceback) = runtime_error(result.error);
+        assert!(
+            traceback.contains("PermissionError"),
+            "mode {mode}: {traceback}"
+        );
+    }
+}
+
+/// Host files are not reachable, even without any virtual files.
+#[test]
+fn test_host_filesystem_is_not_exposed() {
+    let manifest = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+    assert!(std::path::Path::new(manifest).is_file());
+    let code = format!("open({manifest:?}).read()");
+    let result = execute(&code, ExecutionSettings::default());
+    let (_, traceback) = runtime_error(result.error);
+    assert!(
+        traceback.contains("FileNotFoundError"),
+        "traceback: {traceback}"
+    );
+}
@@ -0,0 +1,102 @@
+// crates/llm-pyexec/tests/virtual_modules.rs
+// Tests: ExecutionSettings::virtual_modules
+
+use llm_pyexec::{execute, ExecutionError, ExecutionSettings};
+
+fn with_modules(modules: &[(&str, &str)]) -> ExecutionSettings {
+    ExecutionSettings {
+        virtual_modules: modules
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect(),
+        ..ExecutionSettings::default()
+    }
+}
+
+/// A supplied module can be imported and its functions called.
+#[test]
+fn test_import_virtual_module_and_call_function() {
+    let settings = with_modules(&[(
+        "helpers",
+        "import math\n\ndef hypot(a, b):\n    return math.sqrt(a * a + b * b)\n",
+    )]);
+
+    let result = execute("import helpers\nhelpers.hypot(3, 4)", settings.clone());
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("5.0"));
+
+    let result = execute("from helpers import hypot\nhypot(6, 8)", settings);
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("10.0"));
+}
+
+/// Virtual modules may import each other.
+#[test]
+fn test_virtual_modules_import_each_other() {
+    let settings = with_modules(&[
+        (
+            "shapes",
+            "from units import scale\n\ndef area(w, h):\n    return scale(w) * scale(h)\n",
+        ),
+        ("units", "def scale(x):\n    return x * 10\n"),
+    ]);
+    let result = execute("import shapes\nshapes.area(1, 2)", settings);
+    assert!(
+        result.error.is_none(),
+        "unexpected error: {:?}",
+        result.error
+    );
+    assert_eq!(result.return_value.as_deref(), Some("200"));
+}
+
+/// Imports made inside a virtual module are still subject to the allowlist.
+#[test]
+fn test_virtual_module_imports_are_allowlisted() {
+    let settings = with_modules(&[("sneaky", "import socket\n")]);
+    let result = execute("import sneaky", settings);
+    assert_eq!(
+        result.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "socket".to_string()
+        })
+    );
+}
+
+/// A circular import that needs a not-yet-defined name is an ordinary runtime error.
+#[test]
+fn test_circular_virtual_import_is_runtime_error() {
+    let settings = with_modules(&[
+        ("a", "from b import B\nA = 1\n"),
+        ("b", "from a import A\nB = 2\n"),
+    ]);
+    let result = execute("import a", settings);
+    match &result.error {
+        Some(ExecutionError::RuntimeError { message, .. }) => {
+            assert!(message.contains('A'), "got: {message}")
+        }
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+/// Virtual modules exist only for the call that supplied them.
+#[test]
+fn test_virtual_module_not_visible_to_later_calls() {
+    let first = execute("import helpers", with_modules(&[("helpers", "X = 1\n")]));
+    assert!(first.error.is_none(), "unexpected error: {:?}", first.error);
+
+    let second = execute("import helpers", ExecutionSettings::default());
+    assert_eq!(
+        second.error,
+        Some(ExecutionError::ModuleNotAllowed {
+            module_name: "helpers".to_string()
+        })
+    );
+}
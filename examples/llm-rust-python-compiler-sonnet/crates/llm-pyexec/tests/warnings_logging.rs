@@ -0,0 +1,109 @@
+// crates/llm-pyexec/tests/warnings_logging.rs
+// Tests: ExecutionResult::warnings; logging output in ExecutionResult::stderr
+
+use llm_pyexec::{
+    execute, execute_with, BytecodeCache, ExecutionSettings, InterpreterPool, Session,
+};
+
+#[test]
+fn test_warnings_warn_is_recorded_not_printed() {
+    let code = "import warnings\nwarnings.warn('careful')\nprint('done')";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "done\n");
+    assert_eq!(result.stderr, "");
+    assert_eq!(result.warnings, ["<string>:2: UserWarning: careful"]);
+}
+
+#[test]
+fn test_syntax_warning_populates_warnings() {
+    let code = "\
+import warnings
+warnings.warn('\"is\" with a literal', SyntaxWarning)
+print('done')";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None);
+    assert_eq!(result.stderr, "");
+    assert_eq!(
+        result.warnings,
+        ["<string>:2: SyntaxWarning: \"is\" with a literal"]
+    );
+}
+
+/// `catch_warnings(record=True)` still sees the warnings it catches.
+#[test]
+fn test_catch_warnings_record_still_works() {
+    let code = "\
+import warnings
+with warnings.catch_warnings(record=True) as caught:
+    warnings.simplefilter('always')
+    warnings.warn('inner')
+warnings.warn('outer')
+len(caught)";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.return_value.as_deref(), Some("1"));
+    assert_eq!(result.warnings, ["<string>:5: UserWarning: outer"]);
+}
+
+/// A `warnings` module imported by an earlier session call records into the
+/// current call.
+#[test]
+fn test_session_warnings_follow_each_call() {
+    let session = Session::new(ExecutionSettings::default());
+    let first = session.eval("import warnings\nwarnings.warn('one')");
+    assert_eq!(first.warnings, ["<string>:2: UserWarning: one"]);
+
+    let second = session.eval("warnings.warn('two')");
+    assert_eq!(second.error, None, "stderr: {}", second.stderr);
+    assert_eq!(second.warnings, ["<string>:1: UserWarning: two"]);
+    assert_eq!(second.stderr, "");
+}
+
+#[test]
+fn test_logging_warning_writes_to_stderr() {
+    let code = "import logging\nlogging.warning('disk %s', 'low')\nprint('done')";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stdout, "done\n");
+    assert_eq!(result.stderr, "WARNING:root:disk low\n");
+}
+
+/// A logger with no handlers falls back to `logging.lastResort`.
+#[test]
+fn test_named_logger_without_handlers_writes_to_stderr() {
+    let code = "import logging\nlogging.getLogger('app').error('failed')";
+    let result = execute(code, ExecutionSettings::default());
+    assert_eq!(result.error, None, "stderr: {}", result.stderr);
+    assert_eq!(result.stderr, "failed\n");
+}
+
+/// The handler `basicConfig` adds in one session call writes to the stderr
+/// of each later call, not the first one's.
+#[test]
+fn test_session_log_handler_follows_each_call() {
+    let session = Session::new(ExecutionSettings::default());
+    let first = session.eval("import logging\nlogging.warning('one')");
+    assert_eq!(first.stderr, "WARNING:root:one\n");
+
+    let second = session.eval("logging.warning('two')");
+    assert_eq!(second.error, None, "stderr: {}", second.stderr);
+    assert_eq!(second.stderr, "WARNING:root:two\n");
+}
+
+/// A preimported `logging` survives between calls on a pooled interpreter;
+/// its handlers still write to the current call.
+#[test]
+fn test_preimported_logging_follows_each_call() {
+    let pool = InterpreterPool::builder()
+        .size(1)
+        .preimport(&["logging"])
+        .build();
+    let cache = BytecodeCache::new(8);
+    for message in ["first", "second"] {
+        let code = format!("import logging\nlogging.warning('{message}')");
+        let result = execute_with(&pool, &cache, &code, ExecutionSettings::default());
+        assert_eq!(result.error, None, "stderr: {}", result.stderr);
+        assert_eq!(result.stderr, format!("WARNING:root:{message}\n"));
+    }
+}
@@ -0,0 +1,88 @@
+// crates/llm-pyexec/tests/wrap_last_expr.rs
+// Tests: ExecutionSettings::wrap_last_expr
+
+use llm_pyexec::{
+    compile, execute, execute_compiled, ExecutionError, ExecutionSettings, ResultCapture, Session,
+};
+
+fn no_wrap() -> ExecutionSettings {
+    ExecutionSettings {
+        wrap_last_expr: false,
+        debug: true,
+        ..ExecutionSettings::default()
+    }
+}
+
+/// With wrapping off the source runs untouched and yields no return value.
+#[test]
+fn test_no_return_value_and_no_rewrite() {
+    let result = execute("1 + 1", no_wrap());
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.return_value, None);
+    assert_eq!(result.typed_value, None);
+    assert_eq!(result.wrapped_source.as_deref(), Some("1 + 1"));
+}
+
+#[test]
+fn test_print_still_captured() {
+    let result = execute("print(2)", no_wrap());
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.stdout, "2\n");
+    assert_eq!(result.return_value, None);
+}
+
+/// `result_capture` has no effect once wrapping is off: nothing is captured
+/// and a bare expression prints nothing.
+#[test]
+fn test_overrides_single_capture() {
+    let settings = ExecutionSettings {
+        result_capture: ResultCapture::Single,
+        ..no_wrap()
+    };
+    let result = execute("x = 6\nx * 7", settings);
+    assert!(result.error.is_none(), "{:?}", result.error);
+    assert_eq!(result.return_value, None);
+    assert_eq!(result.stdout, "");
+}
+
+/// Error positions on the last line refer to the source as written.
+#[test]
+fn test_error_positions_match_source() {
+    let code = "x = 1\ny = (x +";
+    match execute(code, no_wrap()).error {
+        Some(ExecutionError::SyntaxError { line, .. }) => assert_eq!(line, 2),
+        other => panic!("expected SyntaxError, got {other:?}"),
+    }
+
+    match execute("x = 1\nx + 'a'", no_wrap()).error {
+        Some(ExecutionError::RuntimeError { line, .. }) => assert_eq!(line, Some(2)),
+        other => panic!("expected RuntimeError, got {other:?}"),
+    }
+}
+
+#[test]
+fn test_session_without_wrapping() {
+    let session = Session::new(no_wrap());
+    let result = session.eval("x = 3\nx * 2");
+    assert_eq!(result.return_value, None);
+    assert_eq!(result.wrapped_source.as_deref(), Some("x = 3\nx * 2"));
+    assert_eq!(session.eval("print(x)").stdout, "3\n");
+}
+
+/// A compiled snippet keeps the wrapping it was compiled with.
+#[test]
+fn test_execute_compiled_keeps_compile_time_wrapping() {
+    let snippet = compile("2 * 21").expect("compile");
+    let result = execute_compiled(&snippet, no_wrap());
+    assert_eq!(result.return_value.as_deref(), Some("42"));
+}
+
+#[test]
+fn test_defaults_to_true_when_missing() {
+    let mut json = serde_json::to_value(ExecutionSettings::default()).expect("serialize");
+    json.as_object_mut()
+        .expect("object")
+        .remove("wrap_last_expr");
+    let settings: ExecutionSettings = serde_json::from_value(json).expect("deserialize");
+    assert!(settings.wrap_last_expr);
+}